@@ -2,14 +2,24 @@
 //! `builder` feature.
 
 use anyhow::Context;
-use clap::{Parser, Subcommand};
-use sde::builder::parser::{ParserConfig, ProjectedAxis};
-use sde::builder::{extract, http, parser, schema, sde_index};
+use clap::{Args, Parser, Subcommand};
+use sde::builder::cancel::CancellationToken;
+use sde::builder::community::CommunityLayer;
+use sde::builder::parser::{PHASES, ParserConfig, Projection, SdeFormat, Strictness};
+use sde::builder::profile::{BuildProfile, Preset};
+use sde::builder::progress::{BuildEvent, BuildObserver};
+use sde::builder::sde_index::SdeRelease;
+use sde::builder::{BuilderError, audit, extract, http, install, parser, sde_index, verify};
+use sde::diff::{self, DiffFormat};
+use sde::export::geojson::{self, GeoJsonLayout};
+use sde::export::graph::{self, GraphFilter, GraphFormat};
+use sde::query::{self, QueryFormat};
+use std::io::{IsTerminal, Write};
 use std::path::PathBuf;
+use std::sync::Mutex;
 
 const SDE_URL: &str = "https://developers.eveonline.com/static-data/tranquility/";
 const MAPS_URL: &str = "http://evemaps.dotlan.net/svg/";
-const SDE_VARIANT: &str = "jsonl";
 
 #[derive(Parser)]
 #[command(
@@ -26,56 +36,362 @@ struct Cli {
 enum Command {
     /// Check for a new SDE build and rebuild the database if one is
     /// available (or if the database doesn't exist yet).
-    Build {
-        /// Rebuild even if the local database is already up to date.
-        #[arg(long)]
-        force: bool,
-        /// Suppress the progress output the parser prints by
-        /// default.
-        #[arg(short, long)]
-        quiet: bool,
-        /// Path to write the database to.
-        #[arg(short, long, default_value = "sde.db")]
-        output: PathBuf,
-        /// Also fetch and layer in community-maintained data on top of
-        /// the canonical SDE (ice belts, Jove Observatories, Triglavian
-        /// invasion status, special ore anomalies -- everything
-        /// `builder::community` provides, including `mapAbstractSystems`,
-        /// the one part of it that isn't gated by its own flag). Off by
-        /// default: none of this comes from CCP's official export, so
-        /// a plain `build` produces a database that's canonical SDE
-        /// data only. Sets `ParserConfig.with_third_party` -- see
-        /// [`parser::Parser::build_database`], which is what actually
-        /// consults it; this binary itself makes no
-        /// canonical-vs-third-party decision on its own.
+    Build(Box<BuildArgs>),
+    /// Compare two databases and print what changed between them, as
+    /// Markdown or JSON.
+    Diff {
+        /// The older database.
+        old: PathBuf,
+        /// The newer database.
+        new: PathBuf,
+        /// `markdown` or `json`.
+        #[arg(long, default_value = "markdown")]
+        format: DiffFormat,
+        /// Write the changelog to this file instead of stdout.
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// Inventory an extracted SDE directory: per file, the fields its
+    /// records carry, their JSON types and presence rates, and which
+    /// ones the parser ignores or expects but can't find.
+    Audit {
+        /// The extracted SDE directory.
+        dir: PathBuf,
+        /// Which export `dir` holds: `jsonl` or `yaml`.
+        #[arg(long, default_value = "jsonl")]
+        variant: SdeFormat,
+        /// Write the report to this file instead of stdout.
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// Check a built database's integrity and the invariants of its
+    /// data, and print a pass/fail report with the offending ids. Exits
+    /// with an error when a check fails.
+    Verify {
+        /// The database to check.
+        #[arg(default_value = "sde.db")]
+        database: PathBuf,
+        /// Write the report to this file instead of stdout.
+        #[arg(short, long, value_name = "PATH")]
+        output: Option<PathBuf>,
+    },
+    /// Look up systems, regions, routes, neighbors, stations or item
+    /// types in a built database, by name or id.
+    Query(QueryArgs),
+    /// Export a built database's map to a format other tools read.
+    Export(ExportArgs),
+}
+
+#[derive(Args)]
+struct QueryArgs {
+    #[command(subcommand)]
+    query: Query,
+    /// The database to query.
+    #[arg(short, long, default_value = "sde.db", global = true)]
+    database: PathBuf,
+    /// `table`, `json` or `csv`.
+    #[arg(long, default_value = "table", global = true)]
+    format: QueryFormat,
+    /// Language names are shown in (`en`, `de`, `ja`, ...).
+    #[arg(long, default_value = "en", global = true)]
+    language: String,
+    /// Write the result to this file instead of stdout.
+    #[arg(short, long, value_name = "PATH", global = true)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Args)]
+struct ExportArgs {
+    #[command(subcommand)]
+    export: Export,
+    /// The database to export.
+    #[arg(short, long, default_value = "sde.db", global = true)]
+    database: PathBuf,
+    /// Language names are written in (`en`, `de`, `ja`, ...).
+    #[arg(long, default_value = "en", global = true)]
+    language: String,
+    /// Divide coordinates by this (a negative one multiplies).
+    #[arg(
+        long,
+        default_value_t = 1.0,
+        allow_negative_numbers = true,
+        global = true
+    )]
+    factor: f64,
+    /// Keep the coordinates' signs instead of inverting them (the
+    /// read API's default).
+    #[arg(long, global = true)]
+    no_invert: bool,
+    /// Write the export to this file instead of stdout.
+    #[arg(short, long, value_name = "PATH", global = true)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Subcommand)]
+enum Export {
+    /// Systems as GeoJSON Points and stargate connections as
+    /// LineStrings, in one FeatureCollection.
+    Geojson {
+        /// `position2d` (the canonical map) or `abstract` (the
+        /// community abstract map).
+        #[arg(long, default_value = "position2d")]
+        layout: GeoJsonLayout,
+        /// Indent the JSON.
         #[arg(long)]
-        with_third_party: bool,
+        pretty: bool,
+    },
+    /// The stargate network, with each system's name, security,
+    /// region and position, as GraphML, Graphviz DOT or CSV (an edge
+    /// list and, separately, the node table).
+    Graph {
+        /// `graphml`, `dot`, `csv` (the edge list) or `csv-nodes` (the
+        /// nodes and their attributes).
+        #[arg(long, default_value = "graphml")]
+        format: GraphFormat,
+        /// Only these regions (comma-separated ids).
+        #[arg(long, value_delimiter = ',', value_name = "IDS")]
+        regions: Vec<u32>,
+        /// Only systems with at least this security status.
+        #[arg(long, allow_negative_numbers = true)]
+        min_security: Option<f64>,
+        /// Only systems with at most this security status.
+        #[arg(long, allow_negative_numbers = true)]
+        max_security: Option<f64>,
     },
 }
 
-/// (Re)builds the database from scratch whenever a new SDE build is
-/// available: checks for an update, and if one exists (or the database
-/// doesn't exist yet, or `--force` was passed), deletes the old
-/// database, cleans `sde/` (preserving `maps/`), decompresses the new
-/// zip, and parses it.
-///
-/// The whole rebuild (delete + clean + unzip + parse + community) only
-/// runs when `update_as_needed()` reports a change, the database
-/// doesn't exist yet, or `--force` was passed -- deliberately, to
-/// avoid wasted work (a full unzip + reparse of the whole SDE) on runs
-/// where nothing actually changed.
-///
-/// # This binary only turns flags into a `ParserConfig`
-///
-/// Whether to include `builder::community`'s community-maintained,
-/// third-party data (see `--with-third-party` above) -- and everything
-/// else about how the database gets built -- is decided by
-/// [`parser::Parser::build_database`], not by this function. That's
-/// deliberate: a library consumer calling `build_database` directly,
-/// without going through this binary at all, gets the exact same
-/// behavior for the exact same `ParserConfig`, since the decision
-/// lives in one place instead of being duplicated (and potentially
-/// drifting) between this binary and the library.
+#[derive(Subcommand)]
+enum Query {
+    /// A solar system: security, constellation, region and gates.
+    System {
+        /// Name (or id) of the system.
+        name: String,
+    },
+    /// A region: its constellation and system counts.
+    Region {
+        /// Name (or id) of the region.
+        name: String,
+    },
+    /// The shortest route by stargate jumps between two systems.
+    Route {
+        /// Name (or id) of the starting system.
+        from: String,
+        /// Name (or id) of the destination system.
+        to: String,
+    },
+    /// Every system within a number of stargate jumps of a system.
+    Neighbors {
+        /// Name (or id) of the system.
+        system: String,
+        /// How many jumps away to look.
+        #[arg(long, default_value_t = 1)]
+        jumps: usize,
+    },
+    /// The NPC stations in a system.
+    Stations {
+        /// Name (or id) of the system.
+        system: String,
+    },
+    /// An item type: group, category, published flag and volume.
+    Type {
+        /// Name (or id) of the type.
+        name: String,
+    },
+}
+
+/// Flags of `build`.
+#[derive(Args)]
+struct BuildArgs {
+    /// Rebuild even if the local database is already up to date.
+    #[arg(long)]
+    force: bool,
+    /// Suppress the progress bar and messages printed by default
+    /// (warnings are still printed).
+    #[arg(short, long)]
+    quiet: bool,
+    /// Path to write the database to.
+    #[arg(short, long, default_value = "sde.db")]
+    output: PathBuf,
+    /// Build from this local SDE zip instead of checking CCP's
+    /// index and downloading: no network access at all (unless
+    /// third-party data is on, see `--with-third-party`). The zip is extracted
+    /// into `--work-dir` as usual; `--variant` must match what it
+    /// contains. Always rebuilds, `--force` or not.
+    #[arg(long, value_name = "PATH", conflicts_with = "from_dir")]
+    from_zip: Option<PathBuf>,
+    /// Build from this already-extracted SDE directory, parsed in
+    /// place: it's never cleaned, and `--work-dir` is unused
+    /// (`--with-third-party` caches dotlan's maps in its `maps/`,
+    /// as it would in `--work-dir`). Offline like `--from-zip`.
+    #[arg(long, value_name = "PATH")]
+    from_dir: Option<PathBuf>,
+    /// Where the downloaded zip and its `.build` file are kept
+    /// between runs.
+    #[arg(long, value_name = "PATH", default_value = "data")]
+    data_dir: PathBuf,
+    /// Where the zip is extracted to before parsing (emptied on
+    /// every build, except for its `maps/` folder).
+    #[arg(long, value_name = "PATH", default_value = "sde")]
+    work_dir: PathBuf,
+    /// Update an existing database in place, writing only the rows
+    /// that changed, instead of replacing it with a new one (see
+    /// `builder::incremental`): every record is compared by key with
+    /// the row stored for it. Needs a database built by this crate
+    /// version's schema, without `--with-third-party`; without a
+    /// database to update, builds one from scratch as usual.
+    #[arg(long)]
+    incremental: bool,
+    /// How many previous databases to keep next to `--output`, as
+    /// `<stem>.build-<build number>.db`, for rollback. A full build is
+    /// written to `<output>.building.db` and only replaces the output
+    /// once it's complete and verified; the database it replaces is
+    /// kept (an `--incremental` update copies it first). `0` makes
+    /// none, and deletes none made by an earlier build either.
+    #[arg(long, default_value_t = 0)]
+    keep_previous: usize,
+    #[command(flatten)]
+    config: ConfigArgs,
+}
+
+/// Flags of `build` that make up its `ParserConfig`: a preset, a
+/// profile file over it, and single options over both (see
+/// `builder::profile`). An option that isn't given keeps the profile's
+/// (or the preset's) value.
+#[derive(Args)]
+struct ConfigArgs {
+    /// Starting point for every other option: `canonical` (the whole
+    /// SDE, no third-party data), `full` (plus every community layer)
+    /// or `kspace-map-only` (k-space systems and gates only). Default
+    /// `canonical`, unless the profile names one.
+    #[arg(long)]
+    preset: Option<Preset>,
+    /// A TOML or JSON build profile (`.toml`/`.json`): any of the
+    /// options below, by `ParserConfig` field name, plus `preset`.
+    #[arg(long, value_name = "PATH")]
+    profile: Option<PathBuf>,
+    /// Language of the base name/description columns. Sets
+    /// `ParserConfig.language`.
+    #[arg(long)]
+    language: Option<String>,
+    /// `true` to compute `position2DX`/`Y` with `--projection`, `false`
+    /// to keep CCP's own. Sets `ParserConfig.force_position_2d`.
+    #[arg(long, value_name = "BOOL")]
+    force_position_2d: Option<bool>,
+    /// Include k-space systems. Sets `ParserConfig.map_kspace`.
+    #[arg(long, value_name = "BOOL")]
+    map_kspace: Option<bool>,
+    /// Include wormhole systems. Sets `ParserConfig.map_wspace`.
+    #[arg(long, value_name = "BOOL")]
+    map_wspace: Option<bool>,
+    /// Include abyssal systems. Sets `ParserConfig.map_abyssal`.
+    #[arg(long, value_name = "BOOL")]
+    map_abyssal: Option<bool>,
+    /// Include void systems. Sets `ParserConfig.map_void`.
+    #[arg(long, value_name = "BOOL")]
+    map_void: Option<bool>,
+    /// Only the systems of these regions (comma-separated
+    /// `regionId`s), with their stars, planets, moons, gates and
+    /// stations. Combined with `--constellations`/`--factions`, a
+    /// system in any of them is kept. Sets `ParserConfig.region_ids`.
+    #[arg(long = "regions", value_name = "IDS", value_delimiter = ',')]
+    region_ids: Option<Vec<i64>>,
+    /// Only the systems of these constellations (comma-separated
+    /// `constellationId`s). Sets `ParserConfig.constellation_ids`.
+    #[arg(long = "constellations", value_name = "IDS", value_delimiter = ',')]
+    constellation_ids: Option<Vec<i64>>,
+    /// Only the systems of these factions (comma-separated
+    /// `factionId`s; a system's own faction, or its region's). Sets
+    /// `ParserConfig.faction_ids`.
+    #[arg(long = "factions", value_name = "IDS", value_delimiter = ',')]
+    faction_ids: Option<Vec<i64>>,
+    /// Import stargates (and the connections between systems). Sets
+    /// `ParserConfig.with_gates`.
+    #[arg(long, value_name = "BOOL")]
+    with_gates: Option<bool>,
+    /// Import moons. Sets `ParserConfig.with_moons`.
+    #[arg(long, value_name = "BOOL")]
+    with_moons: Option<bool>,
+    /// Also fetch and layer in community-maintained data on top of
+    /// the canonical SDE (ice belts, Jove Observatories, Triglavian
+    /// invasion status, special ore anomalies -- everything
+    /// `builder::community` provides, including `mapAbstractSystems`,
+    /// the one part of it that isn't gated by its own flag). Off by
+    /// default (but for `--preset full`): none of this comes from
+    /// CCP's official export, so a plain `build` produces a database
+    /// that's canonical SDE data only; `false` turns it back off for
+    /// a preset or profile that has it on. Sets
+    /// `ParserConfig.with_third_party` -- see
+    /// [`parser::Parser::build_database`], which is what actually
+    /// consults it; this binary itself makes no
+    /// canonical-vs-third-party decision on its own.
+    #[arg(long, value_name = "BOOL")]
+    with_third_party: Option<bool>,
+    /// Only these community layers, comma-separated (`icebelts`,
+    /// `triglavian-status`, `jove-observatories`, `special-ore`);
+    /// implies `--with-third-party`. Sets `ParserConfig.community`.
+    #[arg(long, value_name = "LAYERS", value_delimiter = ',')]
+    community_layers: Option<Vec<CommunityLayer>>,
+    /// Which of CCP's SDE exports to download and parse: `jsonl`
+    /// (default) or `yaml` (e.g. to rebuild from an archived snapshot
+    /// that only exists as YAML). Both produce the same database. Sets
+    /// `ParserConfig.format`.
+    #[arg(long)]
+    variant: Option<SdeFormat>,
+    /// Projection `position2DX`/`position2DY` are computed with:
+    /// `<mode><axis>`, mode one of `isometric`, `dimetric` or
+    /// `orthographic` (top-down), axis the one collapsed (`X`, `Y`
+    /// or `Z`). Default `isometricY`. Sets
+    /// `ParserConfig.position_2d_projection`.
+    #[arg(long)]
+    projection: Option<Projection>,
+    /// Also store this projection for every system, in
+    /// `mapSolarSystemProjections` (same syntax as `--projection`;
+    /// repeatable), so clients can switch layouts at runtime. Sets
+    /// `ParserConfig.projections`.
+    #[arg(long = "store-projection")]
+    store_projections: Vec<Projection>,
+    /// `strict` (any malformed record fails the build, the default) or
+    /// `lenient` (malformed records are skipped and listed in the
+    /// database's `buildIssues` table, e.g. for unattended nightly
+    /// builds). Sets `ParserConfig.strictness`.
+    #[arg(long)]
+    strictness: Option<Strictness>,
+}
+
+impl ConfigArgs {
+    /// The `ParserConfig` these flags describe: the preset, then the
+    /// profile file, then the flags themselves.
+    fn parser_config(self) -> anyhow::Result<ParserConfig> {
+        let file = match &self.profile {
+            Some(path) => BuildProfile::load(path)
+                .with_context(|| format!("reading the build profile {}", path.display()))?,
+            None => BuildProfile::default(),
+        };
+        let flags = BuildProfile {
+            preset: self.preset,
+            language: self.language,
+            force_position_2d: self.force_position_2d,
+            projection: self.projection,
+            projections: (!self.store_projections.is_empty()).then_some(self.store_projections),
+            map_kspace: self.map_kspace,
+            map_wspace: self.map_wspace,
+            map_abyssal: self.map_abyssal,
+            map_void: self.map_void,
+            region_ids: self.region_ids,
+            constellation_ids: self.constellation_ids,
+            faction_ids: self.faction_ids,
+            with_gates: self.with_gates,
+            with_moons: self.with_moons,
+            with_third_party: self.with_third_party,
+            community_layers: self.community_layers,
+            format: self.variant,
+            strictness: self.strictness,
+        };
+        Ok(file.overridden_by(flags).config())
+    }
+}
+
+/// Entry point: sets up tracing and runs the subcommand.
 #[tokio::main]
 #[tracing::instrument]
 async fn main() -> anyhow::Result<()> {
@@ -97,66 +413,211 @@ async fn main() -> anyhow::Result<()> {
         .expect("setting the global tracing subscriber");
     }
 
-    let cli = Cli::parse();
-    let Command::Build {
+    match Cli::parse().command {
+        Command::Build(args) => build(*args).await,
+        Command::Diff {
+            old,
+            new,
+            format,
+            output,
+        } => diff(&old, &new, format, output.as_deref()),
+        Command::Audit {
+            dir,
+            variant,
+            output,
+        } => audit(&dir, variant, output.as_deref()),
+        Command::Verify { database, output } => verify(&database, output.as_deref()),
+        Command::Query(args) => run_query(args),
+        Command::Export(args) => export(args),
+    }
+}
+
+/// (Re)builds the database from scratch whenever a new SDE build is
+/// available: checks for an update, and if one exists (or the database
+/// doesn't exist yet, or `--force` was passed), cleans `--work-dir`
+/// (preserving `maps/`), decompresses the new zip, parses it into a
+/// scratch database and swaps that in for the old one
+/// ([`install::replace_database`]). `--from-zip`/`--from-dir` skip the update
+/// check (and the network) and build from a local SDE instead;
+/// `--incremental` keeps the old database and only applies what changed
+/// ([`parser::Parser::update_database`]).
+///
+/// The whole rebuild (clean + unzip + parse + community + swap) only
+/// runs when `update_as_needed()` reports a change, the database
+/// doesn't exist yet, or `--force` was passed -- deliberately, to
+/// avoid wasted work (a full unzip + reparse of the whole SDE) on runs
+/// where nothing actually changed.
+///
+/// # This binary only turns flags into a `ParserConfig`
+///
+/// Whether to include `builder::community`'s community-maintained,
+/// third-party data (see `--with-third-party` above) -- and everything
+/// else about how the database gets built -- is decided by
+/// [`parser::Parser::build_database`], not by this function. That's
+/// deliberate: a library consumer calling `build_database` directly,
+/// without going through this binary at all, gets the exact same
+/// behavior for the exact same `ParserConfig`, since the decision
+/// lives in one place instead of being duplicated (and potentially
+/// drifting) between this binary and the library.
+#[tracing::instrument(skip_all)]
+async fn build(args: BuildArgs) -> anyhow::Result<()> {
+    let BuildArgs {
         force,
         quiet,
         output,
-        with_third_party,
-    } = cli.command;
+        from_zip,
+        from_dir,
+        data_dir,
+        work_dir,
+        incremental,
+        keep_previous,
+        config,
+    } = args;
+    let mut parser_config = config.parser_config()?;
+    let variant = parser_config.format;
 
     let client = http::build_client().context("building the HTTP client")?;
-    let data_dir = PathBuf::from("data");
-    let sde_dir = PathBuf::from("sde");
-
-    let changed = sde_index::update_as_needed(&client, &data_dir, SDE_URL, SDE_VARIANT)
-        .await
-        .context("checking for a new SDE build")?;
+    let progress = ProgressDisplay::new(quiet);
+    // Ctrl-C stops the build at its next check instead of killing it
+    // mid-write (a second one still kills it: the handler only runs
+    // once).
+    let cancellation = CancellationToken::new();
+    tokio::spawn({
+        let cancellation = cancellation.clone();
+        async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                cancellation.cancel();
+            }
+        }
+    });
 
-    if !force && !changed && output.exists() {
-        println!(
-            "sde: {} is already up to date, nothing to do",
-            output.display()
-        );
-        return Ok(());
-    }
+    // Offline builds (`--from-zip`/`--from-dir`) skip the index check
+    // and always rebuild; the online one only does when there's
+    // something new (or nothing built yet, or `--force`).
+    let (sde_dir, release) = if let Some(zip_path) = from_zip {
+        let release = extract::archive_release(&zip_path, variant)
+            .with_context(|| format!("reading the build number of {}", zip_path.display()))?;
+        extract_sde(&zip_path, &work_dir, &cancellation)?;
+        (work_dir, release)
+    } else if let Some(sde_dir) = from_dir {
+        let release = extract::directory_release(&sde_dir, variant)
+            .with_context(|| format!("reading the build number of {}", sde_dir.display()))?;
+        (sde_dir, release)
+    } else {
+        let changed = sde_index::update_as_needed(
+            &client,
+            &data_dir,
+            SDE_URL,
+            variant.variant(),
+            &http::RetryPolicy::default(),
+            &progress,
+            &cancellation,
+        )
+        .await;
+        progress.clear();
+        if matches!(changed, Err(BuilderError::Cancelled)) {
+            // The partial zip stays in `--data-dir`: the next run
+            // resumes it.
+            anyhow::bail!("download cancelled");
+        }
+        let changed = changed.context("checking for a new SDE build")?;
 
-    if output.exists() {
-        std::fs::remove_file(&output).context("removing the previous database")?;
-        println!(
-            "sde: removing the previous {}, a new SDE build is available",
-            output.display()
-        );
-    }
+        if !force && !changed && output.exists() {
+            println!(
+                "sde: {} is already up to date, nothing to do",
+                output.display()
+            );
+            return Ok(());
+        }
 
-    let zip_path = data_dir.join(format!("sde-{SDE_VARIANT}.zip"));
-    extract::prepare_sde_directory(&zip_path, &sde_dir).context("decompressing the SDE zip")?;
-
-    let mut connection = rusqlite::Connection::open(&output).context("creating the database")?;
-    schema::create_schema(&connection).context("creating the schema")?;
-
-    // `force_isometric_position_2d: true`: this CLI always uses a
-    // locally-computed isometric projection for `position2DX`/`Y`,
-    // rather than trusting CCP's own precomputed `position2D` value.
-    let parser_config = ParserConfig {
-        language: "en".to_string(),
-        force_isometric_position_2d: true,
-        isometric_projected_axis: ProjectedAxis::Y,
-        map_kspace: true,
-        map_wspace: true,
-        map_abyssal: true,
-        map_void: true,
-        with_gates: true,
-        with_moons: true,
-        verbose: !quiet,
-        with_third_party,
+        let zip_path = data_dir.join(format!("sde-{variant}.zip"));
+        // The zip's own `_sde` record first (it has the release date
+        // too), then the `.build` file `update_as_needed` keeps next to
+        // it.
+        let release = extract::archive_release(&zip_path, variant)
+            .context("reading the build number of the SDE zip")?
+            .or_else(|| {
+                std::fs::read_to_string(data_dir.join(format!("sde-{variant}.build")))
+                    .ok()
+                    .map(|build| SdeRelease {
+                        build_number: build.trim().to_string(),
+                        release_date: None,
+                    })
+            });
+        extract_sde(&zip_path, &work_dir, &cancellation)?;
+        (work_dir, release)
     };
+    println!(
+        "sde: building from {} (build {})",
+        sde_dir.display(),
+        release
+            .as_ref()
+            .map_or("unknown", |release| release.build_number.as_str())
+    );
+
+    // A full build never touches `output` until it's done: it's
+    // written next to it and swapped in by `install::replace_database`
+    // (`build_database_file` deletes it if it fails or is cancelled).
+    // An incremental update writes `output` in place, in one
+    // transaction, so it's backed up first instead.
+    let incremental = incremental && output.exists();
+    parser_config.release = release;
+    parser_config.cancellation = cancellation;
+    let with_third_party = parser_config.with_third_party;
     let sde_parser = parser::Parser::new(&sde_dir, parser_config);
-    let _summary = sde_parser
-        .build_database(&mut connection, &client, MAPS_URL)
-        .await
-        .context("building the database")?;
+    let summary = if incremental {
+        if let Some(backup) = install::back_up_database(&output, keep_previous)
+            .context("backing up the previous database")?
+        {
+            println!("sde: previous database kept as {}", backup.path.display());
+        }
+        let mut connection = rusqlite::Connection::open(&output).context("opening the database")?;
+        let result = sde_parser.update_database(&mut connection, &progress);
+        progress.clear();
+        if matches!(result, Err(BuilderError::Cancelled)) {
+            anyhow::bail!("build cancelled");
+        }
+        let summary = result.context("updating the database")?;
+        let mut unchanged = true;
+        for (table, changes) in &summary.changes {
+            if !changes.is_empty() {
+                unchanged = false;
+                println!(
+                    "sde: {table}: {} inserted, {} updated, {} deleted",
+                    changes.inserted, changes.updated, changes.deleted
+                );
+            }
+        }
+        if unchanged {
+            println!("sde: no rows changed");
+        }
+        summary
+    } else {
+        let result = sde_parser
+            .build_database_file(&output, keep_previous, &client, MAPS_URL, &progress)
+            .await;
+        progress.clear();
+        if matches!(result, Err(BuilderError::Cancelled)) {
+            anyhow::bail!("build cancelled");
+        }
+        let (summary, backup) = result.context("building the database")?;
+        if let Some(backup) = backup {
+            println!("sde: previous database kept as {}", backup.path.display());
+        }
+        summary
+    };
     println!("sde: Parse complete");
+    if summary.skipped_records > 0 {
+        println!(
+            "sde: skipped {} malformed records, listed in buildIssues",
+            summary.skipped_records
+        );
+    }
+    if !quiet {
+        for (phase, elapsed) in &summary.timings {
+            println!("sde: {phase} took {elapsed:.2?}");
+        }
+    }
 
     let third_party_note = if with_third_party {
         " (with community-maintained third-party data)"
@@ -169,3 +630,256 @@ async fn main() -> anyhow::Result<()> {
     );
     Ok(())
 }
+
+/// Decompresses the SDE zip into `work_dir`
+/// ([`extract::prepare_sde_directory`], which cleans up after itself
+/// when Ctrl-C was pressed meanwhile).
+fn extract_sde(
+    zip_path: &std::path::Path,
+    work_dir: &std::path::Path,
+    cancellation: &CancellationToken,
+) -> anyhow::Result<()> {
+    match extract::prepare_sde_directory(zip_path, work_dir, cancellation) {
+        Err(BuilderError::Cancelled) => anyhow::bail!("build cancelled"),
+        result => result.context("decompressing the SDE zip"),
+    }
+}
+
+/// The `build` command's [`BuildObserver`]: a progress bar on stderr
+/// (the current phase out of [`PHASES`], or how much of the SDE zip is
+/// downloaded) with messages printed above it. When stderr isn't a
+/// terminal there's no bar to redraw, so only the messages and a line
+/// every 10% of the download are printed. `quiet` keeps the bar and the
+/// messages off; warnings are always printed.
+struct ProgressDisplay {
+    quiet: bool,
+    terminal: bool,
+    state: Mutex<ProgressState>,
+}
+
+#[derive(Default)]
+struct ProgressState {
+    /// What the bar currently shows, if it's drawn.
+    bar: Option<String>,
+    /// The next download percentage worth a line, without a terminal.
+    next_percent: u64,
+}
+
+impl ProgressDisplay {
+    const WIDTH: usize = 30;
+
+    fn new(quiet: bool) -> Self {
+        Self {
+            quiet,
+            terminal: std::io::stderr().is_terminal(),
+            state: Mutex::new(ProgressState::default()),
+        }
+    }
+
+    /// `[=====>      ] <label>`, `done` out of `total`.
+    fn bar(done: u64, total: u64, label: &str) -> String {
+        let filled = (done.min(total) * Self::WIDTH as u64 / total.max(1)) as usize;
+        let arrow = if filled < Self::WIDTH { ">" } else { "" };
+        format!(
+            "[{}{arrow}{}] {label}",
+            "=".repeat(filled),
+            " ".repeat(Self::WIDTH.saturating_sub(filled + arrow.len()))
+        )
+    }
+
+    /// Erases the bar (before printing something else, or once done).
+    fn clear(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        if state.bar.take().is_some() {
+            eprint!("\r\x1b[2K");
+        }
+    }
+
+    fn draw(&self, state: &mut ProgressState, bar: String) {
+        if self.quiet || !self.terminal {
+            return;
+        }
+        eprint!("\r\x1b[2K{bar}");
+        let _ = std::io::stderr().flush();
+        state.bar = Some(bar);
+    }
+
+    /// Prints `line` above the bar, which is then redrawn.
+    fn print_above(&self, state: &mut ProgressState, line: &str, warning: bool) {
+        let bar = state.bar.take();
+        if bar.is_some() {
+            eprint!("\r\x1b[2K");
+        }
+        if warning {
+            eprintln!("{line}");
+        } else {
+            println!("{line}");
+        }
+        if let Some(bar) = bar {
+            self.draw(state, bar);
+        }
+    }
+}
+
+impl BuildObserver for ProgressDisplay {
+    fn event(&self, event: BuildEvent) {
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        match event {
+            BuildEvent::PhaseStarted { phase } => {
+                let bar = match PHASES.iter().position(|known| *known == phase) {
+                    Some(index) => Self::bar(
+                        index as u64,
+                        PHASES.len() as u64,
+                        &format!("{}/{} {phase}", index + 1, PHASES.len()),
+                    ),
+                    None => Self::bar(0, 0, phase),
+                };
+                self.draw(&mut state, bar);
+            }
+            BuildEvent::PhaseFinished { .. } => {}
+            BuildEvent::Download { progress, .. } => {
+                // Only when the server said how much.
+                let Some(total) = progress.total_bytes.filter(|total| *total > 0) else {
+                    return;
+                };
+                let percent = progress.downloaded_bytes * 100 / total;
+                let label = format!(
+                    "downloaded {percent}% ({:.1} of {:.1} MB)",
+                    progress.downloaded_bytes as f64 / 1e6,
+                    total as f64 / 1e6
+                );
+                if self.terminal {
+                    self.draw(
+                        &mut state,
+                        Self::bar(progress.downloaded_bytes, total, &label),
+                    );
+                } else if !self.quiet && percent >= state.next_percent {
+                    println!("sde: {label}");
+                    state.next_percent = percent / 10 * 10 + 10;
+                }
+            }
+            BuildEvent::Message(message) => {
+                if !self.quiet {
+                    self.print_above(&mut state, &message, false);
+                }
+            }
+            BuildEvent::Warning(warning) => self.print_above(&mut state, &warning, true),
+        }
+    }
+}
+
+/// `diff`: compares `old` with `new` ([`diff::diff_databases`]) and
+/// writes the changelog in `format` to `output`, or stdout without one.
+fn diff(
+    old: &std::path::Path,
+    new: &std::path::Path,
+    format: DiffFormat,
+    output: Option<&std::path::Path>,
+) -> anyhow::Result<()> {
+    let changelog = diff::diff_databases(old, new)
+        .with_context(|| format!("comparing {} with {}", old.display(), new.display()))?;
+    let rendered = changelog.render(format);
+    write_output(&rendered, output)
+}
+
+/// `audit`: inventories `dir` ([`audit::audit_directory`]) and writes
+/// the Markdown report to `output`, or stdout without one. Files or
+/// fields the parser expects but can't find are in the report, not an
+/// error: some optional fields are legitimately absent.
+fn audit(
+    dir: &std::path::Path,
+    variant: SdeFormat,
+    output: Option<&std::path::Path>,
+) -> anyhow::Result<()> {
+    let report = audit::audit_directory(dir, variant)
+        .with_context(|| format!("auditing {}", dir.display()))?;
+    let rendered = report.to_markdown();
+    write_output(&rendered, output)
+}
+
+/// `verify`: runs every check of [`verify::verify_database`] against
+/// `database` and writes the Markdown report to `output`, or stdout
+/// without one. A failed error-severity check makes the command fail
+/// (after the report is written), so scripts can gate on it; warnings
+/// don't.
+fn verify(database: &std::path::Path, output: Option<&std::path::Path>) -> anyhow::Result<()> {
+    let report = verify::verify_database(database)
+        .with_context(|| format!("verifying {}", database.display()))?;
+    let rendered = report.to_markdown();
+    write_output(&rendered, output)?;
+    anyhow::ensure!(
+        report.passed(),
+        "{} failed verification",
+        database.display()
+    );
+    Ok(())
+}
+
+/// `query`: answers one lookup ([`query`]) against `--database` and
+/// writes it to `--output`, or stdout without one. Opened with
+/// [`sde::SdeManager::open`] (read-only), so a missing file or one that
+/// isn't an SDE database fails up front instead of on its first query.
+fn run_query(args: QueryArgs) -> anyhow::Result<()> {
+    let mut sde = sde::SdeManager::open(&args.database, 1.0)
+        .with_context(|| format!("opening {}", args.database.display()))?;
+    sde.language = args.language;
+    let table = match &args.query {
+        Query::System { name } => query::system(&sde, name),
+        Query::Region { name } => query::region(&sde, name),
+        Query::Route { from, to } => query::route(&sde, from, to),
+        Query::Neighbors { system, jumps } => query::neighbors(&sde, system, *jumps),
+        Query::Stations { system } => query::stations(&sde, system),
+        Query::Type { name } => query::item_type(&sde, name),
+    }
+    .with_context(|| format!("querying {}", args.database.display()))?;
+    let rendered = table.render(args.format);
+    write_output(&rendered, args.output.as_deref())
+}
+
+/// `export`: writes `--database` in the chosen format ([`sde::export`])
+/// to `--output`, or stdout without one.
+fn export(args: ExportArgs) -> anyhow::Result<()> {
+    let mut sde = sde::SdeManager::open(&args.database, args.factor)
+        .with_context(|| format!("opening {}", args.database.display()))?;
+    sde.language = args.language;
+    sde.invert_coordinates = !args.no_invert;
+    let rendered = match args.export {
+        Export::Geojson { layout, pretty } => {
+            let collection = geojson::export_geojson(&sde, layout)
+                .with_context(|| format!("exporting {}", args.database.display()))?;
+            let json = if pretty {
+                serde_json::to_string_pretty(&collection)?
+            } else {
+                serde_json::to_string(&collection)?
+            };
+            json + "\n"
+        }
+        Export::Graph {
+            format,
+            regions,
+            min_security,
+            max_security,
+        } => {
+            let filter = GraphFilter {
+                regions,
+                min_security,
+                max_security,
+            };
+            graph::jump_graph(&sde, &filter)
+                .with_context(|| format!("exporting {}", args.database.display()))?
+                .render(format)
+        }
+    };
+    write_output(&rendered, args.output.as_deref())
+}
+
+/// Writes a subcommand's `rendered` result to `output`, or stdout
+/// without one.
+fn write_output(rendered: &str, output: Option<&std::path::Path>) -> anyhow::Result<()> {
+    match output {
+        Some(output) => std::fs::write(output, rendered)
+            .with_context(|| format!("writing {}", output.display()))?,
+        None => print!("{rendered}"),
+    }
+    Ok(())
+}
@@ -0,0 +1,395 @@
+//! Inventory of an extracted SDE directory against what
+//! [`super::parser`] reads, to catch schema drift in a new SDE release
+//! before a build trips over it.
+//!
+//! [`audit_directory`] reads every file of the chosen format in the
+//! directory (the parser's own readers, so YAML gets the same JSONL
+//! shape) and reports, per file, how many records it holds and, per
+//! top-level field, how many records carry it and with which JSON types.
+//! Each field is then checked against [`super::records::expected_fields`]:
+//!
+//! - [`FieldStatus::Read`]: the parser reads it.
+//! - [`FieldStatus::Ignored`]: present in the file, but the parser
+//!   doesn't read it -- a new field in the export, or one deliberately
+//!   left out (every field of a file the parser doesn't read at all).
+//! - [`FieldStatus::NotFound`]: the parser reads it, but no record
+//!   carries it. Normal for some optional fields (`locked` in
+//!   `mapStars`, `_value` outside a YAML export); for anything else a
+//!   sign CCP renamed or dropped it.
+//!
+//! A file the parser reads that isn't in the directory at all is
+//! reported too, with [`FileAudit::present`] `false`.
+//!
+//! Only the top level is inventoried: nested objects (`position`,
+//! `statistics`, ...) count as one field each, the way
+//! [`super::records::expected_fields`] lists them.
+
+use crate::builder::BuilderError;
+use crate::builder::parser::{self, SdeFormat};
+use crate::builder::records;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Result of [`audit_directory`]: one entry per file, sorted by name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditReport {
+    pub files: Vec<FileAudit>,
+}
+
+/// One SDE file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileAudit {
+    /// File name without its extension (`mapStars`).
+    pub stem: String,
+    /// Whether the parser reads this file.
+    pub parsed: bool,
+    /// `false` for a file the parser reads that isn't in the directory.
+    pub present: bool,
+    pub records: usize,
+    /// Every field seen in the file, then every expected one that
+    /// wasn't, in that order; each group sorted by name.
+    pub fields: Vec<FieldAudit>,
+}
+
+/// One top-level field of a [`FileAudit`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldAudit {
+    pub name: String,
+    /// How many records carry the field (`null` included).
+    pub present: usize,
+    /// How many of those records carry it as each JSON type: `null`,
+    /// `boolean`, `integer`, `number`, `string`, `array` or `object`.
+    pub types: BTreeMap<&'static str, usize>,
+    pub status: FieldStatus,
+}
+
+/// How a [`FieldAudit`] relates to what the parser reads, see the
+/// module docs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FieldStatus {
+    Read,
+    Ignored,
+    NotFound,
+}
+
+impl FieldStatus {
+    fn label(&self) -> &'static str {
+        match self {
+            FieldStatus::Read => "read",
+            FieldStatus::Ignored => "ignored",
+            FieldStatus::NotFound => "**not found**",
+        }
+    }
+}
+
+impl FileAudit {
+    /// Share of the file's records carrying `field`, from 0 to 1. `None`
+    /// for an empty file.
+    pub fn presence(&self, field: &FieldAudit) -> Option<f64> {
+        (self.records > 0).then(|| field.present as f64 / self.records as f64)
+    }
+
+    /// Fields with the given status.
+    pub fn with_status(&self, status: FieldStatus) -> impl Iterator<Item = &FieldAudit> {
+        self.fields
+            .iter()
+            .filter(move |field| field.status == status)
+    }
+}
+
+/// Inventories every `*.<format>` file in `sde_directory` (not its
+/// subdirectories, so `maps/` is left alone), plus every file the parser
+/// reads that's missing from it. A record that isn't an object is a
+/// [`BuilderError::Data`]; a file that doesn't parse fails as it would
+/// in a build.
+pub fn audit_directory(
+    sde_directory: &Path,
+    format: SdeFormat,
+) -> Result<AuditReport, BuilderError> {
+    let mut stems = Vec::new();
+    for entry in std::fs::read_dir(sde_directory)? {
+        let path = entry?.path();
+        if !path.is_file()
+            || path.extension().and_then(|ext| ext.to_str()) != Some(format.variant())
+        {
+            continue;
+        }
+        if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+            stems.push(stem.to_string());
+        }
+    }
+    for stem in parser::DECODED_FILES {
+        if !stems.iter().any(|present| present == stem) {
+            stems.push(stem.to_string());
+        }
+    }
+    stems.sort();
+
+    let mut files = Vec::with_capacity(stems.len());
+    for stem in stems {
+        let expected = records::expected_fields(&stem);
+        let present = sde_directory
+            .join(format!("{stem}.{}", format.variant()))
+            .is_file();
+        let mut seen: BTreeMap<String, FieldAudit> = BTreeMap::new();
+        let mut count = 0usize;
+        if present {
+            for source in parser::iter_records(sde_directory, &stem, format)? {
+                let record = source?;
+                let Value::Object(object) = record else {
+                    return Err(BuilderError::Data(format!(
+                        "record {} of {stem} is not an object",
+                        count + 1
+                    )));
+                };
+                count += 1;
+                for (name, value) in object {
+                    let field = seen.entry(name).or_insert_with_key(|name| FieldAudit {
+                        name: name.clone(),
+                        present: 0,
+                        types: BTreeMap::new(),
+                        status: if expected.is_some_and(|fields| fields.contains(&name.as_str())) {
+                            FieldStatus::Read
+                        } else {
+                            FieldStatus::Ignored
+                        },
+                    });
+                    field.present += 1;
+                    *field.types.entry(json_type(&value)).or_default() += 1;
+                }
+            }
+        }
+
+        let mut fields: Vec<FieldAudit> = seen.into_values().collect();
+        let mut not_found: Vec<FieldAudit> = expected
+            .unwrap_or_default()
+            .iter()
+            .filter(|name| !fields.iter().any(|field| field.name == **name))
+            .map(|name| FieldAudit {
+                name: name.to_string(),
+                present: 0,
+                types: BTreeMap::new(),
+                status: FieldStatus::NotFound,
+            })
+            .collect();
+        not_found.sort_by(|a, b| a.name.cmp(&b.name));
+        fields.append(&mut not_found);
+
+        files.push(FileAudit {
+            stem,
+            parsed: expected.is_some(),
+            present,
+            records: count,
+            fields,
+        });
+    }
+    Ok(AuditReport { files })
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(number) if number.is_f64() => "number",
+        Value::Number(_) => "integer",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+impl AuditReport {
+    /// The report as Markdown: a summary table, then one table of fields
+    /// per file, files the parser reads first.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# SDE audit\n\n");
+        out.push_str(
+            "| File | Parsed | Records | Fields | Ignored | Not found |\n|---|---|---|---|---|---|\n",
+        );
+        let ordered = self
+            .files
+            .iter()
+            .filter(|file| file.parsed)
+            .chain(self.files.iter().filter(|file| !file.parsed));
+        for file in ordered.clone() {
+            let records = if file.present {
+                file.records.to_string()
+            } else {
+                "**missing**".to_string()
+            };
+            let _ = writeln!(
+                out,
+                "| `{}` | {} | {records} | {} | {} | {} |",
+                file.stem,
+                if file.parsed { "yes" } else { "no" },
+                file.fields
+                    .iter()
+                    .filter(|field| field.status != FieldStatus::NotFound)
+                    .count(),
+                file.with_status(FieldStatus::Ignored).count(),
+                file.with_status(FieldStatus::NotFound).count(),
+            );
+        }
+
+        for file in ordered {
+            let _ = write!(out, "\n## {}\n\n", file.stem);
+            if !file.present {
+                out.push_str("Not in the directory.\n");
+                continue;
+            }
+            if file.fields.is_empty() {
+                out.push_str("No fields.\n");
+                continue;
+            }
+            out.push_str("| Field | Present | Types | Status |\n|---|---|---|---|\n");
+            for field in &file.fields {
+                let presence = match file.presence(field) {
+                    Some(share) => {
+                        format!("{}/{} ({:.1}%)", field.present, file.records, share * 100.0)
+                    }
+                    None => "0/0".to_string(),
+                };
+                let types = field
+                    .types
+                    .iter()
+                    .map(|(kind, count)| {
+                        if *count == field.present {
+                            kind.to_string()
+                        } else {
+                            format!("{kind} ({count})")
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let _ = writeln!(
+                    out,
+                    "| `{}` | {presence} | {types} | {} |",
+                    field.name,
+                    field.status.label()
+                );
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct TempDir {
+        path: std::path::PathBuf,
+    }
+
+    impl TempDir {
+        fn new(test_name: &str, files: &[(&str, &str)]) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "sde_audit_test_{}_{}",
+                std::process::id(),
+                test_name
+            ));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).unwrap();
+            for (name, content) in files {
+                std::fs::write(path.join(name), content).unwrap();
+            }
+            Self { path }
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.path);
+        }
+    }
+
+    fn file<'a>(report: &'a AuditReport, stem: &str) -> &'a FileAudit {
+        report.files.iter().find(|file| file.stem == stem).unwrap()
+    }
+
+    fn field<'a>(file: &'a FileAudit, name: &str) -> &'a FieldAudit {
+        file.fields.iter().find(|field| field.name == name).unwrap()
+    }
+
+    #[test]
+    fn audit_counts_presence_types_and_status_per_field() {
+        let dir = TempDir::new(
+            "presence",
+            &[
+                (
+                    "categories.jsonl",
+                    "{\"_key\": 6, \"name\": {\"en\": \"Ship\"}, \"published\": true, \"iconID\": 1}\n\
+                    {\"_key\": 7, \"name\": \"Module\", \"iconID\": 2.5}\n",
+                ),
+                (
+                    "dogmaUnits.jsonl",
+                    "{\"_key\": 1, \"displayName\": \"m\"}\n",
+                ),
+                ("notes.txt", "not an SDE file"),
+            ],
+        );
+        let report = audit_directory(&dir.path, SdeFormat::Jsonl).unwrap();
+        assert!(!report.files.iter().any(|file| file.stem == "notes"));
+
+        let categories = file(&report, "categories");
+        assert!(categories.parsed && categories.present);
+        assert_eq!(categories.records, 2);
+        let name = field(categories, "name");
+        assert_eq!(name.status, FieldStatus::Read);
+        assert_eq!(name.types, BTreeMap::from([("object", 1), ("string", 1)]));
+        let published = field(categories, "published");
+        assert_eq!(categories.presence(published), Some(0.5));
+        let icon = field(categories, "iconID");
+        assert_eq!(icon.status, FieldStatus::Ignored);
+        assert_eq!(icon.types, BTreeMap::from([("integer", 1), ("number", 1)]));
+
+        let units = file(&report, "dogmaUnits");
+        assert!(!units.parsed);
+        assert_eq!(field(units, "displayName").status, FieldStatus::Ignored);
+
+        // Every other file the parser reads is reported missing.
+        let types = file(&report, "types");
+        assert!(types.parsed && !types.present);
+        assert_eq!(field(types, "groupID").status, FieldStatus::NotFound);
+    }
+
+    #[test]
+    fn audit_reports_an_expected_field_no_record_carries() {
+        let dir = TempDir::new(
+            "not_found",
+            &[(
+                "races.jsonl",
+                "{\"_key\": 1, \"title\": {\"en\": \"Caldari\"}}\n",
+            )],
+        );
+        let report = audit_directory(&dir.path, SdeFormat::Jsonl).unwrap();
+        let races = file(&report, "races");
+        let statuses: Vec<(&str, FieldStatus)> = races
+            .fields
+            .iter()
+            .map(|field| (field.name.as_str(), field.status))
+            .collect();
+        assert_eq!(
+            statuses,
+            [
+                ("_key", FieldStatus::Read),
+                ("title", FieldStatus::Ignored),
+                ("name", FieldStatus::NotFound),
+            ]
+        );
+        let markdown = report.to_markdown();
+        assert!(markdown.contains("| `races` | yes | 1 | 2 | 1 | 1 |"));
+        assert!(markdown.contains("| `name` | 0/1 (0.0%) |  | **not found** |"));
+    }
+
+    #[test]
+    fn audit_rejects_a_record_that_is_not_an_object() {
+        let dir = TempDir::new("not_object", &[("races.jsonl", "[1, 2]\n")]);
+        assert!(matches!(
+            audit_directory(&dir.path, SdeFormat::Jsonl),
+            Err(BuilderError::Data(message)) if message.contains("races")
+        ));
+    }
+}
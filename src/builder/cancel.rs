@@ -0,0 +1,67 @@
+//! Stopping a build from outside: a [`CancellationToken`] handed to the
+//! pipeline (in [`super::parser::ParserConfig::cancellation`], and to
+//! [`super::community::process`] and [`super::sde_index::update_as_needed`])
+//! and cancelled from wherever the embedding application decides to --
+//! a "Cancel" button, the window closing, Ctrl-C.
+//!
+//! Cancelling doesn't interrupt anything by itself: the pipeline checks
+//! the token between phases, between records within a phase, between
+//! regions of the community download and between chunks of the SDE
+//! download, and stops at the first check after it was cancelled with
+//! [`BuilderError::Cancelled`] -- through the same `?` path as any other
+//! error, so whatever a failed build undoes (the open transaction, the
+//! scratch file of a full build) a cancelled one undoes too.
+
+use crate::builder::BuilderError;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A shared "stop" flag: clones share it, so the caller keeps one and
+/// the build gets another. Starts out not cancelled, and once cancelled
+/// stays cancelled -- a new build needs a new token.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks every build holding a clone of this token to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// `Err(BuilderError::Cancelled)` once [`Self::cancel`] was called,
+    /// to `?` out of the build at a point where stopping is safe.
+    pub fn check(&self) -> Result<(), BuilderError> {
+        if self.is_cancelled() {
+            Err(BuilderError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clones_share_the_cancellation() {
+        let token = CancellationToken::new();
+        let held_by_the_build = token.clone();
+        assert!(held_by_the_build.check().is_ok());
+
+        token.cancel();
+        assert!(held_by_the_build.is_cancelled());
+        assert!(matches!(
+            held_by_the_build.check(),
+            Err(BuilderError::Cancelled)
+        ));
+    }
+}
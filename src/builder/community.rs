@@ -33,7 +33,9 @@
 //! error (`process()`), which will reuse [`super::http`]/
 //! [`super::manifest`].
 
+use crate::builder::cancel::CancellationToken;
 use crate::builder::manifest::{self, Manifest};
+use crate::builder::progress::{BuildEvent, BuildObserver};
 use crate::builder::{BuilderError, http};
 use reqwest::Client;
 use rusqlite::Connection;
@@ -75,6 +77,96 @@ impl Default for CommunityConfig {
     }
 }
 
+impl CommunityConfig {
+    /// Every layer on: what `--with-third-party` builds.
+    pub const ALL: CommunityConfig = CommunityConfig {
+        with_icebelts: true,
+        with_triglavian_status: true,
+        with_jove_observatories: true,
+        with_special_ore: true,
+    };
+
+    /// Only the layers in `layers` on.
+    pub fn with_layers(layers: &[CommunityLayer]) -> Self {
+        Self {
+            with_icebelts: layers.contains(&CommunityLayer::Icebelts),
+            with_triglavian_status: layers.contains(&CommunityLayer::TriglavianStatus),
+            with_jove_observatories: layers.contains(&CommunityLayer::JoveObservatories),
+            with_special_ore: layers.contains(&CommunityLayer::SpecialOre),
+        }
+    }
+
+    /// The layers that are on, in [`CommunityLayer::ALL`]'s order.
+    pub fn layers(&self) -> Vec<CommunityLayer> {
+        CommunityLayer::ALL
+            .into_iter()
+            .filter(|layer| match layer {
+                CommunityLayer::Icebelts => self.with_icebelts,
+                CommunityLayer::TriglavianStatus => self.with_triglavian_status,
+                CommunityLayer::JoveObservatories => self.with_jove_observatories,
+                CommunityLayer::SpecialOre => self.with_special_ore,
+            })
+            .collect()
+    }
+}
+
+/// One of [`CommunityConfig`]'s flags, by name -- how a layer is picked
+/// on the command line (`--community-layers`) or in a build profile
+/// (`builder::profile`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommunityLayer {
+    /// `with_icebelts`.
+    Icebelts,
+    /// `with_triglavian_status`.
+    TriglavianStatus,
+    /// `with_jove_observatories`.
+    JoveObservatories,
+    /// `with_special_ore`.
+    SpecialOre,
+}
+
+impl CommunityLayer {
+    pub const ALL: [CommunityLayer; 4] = [
+        CommunityLayer::Icebelts,
+        CommunityLayer::TriglavianStatus,
+        CommunityLayer::JoveObservatories,
+        CommunityLayer::SpecialOre,
+    ];
+
+    /// `"icebelts"`, `"triglavian-status"`, `"jove-observatories"` or
+    /// `"special-ore"`, also what [`std::str::FromStr`] accepts.
+    pub fn name(&self) -> &'static str {
+        match self {
+            CommunityLayer::Icebelts => "icebelts",
+            CommunityLayer::TriglavianStatus => "triglavian-status",
+            CommunityLayer::JoveObservatories => "jove-observatories",
+            CommunityLayer::SpecialOre => "special-ore",
+        }
+    }
+}
+
+impl std::fmt::Display for CommunityLayer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for CommunityLayer {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        CommunityLayer::ALL
+            .into_iter()
+            .find(|layer| layer.name() == value)
+            .ok_or_else(|| {
+                format!(
+                    "unknown community layer `{value}` (expected one of `icebelts`, \
+                     `triglavian-status`, `jove-observatories`, `special-ore`)"
+                )
+            })
+    }
+}
+
 /// Creates `mapAbstractSystems`, the table where dotlan's SVG parsing
 /// (not yet implemented in this module) would insert the "abstract" 2D
 /// coordinates dotlan computes for its own map layout -- unrelated to
@@ -286,24 +378,25 @@ pub fn update_tables(
 ///
 /// If some other regional map carried a different format anyway, this
 /// function doesn't break: any `id`/`x`/`y` that doesn't parse as a
-/// number is simply skipped (with a warning on stderr), row by row,
+/// number is simply skipped (with a [`BuildEvent::Warning`]), row by row,
 /// without aborting the rest of the parsing.
 ///
 /// All three values (`solarSystemId`, `x`, `y`) are explicitly parsed
 /// to `i64`/`f64` before binding them: this is a real requirement of
 /// this crate's `STRICT` tables, which don't accept the implicit
 /// text-to-number coercion classic SQLite does.
-#[tracing::instrument]
+#[tracing::instrument(skip(observer))]
 pub fn extract_map_data(
     connection: &Connection,
     map_path: &Path,
     config: &CommunityConfig,
+    observer: &dyn BuildObserver,
 ) -> Result<bool, BuilderError> {
     if !map_path.exists() {
-        eprintln!(
+        observer.event(BuildEvent::Warning(format!(
             "community: {} doesn't exist, skipping parsing",
             map_path.display()
-        );
+        )));
         return Ok(false);
     }
 
@@ -311,7 +404,10 @@ pub fn extract_map_data(
     let doc = match roxmltree::Document::parse(&content) {
         Ok(doc) => doc,
         Err(err) => {
-            eprintln!("community: error parsing {} - {err}", map_path.display());
+            observer.event(BuildEvent::Warning(format!(
+                "community: error parsing {} - {err}",
+                map_path.display()
+            )));
             return Ok(false);
         }
     };
@@ -327,10 +423,10 @@ pub fn extract_map_data(
         };
         match raw_id.get(3..).and_then(|s| s.parse::<i64>().ok()) {
             Some(id) => icebelt_ids.push(id),
-            None => eprintln!(
+            None => observer.event(BuildEvent::Warning(format!(
                 "community: unexpected icebelt id '{raw_id}' in {}, skipping",
                 map_path.display()
-            ),
+            ))),
         }
     }
     if !icebelt_ids.is_empty() && config.with_icebelts {
@@ -353,9 +449,12 @@ pub fn extract_map_data(
             ))
         })?;
 
+    // Only systems the database has: a scoped build (see
+    // `ParserConfig::region_ids`) leaves some of a map's systems out.
     let mut insert_abstract = connection.prepare(
         "INSERT INTO mapAbstractSystems (solarSystemId, regionId, x, y) \
-         VALUES (?1, (SELECT regionId FROM mapRegions WHERE regionName = ?2), ?3, ?4)",
+         SELECT ?1, (SELECT regionId FROM mapRegions WHERE regionName = ?2), ?3, ?4 \
+         WHERE EXISTS (SELECT 1 FROM mapSolarSystems WHERE solarSystemId = ?1)",
     )?;
     for tag in doc
         .descendants()
@@ -372,10 +471,10 @@ pub fn extract_map_data(
             .zip(raw_x.parse::<f64>().ok())
             .zip(raw_y.parse::<f64>().ok());
         let Some(((id, x), y)) = parsed else {
-            eprintln!(
+            observer.event(BuildEvent::Warning(format!(
                 "community: unexpected <use id='{raw_id}' x='{raw_x}' y='{raw_y}'> in {}, skipping",
                 map_path.display()
-            );
+            )));
             continue;
         };
         insert_abstract.execute(rusqlite::params![id, region_name, x, y])?;
@@ -411,6 +510,15 @@ fn get_all_regions(connection: &Connection) -> Result<Vec<(i64, String)>, Builde
 /// `sde_directory` is the builder's root working directory; the maps
 /// and the manifest ([`manifest`]) live in `<sde_directory>/maps/`.
 ///
+/// What happens to each region (downloaded, unchanged, parsed) goes to
+/// `observer` as a [`BuildEvent::Message`]; a failed attempt, as a
+/// [`BuildEvent::Warning`].
+///
+/// `cancellation` is checked before each region: once it's cancelled,
+/// this stops with [`BuilderError::Cancelled`] between two regions,
+/// after saving the manifest -- the maps downloaded so far aren't
+/// downloaded again next time.
+///
 /// # Error handling
 ///
 /// - If the download itself fails (network error, non-2xx HTTP
@@ -421,13 +529,15 @@ fn get_all_regions(connection: &Connection) -> Result<Vec<(i64, String)>, Builde
 ///   acceptable given that, worst case, an invalid file that couldn't
 ///   be removed just ends up getting overwritten on the next
 ///   successful attempt anyway.
-#[tracing::instrument]
+#[tracing::instrument(skip(observer))]
 pub async fn process(
     connection: &Connection,
     client: &Client,
     sde_directory: &Path,
     map_url_base: &str,
     config: &CommunityConfig,
+    observer: &dyn BuildObserver,
+    cancellation: &CancellationToken,
 ) -> Result<(), BuilderError> {
     update_tables(connection, config)?;
     let regions = get_all_regions(connection)?;
@@ -437,11 +547,25 @@ pub async fn process(
     let mut manifest_changed = false;
 
     for (_region_id, region_name) in regions {
+        if cancellation.is_cancelled() {
+            if manifest_changed {
+                manifest::save(&maps_dir, &manifest)?;
+            }
+            return Err(BuilderError::Cancelled);
+        }
         let file_name = format!("{}.svg", region_name.replace(' ', "_"));
         let map_path = maps_dir.join(&file_name);
         let map_url = format!("{map_url_base}{file_name}");
 
-        let remote_fingerprint = http::fingerprint(client, &map_url).await;
+        let remote_fingerprint = match http::fingerprint(client, &map_url).await {
+            Ok(fingerprint) => Some(fingerprint),
+            Err(err) => {
+                observer.event(BuildEvent::Warning(format!(
+                    "community: {map_url} can't be verified ({err})"
+                )));
+                None
+            }
+        };
         let mut needs_download = manifest::needs_download(
             map_path.exists(),
             manifest.get(&region_name),
@@ -452,7 +576,9 @@ pub async fn process(
             if needs_download {
                 match http::download(client, &map_url, &map_path, |_| {}).await {
                     Ok(size) if size > 100 => {
-                        println!("community: map downloaded for {region_name}");
+                        observer.event(BuildEvent::Message(format!(
+                            "community: map downloaded for {region_name}"
+                        )));
                         if let Some(fp) = &remote_fingerprint {
                             manifest.insert(region_name.clone(), fp.clone());
                             manifest_changed = true;
@@ -460,24 +586,34 @@ pub async fn process(
                     }
                     Ok(_) => {
                         let _ = std::fs::remove_file(&map_path);
-                        eprintln!("community: invalid data received for {region_name}");
+                        observer.event(BuildEvent::Warning(format!(
+                            "community: invalid data received for {region_name}"
+                        )));
                     }
                     Err(err) => {
                         let _ = std::fs::remove_file(&map_path);
-                        eprintln!("community: error downloading the map for {region_name}: {err}");
+                        observer.event(BuildEvent::Warning(format!(
+                            "community: error downloading the map for {region_name}: {err}"
+                        )));
                     }
                 }
             } else {
-                println!("community: {region_name} unchanged, skipping download.");
+                observer.event(BuildEvent::Message(format!(
+                    "community: {region_name} unchanged, skipping download."
+                )));
             }
 
-            println!("community: parsing data for {region_name}");
-            if extract_map_data(connection, &map_path, config)? {
+            observer.event(BuildEvent::Message(format!(
+                "community: parsing data for {region_name}"
+            )));
+            if extract_map_data(connection, &map_path, config, observer)? {
                 break;
             }
             needs_download = true;
             let _ = std::fs::remove_file(&map_path);
-            eprintln!("community: invalid data for {region_name}, retrying download ({attempt}).");
+            observer.event(BuildEvent::Warning(format!(
+                "community: invalid data for {region_name}, retrying download ({attempt})."
+            )));
         }
     }
 
@@ -526,6 +662,7 @@ const TRIGLAVIAN_MINOR_VICTORY: &[i64] = &[
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::builder::progress::Silent;
 
     /// Common prerequisites: full schema + one region/constellation and
     /// two solar systems (one with an id from the Edencom list, to test
@@ -603,7 +740,7 @@ mod tests {
 
         let missing = std::env::temp_dir().join("sde-community-test-no-existe.svg");
         let config = CommunityConfig::default();
-        let result = extract_map_data(&connection, &missing, &config).unwrap();
+        let result = extract_map_data(&connection, &missing, &config, &Silent).unwrap();
         assert!(!result);
     }
 
@@ -615,7 +752,7 @@ mod tests {
 
         let path = write_temp_svg("malformed", "The_Forge.svg", "<svg><rect></svg>");
         let config = CommunityConfig::default();
-        let result = extract_map_data(&connection, &path, &config).unwrap();
+        let result = extract_map_data(&connection, &path, &config, &Silent).unwrap();
         assert!(!result);
     }
 
@@ -627,7 +764,7 @@ mod tests {
 
         let path = write_temp_svg("abstract_systems", "The_Forge.svg", SAMPLE_SVG);
         let config = CommunityConfig::default();
-        let ok = extract_map_data(&connection, &path, &config).unwrap();
+        let ok = extract_map_data(&connection, &path, &config, &Silent).unwrap();
         assert!(ok);
 
         let (region_id, x, y): (i64, f64, f64) = connection
@@ -658,7 +795,7 @@ mod tests {
         create_icebelts(&connection).unwrap();
         // with_icebelts=false (default): the rect gets parsed but NOT written.
         let config = CommunityConfig::default();
-        extract_map_data(&connection, &path, &config).unwrap();
+        extract_map_data(&connection, &path, &config, &Silent).unwrap();
         let ice_belt: i64 = connection
             .query_row(
                 "SELECT iceBelt FROM mapSolarSystems WHERE solarSystemId = 30003088",
@@ -676,7 +813,7 @@ mod tests {
             with_icebelts: true,
             ..config
         };
-        extract_map_data(&connection_enabled, &path, &config_enabled).unwrap();
+        extract_map_data(&connection_enabled, &path, &config_enabled, &Silent).unwrap();
         let ice_belt: i64 = connection_enabled
             .query_row(
                 "SELECT iceBelt FROM mapSolarSystems WHERE solarSystemId = 30003088",
@@ -701,7 +838,7 @@ mod tests {
         );
         let path = write_temp_svg("incomplete_use", "The_Forge.svg", svg);
         let config = CommunityConfig::default();
-        let ok = extract_map_data(&connection, &path, &config).unwrap();
+        let ok = extract_map_data(&connection, &path, &config, &Silent).unwrap();
         assert!(ok, "un <use> incompleto se omite, no aborta el parseo");
 
         let total: i64 = connection
@@ -765,7 +902,7 @@ mod tests {
             with_icebelts: true,
             ..CommunityConfig::default()
         };
-        let ok = extract_map_data(&connection, &path, &config).unwrap();
+        let ok = extract_map_data(&connection, &path, &config, &Silent).unwrap();
         assert!(ok);
 
         // The legend rect (without an id) shouldn't generate any extra UPDATE
@@ -871,9 +1008,17 @@ mod tests {
         let map_url_base = format!("{}/", server.uri());
         let config = CommunityConfig::default();
 
-        process(&connection, &client, &sde_dir, &map_url_base, &config)
-            .await
-            .unwrap();
+        process(
+            &connection,
+            &client,
+            &sde_dir,
+            &map_url_base,
+            &config,
+            &Silent,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
 
         let total: i64 = connection
             .query_row("SELECT COUNT(*) FROM mapAbstractSystems", [], |row| {
@@ -925,9 +1070,27 @@ mod tests {
 
         let map_url_base = format!("{}/", server.uri());
         let config = CommunityConfig::default();
-        process(&connection, &client, &sde_dir, &map_url_base, &config)
-            .await
-            .unwrap();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        process(
+            &connection,
+            &client,
+            &sde_dir,
+            &map_url_base,
+            &config,
+            &sender,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+        drop(sender);
+        let messages: Vec<_> = receiver.iter().collect();
+        assert_eq!(
+            messages,
+            vec![
+                BuildEvent::Message("community: Test Region unchanged, skipping download.".into()),
+                BuildEvent::Message("community: parsing data for Test Region".into()),
+            ]
+        );
 
         // The local (already existing) file still gets parsed -- no download.
         let total: i64 = connection
@@ -967,9 +1130,17 @@ mod tests {
         let map_url_base = format!("{}/", server.uri());
         let config = CommunityConfig::default();
 
-        process(&connection, &client, &sde_dir, &map_url_base, &config)
-            .await
-            .unwrap();
+        process(
+            &connection,
+            &client,
+            &sde_dir,
+            &map_url_base,
+            &config,
+            &Silent,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
 
         let total: i64 = connection
             .query_row("SELECT COUNT(*) FROM mapAbstractSystems", [], |row| {
@@ -979,6 +1150,56 @@ mod tests {
         assert_eq!(total, 1, "should succeed after retrying the download");
     }
 
+    #[tokio::test]
+    async fn process_stops_when_cancelled() {
+        let connection = setup_for_process();
+        let server = wiremock::MockServer::start().await;
+        // Nothing should be requested once cancelled.
+        wiremock::Mock::given(wiremock::matchers::any())
+            .respond_with(wiremock::ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let client = http::build_client().unwrap();
+        let sde_dir = temp_sde_dir("cancelled");
+        let map_url_base = format!("{}/", server.uri());
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+
+        let result = process(
+            &connection,
+            &client,
+            &sde_dir,
+            &map_url_base,
+            &CommunityConfig::default(),
+            &Silent,
+            &cancellation,
+        )
+        .await;
+
+        assert!(matches!(result, Err(BuilderError::Cancelled)));
+    }
+
+    #[test]
+    fn community_layers_round_trip_through_their_names() {
+        for layer in CommunityLayer::ALL {
+            assert_eq!(layer.name().parse::<CommunityLayer>(), Ok(layer));
+        }
+        assert!("ice".parse::<CommunityLayer>().is_err());
+
+        let config =
+            CommunityConfig::with_layers(&[CommunityLayer::SpecialOre, CommunityLayer::Icebelts]);
+        assert_eq!(
+            config.layers(),
+            vec![CommunityLayer::Icebelts, CommunityLayer::SpecialOre]
+        );
+        assert_eq!(
+            CommunityConfig::with_layers(&CommunityLayer::ALL),
+            CommunityConfig::ALL
+        );
+    }
+
     #[test]
     fn jove_observatory_list_has_no_duplicates_and_no_blank_lines() {
         let names: Vec<&str> = JOVE_OBSERVATORY_SYSTEMS
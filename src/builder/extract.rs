@@ -2,11 +2,19 @@
 //! preserving `maps/` (dotlan's SVGs, which come from a different
 //! source and shouldn't be lost nor re-downloaded on every build).
 //!
+//! Also reads the build number out of a zip or directory that didn't
+//! come through `builder::sde_index` (an offline build, see
+//! [`archive_release`]).
+//!
 //! `ZipArchive::extract()`, which already ships in the `zip` crate
 //! itself (version 8.x), is used here -- no auxiliary crate like
 //! `zip-extensions` is needed.
 
 use crate::builder::BuilderError;
+use crate::builder::cancel::CancellationToken;
+use crate::builder::parser::SdeFormat;
+use crate::builder::sde_index::{SdeRelease, find_sde_release};
+use std::io::Read;
 use std::path::Path;
 
 /// Decompresses `zip_path` into `destination` (creates it if it doesn't
@@ -59,13 +67,117 @@ pub fn clean_except_maps(sde_dir: &Path) -> Result<(), BuilderError> {
 /// Cleans `sde_dir` while preserving `maps/` and decompresses
 /// `zip_path` into it -- a direct composition of [`clean_except_maps`]
 /// followed by [`unzip`], in that order.
-#[tracing::instrument]
-pub fn prepare_sde_directory(zip_path: &Path, sde_dir: &Path) -> Result<(), BuilderError> {
+///
+/// `cancellation` is checked before and after unzipping (which itself
+/// can't be interrupted): cancelled, `sde_dir` is cleaned again, so a
+/// half-extracted directory isn't left for the next build to trip over,
+/// and this fails with [`BuilderError::Cancelled`].
+#[tracing::instrument(skip(cancellation))]
+pub fn prepare_sde_directory(
+    zip_path: &Path,
+    sde_dir: &Path,
+    cancellation: &CancellationToken,
+) -> Result<(), BuilderError> {
+    cancellation.check()?;
     clean_except_maps(sde_dir)?;
     unzip(zip_path, sde_dir)?;
+    if cancellation.is_cancelled() {
+        clean_except_maps(sde_dir)?;
+        return Err(BuilderError::Cancelled);
+    }
     Ok(())
 }
 
+/// Build number (and release date) of the SDE export in `zip_path`, for
+/// building from a
+/// zip that didn't come through [`super::sde_index::update_as_needed`]
+/// (and so has no `.build` file next to it).
+///
+/// Read from the `_sde.<variant>` record every export ships at its root
+/// -- the same `{"_key": "sde", "buildNumber": ...}` record
+/// `latest.jsonl` carries (in YAML, an `sde:` mapping with the same
+/// fields). An archive without one falls back to CCP's own file name,
+/// `eve-online-static-data-<build>-<variant>.zip` (without a release
+/// date); `Ok(None)` if neither says. Only a zip that can't be opened at
+/// all is an `Err`.
+#[tracing::instrument]
+pub fn archive_release(
+    zip_path: &Path,
+    format: SdeFormat,
+) -> Result<Option<SdeRelease>, BuilderError> {
+    let file = std::fs::File::open(zip_path)?;
+    let mut archive = zip::ZipArchive::new(file)?;
+    let record_name = format!("_sde.{}", format.variant());
+    let mut contents = String::new();
+    let from_record = match archive.by_name(&record_name) {
+        Ok(mut entry) => {
+            entry.read_to_string(&mut contents)?;
+            release_in_record(&contents, format)
+        }
+        Err(zip::result::ZipError::FileNotFound) => None,
+        Err(err) => return Err(err.into()),
+    };
+    Ok(from_record.or_else(|| {
+        build_number_in_file_name(zip_path).map(|build_number| SdeRelease {
+            build_number,
+            release_date: None,
+        })
+    }))
+}
+
+/// Same as [`archive_release`], for an already-extracted SDE
+/// directory: reads `<sde_dir>/_sde.<variant>`, `Ok(None)` if it's
+/// missing or doesn't carry a build number.
+#[tracing::instrument]
+pub fn directory_release(
+    sde_dir: &Path,
+    format: SdeFormat,
+) -> Result<Option<SdeRelease>, BuilderError> {
+    let path = sde_dir.join(format!("_sde.{}", format.variant()));
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => Ok(release_in_record(&contents, format)),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// `buildNumber`/`releaseDate` of the `sde` record in an
+/// `_sde.<variant>` file (same convention as [`find_sde_release`]). A
+/// YAML file
+/// that doesn't parse is treated like one without the record, not as
+/// an error -- it's only used to label the build.
+fn release_in_record(contents: &str, format: SdeFormat) -> Option<SdeRelease> {
+    match format {
+        SdeFormat::Jsonl => find_sde_release(contents),
+        SdeFormat::Yaml => {
+            let document: serde_norway::Value = serde_norway::from_str(contents).ok()?;
+            let record = document.get("sde")?;
+            let build_number = match record.get("buildNumber")? {
+                serde_norway::Value::String(build) => build.clone(),
+                serde_norway::Value::Number(build) => build.to_string(),
+                _ => return None,
+            };
+            Some(SdeRelease {
+                build_number,
+                release_date: record
+                    .get("releaseDate")
+                    .and_then(|date| date.as_str())
+                    .map(str::to_string),
+            })
+        }
+    }
+}
+
+/// `<build>` out of `eve-online-static-data-<build>-<variant>.zip`, the
+/// name CCP publishes each archive under.
+fn build_number_in_file_name(zip_path: &Path) -> Option<String> {
+    let stem = zip_path.file_stem()?.to_str()?;
+    let (build, _variant) = stem
+        .strip_prefix("eve-online-static-data-")?
+        .split_once('-')?;
+    (!build.is_empty() && build.bytes().all(|b| b.is_ascii_digit())).then(|| build.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -178,11 +290,99 @@ mod tests {
         let zip_path = dir.join("new_sde.zip");
         build_test_zip(&zip_path, &[("types.jsonl", "{\"_key\": 1}\n")]);
 
-        prepare_sde_directory(&zip_path, &sde_dir).unwrap();
+        prepare_sde_directory(&zip_path, &sde_dir, &CancellationToken::new()).unwrap();
 
         assert!(!sde_dir.join("old_data.jsonl").exists());
         assert!(sde_dir.join("maps").join("Domain.svg").exists());
         let types_content = std::fs::read_to_string(sde_dir.join("types.jsonl")).unwrap();
         assert_eq!(types_content, "{\"_key\": 1}\n");
     }
+
+    #[test]
+    fn prepare_sde_directory_stops_when_cancelled() {
+        let dir = temp_dir("prepare_cancelled");
+        let sde_dir = dir.join("sde");
+        std::fs::create_dir_all(sde_dir.join("maps")).unwrap();
+        std::fs::write(sde_dir.join("maps").join("Domain.svg"), "preserved svg").unwrap();
+        let zip_path = dir.join("new_sde.zip");
+        build_test_zip(&zip_path, &[("types.jsonl", "{\"_key\": 1}\n")]);
+        let cancellation = CancellationToken::new();
+        cancellation.cancel();
+
+        let result = prepare_sde_directory(&zip_path, &sde_dir, &cancellation);
+
+        assert!(matches!(result, Err(BuilderError::Cancelled)));
+        assert!(!sde_dir.join("types.jsonl").exists());
+        assert!(sde_dir.join("maps").join("Domain.svg").exists());
+    }
+
+    #[test]
+    fn archive_release_reads_the_sde_record() {
+        let dir = temp_dir("build_record");
+        let zip_path = dir.join("offline.zip");
+        build_test_zip(
+            &zip_path,
+            &[
+                (
+                    "_sde.jsonl",
+                    "{\"_key\": \"sde\", \"buildNumber\": 3458726, \"releaseDate\": \"2026-08-06T11:07:36Z\"}\r\n",
+                ),
+                ("types.jsonl", "{\"_key\": 1}\n"),
+            ],
+        );
+        assert_eq!(
+            archive_release(&zip_path, SdeFormat::Jsonl).unwrap(),
+            Some(SdeRelease {
+                build_number: "3458726".to_string(),
+                release_date: Some("2026-08-06T11:07:36Z".to_string()),
+            })
+        );
+
+        let yaml_path = dir.join("offline-yaml.zip");
+        build_test_zip(
+            &yaml_path,
+            &[("_sde.yaml", "sde:\n  buildNumber: 3458726\n")],
+        );
+        assert_eq!(
+            archive_release(&yaml_path, SdeFormat::Yaml)
+                .unwrap()
+                .map(|release| release.build_number),
+            Some("3458726".to_string())
+        );
+    }
+
+    #[test]
+    fn archive_release_falls_back_to_the_file_name() {
+        let dir = temp_dir("build_file_name");
+        let named = dir.join("eve-online-static-data-3458726-jsonl.zip");
+        build_test_zip(&named, &[("types.jsonl", "{\"_key\": 1}\n")]);
+        assert_eq!(
+            archive_release(&named, SdeFormat::Jsonl).unwrap(),
+            Some(SdeRelease {
+                build_number: "3458726".to_string(),
+                release_date: None,
+            })
+        );
+
+        let unnamed = dir.join("sde.zip");
+        build_test_zip(&unnamed, &[("types.jsonl", "{\"_key\": 1}\n")]);
+        assert_eq!(archive_release(&unnamed, SdeFormat::Jsonl).unwrap(), None);
+    }
+
+    #[test]
+    fn directory_release_is_none_without_the_record() {
+        let dir = temp_dir("build_directory");
+        assert_eq!(directory_release(&dir, SdeFormat::Jsonl).unwrap(), None);
+        std::fs::write(
+            dir.join("_sde.jsonl"),
+            "{\"_key\": \"sde\", \"buildNumber\": 42}\n",
+        )
+        .unwrap();
+        assert_eq!(
+            directory_release(&dir, SdeFormat::Jsonl)
+                .unwrap()
+                .map(|release| release.build_number),
+            Some("42".to_string())
+        );
+    }
 }
@@ -4,15 +4,26 @@
 //!
 //! Async, and able to run many checks in parallel (`fingerprint_many`)
 //! instead of one at a time.
+//!
+//! The SDE zip itself (100+ MB) goes through [`download_resumable`]
+//! instead: a partial file left by an interrupted run is resumed with an
+//! HTTP `Range` request rather than restarted, failed attempts are
+//! retried with backoff ([`RetryPolicy`]), and the finished file is
+//! checked against the size and hash published for it
+//! ([`ExpectedDownload`]).
 
 use crate::builder::BuilderError;
+use crate::builder::cancel::CancellationToken;
 use crate::builder::manifest::MapFingerprint;
+use crate::builder::progress::{BuildEvent, BuildObserver};
 use futures::StreamExt;
 use reqwest::Client;
 use reqwest::header::{HeaderMap, HeaderName};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::path::Path;
-use tokio::io::AsyncWriteExt;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
 /// Progress of an ongoing download: bytes downloaded so far and, if the
 /// server reported it, the expected total (to draw a determinate
@@ -42,49 +53,54 @@ fn header_string(headers: &HeaderMap, name: HeaderName) -> Option<String> {
 }
 
 /// Does a `HEAD` request to `url` and builds its fingerprint
-/// (ETag/Last-Modified/Content-Length). Returns `None` if it couldn't be
-/// verified -- no network, timeout, or a non-2xx status -- so a
-/// one-off network hiccup doesn't block the build.
+/// (ETag/Last-Modified/Content-Length). Fails if it couldn't be
+/// verified -- no network, timeout, or a non-2xx status; callers treat
+/// that as "unknown" rather than stopping, so a one-off network hiccup
+/// doesn't block the build.
 #[tracing::instrument]
-pub async fn fingerprint(client: &Client, url: &str) -> Option<MapFingerprint> {
-    let response = match client.head(url).send().await {
-        Ok(resp) => resp,
-        Err(err) => {
-            eprintln!("http: {url} can't be verified ({err})");
-            return None;
-        }
-    };
+pub async fn fingerprint(client: &Client, url: &str) -> Result<MapFingerprint, BuilderError> {
+    let response = client.head(url).send().await?;
     if !response.status().is_success() {
-        eprintln!(
-            "http: HEAD {url} responded with status {}",
-            response.status()
-        );
-        return None;
+        return Err(BuilderError::HttpStatus {
+            url: url.to_string(),
+            status: response.status().as_u16(),
+        });
     }
     let headers = response.headers();
-    Some(MapFingerprint {
+    Ok(MapFingerprint {
         etag: header_string(headers, reqwest::header::ETAG),
         last_modified: header_string(headers, reqwest::header::LAST_MODIFIED),
         content_length: header_string(headers, reqwest::header::CONTENT_LENGTH),
     })
 }
 
-/// Runs `fingerprint()` for many URLs in parallel, with a concurrency
+/// Runs `fingerprint()` for many URLs in parallel (`None` for the ones
+/// that couldn't be verified, each reported to `observer` as a
+/// [`BuildEvent::Warning`] with its URL and error), with a concurrency
 /// cap (so we don't hammer dotlan with hundreds of simultaneous
 /// requests). `items` are `(key, url)` pairs; the key is typically the
 /// region name, and it's returned as-is so it can be cross-referenced
 /// against the manifest.
-#[tracing::instrument(skip(items))]
+#[tracing::instrument(skip(items, observer))]
 pub async fn fingerprint_many(
     client: &Client,
     items: impl IntoIterator<Item = (String, String)>,
     concurrency: usize,
+    observer: &dyn BuildObserver,
 ) -> HashMap<String, Option<MapFingerprint>> {
     futures::stream::iter(items)
         .map(|(key, url)| {
             let client = client.clone();
             async move {
-                let fp = fingerprint(&client, &url).await;
+                let fp = match fingerprint(&client, &url).await {
+                    Ok(fp) => Some(fp),
+                    Err(err) => {
+                        observer.event(BuildEvent::Warning(format!(
+                            "http: {url} can't be verified ({err})"
+                        )));
+                        None
+                    }
+                };
                 (key, fp)
             }
         })
@@ -151,10 +167,298 @@ pub async fn download(
     Ok(downloaded_bytes)
 }
 
+/// What a download is checked against once finished, in
+/// [`download_resumable`]. Each check only runs when its value is known.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpectedDownload {
+    /// Size in bytes.
+    pub size: Option<u64>,
+    /// SHA-256 of the whole file, as hex (either case).
+    pub sha256: Option<String>,
+}
+
+/// How [`download_resumable`] retries a failed attempt: up to `attempts`
+/// in total, waiting `initial_delay` before the second one and twice as
+/// long before each one after that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub initial_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// 4 attempts, 1s then 2s then 4s apart.
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 4,
+            initial_delay: Duration::from_secs(1),
+        }
+    }
+}
+
+/// Downloads `url` to `destination` like [`download`], but built for
+/// large files over unreliable connections. Returns the size of the
+/// finished file.
+///
+/// - **Resume**: if `destination` already holds part of the file (an
+///   earlier, interrupted run, or a failed attempt of this one), only
+///   the rest is requested (`Range: bytes=<len>-`) and appended. A
+///   server that ignores the range (`200` instead of `206`) gets the
+///   file rewritten from the start; one that answers
+///   `416 Range Not Satisfiable` either confirms the file is already
+///   complete or gets it restarted. The caller must make sure a partial
+///   `destination` is of the same file as `url` -- nothing here can tell
+///   two builds apart.
+/// - **Retries**: a network error, a `5xx`/`429` status, a body that
+///   ends early, or a corrupt result (below) is retried per `retry`,
+///   resuming where the previous attempt stopped. Any other status
+///   fails right away.
+/// - **Validation**: the finished file must be as long as the server
+///   said (`Content-Length`/`Content-Range`) and match `expected`; a
+///   mismatch with `expected` is a [`BuilderError::CorruptDownload`],
+///   and the file is deleted (a resume would only build on it).
+///
+/// `observer` gets a [`BuildEvent::Download`] per chunk, counting bytes
+/// of the whole file, not of the attempt (a resumed download starts
+/// where the partial file ends), and a [`BuildEvent::Warning`] per
+/// attempt that's retried.
+///
+/// `cancellation` is checked before each attempt and after each chunk:
+/// a cancelled download stops with [`BuilderError::Cancelled`] and keeps
+/// what it had downloaded in `destination`, like an interrupted one, for
+/// the next call to resume.
+#[tracing::instrument(skip(observer))]
+pub async fn download_resumable(
+    client: &Client,
+    url: &str,
+    destination: &Path,
+    expected: &ExpectedDownload,
+    retry: &RetryPolicy,
+    observer: &dyn BuildObserver,
+    cancellation: &CancellationToken,
+) -> Result<u64, BuilderError> {
+    let mut on_progress = |progress| {
+        observer.event(BuildEvent::Download {
+            url: url.to_string(),
+            progress,
+        })
+    };
+    if let Some(parent) = destination.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    let attempts = retry.attempts.max(1);
+    let mut delay = retry.initial_delay;
+    for attempt in 1.. {
+        cancellation.check()?;
+        let result = match download_attempt(
+            client,
+            url,
+            destination,
+            expected,
+            &mut on_progress,
+            cancellation,
+        )
+        .await
+        {
+            Ok(size) => check_download(url, destination, expected)
+                .await
+                .map(|()| size),
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(size) => return Ok(size),
+            Err(err) if attempt < attempts && is_retryable(&err) => {
+                observer.event(BuildEvent::Warning(format!(
+                    "http: downloading {url} failed ({err}), retrying in {delay:?} \
+                     (attempt {attempt} of {attempts})"
+                )));
+                tokio::time::sleep(delay).await;
+                delay = delay.saturating_mul(2);
+            }
+            Err(err) => return Err(err),
+        }
+    }
+    unreachable!("the last attempt always returns")
+}
+
+/// Whether [`download_resumable`] tries again after `err`.
+fn is_retryable(err: &BuilderError) -> bool {
+    match err {
+        BuilderError::Http(_) | BuilderError::CorruptDownload { .. } => true,
+        BuilderError::HttpStatus { status, .. } => *status >= 500 || *status == 429,
+        BuilderError::Io(err) => err.kind() == std::io::ErrorKind::UnexpectedEof,
+        _ => false,
+    }
+}
+
+/// `(first byte, total size)` of a `Content-Range: bytes <first>-<last>/<total>`
+/// (or `bytes */<total>`) header; the total is `None` when it's `*`.
+fn content_range(headers: &HeaderMap) -> Option<(Option<u64>, Option<u64>)> {
+    let value = header_string(headers, reqwest::header::CONTENT_RANGE)?;
+    let (range, total) = value.strip_prefix("bytes ")?.split_once('/')?;
+    let first = range
+        .split_once('-')
+        .and_then(|(first, _)| first.parse().ok());
+    Some((first, total.parse().ok()))
+}
+
+/// One attempt of [`download_resumable`], resuming from whatever
+/// `destination` already holds. Returns the file's size once the body
+/// ended.
+async fn download_attempt(
+    client: &Client,
+    url: &str,
+    destination: &Path,
+    expected: &ExpectedDownload,
+    on_progress: &mut impl FnMut(DownloadProgress),
+    cancellation: &CancellationToken,
+) -> Result<u64, BuilderError> {
+    let mut offset = match tokio::fs::metadata(destination).await {
+        Ok(metadata) => metadata.len(),
+        Err(_) => 0,
+    };
+    if let Some(size) = expected.size {
+        if offset == size {
+            // Complete already; `check_download` has the last word.
+            return Ok(offset);
+        }
+        if offset > size {
+            tokio::fs::remove_file(destination).await?;
+            offset = 0;
+        }
+    }
+
+    let mut request = client.get(url);
+    if offset > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={offset}-"));
+    }
+    let response = request.send().await?;
+    let status = response.status();
+
+    let (mut file, start, total_bytes) = if offset > 0
+        && status == reqwest::StatusCode::PARTIAL_CONTENT
+    {
+        let (first, total) = content_range(response.headers()).unwrap_or((None, None));
+        if first != Some(offset) {
+            tokio::fs::remove_file(destination).await?;
+            return Err(BuilderError::CorruptDownload {
+                url: url.to_string(),
+                reason: format!("asked to resume at byte {offset}, got {first:?}"),
+            });
+        }
+        let total = total.or_else(|| response.content_length().map(|length| offset + length));
+        let file = tokio::fs::OpenOptions::new()
+            .append(true)
+            .open(destination)
+            .await?;
+        (file, offset, total)
+    } else if offset > 0 && status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+        // Nothing left past `offset`: the file is either complete or
+        // longer than it should be.
+        let total = content_range(response.headers()).and_then(|(_, total)| total);
+        if total == Some(offset) {
+            return Ok(offset);
+        }
+        tokio::fs::remove_file(destination).await?;
+        return Err(BuilderError::CorruptDownload {
+            url: url.to_string(),
+            reason: format!("the partial file ({offset} bytes) doesn't fit the file on the server"),
+        });
+    } else if status.is_success() {
+        let file = tokio::fs::File::create(destination).await?;
+        (file, 0, response.content_length())
+    } else {
+        return Err(BuilderError::HttpStatus {
+            url: url.to_string(),
+            status: status.as_u16(),
+        });
+    };
+
+    let mut downloaded_bytes = start;
+    on_progress(DownloadProgress {
+        downloaded_bytes,
+        total_bytes,
+    });
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        file.write_all(&chunk).await?;
+        downloaded_bytes += chunk.len() as u64;
+        on_progress(DownloadProgress {
+            downloaded_bytes,
+            total_bytes,
+        });
+        if cancellation.is_cancelled() {
+            file.flush().await?;
+            return Err(BuilderError::Cancelled);
+        }
+    }
+    file.flush().await?;
+    if let Some(total) = total_bytes
+        && downloaded_bytes < total
+    {
+        return Err(BuilderError::Io(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            format!("{url} ended after {downloaded_bytes} of {total} bytes"),
+        )));
+    }
+    Ok(downloaded_bytes)
+}
+
+/// Checks the finished `destination` against `expected`, deleting it on
+/// a mismatch (see [`download_resumable`]).
+async fn check_download(
+    url: &str,
+    destination: &Path,
+    expected: &ExpectedDownload,
+) -> Result<(), BuilderError> {
+    let mut problem = None;
+    let size = tokio::fs::metadata(destination).await?.len();
+    if let Some(expected_size) = expected.size
+        && size != expected_size
+    {
+        problem = Some(format!("{size} bytes, expected {expected_size}"));
+    }
+    if problem.is_none()
+        && let Some(expected_sha256) = &expected.sha256
+    {
+        let sha256 = sha256_of(destination).await?;
+        if !sha256.eq_ignore_ascii_case(expected_sha256) {
+            problem = Some(format!("SHA-256 {sha256}, expected {expected_sha256}"));
+        }
+    }
+    match problem {
+        None => Ok(()),
+        Some(reason) => {
+            tokio::fs::remove_file(destination).await?;
+            Err(BuilderError::CorruptDownload {
+                url: url.to_string(),
+                reason,
+            })
+        }
+    }
+}
+
+/// SHA-256 of the file at `path`, as lowercase hex.
+async fn sha256_of(path: &Path) -> Result<String, BuilderError> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use wiremock::matchers::{method, path};
+    use crate::builder::progress::Silent;
+    use wiremock::matchers::{header, method, path};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
     #[tokio::test]
@@ -173,9 +477,7 @@ mod tests {
 
         let client = build_client().unwrap();
         let url = format!("{}/The_Forge.svg", server.uri());
-        let fp = fingerprint(&client, &url)
-            .await
-            .expect("should return Some");
+        let fp = fingerprint(&client, &url).await.expect("should succeed");
 
         assert_eq!(fp.etag.as_deref(), Some("\"abc123\""));
         assert_eq!(fp.content_length.as_deref(), Some("184320"));
@@ -192,7 +494,24 @@ mod tests {
 
         let client = build_client().unwrap();
         let url = format!("{}/Does_Not_Exist.svg", server.uri());
-        assert!(fingerprint(&client, &url).await.is_none());
+        assert!(fingerprint(&client, &url).await.ok().is_none());
+    }
+
+    #[tokio::test]
+    async fn fingerprint_fails_on_404() {
+        let server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .and(path("/Does_Not_Exist.svg"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let client = build_client().unwrap();
+        let url = format!("{}/Does_Not_Exist.svg", server.uri());
+        assert!(matches!(
+            fingerprint(&client, &url).await,
+            Err(BuilderError::HttpStatus { status: 404, .. })
+        ));
     }
 
     #[tokio::test]
@@ -247,10 +566,22 @@ mod tests {
             ("Domain".to_string(), format!("{}/Domain.svg", server.uri())),
             ("Impass".to_string(), format!("{}/Impass.svg", server.uri())),
         ];
-        let results = fingerprint_many(&client, items, 4).await;
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let results = fingerprint_many(&client, items, 4, &sender).await;
+        drop(sender);
 
         assert!(results.get("Domain").unwrap().is_some());
         assert!(results.get("Impass").unwrap().is_none());
+        let warnings: Vec<String> = receiver
+            .iter()
+            .filter_map(|event| match event {
+                BuildEvent::Warning(message) => Some(message),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("/Impass.svg"), "{}", warnings[0]);
+        assert!(warnings[0].contains("404"), "{}", warnings[0]);
     }
 
     #[tokio::test]
@@ -301,4 +632,250 @@ mod tests {
             Err(BuilderError::HttpStatus { status: 500, .. })
         ));
     }
+
+    const ZIP_BODY: &[u8] = b"0123456789abcdef";
+
+    /// No waiting between attempts.
+    const IMPATIENT: RetryPolicy = RetryPolicy {
+        attempts: 3,
+        initial_delay: Duration::ZERO,
+    };
+
+    fn temp_destination(name: &str) -> std::path::PathBuf {
+        let dir = std::env::temp_dir().join(format!("sde-http-test-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir.join("sde.zip.tmp")
+    }
+
+    fn sha256_hex(bytes: &[u8]) -> String {
+        format!("{:x}", Sha256::digest(bytes))
+    }
+
+    #[tokio::test]
+    async fn download_resumable_resumes_a_partial_file() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/sde.zip"))
+            .and(header("Range", "bytes=10-"))
+            .respond_with(
+                ResponseTemplate::new(206)
+                    .insert_header("Content-Range", "bytes 10-15/16")
+                    .set_body_bytes(&ZIP_BODY[10..]),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let destination = temp_destination("resume");
+        std::fs::write(&destination, &ZIP_BODY[..10]).unwrap();
+        let client = build_client().unwrap();
+        let expected = ExpectedDownload {
+            size: Some(16),
+            sha256: Some(sha256_hex(ZIP_BODY).to_uppercase()),
+        };
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let size = download_resumable(
+            &client,
+            &format!("{}/sde.zip", server.uri()),
+            &destination,
+            &expected,
+            &IMPATIENT,
+            &sender,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+        drop(sender);
+        let progress: Vec<_> = receiver
+            .iter()
+            .filter_map(|event| match event {
+                BuildEvent::Download { progress, .. } => Some(progress),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(size, 16);
+        assert_eq!(std::fs::read(&destination).unwrap(), ZIP_BODY);
+        assert_eq!(
+            progress.first(),
+            Some(&DownloadProgress {
+                downloaded_bytes: 10,
+                total_bytes: Some(16)
+            })
+        );
+        assert_eq!(progress.last().unwrap().downloaded_bytes, 16);
+        std::fs::remove_dir_all(destination.parent().unwrap()).ok();
+    }
+
+    #[tokio::test]
+    async fn download_resumable_starts_over_when_the_range_is_ignored() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/sde.zip"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(ZIP_BODY))
+            .mount(&server)
+            .await;
+
+        let destination = temp_destination("ignored_range");
+        std::fs::write(&destination, b"stale").unwrap();
+        let client = build_client().unwrap();
+        download_resumable(
+            &client,
+            &format!("{}/sde.zip", server.uri()),
+            &destination,
+            &ExpectedDownload::default(),
+            &IMPATIENT,
+            &Silent,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(std::fs::read(&destination).unwrap(), ZIP_BODY);
+        std::fs::remove_dir_all(destination.parent().unwrap()).ok();
+    }
+
+    #[tokio::test]
+    async fn download_resumable_retries_server_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/sde.zip"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/sde.zip"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(ZIP_BODY))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let destination = temp_destination("retry");
+        let client = build_client().unwrap();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let size = download_resumable(
+            &client,
+            &format!("{}/sde.zip", server.uri()),
+            &destination,
+            &ExpectedDownload::default(),
+            &IMPATIENT,
+            &sender,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+        drop(sender);
+
+        assert_eq!(size, 16);
+        let warnings = receiver
+            .iter()
+            .filter(|event| matches!(event, BuildEvent::Warning(_)))
+            .count();
+        assert_eq!(warnings, 2);
+        std::fs::remove_dir_all(destination.parent().unwrap()).ok();
+    }
+
+    #[tokio::test]
+    async fn download_resumable_does_not_retry_a_missing_file() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/sde.zip"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let destination = temp_destination("missing");
+        let client = build_client().unwrap();
+        let result = download_resumable(
+            &client,
+            &format!("{}/sde.zip", server.uri()),
+            &destination,
+            &ExpectedDownload::default(),
+            &IMPATIENT,
+            &Silent,
+            &CancellationToken::new(),
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(BuilderError::HttpStatus { status: 404, .. })
+        ));
+        std::fs::remove_dir_all(destination.parent().unwrap()).ok();
+    }
+
+    #[tokio::test]
+    async fn download_resumable_keeps_the_partial_file_when_cancelled() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/sde.zip"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(ZIP_BODY))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let destination = temp_destination("cancelled");
+        let client = build_client().unwrap();
+        let cancellation = CancellationToken::new();
+        // Cancelled as soon as the first bytes arrive.
+        let observer = |event| {
+            if let BuildEvent::Download { progress, .. } = event
+                && progress.downloaded_bytes > 0
+            {
+                cancellation.cancel();
+            }
+        };
+        let result = download_resumable(
+            &client,
+            &format!("{}/sde.zip", server.uri()),
+            &destination,
+            &ExpectedDownload::default(),
+            &IMPATIENT,
+            &observer,
+            &cancellation,
+        )
+        .await;
+
+        assert!(matches!(result, Err(BuilderError::Cancelled)));
+        assert!(destination.exists());
+        std::fs::remove_dir_all(destination.parent().unwrap()).ok();
+    }
+
+    #[tokio::test]
+    async fn download_resumable_rejects_and_deletes_a_corrupt_file() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/sde.zip"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(ZIP_BODY))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let destination = temp_destination("corrupt");
+        let client = build_client().unwrap();
+        let result = download_resumable(
+            &client,
+            &format!("{}/sde.zip", server.uri()),
+            &destination,
+            &ExpectedDownload {
+                size: None,
+                sha256: Some(sha256_hex(b"something else")),
+            },
+            &IMPATIENT,
+            &Silent,
+            &CancellationToken::new(),
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(BuilderError::CorruptDownload { ref reason, .. }) if reason.starts_with("SHA-256")
+        ));
+        assert!(!destination.exists());
+        std::fs::remove_dir_all(destination.parent().unwrap()).ok();
+    }
 }
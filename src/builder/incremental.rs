@@ -0,0 +1,706 @@
+//! In-place update of an existing `sde.db`: the new SDE's records are
+//! decoded by the usual pipeline ([`super::parser::Parser::update_database`])
+//! and written straight into the existing tables, where each one is
+//! compared with the row stored under the same primary key, all in one
+//! transaction -- no second database is built.
+//!
+//! The phases themselves don't change: they still `INSERT` every
+//! record. What `Tracker::start` adds, for the length of the
+//! transaction, is a `TEMP` trigger on every table that turns each of
+//! those inserts into a comparison by key (`pragma_table_info`):
+//!
+//! - no stored row with that key: the row is inserted, as in a build;
+//! - a stored row whose other columns all match: nothing is written
+//!   (the insert is dropped with `RAISE(IGNORE)`), so neither is any
+//!   of its indexes;
+//! - a stored row that differs: it's updated in place, and its previous
+//!   version kept aside to count it.
+//!
+//! Either way the key is recorded as written. Once every phase ran,
+//! `Tracker::delete_unwritten` deletes the stored rows whose key no
+//! record wrote -- what the new SDE no longer has. On a typical new
+//! build (a few renamed types, some moved stations) that's a handful of
+//! writes instead of a whole new file, and readers keep the previous
+//! database until the transaction commits.
+//!
+//! A phase that reads a table an earlier phase wrote (connections
+//! derived from the gates, stations resolving their moon or planet,
+//! the scope lookup of systems) reads it through `written`, which
+//! leaves out rows no record of this update wrote yet -- otherwise a
+//! gate the new SDE dropped would still yield its connection.
+//!
+//! ## Foreign keys
+//!
+//! The transaction runs with foreign key enforcement on and `PRAGMA
+//! defer_foreign_keys` set, so every constraint -- the `DEFERRABLE
+//! INITIALLY DEFERRED` ones in `schema.sql` and the immediate ones
+//! alike -- is checked once, at commit, after the parser's own
+//! `foreign_key_check` (see [`super::parser::Parser::parse_data`]).
+//!
+//! `ON DELETE` actions still fire mid-transaction, deferred or not, so
+//! the deletions run children first (the order `pragma_foreign_key_list`
+//! implies, reversed; changes are reported parents first), once every
+//! row that stays is already in its final state: by the time a parent
+//! row goes, the rows that referenced it have either been repointed or
+//! deleted already. The one case order can't cover is a table
+//! referencing itself: deleting one of a pair of `mapSystemGates` would
+//! set its partner's `destinationGateId` (`NOT NULL`) to `NULL` before
+//! the partner itself is deleted. So rows about to be deleted are first
+//! made to reference themselves, which leaves no other row for their
+//! deletion to act on.
+//!
+//! ## What can be updated
+//!
+//! Only a database with the exact same shape as a new build: same
+//! schema version, same tables, same columns as [`create_schema`]
+//! makes. That isn't the case after a crate upgrade that changed
+//! `schema.sql`, or for a database built with `--with-third-party`
+//! (`builder::community` adds tables and columns of its own, with DDL
+//! that only runs on a new database) -- `Tracker::start` then fails
+//! with [`BuilderError::IncompatibleDatabase`] before touching
+//! anything, and a full rebuild is needed instead. Other options
+//! (scope, projections, gates, moons, language) can differ from the
+//! ones the database was built with: the rows they change are updated
+//! like any other.
+//!
+//! `buildIssues` (keyed by an id SQLite assigns) isn't compared: it's
+//! emptied and refilled by the update. The search index
+//! (`builder::search`) is derived data, not compared either: it's
+//! dropped here and rebuilt by the parser's last phase.
+
+use crate::builder::BuilderError;
+use crate::builder::schema::create_schema;
+use rusqlite::{Connection, Transaction};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Rows an update wrote to one table.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TableChanges {
+    pub inserted: usize,
+    pub updated: usize,
+    pub deleted: usize,
+}
+
+impl TableChanges {
+    /// Whether the table was left untouched.
+    pub fn is_empty(&self) -> bool {
+        self.inserted == 0 && self.updated == 0 && self.deleted == 0
+    }
+}
+
+/// The table emptied and refilled rather than compared (see the module
+/// docs).
+const REWRITTEN_TABLE: &str = "buildIssues";
+
+/// Column names of one table, in declaration order, and the subset that
+/// identifies a row (see the module docs).
+struct TableShape {
+    columns: Vec<String>,
+    key_columns: Vec<String>,
+}
+
+/// Double-quotes `name` as an SQL identifier.
+fn quoted(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// `TEMP` table holding the keys written to `table` so far.
+fn written_keys_table(table: &str) -> String {
+    format!("{table}Written")
+}
+
+/// `TEMP` table holding the previous version of the rows of `table` an
+/// update changed.
+fn previous_rows_table(table: &str) -> String {
+    format!("{table}Previous")
+}
+
+/// `TEMP` trigger comparing what's inserted into `table` with what's
+/// stored.
+fn update_trigger(table: &str) -> String {
+    format!("{table}Update")
+}
+
+/// Ordinary tables of `schema`: no views, no FTS5 virtual tables or
+/// their shadow tables (the search index), no `sqlite_*` internals.
+fn table_names(connection: &Connection, schema: &str) -> rusqlite::Result<BTreeSet<String>> {
+    let mut statement = connection.prepare(
+        "SELECT name FROM pragma_table_list WHERE schema = ?1 AND type = 'table' \
+         AND name NOT LIKE 'sqlite\\_%' ESCAPE '\\'",
+    )?;
+    statement.query_map([schema], |row| row.get(0))?.collect()
+}
+
+/// Shape of `schema.table`.
+fn table_shape(connection: &Connection, schema: &str, table: &str) -> rusqlite::Result<TableShape> {
+    let mut statement =
+        connection.prepare("SELECT name, pk FROM pragma_table_info(?1, ?2) ORDER BY cid")?;
+    let columns = statement
+        .query_map([table, schema], |row| {
+            Ok((row.get::<usize, String>(0)?, row.get::<usize, i64>(1)?))
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    let mut key_columns: Vec<&(String, i64)> = columns.iter().filter(|(_, pk)| *pk > 0).collect();
+    key_columns.sort_by_key(|(_, pk)| *pk);
+    let key_columns = if key_columns.is_empty() {
+        columns.iter().map(|(name, _)| name.clone()).collect()
+    } else {
+        key_columns
+            .into_iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    };
+    Ok(TableShape {
+        columns: columns.into_iter().map(|(name, _)| name).collect(),
+        key_columns,
+    })
+}
+
+/// `tables` ordered parents first, by the foreign keys in `schema`.
+/// Self-references don't count; a cycle (none in `schema.sql`) is broken
+/// by falling back to name order for what's left.
+fn dependency_order(
+    connection: &Connection,
+    schema: &str,
+    tables: &BTreeSet<String>,
+) -> rusqlite::Result<Vec<String>> {
+    let mut parents: BTreeMap<&str, BTreeSet<String>> = BTreeMap::new();
+    let mut statement =
+        connection.prepare("SELECT DISTINCT \"table\" FROM pragma_foreign_key_list(?1, ?2)")?;
+    for table in tables {
+        let referenced = statement
+            .query_map([table.as_str(), schema], |row| row.get::<usize, String>(0))?
+            .collect::<rusqlite::Result<BTreeSet<_>>>()?;
+        parents.insert(
+            table,
+            referenced
+                .into_iter()
+                .filter(|parent| parent != table && tables.contains(parent))
+                .collect(),
+        );
+    }
+
+    let mut order = Vec::with_capacity(tables.len());
+    while !parents.is_empty() {
+        let ready: Vec<&str> = parents
+            .iter()
+            .filter(|(_, pending)| pending.is_empty())
+            .map(|(table, _)| *table)
+            .collect();
+        let ready = if ready.is_empty() {
+            parents.keys().copied().collect()
+        } else {
+            ready
+        };
+        for table in ready {
+            parents.remove(table);
+            for pending in parents.values_mut() {
+                pending.remove(table);
+            }
+            order.push(table.to_string());
+        }
+    }
+    Ok(order)
+}
+
+/// Checks the database on `connection` has the shape [`create_schema`]
+/// gives a new one (compared against an empty in-memory copy), and
+/// returns each table's shape.
+fn compatible_shapes(
+    connection: &Connection,
+) -> Result<BTreeMap<String, TableShape>, BuilderError> {
+    let incompatible = |reason: String| Err(BuilderError::IncompatibleDatabase(reason));
+    let expected_schema = Connection::open_in_memory()?;
+    create_schema(&expected_schema)?;
+
+    let found: u32 = connection.query_row("PRAGMA main.user_version", [], |row| row.get(0))?;
+    if found != crate::SCHEMA_VERSION {
+        return incompatible(format!(
+            "schema version {found}, the new build has {}",
+            crate::SCHEMA_VERSION
+        ));
+    }
+
+    let existing = table_names(connection, "main")?;
+    let expected = table_names(&expected_schema, "main")?;
+    if existing != expected {
+        let missing: Vec<&str> = expected.difference(&existing).map(String::as_str).collect();
+        let extra: Vec<&str> = existing.difference(&expected).map(String::as_str).collect();
+        return incompatible(format!(
+            "tables differ (missing: {}; not in the new build: {})",
+            missing.join(", "),
+            extra.join(", ")
+        ));
+    }
+
+    let mut shapes = BTreeMap::new();
+    for table in expected {
+        let shape = table_shape(&expected_schema, "main", &table)?;
+        let existing_shape = table_shape(connection, "main", &table)?;
+        let sorted = |columns: &[String]| columns.iter().cloned().collect::<BTreeSet<_>>();
+        if sorted(&shape.columns) != sorted(&existing_shape.columns)
+            || shape.key_columns != existing_shape.key_columns
+        {
+            return incompatible(format!("columns of {table} differ"));
+        }
+        shapes.insert(table, existing_shape);
+    }
+    Ok(shapes)
+}
+
+/// `alias.key IS other.key AND ...` over `key_columns`.
+fn key_match(key_columns: &[String], alias: &str, other: &str) -> String {
+    key_columns
+        .iter()
+        .map(|key| format!("{alias}.{0} IS {other}.{0}", quoted(key)))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+/// `alias.column IS NOT other.column OR ...` over `columns`, or `0` for
+/// none.
+fn any_differs(columns: &[&String], alias: &str, other: &str) -> String {
+    if columns.is_empty() {
+        return "0".to_string();
+    }
+    columns
+        .iter()
+        .map(|column| format!("{alias}.{0} IS NOT {other}.{0}", quoted(column)))
+        .collect::<Vec<_>>()
+        .join(" OR ")
+}
+
+/// `table` as an SQL source (`FROM {written(..)} AS alias`) of the rows
+/// written to it so far: in the middle of an update, only those whose
+/// key a record of the update wrote (see the module docs); otherwise
+/// the whole table, everything in it having been written by this
+/// build.
+pub(crate) fn written(connection: &Connection, table: &str) -> rusqlite::Result<String> {
+    let tracked: bool = connection.query_row(
+        "SELECT EXISTS (SELECT 1 FROM temp.sqlite_master WHERE type = 'table' AND name = ?1)",
+        [written_keys_table(table)],
+        |row| row.get(0),
+    )?;
+    if !tracked {
+        return Ok(quoted(table));
+    }
+    let shape = table_shape(connection, "main", table)?;
+    Ok(format!(
+        "(SELECT t.* FROM main.{} AS t WHERE EXISTS (SELECT 1 FROM temp.{} AS w WHERE {}))",
+        quoted(table),
+        quoted(&written_keys_table(table)),
+        key_match(&shape.key_columns, "w", "t")
+    ))
+}
+
+/// Sets up `table`'s trigger and `TEMP` tables (see the module docs).
+fn track_table(tx: &Transaction<'_>, table: &str, shape: &TableShape) -> rusqlite::Result<()> {
+    let table_sql = quoted(table);
+    let written_sql = quoted(&written_keys_table(table));
+    let previous_sql = quoted(&previous_rows_table(table));
+    let keys = shape
+        .key_columns
+        .iter()
+        .map(|key| quoted(key))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let new_keys = shape
+        .key_columns
+        .iter()
+        .map(|key| format!("NEW.{}", quoted(key)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let value_columns: Vec<&String> = shape
+        .columns
+        .iter()
+        .filter(|column| !shape.key_columns.contains(column))
+        .collect();
+    let stored = format!(
+        "SELECT 1 FROM main.{table_sql} AS t WHERE {}",
+        key_match(&shape.key_columns, "t", "NEW")
+    );
+
+    // Trigger bodies can't qualify the table they write to: `"T"` is
+    // `main`'s, the others are `TEMP` ones, no name being in both.
+    let update = if value_columns.is_empty() {
+        String::new()
+    } else {
+        let assignments = value_columns
+            .iter()
+            .map(|column| format!("{0} = NEW.{0}", quoted(column)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        format!(
+            "INSERT INTO {previous_sql} SELECT * FROM main.{table_sql} AS t WHERE {} AND ({}); \
+             UPDATE {table_sql} SET {assignments} WHERE {} AND ({});",
+            key_match(&shape.key_columns, "t", "NEW"),
+            any_differs(&value_columns, "t", "NEW"),
+            key_match(&shape.key_columns, &table_sql, "NEW"),
+            any_differs(&value_columns, &table_sql, "NEW"),
+        )
+    };
+    tx.execute_batch(&format!(
+        "CREATE TEMP TABLE {written_sql} AS SELECT {keys} FROM main.{table_sql} WHERE 0; \
+         CREATE INDEX temp.{} ON {written_sql} ({keys}); \
+         CREATE TEMP TABLE {previous_sql} AS SELECT * FROM main.{table_sql} WHERE 0; \
+         INSERT INTO temp.updateInserts (tableName, inserted) VALUES ('{}', 0); \
+         CREATE TEMP TRIGGER {} BEFORE INSERT ON main.{table_sql} BEGIN \
+             SELECT RAISE(ABORT, 'UNIQUE constraint failed: {}') \
+                 WHERE EXISTS (SELECT 1 FROM {written_sql} AS w WHERE {}); \
+             INSERT INTO {written_sql} ({keys}) VALUES ({new_keys}); \
+             UPDATE updateInserts SET inserted = inserted + 1 \
+                 WHERE tableName = '{}' AND NOT EXISTS ({stored}); \
+             {update} \
+             SELECT RAISE(IGNORE) WHERE EXISTS ({stored}); \
+         END;",
+        quoted(&format!("{}Key", written_keys_table(table))),
+        table.replace('\'', "''"),
+        quoted(&update_trigger(table)),
+        table.replace('\'', "''"),
+        key_match(&shape.key_columns, "w", "NEW"),
+        table.replace('\'', "''"),
+    ))
+}
+
+/// Deletes `table`'s rows whose key wasn't written, returning how many.
+/// Counted beforehand rather than with `changes()`, which would miss
+/// rows of a self-referencing table removed by its own `ON DELETE
+/// CASCADE`. A self-reference of those rows is first pointed at the row
+/// itself (see the module docs), so deleting one doesn't fire its
+/// `ON DELETE` action on another.
+fn delete_table(tx: &Transaction<'_>, table: &str, shape: &TableShape) -> rusqlite::Result<usize> {
+    let table_sql = quoted(table);
+    let missing = format!(
+        "NOT EXISTS (SELECT 1 FROM temp.{} AS w WHERE {})",
+        quoted(&written_keys_table(table)),
+        key_match(&shape.key_columns, "w", &table_sql)
+    );
+    let deleted: i64 = tx.query_row(
+        &format!("SELECT COUNT(*) FROM main.{table_sql} WHERE {missing}"),
+        [],
+        |row| row.get(0),
+    )?;
+    if deleted > 0 {
+        for assignments in self_references(tx, table)? {
+            tx.execute(
+                &format!("UPDATE main.{table_sql} SET {assignments} WHERE {missing}"),
+                [],
+            )?;
+        }
+        tx.execute(&format!("DELETE FROM main.{table_sql} WHERE {missing}"), [])?;
+    }
+    Ok(deleted as usize)
+}
+
+/// `table`'s foreign keys onto itself, one per key, as the assignments
+/// pointing a row at itself (`"destinationGateId" = "systemGateId"`).
+fn self_references(tx: &Transaction<'_>, table: &str) -> rusqlite::Result<Vec<String>> {
+    let mut statement = tx.prepare(
+        "SELECT id, \"from\", \"to\" FROM pragma_foreign_key_list(?1, 'main') \
+         WHERE \"table\" = ?1 AND \"to\" IS NOT NULL ORDER BY id, seq",
+    )?;
+    let mut keys: BTreeMap<i64, Vec<String>> = BTreeMap::new();
+    for row in statement.query_map([table], |row| {
+        Ok((
+            row.get::<usize, i64>(0)?,
+            row.get::<usize, String>(1)?,
+            row.get::<usize, String>(2)?,
+        ))
+    })? {
+        let (id, from, to) = row?;
+        keys.entry(id)
+            .or_default()
+            .push(format!("{} = {}", quoted(&from), quoted(&to)));
+    }
+    Ok(keys
+        .into_values()
+        .map(|columns| columns.join(", "))
+        .collect())
+}
+
+/// Number of rows in `main.table`.
+fn row_count(tx: &Transaction<'_>, table: &str) -> rusqlite::Result<usize> {
+    let count: i64 = tx.query_row(
+        &format!("SELECT COUNT(*) FROM main.{}", quoted(table)),
+        [],
+        |row| row.get(0),
+    )?;
+    Ok(count as usize)
+}
+
+/// An update in progress on a transaction: [`Self::start`] before the
+/// parser's phases, [`Self::delete_unwritten`] once they've written
+/// every record, and [`Self::finish`] before the commit (see the module
+/// docs). Everything it sets up is `TEMP` and created in the
+/// transaction, so a rollback takes it along.
+pub(crate) struct Tracker {
+    shapes: BTreeMap<String, TableShape>,
+    /// Every table, [`REWRITTEN_TABLE`] included, parents first.
+    order: Vec<String>,
+    changes: BTreeMap<String, TableChanges>,
+}
+
+impl Tracker {
+    /// Checks the database has the shape of a new build (failing with
+    /// [`BuilderError::IncompatibleDatabase`] otherwise, having changed
+    /// nothing), defers its foreign keys, drops the search index,
+    /// empties [`REWRITTEN_TABLE`] and sets up every other table's
+    /// trigger.
+    ///
+    /// `PRAGMA foreign_keys` can't change inside a transaction: the
+    /// caller turns it on before opening `tx`.
+    pub(crate) fn start(tx: &Transaction<'_>) -> Result<Self, BuilderError> {
+        let shapes = compatible_shapes(tx)?;
+        let tables: BTreeSet<String> = shapes.keys().cloned().collect();
+        let order = dependency_order(tx, "main", &tables)?;
+
+        // Reset by SQLite itself when the transaction ends.
+        tx.execute_batch(
+            "PRAGMA defer_foreign_keys = ON; \
+             DROP TABLE IF EXISTS searchIndex; DROP TABLE IF EXISTS searchTrigrams; \
+             CREATE TEMP TABLE updateInserts (tableName TEXT NOT NULL PRIMARY KEY, inserted INTEGER NOT NULL);",
+        )?;
+        let mut changes = BTreeMap::new();
+        changes.insert(
+            REWRITTEN_TABLE.to_string(),
+            TableChanges {
+                deleted: tx.execute(&format!("DELETE FROM main.{REWRITTEN_TABLE}"), [])?,
+                ..TableChanges::default()
+            },
+        );
+        for (table, shape) in &shapes {
+            if table != REWRITTEN_TABLE {
+                track_table(tx, table, shape)?;
+            }
+        }
+        Ok(Tracker {
+            shapes,
+            order,
+            changes,
+        })
+    }
+
+    /// Deletes every row no record wrote, children first.
+    pub(crate) fn delete_unwritten(&mut self, tx: &Transaction<'_>) -> Result<(), BuilderError> {
+        for table in self.order.iter().rev() {
+            if table != REWRITTEN_TABLE {
+                let deleted = delete_table(tx, table, &self.shapes[table])?;
+                self.changes.entry(table.clone()).or_default().deleted = deleted;
+            }
+        }
+        Ok(())
+    }
+
+    /// Counts what changed per table and drops the triggers and `TEMP`
+    /// tables. Returns every table, changed or not, parents first.
+    ///
+    /// A row counts as updated if it ends up different from its
+    /// previous version: one the parser changed back afterwards (its
+    /// `foreign_key_check` clearing a key, say) doesn't.
+    pub(crate) fn finish(
+        mut self,
+        tx: &Transaction<'_>,
+    ) -> Result<Vec<(String, TableChanges)>, BuilderError> {
+        let mut changes = Vec::with_capacity(self.order.len());
+        for table in &self.order {
+            let mut table_changes = self.changes.remove(table).unwrap_or_default();
+            if table == REWRITTEN_TABLE {
+                table_changes.inserted = row_count(tx, table)?;
+            } else {
+                let shape = &self.shapes[table];
+                let inserted: i64 = tx.query_row(
+                    "SELECT inserted FROM temp.updateInserts WHERE tableName = ?1",
+                    [table],
+                    |row| row.get(0),
+                )?;
+                let columns: Vec<&String> = shape.columns.iter().collect();
+                let updated: i64 = tx.query_row(
+                    &format!(
+                        "SELECT COUNT(*) FROM temp.{} AS p WHERE NOT EXISTS \
+                         (SELECT 1 FROM main.{} AS t WHERE NOT ({}))",
+                        quoted(&previous_rows_table(table)),
+                        quoted(table),
+                        any_differs(&columns, "t", "p")
+                    ),
+                    [],
+                    |row| row.get(0),
+                )?;
+                table_changes.inserted = inserted as usize;
+                table_changes.updated = updated as usize;
+                tx.execute_batch(&format!(
+                    "DROP TRIGGER temp.{}; DROP TABLE temp.{}; DROP TABLE temp.{};",
+                    quoted(&update_trigger(table)),
+                    quoted(&written_keys_table(table)),
+                    quoted(&previous_rows_table(table))
+                ))?;
+            }
+            changes.push((table.clone(), table_changes));
+        }
+        tx.execute_batch("DROP TABLE temp.updateInserts;")?;
+        Ok(changes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An in-memory database with the schema and `sql` applied.
+    fn database(sql: &str) -> Connection {
+        let connection = Connection::open_in_memory().unwrap();
+        create_schema(&connection).unwrap();
+        connection.execute_batch(sql).unwrap();
+        connection
+    }
+
+    fn changes_of<'a>(changes: &'a [(String, TableChanges)], table: &str) -> &'a TableChanges {
+        &changes.iter().find(|(name, _)| name == table).unwrap().1
+    }
+
+    /// Runs an update on `connection` whose records are the `INSERT`s
+    /// in `sql`.
+    fn update(
+        connection: &mut Connection,
+        sql: &str,
+    ) -> Result<Vec<(String, TableChanges)>, BuilderError> {
+        let tx = connection.transaction()?;
+        let mut tracker = Tracker::start(&tx)?;
+        tx.execute_batch(sql)?;
+        tracker.delete_unwritten(&tx)?;
+        let changes = tracker.finish(&tx)?;
+        tx.commit()?;
+        Ok(changes)
+    }
+
+    #[test]
+    fn update_inserts_updates_and_deletes_by_key() {
+        let mut connection = database(
+            "INSERT INTO invCategories VALUES (6, 'Ship', 1), (7, 'Module', 1);
+             INSERT INTO invGroups VALUES (25, 'Frigate', 6, 0), (26, 'Rig', 7, 0);
+             INSERT INTO buildIssues (fileName, reason) VALUES ('types.jsonl', 'bad');",
+        );
+        // Category 7 goes away with its group, 6 is renamed, 8 is new.
+        let changes = update(
+            &mut connection,
+            "INSERT INTO invCategories (categoryId, categoryName, published) \
+                 VALUES (6, 'Ships', 1), (8, 'Charge', 1);
+             INSERT INTO invGroups (groupId, groupName, categoryId, anchorable) \
+                 VALUES (25, 'Frigate', 6, 0), (27, 'Ammo', 8, 0);",
+        )
+        .unwrap();
+
+        let tables: Vec<&str> = changes.iter().map(|(name, _)| name.as_str()).collect();
+        let position = |table: &str| tables.iter().position(|name| *name == table).unwrap();
+        assert!(position("invCategories") < position("invGroups"));
+        assert_eq!(
+            *changes_of(&changes, "invCategories"),
+            TableChanges {
+                inserted: 1,
+                updated: 1,
+                deleted: 1
+            }
+        );
+        assert_eq!(
+            *changes_of(&changes, "invGroups"),
+            TableChanges {
+                inserted: 1,
+                updated: 0,
+                deleted: 1
+            }
+        );
+        assert_eq!(
+            *changes_of(&changes, "buildIssues"),
+            TableChanges {
+                deleted: 1,
+                ..TableChanges::default()
+            }
+        );
+        assert!(changes_of(&changes, "mapSolarSystems").is_empty());
+
+        let categories: Vec<(i64, String)> = connection
+            .prepare("SELECT categoryId, categoryName FROM invCategories ORDER BY categoryId")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            categories,
+            vec![(6, "Ships".to_string()), (8, "Charge".to_string())]
+        );
+        // Nothing of the update's own is left behind.
+        let leftovers: i64 = connection
+            .query_row("SELECT COUNT(*) FROM temp.sqlite_master", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(leftovers, 0);
+    }
+
+    #[test]
+    fn update_is_a_no_op_on_identical_records() {
+        let rows = "INSERT INTO invCategories (categoryId, categoryName, published) \
+                    VALUES (6, 'Ship', 1);";
+        let mut connection = database(rows);
+        let changes = update(&mut connection, rows).unwrap();
+        assert!(changes.iter().all(|(_, changes)| changes.is_empty()));
+    }
+
+    #[test]
+    fn update_refuses_a_duplicate_key_like_a_build() {
+        let mut connection = database("");
+        let result = update(
+            &mut connection,
+            "INSERT INTO races (raceId, raceName) VALUES (1, 'Caldari'), (1, 'Minmatar');",
+        );
+        assert!(matches!(
+            result,
+            Err(BuilderError::Sqlite(ref err)) if err.to_string().contains("races")
+        ));
+    }
+
+    #[test]
+    fn update_refuses_a_database_of_another_shape() {
+        let mut connection = database(
+            "INSERT INTO invCategories VALUES (6, 'Ship', 1);
+             ALTER TABLE mapSolarSystems ADD COLUMN iceBelt INTEGER;",
+        );
+        let result = update(&mut connection, "");
+        assert!(matches!(
+            result,
+            Err(BuilderError::IncompatibleDatabase(ref reason)) if reason.contains("mapSolarSystems")
+        ));
+        let kept: String = connection
+            .query_row(
+                "SELECT categoryName FROM invCategories WHERE categoryId = 6",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(kept, "Ship");
+    }
+
+    #[test]
+    fn written_only_has_the_rows_an_update_wrote() {
+        let mut connection = database("INSERT INTO races VALUES (1, 'Caldari'), (2, 'Minmatar');");
+        let count = |connection: &Connection| -> i64 {
+            let races = written(connection, "races").unwrap();
+            connection
+                .query_row(&format!("SELECT COUNT(*) FROM {races} AS r"), [], |row| {
+                    row.get(0)
+                })
+                .unwrap()
+        };
+        assert_eq!(count(&connection), 2);
+
+        let tx = connection.transaction().unwrap();
+        let _tracker = Tracker::start(&tx).unwrap();
+        assert_eq!(count(&tx), 0);
+        tx.execute(
+            "INSERT INTO races (raceId, raceName) VALUES (2, 'Minmatar')",
+            [],
+        )
+        .unwrap();
+        assert_eq!(count(&tx), 1);
+    }
+}
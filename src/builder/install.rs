@@ -0,0 +1,415 @@
+//! Putting a freshly built database in place without ever leaving the
+//! target path empty or half-written.
+//!
+//! A full build writes into [`building_path`] -- a scratch file next to
+//! the target, so on the same filesystem -- and only once it's complete
+//! does [`replace_database`] verify it ([`super::verify`]) and `rename`
+//! it over the target, which on the same filesystem is atomic: readers
+//! of the target see either the old database or the new one, and a build
+//! that fails or gets cancelled leaves the old one untouched.
+//!
+//! The database being replaced can be kept for rollback, hard-linked
+//! (or copied) as `<stem>.build-<build number>.<extension>` next to it
+//! (`sde.db` -> `sde.build-3012345.db`), the build number being the one
+//! its `sdeMetadata` records (`unknown` without one). Only the newest
+//! `keep` of those are kept, by modification time -- which a link
+//! shares and a copy is given, so it's when each was built. [`backups`]
+//! lists them; rolling back is copying one over the target.
+
+use crate::builder::BuilderError;
+use crate::builder::verify;
+use crate::objects::SdeMetadata;
+use rusqlite::{Connection, OpenFlags, OptionalExtension};
+use std::path::{Path, PathBuf};
+
+/// A previous database kept by [`replace_database`] or
+/// [`back_up_database`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    pub path: PathBuf,
+    /// SDE build number it was built from, as in its file name.
+    pub build_number: String,
+}
+
+/// Where a full build of `target` is written before
+/// [`replace_database`] puts it in place: `sde.db` -> `sde.building.db`.
+pub fn building_path(target: &Path) -> PathBuf {
+    target.with_extension(match target.extension() {
+        Some(extension) => format!("building.{}", extension.to_string_lossy()),
+        None => "building".to_string(),
+    })
+}
+
+/// Verifies the database at `built` and renames it over `target`,
+/// keeping the database it replaces as a [`Backup`] when `keep > 0` (and
+/// pruning the backups past the newest `keep`). Returns that backup, if
+/// one was made. `keep == 0` leaves the backups already there alone,
+/// whatever an earlier call with a `keep` of its own made.
+///
+/// The backup is a hard link to `target` (a copy where the filesystem
+/// can't link), made before the swap: the one `rename` of `built` over
+/// `target` is the only step that touches `target`, so it exists
+/// throughout, old or new.
+///
+/// A database that fails verification (a failed [`verify::Severity::Error`]
+/// check) is deleted, and `target` left as it was:
+/// [`BuilderError::VerificationFailed`].
+#[tracing::instrument]
+pub fn replace_database(
+    built: &Path,
+    target: &Path,
+    keep: usize,
+) -> Result<Option<Backup>, BuilderError> {
+    replace_database_with(built, target, keep, |_| {})
+}
+
+/// [`replace_database`], calling `after_step` with the name of each
+/// step once it's done (for the tests to check `target` in between).
+fn replace_database_with(
+    built: &Path,
+    target: &Path,
+    keep: usize,
+    mut after_step: impl FnMut(&str),
+) -> Result<Option<Backup>, BuilderError> {
+    let report = verify::verify_database(built)?;
+    if !report.passed() {
+        std::fs::remove_file(built)?;
+        return Err(BuilderError::VerificationFailed {
+            failed: report
+                .checks
+                .iter()
+                .filter(|check| {
+                    check.severity == verify::Severity::Error && !check.offenders().is_empty()
+                })
+                .map(|check| check.name)
+                .collect(),
+        });
+    }
+    after_step("verify");
+
+    let backup = if keep > 0 && target.exists() {
+        let backup = backup_of(target)?;
+        link_or_copy(target, &backup.path)?;
+        after_step("backup");
+        Some(backup)
+    } else {
+        None
+    };
+    std::fs::rename(built, target)?;
+    after_step("rename");
+    if keep > 0 {
+        prune(target, keep)?;
+        after_step("prune");
+    }
+    Ok(backup)
+}
+
+/// Hard-links `from` as `to`, replacing any `to` already there (a
+/// rebuild of the same build number), or copies it -- keeping its
+/// modification time, what [`backups`] orders by -- where the
+/// filesystem doesn't support links.
+fn link_or_copy(from: &Path, to: &Path) -> Result<(), BuilderError> {
+    if to.exists() {
+        std::fs::remove_file(to)?;
+    }
+    if std::fs::hard_link(from, to).is_err() {
+        let modified = std::fs::metadata(from)?.modified()?;
+        std::fs::copy(from, to)?;
+        std::fs::File::options()
+            .write(true)
+            .open(to)?
+            .set_modified(modified)?;
+    }
+    Ok(())
+}
+
+/// Copies `target` to a [`Backup`] before it's updated in place (an
+/// incremental update, which isn't swapped in like a full build),
+/// pruning the backups past the newest `keep`. Does nothing when
+/// `keep == 0` or there's no `target` yet.
+#[tracing::instrument]
+pub fn back_up_database(target: &Path, keep: usize) -> Result<Option<Backup>, BuilderError> {
+    if keep == 0 || !target.exists() {
+        return Ok(None);
+    }
+    let backup = backup_of(target)?;
+    // Through SQLite rather than a file copy: consistent even if
+    // something is writing to `target` meanwhile.
+    Connection::open_with_flags(target, OpenFlags::SQLITE_OPEN_READ_ONLY)?.backup(
+        rusqlite::MAIN_DB,
+        &backup.path,
+        None,
+    )?;
+    prune(target, keep)?;
+    Ok(Some(backup))
+}
+
+/// Every [`Backup`] of `target` in its directory, newest first.
+pub fn backups(target: &Path) -> Result<Vec<Backup>, BuilderError> {
+    let (prefix, suffix) = backup_affixes(target);
+    let directory = match target.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut found = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let Some(build_number) = name
+            .to_str()
+            .and_then(|name| name.strip_prefix(&prefix))
+            .and_then(|name| name.strip_suffix(&suffix))
+        else {
+            continue;
+        };
+        found.push((
+            entry.metadata()?.modified()?,
+            Backup {
+                path: entry.path(),
+                build_number: build_number.to_string(),
+            },
+        ));
+    }
+    found.sort_by(|(a_time, a), (b_time, b)| b_time.cmp(a_time).then_with(|| b.path.cmp(&a.path)));
+    Ok(found.into_iter().map(|(_, backup)| backup).collect())
+}
+
+/// `("<stem>.build-", ".<extension>")` of `target`'s backups.
+fn backup_affixes(target: &Path) -> (String, String) {
+    let stem = target
+        .file_stem()
+        .map_or_else(String::new, |stem| stem.to_string_lossy().into_owned());
+    let suffix = target.extension().map_or_else(String::new, |extension| {
+        format!(".{}", extension.to_string_lossy())
+    });
+    (format!("{stem}.build-"), suffix)
+}
+
+/// The [`Backup`] `target` would be kept as, named after the build
+/// number its `sdeMetadata` records.
+fn backup_of(target: &Path) -> Result<Backup, BuilderError> {
+    let connection = Connection::open_with_flags(target, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let has_metadata: bool = connection.query_row(
+        "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'sdeMetadata'",
+        [],
+        |row| row.get(0),
+    )?;
+    let build_number = if has_metadata {
+        connection
+            .query_row(
+                "SELECT value FROM sdeMetadata WHERE key = ?1",
+                [SdeMetadata::BUILD_NUMBER],
+                |row| row.get::<usize, String>(0),
+            )
+            .optional()?
+    } else {
+        None
+    };
+    // Only what's safe in a file name.
+    let build_number = build_number
+        .map(|build| {
+            build
+                .chars()
+                .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+                .collect::<String>()
+        })
+        .filter(|build| !build.is_empty())
+        .unwrap_or_else(|| "unknown".to_string());
+    let (prefix, suffix) = backup_affixes(target);
+    Ok(Backup {
+        path: target.with_file_name(format!("{prefix}{build_number}{suffix}")),
+        build_number,
+    })
+}
+
+/// Deletes the backups of `target` past the newest `keep` (all of them
+/// for `0`: callers only prune when keeping some).
+fn prune(target: &Path, keep: usize) -> Result<(), BuilderError> {
+    for backup in backups(target)?.into_iter().skip(keep) {
+        std::fs::remove_file(&backup.path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("sde-install-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// A database that passes verification, recording `build` (if any)
+    /// in `sdeMetadata`, built `age_secs` seconds ago.
+    fn built_database(path: &Path, build: Option<&str>, age_secs: u64) {
+        let connection = Connection::open(path).unwrap();
+        crate::builder::schema::create_schema(&connection).unwrap();
+        if let Some(build) = build {
+            connection
+                .execute(
+                    "INSERT INTO sdeMetadata (key, value) VALUES (?1, ?2)",
+                    [SdeMetadata::BUILD_NUMBER, build],
+                )
+                .unwrap();
+        }
+        drop(connection);
+        std::fs::File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(age_secs))
+            .unwrap();
+    }
+
+    #[test]
+    fn building_path_sits_next_to_the_target() {
+        assert_eq!(
+            building_path(Path::new("out/sde.db")),
+            PathBuf::from("out/sde.building.db")
+        );
+        assert_eq!(
+            building_path(Path::new("sde")),
+            PathBuf::from("sde.building")
+        );
+    }
+
+    #[test]
+    fn replace_database_swaps_in_place_without_backups() {
+        let dir = temp_dir("no_backups");
+        let target = dir.join("sde.db");
+        built_database(&target, Some("100"), 60);
+        let built = building_path(&target);
+        built_database(&built, Some("200"), 0);
+
+        assert_eq!(replace_database(&built, &target, 0).unwrap(), None);
+        assert!(!built.exists());
+        assert!(backups(&target).unwrap().is_empty());
+        let build: String = Connection::open(&target)
+            .unwrap()
+            .query_row("SELECT value FROM sdeMetadata", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(build, "200");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn replace_database_without_keep_leaves_existing_backups_alone() {
+        let dir = temp_dir("keep_zero");
+        let target = dir.join("sde.db");
+        built_database(&dir.join("sde.build-100.db"), Some("100"), 300);
+        built_database(&dir.join("sde.build-200.db"), Some("200"), 200);
+        built_database(&target, Some("300"), 100);
+        let built = building_path(&target);
+        built_database(&built, Some("400"), 0);
+
+        assert_eq!(replace_database(&built, &target, 0).unwrap(), None);
+
+        let kept: Vec<String> = backups(&target)
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.build_number)
+            .collect();
+        assert_eq!(kept, vec!["200", "100"]);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn replace_database_keeps_the_newest_backups() {
+        let dir = temp_dir("keep");
+        let target = dir.join("sde.db");
+        built_database(&dir.join("sde.build-100.db"), Some("100"), 300);
+        built_database(&dir.join("sde.build-200.db"), Some("200"), 200);
+        built_database(&target, Some("300"), 100);
+        let built = building_path(&target);
+        built_database(&built, Some("400"), 0);
+
+        let backup = replace_database(&built, &target, 2).unwrap().unwrap();
+
+        assert_eq!(backup.build_number, "300");
+        assert_eq!(backup.path, dir.join("sde.build-300.db"));
+        let kept: Vec<String> = backups(&target)
+            .unwrap()
+            .into_iter()
+            .map(|backup| backup.build_number)
+            .collect();
+        assert_eq!(kept, vec!["300", "200"]);
+        assert!(!dir.join("sde.build-100.db").exists());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn replace_database_never_leaves_the_target_missing() {
+        let dir = temp_dir("always_there");
+        let target = dir.join("sde.db");
+        built_database(&target, Some("100"), 60);
+        let built = building_path(&target);
+        built_database(&built, Some("200"), 0);
+
+        let mut steps = Vec::new();
+        let backup = replace_database_with(&built, &target, 1, |step| {
+            assert!(target.exists(), "no target after {step}");
+            steps.push(step.to_string());
+        })
+        .unwrap()
+        .unwrap();
+
+        assert_eq!(steps, vec!["verify", "backup", "rename", "prune"]);
+        assert_eq!(backup.path, dir.join("sde.build-100.db"));
+        let kept: String = Connection::open(&backup.path)
+            .unwrap()
+            .query_row("SELECT value FROM sdeMetadata", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(kept, "100");
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn replace_database_refuses_a_database_that_fails_verification() {
+        let dir = temp_dir("unverified");
+        let target = dir.join("sde.db");
+        built_database(&target, Some("100"), 60);
+        let built = building_path(&target);
+        built_database(&built, Some("200"), 0);
+        // A system in no constellation at all.
+        let connection = Connection::open(&built).unwrap();
+        connection
+            .execute(
+                "INSERT INTO mapSolarSystems (solarSystemId, solarSystemName, constellationId, \
+                 radius, centerX, centerY, centerZ, security) \
+                 VALUES (30000142, 'Jita', NULL, 1, 0, 0, 0, 0.9)",
+                [],
+            )
+            .unwrap();
+        drop(connection);
+
+        let result = replace_database(&built, &target, 1);
+
+        assert!(matches!(
+            result,
+            Err(BuilderError::VerificationFailed { ref failed }) if failed.contains(&"systems_in_constellations")
+        ));
+        assert!(!built.exists());
+        assert!(backups(&target).unwrap().is_empty());
+        assert!(target.exists());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn back_up_database_copies_and_names_by_build() {
+        let dir = temp_dir("copy");
+        let target = dir.join("sde.db");
+        assert_eq!(back_up_database(&target, 1).unwrap(), None);
+        built_database(&target, None, 0);
+
+        let backup = back_up_database(&target, 1).unwrap().unwrap();
+
+        assert_eq!(backup.build_number, "unknown");
+        assert!(backup.path.exists());
+        assert!(target.exists());
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
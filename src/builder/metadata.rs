@@ -0,0 +1,237 @@
+//! Build provenance: the `sdeMetadata` table, written once at the end of
+//! [`super::parser::Parser::build_database`] and read back by
+//! [`crate::SdeManager::metadata`].
+//!
+//! Before it, the only record of which SDE build a database came from
+//! was `data/sde-<variant>.build`, next to the zip -- not inside the
+//! database, so lost as soon as `sde.db` was copied anywhere else. The
+//! keys are [`SdeMetadata`]'s associated constants, shared with the read
+//! side so the two can't drift apart.
+
+use crate::builder::BuilderError;
+use crate::builder::manifest::{self, MapFingerprint};
+use crate::builder::parser::ParserConfig;
+use crate::builder::sde_index::SdeRelease;
+use crate::objects::SdeMetadata;
+use rusqlite::{Connection, params};
+use serde_json::{Value, json};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// `config` as a JSON object, one member per field (projections by
+/// name, e.g. `"isometricY"`; the community layers by name too, and
+/// only with third-party data) -- what [`write_metadata`] stores under
+/// [`SdeMetadata::PARSER_CONFIG`].
+pub fn parser_config_json(config: &ParserConfig) -> Value {
+    json!({
+        "language": config.language,
+        "forcePosition2d": config.position_2d().is_some(),
+        "position2dProjection": config
+            .position_2d()
+            .unwrap_or(config.position_2d_projection)
+            .name(),
+        "projections": config
+            .projections
+            .iter()
+            .map(|projection| projection.name())
+            .collect::<Vec<_>>(),
+        "mapKspace": config.map_kspace,
+        "mapWspace": config.map_wspace,
+        "mapAbyssal": config.map_abyssal,
+        "mapVoid": config.map_void,
+        "regionIds": config.region_ids,
+        "constellationIds": config.constellation_ids,
+        "factionIds": config.faction_ids,
+        "withGates": config.with_gates,
+        "withMoons": config.with_moons,
+        "withThirdParty": config.with_third_party,
+        "communityLayers": if config.with_third_party {
+            config.community.layers()
+        } else {
+            Vec::new()
+        }
+        .iter()
+        .map(|layer| layer.name())
+        .collect::<Vec<_>>(),
+        "format": config.format.variant(),
+        "strictness": config.strictness.name(),
+    })
+}
+
+/// Writes `sdeMetadata` (replacing any previous value of each key) and
+/// returns how many keys it wrote.
+///
+/// `release` is `None` when the build number couldn't be determined --
+/// its two keys are then left out rather than written empty, same for
+/// the release date alone. `languages` comes from `translationLanguages`
+/// as already parsed on `connection`. The map fingerprints are read from
+/// `maps_dir`'s manifest, and only when `config.with_third_party` (a
+/// leftover manifest from an earlier build says nothing about this one
+/// otherwise); sorted by region so the JSON is stable between builds.
+#[tracing::instrument(skip(connection))]
+pub fn write_metadata(
+    connection: &Connection,
+    config: &ParserConfig,
+    release: Option<&SdeRelease>,
+    maps_dir: &Path,
+) -> Result<usize, BuilderError> {
+    let mut statement =
+        connection.prepare("SELECT languageId FROM translationLanguages ORDER BY languageId")?;
+    let languages = statement
+        .query_map([], |row| row.get::<usize, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+
+    let mut entries: Vec<(&str, String)> = vec![
+        (
+            SdeMetadata::CRATE_VERSION,
+            env!("CARGO_PKG_VERSION").to_string(),
+        ),
+        (SdeMetadata::BASE_LANGUAGE, config.language.clone()),
+        (SdeMetadata::LANGUAGES, languages.join(",")),
+        (
+            SdeMetadata::WITH_THIRD_PARTY,
+            if config.with_third_party { "1" } else { "0" }.to_string(),
+        ),
+        (SdeMetadata::FORMAT, config.format.variant().to_string()),
+        (
+            SdeMetadata::PARSER_CONFIG,
+            parser_config_json(config).to_string(),
+        ),
+    ];
+    if let Some(release) = release {
+        entries.push((SdeMetadata::BUILD_NUMBER, release.build_number.clone()));
+        if let Some(release_date) = &release.release_date {
+            entries.push((SdeMetadata::RELEASE_DATE, release_date.clone()));
+        }
+    }
+    if config.with_third_party {
+        let fingerprints: BTreeMap<String, MapFingerprint> =
+            manifest::load(maps_dir).into_iter().collect();
+        entries.push((
+            SdeMetadata::MAP_FINGERPRINTS,
+            serde_json::to_string(&fingerprints)?,
+        ));
+    }
+
+    let mut insert =
+        connection.prepare("INSERT OR REPLACE INTO sdeMetadata (key, value) VALUES (?1, ?2)")?;
+    for (key, value) in &entries {
+        insert.execute(params![key, value])?;
+    }
+    Ok(entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::parser::{ProjectedAxis, Projection};
+    use crate::builder::schema::create_schema;
+
+    fn metadata_of(connection: &Connection) -> SdeMetadata {
+        let mut statement = connection
+            .prepare("SELECT key, value FROM sdeMetadata")
+            .unwrap();
+        let entries = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<Vec<(String, String)>>>()
+            .unwrap();
+        SdeMetadata::from_entries(entries)
+    }
+
+    #[test]
+    fn write_metadata_round_trips_through_sde_metadata() {
+        let connection = Connection::open_in_memory().unwrap();
+        create_schema(&connection).unwrap();
+        connection
+            .execute_batch(
+                "INSERT INTO translationLanguages (languageId, languageName) \
+                 VALUES ('en', 'English'), ('de', 'German');",
+            )
+            .unwrap();
+        let config = ParserConfig {
+            projections: vec![Projection::Dimetric(ProjectedAxis::Z)],
+            ..ParserConfig::default()
+        };
+        let release = SdeRelease {
+            build_number: "3458726".to_string(),
+            release_date: Some("2026-08-06T11:07:36Z".to_string()),
+        };
+
+        let written = write_metadata(
+            &connection,
+            &config,
+            Some(&release),
+            Path::new("does-not-exist"),
+        )
+        .unwrap();
+        assert_eq!(written, 8);
+
+        let metadata = metadata_of(&connection);
+        assert_eq!(metadata.build_number.as_deref(), Some("3458726"));
+        assert_eq!(
+            metadata.release_date.as_deref(),
+            Some("2026-08-06T11:07:36Z")
+        );
+        assert_eq!(
+            metadata.crate_version.as_deref(),
+            Some(env!("CARGO_PKG_VERSION"))
+        );
+        assert_eq!(metadata.base_language.as_deref(), Some("en"));
+        assert_eq!(metadata.languages, vec!["de", "en"]);
+        assert!(!metadata.with_third_party);
+        assert_eq!(metadata.format.as_deref(), Some("jsonl"));
+        assert_eq!(metadata.map_fingerprints, None);
+        let parser_config: Value =
+            serde_json::from_str(metadata.parser_config.as_deref().unwrap()).unwrap();
+        assert_eq!(parser_config["projections"], json!(["dimetricZ"]));
+        assert_eq!(parser_config["position2dProjection"], json!("isometricY"));
+    }
+
+    #[test]
+    fn write_metadata_records_map_fingerprints_with_third_party_data() {
+        let connection = Connection::open_in_memory().unwrap();
+        create_schema(&connection).unwrap();
+        let maps_dir = std::env::temp_dir().join(format!(
+            "sde-metadata-test-fingerprints-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&maps_dir).unwrap();
+        let mut fingerprints = manifest::Manifest::new();
+        fingerprints.insert(
+            "The Forge".to_string(),
+            MapFingerprint {
+                etag: Some("\"v1\"".to_string()),
+                last_modified: None,
+                content_length: Some("1024".to_string()),
+            },
+        );
+        manifest::save(&maps_dir, &fingerprints).unwrap();
+        let config = ParserConfig {
+            with_third_party: true,
+            ..ParserConfig::default()
+        };
+
+        write_metadata(&connection, &config, None, &maps_dir).unwrap();
+
+        let metadata = metadata_of(&connection);
+        assert!(metadata.with_third_party);
+        let parser_config: Value =
+            serde_json::from_str(metadata.parser_config.as_deref().unwrap()).unwrap();
+        assert_eq!(
+            parser_config["communityLayers"],
+            json!([
+                "icebelts",
+                "triglavian-status",
+                "jove-observatories",
+                "special-ore"
+            ])
+        );
+        assert_eq!(metadata.build_number, None);
+        assert!(metadata.languages.is_empty());
+        let stored: Value =
+            serde_json::from_str(metadata.map_fingerprints.as_deref().unwrap()).unwrap();
+        assert_eq!(stored["The Forge"]["etag"], json!("\"v1\""));
+        std::fs::remove_dir_all(&maps_dir).unwrap();
+    }
+}
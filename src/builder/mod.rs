@@ -7,20 +7,52 @@
 //! The `sde` (CLI) and `sde-gui` binaries enable this feature and call
 //! into this module's functions.
 
+pub mod audit;
+pub mod cancel;
 pub mod community;
 pub mod extract;
 pub mod http;
+pub mod incremental;
+pub mod install;
 pub mod manifest;
+pub mod metadata;
 pub mod parser;
+pub mod profile;
+pub mod progress;
+pub mod records;
 pub mod schema;
 pub mod sde_index;
+pub mod search;
+pub mod verify;
 
+// `audit` (inventory of an extracted SDE directory against what the
+// parser reads, for schema drift): see builder::audit's docstring.
+// `cancel` (stopping a running build from outside): see
+// builder::cancel's docstring.
 // `schema` (STRICT DDL): see builder::schema::create_schema().
 // `parser` (data writing): see builder::parser's docstring for the
 // full list of tables it covers.
+// `records` (typed serde shape of every SDE file the parser reads): see
+// builder::records's docstring.
 // `community` (community data external to the SDE): dynamic DDL, static
 // list population, SVG parsing, and the download orchestrator with
 // retries. See builder::community's docstring for the detail.
+// `incremental` (updating an existing database in place, comparing the
+// new records with the stored rows by key): see builder::incremental's
+// docstring.
+// `install` (swapping a finished build in place of the previous
+// database, keeping backups): see builder::install's docstring.
+// `metadata` (build provenance, `sdeMetadata`): see
+// builder::metadata's docstring.
+// `search` (FTS5 search index, derived from the parsed tables): see
+// builder::search's docstring.
+// `verify` (integrity and domain invariants of a built database): see
+// builder::verify's docstring.
+// `profile` (named presets and TOML/JSON files of `ParserConfig`
+// overrides): see builder::profile's docstring.
+// `progress` (the `BuildObserver` the pipeline reports phases,
+// downloads, messages and warnings to, instead of printing them): see
+// builder::progress's docstring.
 // `sde_index` (build number check + conditional SDE download): see
 // builder::sde_index's docstring.
 // `extract` (SDE zip decompression, preserving maps/): see
@@ -28,9 +60,14 @@ pub mod sde_index;
 //
 // The top-level orchestrator that ties all of this together lives in
 // src/bin/cli.rs's `main()`: sde_index::update_as_needed() ->
-// extract::prepare_sde_directory() -> parser::Parser::build_database()
-// (which itself runs parse_data(), then community::process() only if
-// `--with-third-party` was passed).
+// extract::prepare_sde_directory() -> parser::Parser::build_database_file()
+// (which runs build_database() into a scratch file and swaps it in;
+// build_database() itself runs parse_data(), then community::process()
+// only if `--with-third-party` was passed; with `--incremental`,
+// parser::Parser::update_database() runs parse_data()'s phases on the
+// existing database instead, writing only what changed) -- or, offline
+// (`--from-zip`/`--from-dir`), straight to extract::prepare_sde_directory()
+// (or nothing, for a directory) without the index check.
 
 /// Build process errors. Deliberately without `thiserror`: same
 /// "no abstraction" pattern `SdeManager` already uses (propagates the
@@ -40,6 +77,11 @@ pub mod sde_index;
 pub enum BuilderError {
     Io(std::io::Error),
     Json(serde_json::Error),
+    /// A YAML SDE file doesn't parse (see `parser::SdeFormat::Yaml`).
+    Yaml(serde_norway::Error),
+    /// A TOML build profile doesn't parse, or has a key or value
+    /// `profile::BuildProfile` doesn't know.
+    Toml(toml::de::Error),
     Http(reqwest::Error),
     /// The server responded, but with a non-2xx HTTP status.
     HttpStatus {
@@ -58,6 +100,26 @@ pub enum BuilderError {
     /// read and parsed fine, its content simply doesn't match what the
     /// parser needs.
     Data(String),
+    /// The database an incremental update was asked to update doesn't
+    /// have the shape of a new build (schema version, tables or columns
+    /// differ), or third-party data is involved (see
+    /// `builder::incremental`); it needs a full rebuild instead.
+    IncompatibleDatabase(String),
+    /// A finished download doesn't match what was published for it --
+    /// its size or its SHA-256 (see `http::download_resumable`). The
+    /// file is deleted rather than kept for a resume.
+    CorruptDownload {
+        url: String,
+        reason: String,
+    },
+    /// A finished build failed one of `verify`'s error checks (named in
+    /// `failed`), so `install::replace_database` didn't put it in place.
+    VerificationFailed {
+        failed: Vec<&'static str>,
+    },
+    /// The build was stopped through its `cancel::CancellationToken`.
+    /// Whatever it had written in its transaction was rolled back.
+    Cancelled,
 }
 
 impl std::fmt::Display for BuilderError {
@@ -65,6 +127,8 @@ impl std::fmt::Display for BuilderError {
         match self {
             BuilderError::Io(err) => write!(f, "I/O error: {err}"),
             BuilderError::Json(err) => write!(f, "JSON error: {err}"),
+            BuilderError::Yaml(err) => write!(f, "YAML error: {err}"),
+            BuilderError::Toml(err) => write!(f, "TOML error: {err}"),
             BuilderError::Http(err) => write!(f, "HTTP error: {err}"),
             BuilderError::HttpStatus { url, status } => {
                 write!(f, "{url} responded with status {status}")
@@ -72,6 +136,19 @@ impl std::fmt::Display for BuilderError {
             BuilderError::Sqlite(err) => write!(f, "SQLite error: {err}"),
             BuilderError::Zip(err) => write!(f, "Zip error: {err}"),
             BuilderError::Data(message) => write!(f, "malformed SDE record: {message}"),
+            BuilderError::IncompatibleDatabase(reason) => write!(
+                f,
+                "can't update the database incrementally, it needs a full rebuild: {reason}"
+            ),
+            BuilderError::CorruptDownload { url, reason } => {
+                write!(f, "download of {url} is corrupt: {reason}")
+            }
+            BuilderError::VerificationFailed { failed } => write!(
+                f,
+                "the new database failed verification ({}), the previous one was kept",
+                failed.join(", ")
+            ),
+            BuilderError::Cancelled => write!(f, "the build was cancelled"),
         }
     }
 }
@@ -102,6 +179,18 @@ impl From<serde_json::Error> for BuilderError {
     }
 }
 
+impl From<serde_norway::Error> for BuilderError {
+    fn from(err: serde_norway::Error) -> Self {
+        BuilderError::Yaml(err)
+    }
+}
+
+impl From<toml::de::Error> for BuilderError {
+    fn from(err: toml::de::Error) -> Self {
+        BuilderError::Toml(err)
+    }
+}
+
 impl From<reqwest::Error> for BuilderError {
     fn from(err: reqwest::Error) -> Self {
         BuilderError::Http(err)
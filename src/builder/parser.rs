@@ -16,8 +16,9 @@
 //! The map tables build up from there: `mapRegions` and
 //! `mapConstellations` first (no isometric/dimetric projection
 //! complexity), then `mapSolarSystems`, with the k-space/w-space/
-//! abyssal/void scope filter (`ParserConfig::system_in_scope`) and the
-//! isometric projection ([`isometric_projection_2d`]).
+//! abyssal/void scope filter (`ParserConfig::system_in_scope`), the
+//! region/constellation/faction one (`ParserConfig::system_in_filters`)
+//! and the isometric projection ([`isometric_projection_2d`]).
 //! [`Parser::parse_stargates`] adds `mapSystemGates`, gated by
 //! `config.with_gates` -- see its docstring for an important note on
 //! why this particular function needs to run inside an explicit
@@ -32,11 +33,28 @@
 //! simplest of all: a single SQL statement that derives the connections
 //! directly from `mapSystemGates`, without reading any SDE file at all.
 //!
-//! ## Supported file format
+//! Every phase reads its file's records as the typed structs in
+//! [`records`] (see [`records::decode`]): a record missing a field the
+//! phase needs, or carrying one of the wrong type, fails the build with
+//! the file, the record's `_key` and the path of the field. An optional
+//! field of the wrong type is read as absent instead.
 //!
-//! JSONL only. Adding YAML support would mean pulling in a new
-//! YAML-parsing dependency into the crate, a decision that was
-//! deliberately left out of scope.
+//! Localized fields (`name`, `description`, ...) are stored twice: once
+//! in the entity's own column, in `config.language`, and once per
+//! language in `trnTranslations` (see `Parser::store_translations`),
+//! restricted to the languages [`Parser::parse_translation_languages`]
+//! loaded from `translationLanguages.jsonl`.
+//!
+//! ## Supported file formats
+//!
+//! CCP publishes the same SDE as JSONL (one record per line) and as YAML
+//! (one mapping per file, keyed by id); `config.format` ([`SdeFormat`])
+//! picks which one `<sde_directory>` holds. File names throughout these
+//! docs say `.jsonl`; with [`SdeFormat::Yaml`] read `.yaml` instead.
+//! Every `parse_*` phase reads its records through `typed_records`,
+//! which decodes the JSONL shape either way -- YAML documents are
+//! normalized into it by `yaml_records` -- so the phases themselves
+//! don't know, and both formats produce the same database.
 //!
 //! ## Notable behavior
 //!
@@ -58,7 +76,18 @@
 //!   or nothing" semantics: if any phase fails, EVERYTHING inserted up
 //!   to that point gets rolled back. Calling an individual function
 //!   directly, outside of `parse_data`, doesn't get that atomicity
-//!   guarantee -- only `parse_data` provides it.
+//!   guarantee -- only `parse_data` provides it. In
+//!   [`Strictness::Lenient`] mode each record additionally gets a
+//!   savepoint of its own, so a bad one is undone alone and logged in
+//!   `buildIssues` instead of failing the whole build. Cancelling
+//!   `ParserConfig::cancellation` is just one more way for a phase to
+//!   fail: [`BuilderError::Cancelled`], at the next phase or record,
+//!   and the same rollback.
+//! - **Threads**: [`Parser::parse_data`] decodes every file on a worker
+//!   thread of its own, ahead of the phase that reads it, but still
+//!   writes from the calling thread only, phase after phase (see its
+//!   "Pipelining" section). A `parse_*` function called on its own reads
+//!   its file itself, on the spot.
 //! - [`Parser::parse_factions`] validates every element of
 //!   `memberRaces` and returns [`BuilderError::Data`] on the first one
 //!   that isn't an integer, since it would violate
@@ -70,42 +99,80 @@
 //!   criterion as `name`): it fails with [`BuilderError::Data`] and a
 //!   clear message instead of letting SQLite reject it further down.
 //! - [`Parser::parse_constellations`] computes the id as
-//!   `constellationID` when present, falling back to `_key` otherwise
-//!   (absent or present-but-not-an-integer) -- tolerant of either case,
-//!   same result with well-formed data.
-//! - `position2DX`/`position2DY` are how every 2D map projection is
-//!   stored -- either CCP's own precomputed value, or one computed
-//!   locally via [`isometric_projection_2d`] when
-//!   `config.force_isometric_position_2d` is on.
+//!   `constellationID` when present, falling back to `_key` when it's
+//!   absent -- same result with well-formed data. A `constellationID`
+//!   that isn't an integer counts as absent, like any other optional
+//!   field of the wrong type (see [`records`]).
+//! - `position2DX`/`position2DY` hold the system's main 2D map position
+//!   -- either CCP's own precomputed value, or one computed locally per
+//!   `config.position_2d_projection` when `config.force_position_2d` is
+//!   on. Any further projections listed in `config.projections` are
+//!   stored alongside it, in `mapSolarSystemProjections`, so a client
+//!   can switch between them without a rebuild.
 
 use crate::builder::BuilderError;
+use crate::builder::cancel::CancellationToken;
 use crate::builder::community::{self, CommunityConfig};
+use crate::builder::incremental::{self, TableChanges};
+use crate::builder::install::{self, Backup};
+use crate::builder::progress::{BuildEvent, BuildObserver};
+use crate::builder::records::{self, Localized};
+use crate::builder::sde_index::SdeRelease;
+use crate::builder::{extract, metadata, search};
 use reqwest::Client;
 use rusqlite::Connection;
+use serde::de::DeserializeOwned;
 use serde_json::Value;
+use std::collections::HashMap;
 use std::io::BufRead;
 use std::path::Path;
+use std::sync::mpsc::{Receiver, SyncSender, sync_channel};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
 
 /// Config for the parser. Covers what's needed for localizing names
-/// and the optional isometric computation of
-/// `position2DX`/`position2DY` (see [`ProjectedAxis`]/
-/// [`isometric_projection_2d`]), plus the solar-system scope flags
-/// (k-space/w-space/abyssal/void).
+/// and the 2D map projections (`position2DX`/`position2DY` and
+/// `mapSolarSystemProjections`, see [`Projection`]), plus the
+/// solar-system scope: flags for whole classes of space
+/// (k-space/w-space/abyssal/void) and filters by region, constellation
+/// or faction.
 #[derive(Debug, Clone)]
 pub struct ParserConfig {
-    /// Language to extract from localized `name`/`description` fields
+    /// Language stored in the base columns (`typeName`,
+    /// `solarSystemName`, ...) for localized `name`/`description` fields
     /// (e.g. `{"en": "Jita", "es": "Jita"}` -> `"Jita"`), falling back
     /// to `"en"` if the requested language isn't there. Default `"en"`.
+    /// Every other language listed in `translationLanguages.jsonl` is
+    /// stored too, in `trnTranslations` (see
+    /// [`Parser::parse_translation_languages`]) -- this only picks which
+    /// one the base columns carry.
     pub language: String,
     /// If `true`, `position2DX`/`position2DY` are always computed
-    /// locally via [`isometric_projection_2d`], **ignoring** the
+    /// locally via `position_2d_projection`, **ignoring** the
     /// `position2D` field CCP already provides in the reworked SDE --
     /// instead of directly using the precomputed value CCP provides
     /// (which is the default behavior, `false`).
+    pub force_position_2d: bool,
+    /// Projection `position2DX`/`position2DY` are computed with when
+    /// `force_position_2d` is on (no effect if it isn't). Default
+    /// [`Projection::Isometric`] on [`ProjectedAxis::Y`].
+    pub position_2d_projection: Projection,
+    /// Extra projections computed for every solar system and stored in
+    /// `mapSolarSystemProjections` (one row per system and projection),
+    /// independently of `position2DX`/`position2DY`. Default empty.
+    pub projections: Vec<Projection>,
+    /// Former name of `force_position_2d`, from when the local
+    /// computation could only be isometric: still honored when
+    /// `force_position_2d` is off, projecting with
+    /// [`Projection::Isometric`] on `isometric_projected_axis` (see
+    /// [`ParserConfig::position_2d`]). Default `false`.
+    #[deprecated(
+        note = "use `force_position_2d` with `position_2d_projection: Projection::Isometric(axis)`"
+    )]
     pub force_isometric_position_2d: bool,
-    /// Axis collapsed in [`isometric_projection_2d`]'s computation when
-    /// `force_isometric_position_2d` is on (no effect if it isn't).
-    /// Default [`ProjectedAxis::Y`].
+    /// Axis of the isometric projection `force_isometric_position_2d`
+    /// computes with (no effect if it's off). Default [`ProjectedAxis::Y`].
+    #[deprecated(note = "use `position_2d_projection: Projection::Isometric(axis)`")]
     pub isometric_projected_axis: ProjectedAxis,
     /// Include k-space systems (no `wormholeClassID`). Default `true`.
     pub map_kspace: bool,
@@ -118,6 +185,27 @@ pub struct ParserConfig {
     /// `map_wspace`/`map_abyssal`/`map_void` all end up gating on the
     /// same check.
     pub map_void: bool,
+    /// Only import the systems of these regions (`regionId`s). Default
+    /// empty: no region filter. With more than one of `region_ids`,
+    /// `constellation_ids` and `faction_ids` set, a system in any of
+    /// them is in scope -- see `ParserConfig::system_in_filters`. Either
+    /// way the class flags above still apply on top.
+    ///
+    /// Scope only ever removes systems, and everything hanging off them
+    /// goes with them -- stars, planets, moons, gates (including gates
+    /// *into* an out-of-scope system), connections and NPC stations --
+    /// so the database stays self-consistent: see [`SystemScopeState`].
+    /// `mapRegions`/`mapConstellations` are kept whole.
+    pub region_ids: Vec<i64>,
+    /// Only import the systems of these constellations
+    /// (`constellationId`s). Default empty: no constellation filter.
+    /// See `region_ids`.
+    pub constellation_ids: Vec<i64>,
+    /// Only import the systems of these factions (`factionId`s): a
+    /// system's own `factionID`, or its region's when it carries none
+    /// (most empire space only has it at region level). Default empty:
+    /// no faction filter. See `region_ids`.
+    pub faction_ids: Vec<i64>,
     /// If `false`, [`Parser::parse_data`] skips the stargates phase
     /// ([`Parser::parse_stargates`]) entirely -- doesn't call it at all, not
     /// just filter its results. Default `true`.
@@ -126,9 +214,6 @@ pub struct ParserConfig {
     /// ([`Parser::parse_moons`]) entirely -- doesn't call it at all. Default
     /// `true`.
     pub with_moons: bool,
-    /// if true, [`Parser::parse_data`] present elements bieng parsed in stdout as they are processed,
-    /// otherwise it will be silent. Default `false`.
-    pub verbose: bool,
     /// If `true`, [`Parser::build_database`] also fetches and layers in
     /// community-maintained data (`builder::community`) on top of the
     /// canonical SDE (ice belts, Jove Observatories, Triglavian
@@ -140,27 +225,76 @@ pub struct ParserConfig {
     /// Has no effect on [`Parser::parse_data`] directly -- only
     /// [`Parser::build_database`], which calls both, consults it.
     pub with_third_party: bool,
+    /// Which of the community layers `with_third_party` adds. Default
+    /// [`CommunityConfig::ALL`] (`mapAbstractSystems` comes with any of
+    /// them, or none).
+    pub community: CommunityConfig,
+    /// Layout of the files in `sde_directory`. Default
+    /// [`SdeFormat::Jsonl`].
+    pub format: SdeFormat,
+    /// SDE build `sde_directory` holds, recorded in `sdeMetadata` by
+    /// [`Parser::build_database`]. Default `None`: read from the
+    /// `_sde.<variant>` record in `sde_directory` itself (see
+    /// `extract::directory_release`); set it when the caller knows
+    /// better (e.g. from `sde_index`'s `.build` file).
+    pub release: Option<SdeRelease>,
+    /// What a malformed record does to the build. Default
+    /// [`Strictness::Strict`]: [`Parser::parse_data`] fails, and rolls
+    /// everything back.
+    pub strictness: Strictness,
+    /// Stops the build when cancelled (see [`crate::builder::cancel`]):
+    /// checked before every phase and every record, and passed on to
+    /// [`community::process`]. Default: a token nobody else holds, so
+    /// never cancelled.
+    pub cancellation: CancellationToken,
 }
 
 impl Default for ParserConfig {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             language: "en".to_string(),
+            force_position_2d: false,
+            position_2d_projection: Projection::default(),
+            projections: Vec::new(),
             force_isometric_position_2d: false,
             isometric_projected_axis: ProjectedAxis::default(),
             map_kspace: true,
             map_wspace: true,
             map_abyssal: true,
             map_void: false,
+            region_ids: Vec::new(),
+            constellation_ids: Vec::new(),
+            faction_ids: Vec::new(),
             with_gates: true,
             with_moons: true,
-            verbose: false,
             with_third_party: false,
+            community: CommunityConfig::ALL,
+            format: SdeFormat::Jsonl,
+            release: None,
+            strictness: Strictness::Strict,
+            cancellation: CancellationToken::new(),
         }
     }
 }
 
 impl ParserConfig {
+    /// Projection `position2DX`/`position2DY` are computed with locally,
+    /// or `None` to keep CCP's precomputed `position2D`:
+    /// `position_2d_projection` when `force_position_2d` is on, else the
+    /// isometric one the deprecated `force_isometric_position_2d` and
+    /// `isometric_projected_axis` ask for.
+    #[allow(deprecated)]
+    pub fn position_2d(&self) -> Option<Projection> {
+        if self.force_position_2d {
+            Some(self.position_2d_projection)
+        } else if self.force_isometric_position_2d {
+            Some(Projection::Isometric(self.isometric_projected_axis))
+        } else {
+            None
+        }
+    }
+
     /// Decides whether a solar system should be imported, based on the
     /// `map_kspace`/`map_wspace`/`map_abyssal`/`map_void` flags.
     ///
@@ -178,57 +312,75 @@ impl ParserConfig {
         }
     }
 
-    /// Reads a localized string field (`{"en": "...", "es": "...",
-    /// ...}`), preferring `self.language`, falling back to `"en"`.
-    /// Also accepts a plain (non-localized) string field. `None` if the
-    /// field is absent or neither shape.
-    fn localized<'a>(&self, record: &'a Value, field: &str) -> Option<&'a str> {
-        match record.get(field) {
-            Some(Value::Object(map)) => map
-                .get(self.language.as_str())
-                .or_else(|| map.get("en"))
-                .and_then(Value::as_str),
-            Some(Value::String(s)) => Some(s.as_str()),
-            _ => None,
-        }
+    /// Whether any of `region_ids`/`constellation_ids`/`faction_ids` is
+    /// set.
+    fn scope_filtered(&self) -> bool {
+        !self.region_ids.is_empty()
+            || !self.constellation_ids.is_empty()
+            || !self.faction_ids.is_empty()
     }
 
-    /// Same as [`Self::localized`], but a missing/unusable field is a
-    /// data error ([`BuilderError::Data`]) instead of a silent `None`.
+    /// Decides whether a solar system passes the region/constellation/
+    /// faction filters: always, when none is set ([`Self::scope_filtered`]);
+    /// otherwise when it's in any of them (a union, not an intersection:
+    /// `--regions` plus `--factions` is "this region, and that faction's
+    /// space"). `faction_id` is the system's effective faction (see
+    /// `faction_ids`).
+    fn system_in_filters(
+        &self,
+        region_id: i64,
+        constellation_id: i64,
+        faction_id: Option<i64>,
+    ) -> bool {
+        !self.scope_filtered()
+            || self.region_ids.contains(&region_id)
+            || self.constellation_ids.contains(&constellation_id)
+            || faction_id.is_some_and(|faction_id| self.faction_ids.contains(&faction_id))
+    }
+
+    /// `text` in `self.language`, falling back to `"en"` (see
+    /// [`Localized::get`]). `None` if the field is absent.
+    fn localized<'a>(&self, text: Option<&'a Localized>) -> Option<&'a str> {
+        text.and_then(|text| text.get(&self.language))
+    }
+
+    /// Same as [`Self::localized`], for a field the record must carry: a
+    /// text in neither `self.language` nor `"en"` is a data error
+    /// ([`BuilderError::Data`]) naming `stem`, the record's `key` and
+    /// `field`, instead of a silent `None`.
     fn required_localized<'a>(
         &self,
-        record: &'a Value,
+        stem: &str,
+        key: i64,
         field: &str,
+        text: &'a Localized,
     ) -> Result<&'a str, BuilderError> {
-        self.localized(record, field).ok_or_else(|| {
+        text.get(&self.language).ok_or_else(|| {
             BuilderError::Data(format!(
-                "record has no localizable field `{field}` in `{}`/`en`: {record}",
+                "{stem} {key}: {field} has no text in `{}`/`en`",
                 self.language
             ))
         })
     }
 }
 
-/// Axis choice used by [`crate::objects::SdePoint::to_2d`] and
-/// [`isometric_projection_2d`] -- moved to `crate::objects` (core, not
-/// gated by the `builder` feature) since `SdePoint` needs it too, on
-/// the read side. Re-exported here so existing `parser::ProjectedAxis`
-/// references throughout this module keep working unchanged.
-pub use crate::objects::ProjectedAxis;
+/// Axis and projection choice used by [`crate::objects::SdePoint::to_2d`]
+/// and by the parser's 2D map positions -- both live in `crate::objects`
+/// (core, not gated by the `builder` feature) since `SdePoint` needs
+/// them too, on the read side. Re-exported here so existing
+/// `parser::ProjectedAxis` references keep working unchanged.
+pub use crate::objects::{ProjectedAxis, Projection};
 
 /// 2D isometric projection of a 3D point, collapsing `axis`. Returns
-/// the two non-null components directly as `(x2d, y2d)`.
+/// the two non-null components directly as `(x2d, y2d)`. Shorthand for
+/// [`Projection::Isometric`]'s [`Projection::project`].
 ///
 /// Formulas (from <https://www.compuphase.com/axometr.htm>):
 /// - Z axis collapsed: `(x - z, y + (x + z) / 2)`
 /// - Y axis collapsed: `(x - y, z + (x + y) / 2)`
 /// - X axis collapsed: `(y - x, z + (y + x) / 2)`
 pub fn isometric_projection_2d(x: f64, y: f64, z: f64, axis: ProjectedAxis) -> (f64, f64) {
-    match axis {
-        ProjectedAxis::Z => (x - z, y + (x + z) / 2.0),
-        ProjectedAxis::Y => (x - y, z + (x + y) / 2.0),
-        ProjectedAxis::X => (y - x, z + (y + x) / 2.0),
-    }
+    Projection::Isometric(axis).project(x, y, z)
 }
 
 /// State shared between [`Parser::parse_groups`] and [`Parser::parse_types`].
@@ -243,11 +395,14 @@ pub struct StarTypeState {
     pub star_type_ids: std::collections::HashMap<i64, i64>,
 }
 
-/// Solar system ids that passed the `ParserConfig::system_in_scope`
-/// filter, populated by [`Parser::parse_solar_systems`]. Used by
-/// [`Parser::parse_stargates`], [`Parser::parse_stars`],
-/// [`Parser::parse_planets`] and [`Parser::parse_moons`] to filter
-/// their own records by `solarSystemID`.
+/// Solar system ids that passed the `ParserConfig::system_in_scope` and
+/// `ParserConfig::system_in_filters` filters, populated by
+/// [`Parser::parse_solar_systems`]. Used by [`Parser::parse_stargates`]
+/// (on both ends of a gate), [`Parser::parse_stars`],
+/// [`Parser::parse_planets`], [`Parser::parse_moons`] and
+/// [`Parser::parse_npc_stations`] to filter their own records by
+/// `solarSystemID` -- and `mapSystemConnections`, derived from the
+/// gates, follows.
 #[derive(Debug, Default)]
 pub struct SystemScopeState {
     pub systems_in_scope: std::collections::HashSet<i64>,
@@ -257,6 +412,120 @@ pub struct SystemScopeState {
 // Shared infrastructure: flat-file reading + field-extraction helpers.
 // ---------------------------------------------------------------------
 
+/// Layout of the SDE export in `<sde_directory>`: which file extension
+/// `iter_records` looks for and how it decodes it. Its string form
+/// ([`Self::variant`], also what [`std::str::FromStr`] accepts) is the
+/// variant name in CCP's archive names
+/// (`eve-online-static-data-<build>-<variant>.zip`), so the same value
+/// drives `sde_index::update_as_needed` too.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SdeFormat {
+    /// One JSON record per line, each carrying its id in `_key`.
+    #[default]
+    Jsonl,
+    /// One YAML mapping per file, keyed by id. See `yaml_records` for
+    /// how it's turned into JSONL-shaped records.
+    Yaml,
+}
+
+impl SdeFormat {
+    /// `"jsonl"` or `"yaml"`: the archive variant and the file extension.
+    pub fn variant(&self) -> &'static str {
+        match self {
+            SdeFormat::Jsonl => "jsonl",
+            SdeFormat::Yaml => "yaml",
+        }
+    }
+}
+
+impl std::fmt::Display for SdeFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.variant())
+    }
+}
+
+impl std::str::FromStr for SdeFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "jsonl" => Ok(SdeFormat::Jsonl),
+            "yaml" => Ok(SdeFormat::Yaml),
+            other => Err(format!(
+                "unknown SDE variant `{other}` (expected `jsonl` or `yaml`)"
+            )),
+        }
+    }
+}
+
+/// How [`Parser::parse_data`] treats a record it can't write: one that
+/// isn't valid JSON, doesn't match its [`records`] struct, or that a
+/// table's constraints reject.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Strictness {
+    /// Fail with that record's error; nothing is written.
+    #[default]
+    Strict,
+    /// Skip the record, logging its file, line, `_key` and the reason in
+    /// `buildIssues`, and keep going ([`ParseSummary::skipped_records`]
+    /// counts them). A reference only checked at commit (the schema's
+    /// `DEFERRABLE` foreign keys) still fails the build, skipped record
+    /// or not: by then there's no single record left to skip.
+    Lenient,
+}
+
+impl Strictness {
+    /// `"strict"` or `"lenient"`, also what [`std::str::FromStr`]
+    /// accepts.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Strictness::Strict => "strict",
+            Strictness::Lenient => "lenient",
+        }
+    }
+}
+
+impl std::fmt::Display for Strictness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for Strictness {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "strict" => Ok(Strictness::Strict),
+            "lenient" => Ok(Strictness::Lenient),
+            other => Err(format!(
+                "unknown strictness `{other}` (expected `strict` or `lenient`)"
+            )),
+        }
+    }
+}
+
+/// Records of one SDE file, as yielded by [`iter_records`]: each one,
+/// or why it couldn't be read.
+pub(crate) type Records = Box<dyn Iterator<Item = Result<Value, BuilderError>>>;
+
+/// Iterates the records in `<sde_directory>/<stem>.<format>`, always in
+/// the JSONL shape (see [`SdeFormat`]), as [`Value`]s -- for a look at
+/// the files themselves (`audit`); the phases read theirs decoded, via
+/// [`typed_records`].
+pub(crate) fn iter_records(
+    sde_directory: &Path,
+    stem: &str,
+    format: SdeFormat,
+) -> Result<Records, BuilderError> {
+    match format {
+        SdeFormat::Jsonl => Ok(Box::new(iter_jsonl_records(sde_directory, stem)?)),
+        SdeFormat::Yaml => Ok(Box::new(
+            yaml_records(sde_directory, stem)?.into_iter().map(Ok),
+        )),
+    }
+}
+
 /// Iterates the records in `<sde_directory>/<stem>.jsonl`, one
 /// non-empty line at a time, as [`serde_json::Value`].
 ///
@@ -265,7 +534,7 @@ pub struct SystemScopeState {
 fn iter_jsonl_records(
     sde_directory: &Path,
     stem: &str,
-) -> Result<impl Iterator<Item = Result<Value, BuilderError>>, BuilderError> {
+) -> Result<impl Iterator<Item = Result<Value, BuilderError>> + 'static, BuilderError> {
     let path = sde_directory.join(format!("{stem}.jsonl"));
     let file = std::fs::File::open(&path)?;
     let reader = std::io::BufReader::new(file);
@@ -276,6 +545,421 @@ fn iter_jsonl_records(
     }))
 }
 
+/// Reads `<sde_directory>/<stem>.yaml` and reshapes it into the records
+/// the JSONL export has for the same file:
+///
+/// - The top-level mapping (`587: {name: ..., ...}`) becomes one record
+///   per entry, its key moved into `_key`.
+/// - Nested mappings keyed by integers (`corporationTrades: {41: 0.42}`,
+///   `divisions: {22: {size: 1, ...}}`) become arrays of `_key` objects,
+///   which is how the JSONL export writes them (`[{"_key": 41, "_value":
+///   0.42}]`). Mappings keyed by strings -- localized fields, `position`
+///   -- stay objects.
+/// - Entries whose value isn't a mapping get it in `_value`, at both
+///   levels.
+///
+/// Unlike [`iter_jsonl_records`] this loads the whole file at once: a
+/// YAML document can't be split into records line by line.
+fn yaml_records(sde_directory: &Path, stem: &str) -> Result<Vec<Value>, BuilderError> {
+    let path = sde_directory.join(format!("{stem}.yaml"));
+    let file = std::fs::File::open(&path)?;
+    let document: serde_norway::Value = serde_norway::from_reader(std::io::BufReader::new(file))?;
+    match document {
+        serde_norway::Value::Null => Ok(Vec::new()),
+        serde_norway::Value::Mapping(mapping) => mapping
+            .into_iter()
+            .map(|(key, value)| Ok(keyed_entry(yaml_to_json(key)?, yaml_to_json(value)?)))
+            .collect(),
+        _ => Err(BuilderError::Data(format!(
+            "{} isn't a mapping of records",
+            path.display()
+        ))),
+    }
+}
+
+/// A `{key: value}` entry as a JSONL-style record: `value` itself with
+/// `_key` added if it's an object, `{"_key": key, "_value": value}`
+/// otherwise.
+fn keyed_entry(key: Value, value: Value) -> Value {
+    match value {
+        Value::Object(mut object) => {
+            object.insert("_key".to_string(), key);
+            Value::Object(object)
+        }
+        value => serde_json::json!({"_key": key, "_value": value}),
+    }
+}
+
+/// Converts a YAML value into the JSON value the JSONL export has in its
+/// place (see [`yaml_records`]).
+fn yaml_to_json(value: serde_norway::Value) -> Result<Value, BuilderError> {
+    use serde_norway::Value as Yaml;
+    Ok(match value {
+        Yaml::Null => Value::Null,
+        Yaml::Bool(value) => Value::Bool(value),
+        Yaml::Number(number) => {
+            if let Some(value) = number.as_i64() {
+                Value::from(value)
+            } else if let Some(value) = number.as_u64() {
+                Value::from(value)
+            } else {
+                number
+                    .as_f64()
+                    .and_then(serde_json::Number::from_f64)
+                    .map_or(Value::Null, Value::Number)
+            }
+        }
+        Yaml::String(value) => Value::String(value),
+        Yaml::Sequence(items) => Value::Array(
+            items
+                .into_iter()
+                .map(yaml_to_json)
+                .collect::<Result<_, _>>()?,
+        ),
+        Yaml::Mapping(mapping) => {
+            let integer_keyed =
+                !mapping.is_empty() && mapping.keys().all(|key| key.as_i64().is_some());
+            if integer_keyed {
+                Value::Array(
+                    mapping
+                        .into_iter()
+                        .map(|(key, value)| {
+                            Ok(keyed_entry(yaml_to_json(key)?, yaml_to_json(value)?))
+                        })
+                        .collect::<Result<_, BuilderError>>()?,
+                )
+            } else {
+                let mut object = serde_json::Map::new();
+                for (key, value) in mapping {
+                    let key = match key {
+                        Yaml::String(key) => key,
+                        Yaml::Number(number) => number.to_string(),
+                        Yaml::Bool(value) => value.to_string(),
+                        other => {
+                            return Err(BuilderError::Data(format!(
+                                "unsupported YAML mapping key: {other:?}"
+                            )));
+                        }
+                    };
+                    object.insert(key, yaml_to_json(value)?);
+                }
+                Value::Object(object)
+            }
+        }
+        Yaml::Tagged(tagged) => yaml_to_json(tagged.value)?,
+    })
+}
+
+// ---------------------------------------------------------------------
+// Pipelined decoding: worker threads decode, the phases write.
+// ---------------------------------------------------------------------
+
+/// Every SDE file [`Parser::parse_data`] reads, in phase order: each one
+/// gets a worker decoding it ahead of its phase (see
+/// [`Parser::start_decoding`]).
+pub(crate) const DECODED_FILES: [&str; 18] = [
+    "translationLanguages",
+    "categories",
+    "groups",
+    "types",
+    "races",
+    "npcCorporationDivisions",
+    "npcCorporations",
+    "factions",
+    "mapRegions",
+    "mapConstellations",
+    "mapSolarSystems",
+    "mapStargates",
+    "mapStars",
+    "mapPlanets",
+    "mapMoons",
+    "stationServices",
+    "stationOperations",
+    "npcStations",
+];
+
+/// How many decoded records a worker keeps ready before it waits for its
+/// phase to catch up. Bounds memory: without it, every file would sit
+/// fully decoded in memory by the time the first few phases are done.
+const DECODE_CAPACITY: usize = 1024;
+
+/// One record of an SDE file decoded into its [`records`] struct `T`, as
+/// [`typed_records`] yields it: the line it's on (JSONL only -- a YAML
+/// document isn't split into records line by line), its `_key` when a lenient build needs it for
+/// `buildIssues` (see [`Strictness::Lenient`]), and the record, or why it
+/// couldn't be read or decoded.
+pub(crate) struct TypedRecord<T> {
+    pub(crate) line: Option<usize>,
+    pub(crate) key: Option<String>,
+    pub(crate) record: Result<T, BuilderError>,
+}
+
+/// Records of one SDE file, as yielded by [`typed_records`].
+pub(crate) type TypedRecords<T> = Box<dyn Iterator<Item = TypedRecord<T>>>;
+
+/// Iterates the records in `<sde_directory>/<stem>.<format>`, each
+/// decoded into `T` and checked to be an object. A JSON line is
+/// deserialized straight into `T` (see [`records::decode_str`]), without
+/// the [`Value`] [`iter_records`] would build for it first; a YAML
+/// document still goes through one, being reshaped by [`yaml_records`].
+///
+/// `_key`s are only looked up with `lenient` set: a strict build never
+/// logs them, and on JSONL that lookup is a second pass over the line.
+pub(crate) fn typed_records<T: DeserializeOwned + 'static>(
+    sde_directory: &Path,
+    stem: &str,
+    format: SdeFormat,
+    lenient: bool,
+) -> Result<TypedRecords<T>, BuilderError> {
+    let stem = stem.to_string();
+    match format {
+        SdeFormat::Jsonl => {
+            let file = std::fs::File::open(sde_directory.join(format!("{stem}.jsonl")))?;
+            let lines = std::io::BufReader::new(file).lines().enumerate();
+            Ok(Box::new(
+                lines
+                    .filter(|(_, line)| !matches!(line, Ok(line) if line.trim().is_empty()))
+                    .enumerate()
+                    .map(move |(index, (line_index, line))| match line {
+                        Ok(line) => TypedRecord {
+                            line: Some(line_index + 1),
+                            key: lenient.then(|| records::line_key(&line)).flatten(),
+                            record: decode_line(&stem, index, &line),
+                        },
+                        Err(err) => TypedRecord {
+                            line: Some(line_index + 1),
+                            key: None,
+                            record: Err(BuilderError::Io(err)),
+                        },
+                    }),
+            ))
+        }
+        SdeFormat::Yaml => Ok(Box::new(
+            yaml_records(sde_directory, &stem)?
+                .into_iter()
+                .enumerate()
+                .map(move |(index, value)| TypedRecord {
+                    line: None,
+                    key: records::record_key(&value),
+                    record: if value.is_object() {
+                        records::decode(&stem, &value)
+                    } else {
+                        Err(not_an_object(&stem, index))
+                    },
+                }),
+        )),
+    }
+}
+
+/// Decodes the `index`th record of `<stem>.jsonl`, the JSON text `line`,
+/// into `T`: malformed JSON is a [`BuilderError::Json`], JSON that isn't
+/// an object or doesn't match `T` a [`BuilderError::Data`].
+fn decode_line<T: DeserializeOwned>(
+    stem: &str,
+    index: usize,
+    line: &str,
+) -> Result<T, BuilderError> {
+    if line.trim_start().starts_with('{') {
+        return records::decode_str(stem, line);
+    }
+    serde_json::from_str::<Value>(line)?;
+    Err(not_an_object(stem, index))
+}
+
+/// The error for the `index`th record of `stem` (0-based) not being an
+/// object.
+fn not_an_object(stem: &str, index: usize) -> BuilderError {
+    BuilderError::Data(format!("record {} of {stem} is not an object", index + 1))
+}
+
+/// A record as a decoding worker sends it: the [`records`] struct of its
+/// file behind `dyn Any`, so one channel type serves every file --
+/// [`Parser::records`] downcasts it back.
+type AnyRecord = Box<dyn std::any::Any + Send>;
+
+/// What a decoding worker sends: a decoded record (or why it couldn't be
+/// read or decoded), or `None` once the file is done.
+type Decoded = Option<TypedRecord<AnyRecord>>;
+
+/// A decoding worker's body, for one record type (see [`decode_file`]):
+/// `(sde_directory, stem, format, lenient, sender)`.
+type DecodeFile = fn(&Path, &str, SdeFormat, bool, &SyncSender<Decoded>);
+
+/// The worker body decoding `stem` into the [`records`] struct the phase
+/// reading it expects -- the `T` its [`Parser::each_record`] call names.
+/// `None` for a file outside [`DECODED_FILES`].
+fn decoder(stem: &str) -> Option<DecodeFile> {
+    Some(match stem {
+        "translationLanguages" => decode_file::<records::TranslationLanguage>,
+        "categories" => decode_file::<records::Category>,
+        "groups" => decode_file::<records::Group>,
+        "types" => decode_file::<records::Type>,
+        "races" => decode_file::<records::Race>,
+        "npcCorporationDivisions" => decode_file::<records::NpcCorporationDivision>,
+        "npcCorporations" => decode_file::<records::NpcCorporation>,
+        "factions" => decode_file::<records::Faction>,
+        "mapRegions" => decode_file::<records::Region>,
+        "mapConstellations" => decode_file::<records::Constellation>,
+        "mapSolarSystems" => decode_file::<records::SolarSystem>,
+        "mapStargates" => decode_file::<records::Stargate>,
+        "mapStars" => decode_file::<records::Star>,
+        "mapPlanets" => decode_file::<records::Planet>,
+        "mapMoons" => decode_file::<records::Moon>,
+        "stationServices" => decode_file::<records::StationService>,
+        "stationOperations" => decode_file::<records::StationOperation>,
+        "npcStations" => decode_file::<records::NpcStation>,
+        _ => return None,
+    })
+}
+
+/// Body of a decoding worker: reads `<sde_directory>/<stem>` and decodes
+/// every record into `T` (see [`typed_records`]), sending them down
+/// `sender` in file order -- so the phase, on the thread writing the
+/// database, gets them ready to insert. A record that fails to decode is
+/// sent as its error and decoding goes on (a lenient build skips it, see
+/// [`Strictness`]); an I/O error ends the file, as does the receiving
+/// side going away.
+fn decode_file<T: DeserializeOwned + Send + 'static>(
+    sde_directory: &Path,
+    stem: &str,
+    format: SdeFormat,
+    lenient: bool,
+    sender: &SyncSender<Decoded>,
+) {
+    let records = match typed_records::<T>(sde_directory, stem, format, lenient) {
+        Ok(records) => records,
+        Err(err) => {
+            let _ = sender.send(Some(TypedRecord {
+                line: None,
+                key: None,
+                record: Err(err),
+            }));
+            return;
+        }
+    };
+    for TypedRecord { line, key, record } in records {
+        let fatal = matches!(record, Err(BuilderError::Io(_)));
+        let record = record.map(|record| Box::new(record) as AnyRecord);
+        if sender
+            .send(Some(TypedRecord { line, key, record }))
+            .is_err()
+            || fatal
+        {
+            return;
+        }
+    }
+    let _ = sender.send(None);
+}
+
+/// A decoding worker's records, as [`Parser::records`] receives them.
+/// Ends at the worker's end-of-file marker; a channel closed before it
+/// (the worker panicked) is an I/O error -- one even a lenient build
+/// stops at -- rather than a quietly truncated table.
+struct DecodedRecords {
+    stem: &'static str,
+    receiver: Receiver<Decoded>,
+    finished: bool,
+}
+
+impl Iterator for DecodedRecords {
+    type Item = TypedRecord<AnyRecord>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        match self.receiver.recv() {
+            Ok(Some(record)) => Some(record),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(_) => {
+                self.finished = true;
+                Some(TypedRecord {
+                    line: None,
+                    key: None,
+                    record: Err(BuilderError::Io(std::io::Error::other(format!(
+                        "decoding {} stopped before the end of the file",
+                        self.stem
+                    )))),
+                })
+            }
+        }
+    }
+}
+
+/// Collects [`ParseSummary::timings`] and tells the observer about each
+/// phase as it goes: [`Self::begin`] and [`Self::end`] around a phase
+/// (or [`Self::run`]/[`Self::counted`], for one that isn't `async`)
+/// record its time and emit its [`BuildEvent::PhaseStarted`]/
+/// [`BuildEvent::PhaseFinished`]. A phase doesn't begin once
+/// `cancellation` was cancelled.
+struct PhaseTimer<'a> {
+    observer: &'a dyn BuildObserver,
+    cancellation: &'a CancellationToken,
+    current: Option<(&'static str, Instant)>,
+    timings: Vec<(&'static str, Duration)>,
+}
+
+impl<'a> PhaseTimer<'a> {
+    fn start(observer: &'a dyn BuildObserver, cancellation: &'a CancellationToken) -> Self {
+        Self {
+            observer,
+            cancellation,
+            current: None,
+            timings: Vec::new(),
+        }
+    }
+
+    fn begin(&mut self, phase: &'static str) -> Result<(), BuilderError> {
+        self.cancellation.check()?;
+        self.observer.event(BuildEvent::PhaseStarted { phase });
+        self.current = Some((phase, Instant::now()));
+        Ok(())
+    }
+
+    /// Ends the phase [`Self::begin`] started, which wrote `rows` rows
+    /// if it counts them.
+    fn end(&mut self, rows: Option<usize>) {
+        if let Some((phase, started)) = self.current.take() {
+            let elapsed = started.elapsed();
+            self.timings.push((phase, elapsed));
+            self.observer.event(BuildEvent::PhaseFinished {
+                phase,
+                rows,
+                elapsed,
+            });
+        }
+    }
+
+    /// Runs `run` as `phase`. A failed phase gets no
+    /// [`BuildEvent::PhaseFinished`]: the build stops with its error.
+    fn run<T>(
+        &mut self,
+        phase: &'static str,
+        run: impl FnOnce() -> Result<T, BuilderError>,
+    ) -> Result<T, BuilderError> {
+        self.begin(phase)?;
+        let value = run()?;
+        self.end(None);
+        Ok(value)
+    }
+
+    /// Same as [`Self::run`], for a phase returning how many rows it
+    /// wrote.
+    fn counted(
+        &mut self,
+        phase: &'static str,
+        run: impl FnOnce() -> Result<usize, BuilderError>,
+    ) -> Result<usize, BuilderError> {
+        self.begin(phase)?;
+        let rows = run()?;
+        self.end(Some(rows));
+        Ok(rows)
+    }
+}
+
 // `Debug` (not derived before): needed so `#[tracing::instrument]` can
 // capture `self` on `Parser`'s methods below -- both fields are cheap and
 // meaningful to see on a span (which SDE directory, which config flags),
@@ -285,6 +969,10 @@ fn iter_jsonl_records(
 pub struct Parser {
     sde_directory: std::path::PathBuf,
     config: ParserConfig,
+    /// Files being decoded ahead of their phase, by stem, while
+    /// [`Self::parse_data`] runs (see [`Self::start_decoding`]); empty
+    /// otherwise.
+    decoding: Mutex<HashMap<&'static str, Receiver<Decoded>>>,
 }
 
 impl Parser {
@@ -292,16 +980,176 @@ impl Parser {
         Self {
             sde_directory: sde_directory.to_path_buf(),
             config,
+            decoding: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Records of `<sde_directory>/<stem>`, in `config.format`, decoded
+    /// into `T`: from its decoding worker when [`Self::parse_data`]
+    /// started one, read and decoded on the spot otherwise (a phase called
+    /// on its own).
+    fn records<T: DeserializeOwned + 'static>(
+        &self,
+        stem: &str,
+    ) -> Result<TypedRecords<T>, BuilderError> {
+        let lenient = self.config.strictness == Strictness::Lenient;
+        match self.decoding_lock().remove_entry(stem) {
+            Some((stem, receiver)) => Ok(Box::new(
+                DecodedRecords {
+                    stem,
+                    receiver,
+                    finished: false,
+                }
+                .map(move |TypedRecord { line, key, record }| TypedRecord {
+                    line,
+                    key,
+                    record: record.and_then(|record| {
+                        record.downcast::<T>().map(|record| *record).map_err(|_| {
+                            BuilderError::Io(std::io::Error::other(format!(
+                                "{stem} was decoded into another record type"
+                            )))
+                        })
+                    }),
+                }),
+            )),
+            None => typed_records(&self.sde_directory, stem, self.config.format, lenient),
+        }
+    }
+
+    /// Runs `write` on every record of [`Self::records`], decoded into
+    /// its [`records`] struct -- a record that doesn't match it fails with
+    /// the field at fault (see [`records::decode`]). During
+    /// [`Self::parse_data`] that decoding already happened on the file's
+    /// worker thread, so this thread only writes.
+    ///
+    /// With [`Strictness::Strict`] the first error is returned as-is. With
+    /// [`Strictness::Lenient`], each record is written inside a savepoint
+    /// of its own, and one that can't be read, doesn't decode, or that
+    /// `write` fails on with a data error or a constraint violation is
+    /// rolled back on its own and recorded in `buildIssues` (see
+    /// [`Self::skip_record`]) instead; any other error (I/O, a SQLite
+    /// failure that isn't about the record) still stops the build. So
+    /// `write` must leave state outside the database (the `*State`
+    /// structs) alone until its last fallible step.
+    ///
+    /// Before each record, `config.cancellation` is checked: a cancelled
+    /// build stops here with [`BuilderError::Cancelled`], never skipped.
+    fn each_record<T: DeserializeOwned + 'static>(
+        &self,
+        connection: &Connection,
+        stem: &'static str,
+        mut write: impl FnMut(T) -> Result<(), BuilderError>,
+    ) -> Result<(), BuilderError> {
+        let lenient = self.config.strictness == Strictness::Lenient;
+        for TypedRecord { line, key, record } in self.records(stem)? {
+            self.config.cancellation.check()?;
+            let record = match record {
+                Ok(record) => record,
+                Err(err) => {
+                    self.skip_record(connection, stem, line, key, err)?;
+                    continue;
+                }
+            };
+            if !lenient {
+                write(record)?;
+                continue;
+            }
+            connection.execute_batch("SAVEPOINT sde_record")?;
+            match write(record) {
+                Ok(()) => connection.execute_batch("RELEASE sde_record")?,
+                Err(err) => {
+                    connection.execute_batch("ROLLBACK TO sde_record; RELEASE sde_record")?;
+                    self.skip_record(connection, stem, line, key, err)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Records a record [`Self::each_record`] couldn't write in
+    /// `buildIssues` when the build is lenient and `err` is about the
+    /// record itself (malformed JSON, a data error, a constraint
+    /// violation); returns `err` otherwise.
+    fn skip_record(
+        &self,
+        connection: &Connection,
+        stem: &str,
+        line: Option<usize>,
+        key: Option<String>,
+        err: BuilderError,
+    ) -> Result<(), BuilderError> {
+        let skippable = match &err {
+            BuilderError::Data(_) | BuilderError::Json(_) => true,
+            BuilderError::Sqlite(err) => {
+                err.sqlite_error_code() == Some(rusqlite::ErrorCode::ConstraintViolation)
+            }
+            _ => false,
+        };
+        if self.config.strictness != Strictness::Lenient || !skippable {
+            return Err(err);
         }
+        let reason = match err {
+            BuilderError::Data(message) => message,
+            err => err.to_string(),
+        };
+        connection
+            .prepare_cached(
+                "INSERT INTO buildIssues (fileName, line, recordKey, reason) \
+                VALUES (?1, ?2, ?3, ?4)",
+            )?
+            .execute(rusqlite::params![
+                format!("{stem}.{}", self.config.format.variant()),
+                line.map(|line| line as i64),
+                key,
+                reason
+            ])?;
+        Ok(())
+    }
+
+    /// [`Self::decoding`], locked. A poisoned lock is taken over as-is:
+    /// the map itself is never left half-updated.
+    fn decoding_lock(&self) -> std::sync::MutexGuard<'_, HashMap<&'static str, Receiver<Decoded>>> {
+        self.decoding.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Starts one worker per file [`Self::parse_data`] is going to read
+    /// (see [`DECODED_FILES`]; stargates/moons only when
+    /// `config.with_gates`/`config.with_moons`), each decoding its file
+    /// into its [`records`] struct (see [`decoder`]) and into a bounded
+    /// channel [`Self::records`] later hands to the phase.
+    fn start_decoding<'scope>(
+        &self,
+        scope: &'scope std::thread::Scope<'scope, '_>,
+    ) -> Vec<std::thread::ScopedJoinHandle<'scope, ()>> {
+        let mut decoding = self.decoding_lock();
+        DECODED_FILES
+            .iter()
+            .filter(|stem| match **stem {
+                "mapStargates" => self.config.with_gates,
+                "mapMoons" => self.config.with_moons,
+                _ => true,
+            })
+            .filter_map(|stem| Some((*stem, decoder(stem)?)))
+            .map(|(stem, decode)| {
+                let (sender, receiver) = sync_channel(DECODE_CAPACITY);
+                decoding.insert(stem, receiver);
+                let sde_directory = self.sde_directory.clone();
+                let format = self.config.format;
+                let lenient = self.config.strictness == Strictness::Lenient;
+                scope.spawn(move || decode(&sde_directory, stem, format, lenient, &sender))
+            })
+            .collect()
     }
 
     // ---------------------------------------------------------------------
     // invTypes (+ typeStar for star types)
     // ---------------------------------------------------------------------
 
-    /// Inserts a row into `typeStar` and returns the `starTypeId` SQLite
-    /// assigned it (a plain `ROWID`, no `AUTOINCREMENT`, so it's read back
-    /// via a `SELECT` right after the `INSERT`).
+    /// Inserts a row into `typeStar` and returns its `starTypeId`: the
+    /// next one after those written so far, the same one SQLite would
+    /// assign a plain `ROWID` (no `AUTOINCREMENT`) on a new database --
+    /// given explicitly so an update ([`Self::update_database`]) numbers
+    /// the star types the way the build it's compared with did.
     fn add_star_type(
         &self,
         connection: &Connection,
@@ -309,187 +1157,92 @@ impl Parser {
         name: &str,
         color: &str,
     ) -> Result<i64, BuilderError> {
-        connection.execute(
-            "INSERT INTO typeStar (typeId, name, color) VALUES (?1, ?2, ?3)",
-            rusqlite::params![type_id, name, color],
-        )?;
-        let star_type_id = connection.query_row(
-            "SELECT starTypeId FROM typeStar WHERE typeId = ?1",
-            rusqlite::params![type_id],
+        let star_types = incremental::written(connection, "typeStar")?;
+        let star_type_id: i64 = connection.query_row(
+            &format!("SELECT COALESCE(MAX(starTypeId), 0) + 1 FROM {star_types} AS ts"),
+            [],
             |row| row.get(0),
         )?;
+        connection.execute(
+            "INSERT INTO typeStar (starTypeId, typeId, name, color) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![star_type_id, type_id, name, color],
+        )?;
         Ok(star_type_id)
     }
 
-    /// Extracts a required integer field from the record: if the field
-    /// isn't present or isn't numeric, this is a data error
-    /// ([`BuilderError::Data`]), not a silent `None`.
-    fn required_i64(&self, record: &Value, field: &str) -> Result<i64, BuilderError> {
-        record.get(field).and_then(Value::as_i64).ok_or_else(|| {
-            BuilderError::Data(format!(
-                "record missing required field `{field}` (or it's not an integer): {record}"
-            ))
-        })
-    }
-
-    /// Extracts an optional integer field (`None` if missing, no error).
-    fn optional_i64(&self, record: &Value, field: &str) -> Option<i64> {
-        record.get(field).and_then(Value::as_i64)
-    }
-
-    /// Extracts an optional boolean field.
-    fn optional_bool(&self, record: &Value, field: &str) -> Option<bool> {
-        record.get(field).and_then(Value::as_bool)
-    }
-
-    /// Extracts an optional floating-point field.
-    fn optional_f64(&self, record: &Value, field: &str) -> Option<f64> {
-        record.get(field).and_then(Value::as_f64)
-    }
-
-    /// Extracts a required plain string field (not localized -- for fields
-    /// like `tickerName` that don't carry per-language variants).
-    fn required_str<'a>(&self, record: &'a Value, field: &str) -> Result<&'a str, BuilderError> {
-        record.get(field).and_then(Value::as_str).ok_or_else(|| {
-            BuilderError::Data(format!(
-                "record missing required field `{field}` (or it's not a string): {record}"
-            ))
-        })
-    }
-
-    /// Extracts a required boolean field.
-    fn required_bool(&self, record: &Value, field: &str) -> Result<bool, BuilderError> {
-        record.get(field).and_then(Value::as_bool).ok_or_else(|| {
-            BuilderError::Data(format!(
-                "record missing required field `{field}` (or it's not a boolean): {record}"
-            ))
-        })
-    }
-
-    /// Extracts a required floating-point field.
-    fn required_f64(&self, record: &Value, field: &str) -> Result<f64, BuilderError> {
-        record.get(field).and_then(Value::as_f64).ok_or_else(|| {
-            BuilderError::Data(format!(
-                "record missing required field `{field}` (or it's not a number): {record}"
-            ))
-        })
-    }
-
-    /// Extracts ids from an optional integer array -- empty if the field is
-    /// missing or `null`. If the field IS present but isn't an array, or
-    /// any of its elements isn't an integer, that's a data error.
-    fn optional_i64_array(&self, record: &Value, field: &str) -> Result<Vec<i64>, BuilderError> {
-        match record.get(field) {
-            None | Some(Value::Null) => Ok(Vec::new()),
-            Some(Value::Array(items)) => items
-                .iter()
-                .map(|item| {
-                    item.as_i64().ok_or_else(|| {
-                        BuilderError::Data(format!(
-                            "non-integer element in array `{field}`: {item}"
-                        ))
-                    })
-                })
-                .collect(),
-            Some(other) => Err(BuilderError::Data(format!(
-                "field `{field}` is not an array: {other}"
-            ))),
-        }
-    }
-
-    /// Extracts `record["position"]["x"/"y"/"z"]` as `(f64, f64, f64)`.
-    /// Both levels are required; if `position` or any of its three
-    /// components is missing, that's a data error.
-    fn required_position(&self, record: &Value) -> Result<(f64, f64, f64), BuilderError> {
-        let position = record.get("position").ok_or_else(|| {
-            BuilderError::Data(format!(
-                "record missing required field `position`: {record}"
-            ))
-        })?;
-        let x = self.required_f64(position, "x")?;
-        let y = self.required_f64(position, "y")?;
-        let z = self.required_f64(position, "z")?;
-        Ok((x, y, z))
-    }
-
-    /// Extracts `record[outer][inner]` as a required `i64` -- used for
-    /// `destination.stargateID`/`destination.solarSystemID` in
-    /// [`Self::parse_stargates`].
-    fn required_nested_i64(
-        &self,
-        record: &Value,
-        outer: &str,
-        inner: &str,
-    ) -> Result<i64, BuilderError> {
-        let outer_val = record.get(outer).ok_or_else(|| {
-            BuilderError::Data(format!("record missing required field `{outer}`: {record}"))
-        })?;
-        self.required_i64(outer_val, inner)
-    }
-
-    /// Extracts an optional integer field that can either be at the
-    /// record's top level or nested under `nested_field` (e.g.
-    /// `statistics`), with the top level taking priority -- used for
-    /// `radius`/`locked` in [`Self::parse_stars`]. A key present but
-    /// `null` and a key that's entirely absent are treated alike here
-    /// (both fall through to the nested value), since `optional_i64`
-    /// doesn't distinguish "absent" from "present but of the wrong
-    /// type/null".
-    fn optional_i64_with_nested_fallback(
+    /// Stores every language variant of the localized `text` in
+    /// `trnTranslations`, under `(table, column, key)` -- the row and
+    /// column holding the base-language copy. Languages not loaded into
+    /// `translationLanguages` are skipped by the `INSERT ... SELECT`
+    /// itself (it only matches codes present there), so a record
+    /// carrying an unexpected language code doesn't fail the build, and
+    /// calling an individual `parse_*` function on a database where
+    /// [`Self::parse_translation_languages`] never ran simply stores no
+    /// translations. A plain (non-localized) string, or a missing field
+    /// (`None`), stores nothing either: there's no language to file it
+    /// under.
+    ///
+    /// Uses `prepare_cached` rather than a statement prepared up front by
+    /// each caller: every `parse_*` function needs the exact same
+    /// statement, and the cache keeps it compiled across records.
+    fn store_translations(
         &self,
-        record: &Value,
-        field: &str,
-        nested_field: &str,
-    ) -> Option<i64> {
-        self.optional_i64(record, field).or_else(|| {
-            record
-                .get(nested_field)
-                .and_then(|nested| self.optional_i64(nested, field))
-        })
+        connection: &Connection,
+        table: &str,
+        column: &str,
+        key: i64,
+        text: Option<&Localized>,
+    ) -> Result<(), BuilderError> {
+        let Some(text) = text else {
+            return Ok(());
+        };
+        let mut insert = connection.prepare_cached(
+            "INSERT INTO trnTranslations (tableName, columnName, keyId, languageId, text) \
+            SELECT ?1, ?2, ?3, languageId, ?5 FROM translationLanguages WHERE languageId = ?4",
+        )?;
+        for (language, text) in text.translations() {
+            insert.execute(rusqlite::params![table, column, key, language, text])?;
+        }
+        Ok(())
     }
 
-    /// Same as `optional_i64_with_nested_fallback`, but for boolean
-    /// fields (e.g. `locked`).
-    fn optional_bool_with_nested_fallback(
-        &self,
-        record: &Value,
-        field: &str,
-        nested_field: &str,
-    ) -> Option<bool> {
-        self.optional_bool(record, field).or_else(|| {
-            record
-                .get(nested_field)
-                .and_then(|nested| self.optional_bool(nested, field))
-        })
-    }
+    // ---------------------------------------------------------------------
+    // translationLanguages
+    // ---------------------------------------------------------------------
 
-    /// Same as `optional_i64_with_nested_fallback`, but for floating-point
-    /// fields -- used for `mapPlanets.radius` (a `REAL` column, unlike
-    /// `mapStars.radius`, which is `INTEGER`).
-    fn optional_f64_with_nested_fallback(
+    /// Populates `translationLanguages` from
+    /// `<sde_directory>/translationLanguages.jsonl` (`_key` is the
+    /// language code, `name` its display name, localized or plain).
+    /// Every other `parse_*` function stores its localized fields in
+    /// `trnTranslations` only for the languages loaded here, so
+    /// [`Self::parse_data`] runs this phase first. Returns the number of
+    /// rows inserted.
+    #[tracing::instrument]
+    pub fn parse_translation_languages(
         &self,
-        record: &Value,
-        field: &str,
-        nested_field: &str,
-    ) -> Option<f64> {
-        self.optional_f64(record, field).or_else(|| {
-            record
-                .get(nested_field)
-                .and_then(|nested| self.optional_f64(nested, field))
-        })
-    }
-
-    /// Extracts an optional plain string field.
-    fn optional_str<'a>(&self, record: &'a Value, field: &str) -> Option<&'a str> {
-        record.get(field).and_then(Value::as_str)
-    }
+        connection: &Connection,
+    ) -> Result<usize, BuilderError> {
+        let mut insert = connection.prepare(
+            "INSERT INTO translationLanguages (languageId, languageName) VALUES (?1, ?2)",
+        )?;
 
-    /// Extracts `record[outer][inner]` as `f64`, returning `None` if either
-    /// level is missing (or isn't numeric) -- used for `position2D.x`/`.y`,
-    /// which, unlike `position` (see `required_position`), is optional at
-    /// both levels.
-    fn optional_nested_f64(&self, record: &Value, outer: &str, inner: &str) -> Option<f64> {
-        record.get(outer)?.get(inner).and_then(Value::as_f64)
+        let mut count = 0usize;
+        self.each_record(
+            connection,
+            "translationLanguages",
+            |language: records::TranslationLanguage| {
+                // `_value`: a YAML export listing languages as `en: English`
+                // (see `yaml_records`).
+                let name = self
+                    .config
+                    .localized(language.name.as_ref())
+                    .or_else(|| self.config.localized(language.value.as_ref()));
+                insert.execute(rusqlite::params![language.id, name])?;
+                count += 1;
+                Ok(())
+            },
+        )?;
+        Ok(count)
     }
 
     /// Populates `invTypes` from `<sde_directory>/types.jsonl`, and along
@@ -509,20 +1262,23 @@ impl Parser {
         )?;
 
         let mut count = 0usize;
-        for record in iter_jsonl_records(&self.sde_directory, "types")? {
-            let record = record?;
-            let id = self.required_i64(&record, "_key")?;
-            let group_id = self.required_i64(&record, "groupID")?;
-            let name = self.config.required_localized(&record, "name")?.to_string();
-            let icon_id = self.optional_i64(&record, "iconID");
-            let published = self.optional_bool(&record, "published");
-            let volume = self.optional_f64(&record, "volume");
+        self.each_record(connection, "types", |record: records::Type| {
+            let id = record.id;
+            let name = self
+                .config
+                .required_localized("types", id, "name", &record.name)?;
 
             insert_type.execute(rusqlite::params![
-                id, group_id, name, icon_id, published, volume
+                id,
+                record.group_id,
+                name,
+                record.icon_id,
+                record.published,
+                record.volume
             ])?;
+            self.store_translations(connection, "invTypes", "typeName", id, Some(&record.name))?;
 
-            if state.sun_group_id == Some(group_id) {
+            if state.sun_group_id == Some(record.group_id) {
                 let parts: Vec<&str> = name.split(' ').collect();
                 if parts.len() >= 3 {
                     let star_name = parts[1];
@@ -539,10 +1295,8 @@ impl Parser {
             }
 
             count += 1;
-        }
-        if self.config.verbose {
-            println!("Parsed {count} types");
-        }
+            Ok(())
+        })?;
         Ok(count)
     }
 
@@ -559,18 +1313,23 @@ impl Parser {
         )?;
 
         let mut count = 0usize;
-        for record in iter_jsonl_records(&self.sde_directory, "categories")? {
-            let record = record?;
-            let id = self.required_i64(&record, "_key")?;
-            let name = self.config.required_localized(&record, "name")?;
-            let published = self.optional_bool(&record, "published");
-
-            insert_category.execute(rusqlite::params![id, name, published])?;
+        self.each_record(connection, "categories", |record: records::Category| {
+            let id = record.id;
+            let name = self
+                .config
+                .required_localized("categories", id, "name", &record.name)?;
+
+            insert_category.execute(rusqlite::params![id, name, record.published])?;
+            self.store_translations(
+                connection,
+                "invCategories",
+                "categoryName",
+                id,
+                Some(&record.name),
+            )?;
             count += 1;
-        }
-        if self.config.verbose {
-            println!("Parsed {count} categories");
-        }
+            Ok(())
+        })?;
         Ok(count)
     }
 
@@ -594,24 +1353,27 @@ impl Parser {
         )?;
 
         let mut count = 0usize;
-        for record in iter_jsonl_records(&self.sde_directory, "groups")? {
-            let record = record?;
-            let id = self.required_i64(&record, "_key")?;
-            let category_id = self.required_i64(&record, "categoryID")?;
-            let name = self.config.required_localized(&record, "name")?;
-            let anchorable = self.optional_bool(&record, "anchorable");
+        self.each_record(connection, "groups", |record: records::Group| {
+            let id = record.id;
+            let name = self
+                .config
+                .required_localized("groups", id, "name", &record.name)?;
 
-            insert_group.execute(rusqlite::params![id, category_id, name, anchorable])?;
+            insert_group.execute(rusqlite::params![
+                id,
+                record.category_id,
+                name,
+                record.anchorable
+            ])?;
+            self.store_translations(connection, "invGroups", "groupName", id, Some(&record.name))?;
 
             if name == "Sun" {
                 state.sun_group_id = Some(id);
             }
 
             count += 1;
-        }
-        if self.config.verbose {
-            println!("Parsed {count} groups");
-        }
+            Ok(())
+        })?;
         Ok(count)
     }
 
@@ -627,17 +1389,17 @@ impl Parser {
             connection.prepare("INSERT INTO races (raceId, raceName) VALUES (?1, ?2)")?;
 
         let mut count = 0usize;
-        for record in iter_jsonl_records(&self.sde_directory, "races")? {
-            let record = record?;
-            let id = self.required_i64(&record, "_key")?;
-            let name = self.config.required_localized(&record, "name")?;
+        self.each_record(connection, "races", |record: records::Race| {
+            let id = record.id;
+            let name = self
+                .config
+                .required_localized("races", id, "name", &record.name)?;
 
             insert_race.execute(rusqlite::params![id, name])?;
+            self.store_translations(connection, "races", "raceName", id, Some(&record.name))?;
             count += 1;
-        }
-        if self.config.verbose {
-            println!("Parsed {count} races");
-        }
+            Ok(())
+        })?;
         Ok(count)
     }
 
@@ -659,17 +1421,33 @@ impl Parser {
         )?;
 
         let mut count = 0usize;
-        for record in iter_jsonl_records(&self.sde_directory, "npcCorporationDivisions")? {
-            let record = record?;
-            let id = self.required_i64(&record, "_key")?;
-            let internal_name = self.required_str(&record, "internalName")?;
-            let leader_type_name = self.config.required_localized(&record, "leaderTypeName")?;
-            insert.execute(rusqlite::params![id, internal_name, leader_type_name])?;
-            count += 1;
-        }
-        if self.config.verbose {
-            println!("Parsed {count} npcCorporationDivisions");
-        }
+        self.each_record(
+            connection,
+            "npcCorporationDivisions",
+            |record: records::NpcCorporationDivision| {
+                let id = record.id;
+                let leader_type_name = self.config.required_localized(
+                    "npcCorporationDivisions",
+                    id,
+                    "leaderTypeName",
+                    &record.leader_type_name,
+                )?;
+                insert.execute(rusqlite::params![
+                    id,
+                    record.internal_name,
+                    leader_type_name
+                ])?;
+                self.store_translations(
+                    connection,
+                    "npcCorporationDivisions",
+                    "leaderTypeName",
+                    id,
+                    Some(&record.leader_type_name),
+                )?;
+                count += 1;
+                Ok(())
+            },
+        )?;
         Ok(count)
     }
 
@@ -727,109 +1505,78 @@ impl Parser {
         )?;
 
         let mut count = 0usize;
-        for record in iter_jsonl_records(&self.sde_directory, "npcCorporations")? {
-            let record = record?;
-            let id = self.required_i64(&record, "_key")?;
-            let name = self.config.required_localized(&record, "name")?;
-            let ticker = self.required_str(&record, "tickerName")?;
-            let deleted = self.required_bool(&record, "deleted")?;
-            let description = self.config.localized(&record, "description");
-            let extent = self.required_str(&record, "extent")?;
-            let has_player_personnel_manager =
-                self.required_bool(&record, "hasPlayerPersonnelManager")?;
-            let initial_price = self.required_i64(&record, "initialPrice")?;
-            let member_limit = self.required_i64(&record, "memberLimit")?;
-            let min_security = self.required_f64(&record, "minSecurity")?;
-            let minimum_join_standing = self.required_f64(&record, "minimumJoinStanding")?;
-            let send_char_termination_message =
-                self.required_bool(&record, "sendCharTerminationMessage")?;
-            let shares = self.required_i64(&record, "shares")?;
-            let size = self.required_str(&record, "size")?;
-            let size_factor = self.optional_f64(&record, "sizeFactor");
-            let tax_rate = self.required_f64(&record, "taxRate")?;
-            let unique_name = self.required_bool(&record, "uniqueName")?;
-            let ceo_id = self.optional_i64(&record, "ceoID");
-            let main_activity_id = self.optional_i64(&record, "mainActivityID");
-            let secondary_activity_id = self.optional_i64(&record, "secondaryActivityID");
-            let icon_id = self.optional_i64(&record, "iconID");
-            let race_id = self.optional_i64(&record, "raceID");
-            let enemy_id = self.optional_i64(&record, "enemyID");
-            let friend_id = self.optional_i64(&record, "friendID");
-            let faction_id = self.optional_i64(&record, "factionID");
-            let solar_system_id = self.optional_i64(&record, "solarSystemID");
-            let station_id = self.optional_i64(&record, "stationID");
-
-            insert_corp.execute(rusqlite::params![
-                id,
-                name,
-                ticker,
-                deleted,
-                description,
-                extent,
-                has_player_personnel_manager,
-                initial_price,
-                member_limit,
-                min_security,
-                minimum_join_standing,
-                send_char_termination_message,
-                shares,
-                size,
-                size_factor,
-                tax_rate,
-                unique_name,
-                ceo_id,
-                main_activity_id,
-                secondary_activity_id,
-                icon_id,
-                race_id,
-                enemy_id,
-                friend_id,
-                faction_id,
-                solar_system_id,
-                station_id
-            ])?;
+        self.each_record(
+            connection,
+            "npcCorporations",
+            |record: records::NpcCorporation| {
+                let id = record.id;
+                let name =
+                    self.config
+                        .required_localized("npcCorporations", id, "name", &record.name)?;
+                let description = self.config.localized(record.description.as_ref());
+
+                insert_corp.execute(rusqlite::params![
+                    id,
+                    name,
+                    record.ticker_name,
+                    record.deleted,
+                    description,
+                    record.extent,
+                    record.has_player_personnel_manager,
+                    record.initial_price,
+                    record.member_limit,
+                    record.min_security,
+                    record.minimum_join_standing,
+                    record.send_char_termination_message,
+                    record.shares,
+                    record.size,
+                    record.size_factor,
+                    record.tax_rate,
+                    record.unique_name,
+                    record.ceo_id,
+                    record.main_activity_id,
+                    record.secondary_activity_id,
+                    record.icon_id,
+                    record.race_id,
+                    record.enemy_id,
+                    record.friend_id,
+                    record.faction_id,
+                    record.solar_system_id,
+                    record.station_id
+                ])?;
+                for (column, text) in [
+                    ("corporationName", Some(&record.name)),
+                    ("description", record.description.as_ref()),
+                ] {
+                    self.store_translations(connection, "npcCorporations", column, id, text)?;
+                }
 
-            for allowed_race_id in self.optional_i64_array(&record, "allowedMemberRaces")? {
-                insert_allowed_race.execute(rusqlite::params![id, allowed_race_id])?;
-            }
+                for allowed_race_id in record.allowed_member_races.iter().flatten() {
+                    insert_allowed_race.execute(rusqlite::params![id, allowed_race_id])?;
+                }
 
-            if let Some(Value::Array(divisions)) = record.get("divisions") {
-                for entry in divisions {
-                    let division_id = self.required_i64(entry, "_key")?;
-                    let division_number = self.required_i64(entry, "divisionNumber")?;
-                    let leader_id = self.required_i64(entry, "leaderID")?;
-                    let division_size = self.required_i64(entry, "size")?;
+                for division in record.divisions.iter().flatten() {
                     insert_division.execute(rusqlite::params![
                         id,
-                        division_id,
-                        division_number,
-                        leader_id,
-                        division_size
+                        division.division_id,
+                        division.division_number,
+                        division.leader_id,
+                        division.size
                     ])?;
                 }
-            }
 
-            if let Some(Value::Array(trades)) = record.get("corporationTrades") {
-                for entry in trades {
-                    let type_id = self.required_i64(entry, "_key")?;
-                    let affinity = self.required_f64(entry, "_value")?;
-                    insert_trade.execute(rusqlite::params![id, type_id, affinity])?;
+                for trade in record.corporation_trades.iter().flatten() {
+                    insert_trade.execute(rusqlite::params![id, trade.key, trade.value])?;
                 }
-            }
 
-            if let Some(Value::Array(investors)) = record.get("investors") {
-                for entry in investors {
-                    let investor_id = self.required_i64(entry, "_key")?;
-                    let investor_shares = self.required_f64(entry, "_value")?;
-                    insert_investor.execute(rusqlite::params![id, investor_id, investor_shares])?;
+                for investor in record.investors.iter().flatten() {
+                    insert_investor.execute(rusqlite::params![id, investor.key, investor.value])?;
                 }
-            }
 
-            count += 1;
-        }
-        if self.config.verbose {
-            println!("Parsed {count} npcCorporations");
-        }
+                count += 1;
+                Ok(())
+            },
+        )?;
         Ok(count)
     }
 
@@ -863,46 +1610,48 @@ impl Parser {
             connection.prepare("INSERT INTO factionRace (factionId, raceId) VALUES (?1, ?2)")?;
 
         let mut count = 0usize;
-        for record in iter_jsonl_records(&self.sde_directory, "factions")? {
-            let record = record?;
-            let id = self.required_i64(&record, "_key")?;
-            let name = self.config.required_localized(&record, "name")?;
-            let icon_id = self.required_i64(&record, "iconID")?;
-            let size_factor = self.required_f64(&record, "sizeFactor")?;
-            let unique_name = self.required_bool(&record, "uniqueName")?;
-            let description = self.config.required_localized(&record, "description")?;
-            let short_description = self.config.localized(&record, "shortDescription");
-            let flat_logo = self.optional_str(&record, "flatLogo");
-            let flat_logo_with_name = self.optional_str(&record, "flatLogoWithName");
-            let corporation_id = self.optional_i64(&record, "corporationID");
-            let militia_corporation_id = self.optional_i64(&record, "militiaCorporationID");
-            let solar_system_id = self.optional_i64(&record, "solarSystemID");
-            let member_races = self.optional_i64_array(&record, "memberRaces")?;
+        self.each_record(connection, "factions", |record: records::Faction| {
+            let id = record.id;
+            let name = self
+                .config
+                .required_localized("factions", id, "name", &record.name)?;
+            let description = self.config.required_localized(
+                "factions",
+                id,
+                "description",
+                &record.description,
+            )?;
+            let short_description = self.config.localized(record.short_description.as_ref());
 
             insert_faction.execute(rusqlite::params![
                 id,
                 name,
-                icon_id,
-                size_factor,
-                unique_name,
+                record.icon_id,
+                record.size_factor,
+                record.unique_name,
                 description,
                 short_description,
-                flat_logo,
-                flat_logo_with_name,
-                corporation_id,
-                militia_corporation_id,
-                solar_system_id
+                record.flat_logo,
+                record.flat_logo_with_name,
+                record.corporation_id,
+                record.militia_corporation_id,
+                record.solar_system_id
             ])?;
+            for (column, text) in [
+                ("factionName", Some(&record.name)),
+                ("description", Some(&record.description)),
+                ("shortDescription", record.short_description.as_ref()),
+            ] {
+                self.store_translations(connection, "factions", column, id, text)?;
+            }
 
-            for race_id in member_races {
+            for race_id in record.member_races.iter().flatten() {
                 insert_faction_race.execute(rusqlite::params![id, race_id])?;
             }
 
             count += 1;
-        }
-        if self.config.verbose {
-            println!("Parsed {count} factions");
-        }
+            Ok(())
+        })?;
         Ok(count)
     }
 
@@ -923,30 +1672,33 @@ impl Parser {
         )?;
 
         let mut count = 0usize;
-        for record in iter_jsonl_records(&self.sde_directory, "mapRegions")? {
-            let record = record?;
-            let id = self.required_i64(&record, "_key")?;
-            let name = self.config.required_localized(&record, "name")?;
-            let faction_id = self.optional_i64(&record, "factionID");
-            let nebula = self.required_i64(&record, "nebulaID")?;
-            let wormhole_class_id = self.optional_i64(&record, "wormholeClassID");
-            let (center_x, center_y, center_z) = self.required_position(&record)?;
+        self.each_record(connection, "mapRegions", |record: records::Region| {
+            let id = record.id;
+            let name = self
+                .config
+                .required_localized("mapRegions", id, "name", &record.name)?;
+            let center = record.position;
 
             insert_region.execute(rusqlite::params![
                 id,
                 name,
-                faction_id,
-                center_x,
-                center_y,
-                center_z,
-                nebula,
-                wormhole_class_id
+                record.faction_id,
+                center.x,
+                center.y,
+                center.z,
+                record.nebula_id,
+                record.wormhole_class_id
             ])?;
+            self.store_translations(
+                connection,
+                "mapRegions",
+                "regionName",
+                id,
+                Some(&record.name),
+            )?;
             count += 1;
-        }
-        if self.config.verbose {
-            println!("Parsed {count} regions");
-        }
+            Ok(())
+        })?;
         Ok(count)
     }
 
@@ -969,24 +1721,38 @@ impl Parser {
         )?;
 
         let mut count = 0usize;
-        for record in iter_jsonl_records(&self.sde_directory, "mapConstellations")? {
-            let record = record?;
-            let id = match self.optional_i64(&record, "constellationID") {
-                Some(id) => id,
-                None => self.required_i64(&record, "_key")?,
-            };
-            let name = self.config.required_localized(&record, "name")?;
-            let region_id = self.required_i64(&record, "regionID")?;
-            let (center_x, center_y, center_z) = self.required_position(&record)?;
-
-            insert_constellation.execute(rusqlite::params![
-                id, name, region_id, center_x, center_y, center_z
-            ])?;
-            count += 1;
-        }
-        if self.config.verbose {
-            println!("Parsed {count} constellations");
-        }
+        self.each_record(
+            connection,
+            "mapConstellations",
+            |record: records::Constellation| {
+                let id = record.id();
+                let name = self.config.required_localized(
+                    "mapConstellations",
+                    id,
+                    "name",
+                    &record.name,
+                )?;
+                let center = record.position;
+
+                insert_constellation.execute(rusqlite::params![
+                    id,
+                    name,
+                    record.region_id,
+                    center.x,
+                    center.y,
+                    center.z
+                ])?;
+                self.store_translations(
+                    connection,
+                    "mapConstellations",
+                    "constellationName",
+                    id,
+                    Some(&record.name),
+                )?;
+                count += 1;
+                Ok(())
+            },
+        )?;
         Ok(count)
     }
 
@@ -996,7 +1762,9 @@ impl Parser {
 
     /// Populates `mapSolarSystems` from
     /// `<sde_directory>/mapSolarSystems.jsonl`, filtering by
-    /// `ParserConfig::system_in_scope` and accumulating the ids that pass the filter
+    /// `ParserConfig::system_in_scope` and `ParserConfig::system_in_filters`
+    /// (a system's region and region faction looked up through
+    /// `mapConstellations`/`mapRegions`) and accumulating the ids that pass
     /// into `state.systems_in_scope`. Requires `mapConstellations` to
     /// already be populated (FK `mapSolarSystems.constellationId ->
     /// mapConstellations.constellationId`). Returns the number of rows
@@ -1006,12 +1774,13 @@ impl Parser {
     /// map position lives entirely in `position2DX`/`position2DY`.
     ///
     /// `position2DX`/`position2DY` use the `position2D` CCP already
-    /// provides precomputed, unless `config.force_isometric_position_2d`
-    /// is on -- in which case they're always recomputed via
-    /// [`isometric_projection_2d`] (per
-    /// `config.isometric_projected_axis`), **ignoring** CCP's value, as was
+    /// provides precomputed, unless `config.force_position_2d` is on --
+    /// in which case they're always recomputed via
+    /// `config.position_2d_projection`, **ignoring** CCP's value, as was
     /// explicitly decided for this flag (see its docstring in
-    /// [`ParserConfig`]).
+    /// [`ParserConfig`]). Every projection in `config.projections` also
+    /// gets a `mapSolarSystemProjections` row per system, computed from
+    /// `centerX`/`Y`/`Z` the same way.
     ///
     /// `wormholeClassID` is read (it's needed for the scope filter above)
     /// and persisted as `wormholeClassId`.
@@ -1071,6 +1840,10 @@ impl Parser {
         let mut insert_subtype = connection.prepare(
             "INSERT INTO mapSolarSystemSubType (solarSystemId, subType) VALUES (?1, ?2)",
         )?;
+        let mut insert_projection = connection.prepare(
+            "INSERT INTO mapSolarSystemProjections (solarSystemId, projection, x, y) \
+            VALUES (?1, ?2, ?3, ?4)",
+        )?;
         let mut insert_disallowed_category = connection.prepare(
             "INSERT INTO mapSolarSystemDisallowedAnchorableCategories (solarSystemId, categoryId) \
             VALUES (?1, ?2)",
@@ -1080,101 +1853,148 @@ impl Parser {
             VALUES (?1, ?2)",
         )?;
 
-        let mut count = 0usize;
-        for record in iter_jsonl_records(&self.sde_directory, "mapSolarSystems")? {
-            let record = record?;
-            let system_id = self.required_i64(&record, "_key")?;
-            let wormhole_class_id = self.optional_i64(&record, "wormholeClassID");
-            if !self.config.system_in_scope(wormhole_class_id) {
-                continue;
+        // constellationId -> (regionId, the region's factionId), only
+        // needed (and only loaded) when a region/constellation/faction
+        // filter is set.
+        let mut constellations: std::collections::HashMap<i64, (i64, Option<i64>)> =
+            std::collections::HashMap::new();
+        if self.config.scope_filtered() {
+            let mut statement = connection.prepare(&format!(
+                "SELECT c.constellationId, c.regionId, r.factionId \
+                FROM {} AS c INNER JOIN {} AS r USING (regionId)",
+                incremental::written(connection, "mapConstellations")?,
+                incremental::written(connection, "mapRegions")?
+            ))?;
+            let mut rows = statement.query([])?;
+            while let Some(row) = rows.next()? {
+                constellations.insert(row.get(0)?, (row.get(1)?, row.get(2)?));
             }
-            state.systems_in_scope.insert(system_id);
-
-            let name = self.config.required_localized(&record, "name")?;
-            let constellation_id = self.required_i64(&record, "constellationID")?;
-            // hub/corridor/fringe are confirmed mutually exclusive against
-            // real data (never two at once across 8490 real records) --
-            // collapsed into a single `type` column instead of three
-            // separate booleans. Order doesn't matter here precisely
-            // because they never co-occur.
-            let system_type = if self.optional_bool(&record, "hub") == Some(true) {
-                Some("hub")
-            } else if self.optional_bool(&record, "corridor") == Some(true) {
-                Some("corridor")
-            } else if self.optional_bool(&record, "fringe") == Some(true) {
-                Some("fringe")
-            } else {
-                None
-            };
-            let luminosity = self.optional_f64(&record, "luminosity");
-            let radius = self.required_f64(&record, "radius")?;
-            let (center_x, center_y, center_z) = self.required_position(&record)?;
-
-            let security = self.required_f64(&record, "securityStatus")?;
-            let security_class = self.optional_str(&record, "securityClass");
-            let faction_id = self.optional_i64(&record, "factionID");
-
-            let (position_2d_x, position_2d_y) = if self.config.force_isometric_position_2d {
-                let (x2d, y2d) = isometric_projection_2d(
-                    center_x,
-                    center_y,
-                    center_z,
-                    self.config.isometric_projected_axis,
-                );
-                (Some(x2d), Some(y2d))
-            } else {
-                (
-                    self.optional_nested_f64(&record, "position2D", "x"),
-                    self.optional_nested_f64(&record, "position2D", "y"),
-                )
-            };
+        }
 
-            insert_system.execute(rusqlite::params![
-                system_id,
-                name,
-                constellation_id,
-                system_type,
-                luminosity,
-                radius,
-                center_x,
-                center_y,
-                center_z,
-                security,
-                security_class,
-                position_2d_x,
-                position_2d_y,
-                wormhole_class_id,
-                faction_id,
-            ])?;
+        let mut count = 0usize;
+        self.each_record(
+            connection,
+            "mapSolarSystems",
+            |record: records::SolarSystem| {
+                let system_id = record.id;
+                if !self.config.system_in_scope(record.wormhole_class_id) {
+                    return Ok(());
+                }
+                if self.config.scope_filtered() {
+                    // A constellation that isn't there is the FK's
+                    // problem, not the filter's: it just isn't in scope.
+                    let Some(&(region_id, region_faction_id)) =
+                        constellations.get(&record.constellation_id)
+                    else {
+                        return Ok(());
+                    };
+                    if !self.config.system_in_filters(
+                        region_id,
+                        record.constellation_id,
+                        record.faction_id.or(region_faction_id),
+                    ) {
+                        return Ok(());
+                    }
+                }
 
-            // Unlike hub/corridor/fringe, border/regional/international
-            // are NOT mutually exclusive (confirmed: 104 real systems
-            // carry two or all three at once) -- each one that's true
-            // gets its own row in mapSolarSystemSubType, instead of
-            // collapsing into a single column the way `type` does.
-            for subtype in ["border", "regional", "international"] {
-                if self.optional_bool(&record, subtype) == Some(true) {
-                    insert_subtype.execute(rusqlite::params![system_id, subtype])?;
+                let name = self.config.required_localized(
+                    "mapSolarSystems",
+                    system_id,
+                    "name",
+                    &record.name,
+                )?;
+                // hub/corridor/fringe are confirmed mutually exclusive against
+                // real data (never two at once across 8490 real records) --
+                // collapsed into a single `type` column instead of three
+                // separate booleans. Order doesn't matter here precisely
+                // because they never co-occur.
+                let system_type = if record.hub == Some(true) {
+                    Some("hub")
+                } else if record.corridor == Some(true) {
+                    Some("corridor")
+                } else if record.fringe == Some(true) {
+                    Some("fringe")
+                } else {
+                    None
+                };
+                let center = record.position;
+
+                let (position_2d_x, position_2d_y) =
+                    if let Some(projection) = self.config.position_2d() {
+                        let (x2d, y2d) = projection.project(center.x, center.y, center.z);
+                        (Some(x2d), Some(y2d))
+                    } else {
+                        let position_2d = record.position_2d.unwrap_or_default();
+                        (position_2d.x, position_2d.y)
+                    };
+
+                insert_system.execute(rusqlite::params![
+                    system_id,
+                    name,
+                    record.constellation_id,
+                    system_type,
+                    record.luminosity,
+                    record.radius,
+                    center.x,
+                    center.y,
+                    center.z,
+                    record.security_status,
+                    record.security_class,
+                    position_2d_x,
+                    position_2d_y,
+                    record.wormhole_class_id,
+                    record.faction_id,
+                ])?;
+                self.store_translations(
+                    connection,
+                    "mapSolarSystems",
+                    "solarSystemName",
+                    system_id,
+                    Some(&record.name),
+                )?;
+                for projection in &self.config.projections {
+                    let (x2d, y2d) = projection.project(center.x, center.y, center.z);
+                    insert_projection.execute(rusqlite::params![
+                        system_id,
+                        projection.name(),
+                        x2d,
+                        y2d
+                    ])?;
                 }
-            }
 
-            // disallowedAnchorCategories/disallowedAnchorGroups are
-            // independent arrays (confirmed: neither can be derived
-            // from the other via invGroups.categoryId), so each gets
-            // its own junction table, populated the same way as
-            // subType above -- one row per id present.
-            for category_id in self.optional_i64_array(&record, "disallowedAnchorCategories")? {
-                insert_disallowed_category.execute(rusqlite::params![system_id, category_id])?;
-            }
-            for group_id in self.optional_i64_array(&record, "disallowedAnchorGroups")? {
-                insert_disallowed_group.execute(rusqlite::params![system_id, group_id])?;
-            }
+                // Unlike hub/corridor/fringe, border/regional/international
+                // are NOT mutually exclusive (confirmed: 104 real systems
+                // carry two or all three at once) -- each one that's true
+                // gets its own row in mapSolarSystemSubType, instead of
+                // collapsing into a single column the way `type` does.
+                for (subtype, flag) in [
+                    ("border", record.border),
+                    ("regional", record.regional),
+                    ("international", record.international),
+                ] {
+                    if flag == Some(true) {
+                        insert_subtype.execute(rusqlite::params![system_id, subtype])?;
+                    }
+                }
 
-            count += 1;
-        }
-        if self.config.verbose {
-            println!("Parsed {count} solar systems");
-        }
+                // disallowedAnchorCategories/disallowedAnchorGroups are
+                // independent arrays (confirmed: neither can be derived
+                // from the other via invGroups.categoryId), so each gets
+                // its own junction table, populated the same way as
+                // subType above -- one row per id present.
+                for category_id in record.disallowed_anchor_categories.iter().flatten() {
+                    insert_disallowed_category
+                        .execute(rusqlite::params![system_id, category_id])?;
+                }
+                for group_id in record.disallowed_anchor_groups.iter().flatten() {
+                    insert_disallowed_group.execute(rusqlite::params![system_id, group_id])?;
+                }
+
+                state.systems_in_scope.insert(system_id);
+                count += 1;
+                Ok(())
+            },
+        )?;
         Ok(count)
     }
 
@@ -1186,7 +2006,9 @@ impl Parser {
     /// (the file is named `mapStargates`, even though the destination table
     /// is `mapSystemGates` -- that's how the SDE itself names it). Filters
     /// by `state.systems_in_scope` (populated by [`Self::parse_solar_systems`]): a
-    /// gate whose `solarSystemID` isn't in that set is skipped. Requires
+    /// gate whose `solarSystemID` -- or whose destination's -- isn't in
+    /// that set is skipped, so no gate is left pointing at a system (and
+    /// a gate) that was never imported. Requires
     /// `mapSolarSystems`/`invTypes` to already be populated (FKs). Returns
     /// the number of rows inserted.
     ///
@@ -1232,36 +2054,29 @@ impl Parser {
         )?;
 
         let mut count = 0usize;
-        for record in iter_jsonl_records(&self.sde_directory, "mapStargates")? {
-            let record = record?;
-            let solar_system_id = self.required_i64(&record, "solarSystemID")?;
-            if !state.systems_in_scope.contains(&solar_system_id) {
-                continue;
+        self.each_record(connection, "mapStargates", |record: records::Stargate| {
+            if !state.systems_in_scope.contains(&record.solar_system_id)
+                || !state
+                    .systems_in_scope
+                    .contains(&record.destination.solar_system_id)
+            {
+                return Ok(());
             }
 
-            let id = self.required_i64(&record, "_key")?;
-            let type_id = self.required_i64(&record, "typeID")?;
-            let (pos_x, pos_y, pos_z) = self.required_position(&record)?;
-            let destination_gate_id =
-                self.required_nested_i64(&record, "destination", "stargateID")?;
-            let destination_system_id =
-                self.required_nested_i64(&record, "destination", "solarSystemID")?;
-
+            let position = record.position;
             insert_gate.execute(rusqlite::params![
-                id,
-                solar_system_id,
-                type_id,
-                pos_x,
-                pos_y,
-                pos_z,
-                destination_gate_id,
-                destination_system_id,
+                record.id,
+                record.solar_system_id,
+                record.type_id,
+                position.x,
+                position.y,
+                position.z,
+                record.destination.stargate_id,
+                record.destination.solar_system_id,
             ])?;
             count += 1;
-        }
-        if self.config.verbose {
-            println!("Parsed {count} stargates");
-        }
+            Ok(())
+        })?;
         Ok(count)
     }
 
@@ -1282,9 +2097,9 @@ impl Parser {
     /// `statistics.radius`), `statistics` is always present, and `locked`
     /// **never** shows up -- neither at the top level nor inside
     /// `statistics` -- so in practice that column always comes out
-    /// `NULL`. The nested fallback (see `optional_i64_with_nested_fallback`/
-    /// `optional_bool_with_nested_fallback`) is kept anyway, in case some
-    /// other SDE version does carry it.
+    /// `NULL`. The nested fallback (see [`records::Star::radius`]/
+    /// [`records::Star::locked`]) is kept anyway, in case some other SDE
+    /// version does carry it.
     ///
     /// # `starTypeId` not found
     ///
@@ -1310,17 +2125,13 @@ impl Parser {
         )?;
 
         let mut count = 0usize;
-        for record in iter_jsonl_records(&self.sde_directory, "mapStars")? {
-            let record = record?;
-            let solar_system_id = self.required_i64(&record, "solarSystemID")?;
-            if !state.systems_in_scope.contains(&solar_system_id) {
-                continue;
+        self.each_record(connection, "mapStars", |record: records::Star| {
+            if !state.systems_in_scope.contains(&record.solar_system_id) {
+                return Ok(());
             }
 
-            let star_id = self.required_i64(&record, "_key")?;
-            let locked = self.optional_bool_with_nested_fallback(&record, "locked", "statistics");
-            let radius = self.optional_i64_with_nested_fallback(&record, "radius", "statistics");
-            let type_id = self.required_i64(&record, "typeID")?;
+            let star_id = record.id;
+            let type_id = record.type_id;
             let star_type_id =
                 star_state
                     .star_type_ids
@@ -1335,16 +2146,14 @@ impl Parser {
 
             insert_star.execute(rusqlite::params![
                 star_id,
-                solar_system_id,
-                locked,
-                radius,
+                record.solar_system_id,
+                record.locked(),
+                record.radius(),
                 star_type_id
             ])?;
             count += 1;
-        }
-        if self.config.verbose {
-            println!("Parsed {count} stars");
-        }
+            Ok(())
+        })?;
         Ok(count)
     }
 
@@ -1361,16 +2170,15 @@ impl Parser {
     /// records, EVE Online, August 2026):
     /// - `celestialIndex`, `position`, `typeID` and `solarSystemID` are
     ///   present in 100% of records, so they're treated as required
-    ///   (`required_i64`/`required_position`), same criterion used
+    ///   (non-`Option` fields of [`records::Planet`]), same criterion used
     ///   throughout this file for `NOT NULL` columns
     ///   (`mapPlanets.planetaryIndex` is one) when the real source
     ///   confirms the data is always there: fail early with a clear
     ///   message instead of letting SQLite reject a `NULL` further down.
     /// - `radius` is **always** at the top level (never needs the nested
     ///   fallback to `statistics.radius`) -- but unlike `mapStars.radius`
-    ///   (an `INTEGER` column), `mapPlanets.radius` is `REAL`, so it's
-    ///   read with `optional_f64_with_nested_fallback`, not the `i64`
-    ///   variant.
+    ///   (an `INTEGER` column), `mapPlanets.radius` is `REAL`, so
+    ///   [`records::Planet::radius`] is an `f64`, not an `i64`.
     /// - `fragmented` **never** shows up, neither at the top level nor
     ///   nested (0 out of 68407) -- in practice this column always comes
     ///   out `NULL`.
@@ -1391,39 +2199,27 @@ impl Parser {
         )?;
 
         let mut count = 0usize;
-        for record in iter_jsonl_records(&self.sde_directory, "mapPlanets")? {
-            let record = record?;
-            let solar_system_id = self.required_i64(&record, "solarSystemID")?;
-            if !state.systems_in_scope.contains(&solar_system_id) {
-                continue;
+        self.each_record(connection, "mapPlanets", |record: records::Planet| {
+            if !state.systems_in_scope.contains(&record.solar_system_id) {
+                return Ok(());
             }
 
-            let id = self.required_i64(&record, "_key")?;
-            let planet_index = self.required_i64(&record, "celestialIndex")?;
-            let fragmented =
-                self.optional_bool_with_nested_fallback(&record, "fragmented", "statistics");
-            let radius = self.optional_f64_with_nested_fallback(&record, "radius", "statistics");
-            let locked = self.optional_bool_with_nested_fallback(&record, "locked", "statistics");
-            let type_id = self.required_i64(&record, "typeID")?;
-            let (pos_x, pos_y, pos_z) = self.required_position(&record)?;
-
+            let position = record.position;
             insert_planet.execute(rusqlite::params![
-                id,
-                solar_system_id,
-                planet_index,
-                fragmented,
-                radius,
-                locked,
-                type_id,
-                pos_x,
-                pos_y,
-                pos_z,
+                record.id,
+                record.solar_system_id,
+                record.celestial_index,
+                record.fragmented(),
+                record.radius(),
+                record.locked(),
+                record.type_id,
+                position.x,
+                position.y,
+                position.z,
             ])?;
             count += 1;
-        }
-        if self.config.verbose {
-            println!("Parsed {count} planets");
-        }
+            Ok(())
+        })?;
         Ok(count)
     }
 
@@ -1442,14 +2238,14 @@ impl Parser {
     /// in 100% of records. `locked` is never at the top level, nested
     /// under `statistics` in 99.6% of records -- but genuinely absent
     /// from both places in the remaining 0.4% (1364 of 344457), confirming
-    /// the nested fallback (see `optional_bool_with_nested_fallback`) is
-    /// exercised by real data, not just a theoretical possibility.
+    /// the nested fallback (see [`records::Planet::locked`]) is exercised
+    /// by real data, not just a theoretical possibility.
     ///
     /// `moonIndex` (`orbitIndex` in the JSON) is treated as required
-    /// (`required_i64`) -- confirmed present in every one of the 344457
+    /// (a plain `i64` in [`records::Moon`]) -- confirmed present in every one of the 344457
     /// real records checked, same criterion as `mapPlanets.planetaryIndex`.
     ///
-    /// `typeId` is also treated as required (`required_i64`), confirmed
+    /// `typeId` is also treated as required, confirmed
     /// present in every real record checked -- even though the column
     /// itself is nullable in the schema (`typeId INTEGER REFERENCES
     /// invTypes(typeId)`, without `NOT NULL`).
@@ -1476,36 +2272,26 @@ impl Parser {
         )?;
 
         let mut count = 0usize;
-        for record in iter_jsonl_records(&self.sde_directory, "mapMoons")? {
-            let record = record?;
-            let solar_system_id = self.required_i64(&record, "solarSystemID")?;
-            if !state.systems_in_scope.contains(&solar_system_id) {
-                continue;
+        self.each_record(connection, "mapMoons", |record: records::Moon| {
+            if !state.systems_in_scope.contains(&record.solar_system_id) {
+                return Ok(());
             }
 
-            let id = self.required_i64(&record, "_key")?;
-            let moon_index = self.required_i64(&record, "orbitIndex")?;
-            let planet_id = self.optional_i64(&record, "orbitID");
-            let type_id = self.required_i64(&record, "typeID")?;
-            let radius = self.optional_i64_with_nested_fallback(&record, "radius", "statistics");
-            let (pos_x, pos_y, pos_z) = self.required_position(&record)?;
-
+            let position = record.position;
             insert_moon.execute(rusqlite::params![
-                id,
-                solar_system_id,
-                moon_index,
-                planet_id,
-                type_id,
-                radius,
-                pos_x,
-                pos_y,
-                pos_z,
+                record.id,
+                record.solar_system_id,
+                record.orbit_index,
+                record.orbit_id,
+                record.type_id,
+                record.radius(),
+                position.x,
+                position.y,
+                position.z,
             ])?;
             count += 1;
-        }
-        if self.config.verbose {
-            println!("Parsed {count} moons");
-        }
+            Ok(())
+        })?;
         Ok(count)
     }
 
@@ -1539,19 +2325,29 @@ impl Parser {
     /// `(msga.solarSystemId, msgb.solarSystemId)` in that order.
     #[tracing::instrument]
     pub fn parse_connections(&self, connection: &Connection) -> Result<usize, BuilderError> {
-        let count = connection.execute(
-            "INSERT INTO mapSystemConnections (systemA, systemB) \
-            SELECT MIN(msga.solarSystemId, msgb.solarSystemId), \
-                    MAX(msga.solarSystemId, msgb.solarSystemId) \
-            FROM mapSystemGates AS msga \
-            INNER JOIN mapSystemGates AS msgb ON (msgb.systemGateId = msga.destinationGateId) \
-            WHERE msga.solarSystemId < msgb.solarSystemId",
+        let gates = incremental::written(connection, "mapSystemGates")?;
+        connection.execute(
+            &format!(
+                "INSERT INTO mapSystemConnections (systemA, systemB) \
+                SELECT MIN(msga.solarSystemId, msgb.solarSystemId), \
+                        MAX(msga.solarSystemId, msgb.solarSystemId) \
+                FROM {gates} AS msga \
+                INNER JOIN {gates} AS msgb ON (msgb.systemGateId = msga.destinationGateId) \
+                WHERE msga.solarSystemId < msgb.solarSystemId"
+            ),
             [],
         )?;
-        if self.config.verbose {
-            println!("Parsed {count} system connections");
-        }
-        Ok(count)
+        // Counted afterwards: an update doesn't report the rows it
+        // found unchanged as inserted.
+        let count: i64 = connection.query_row(
+            &format!(
+                "SELECT COUNT(*) FROM {} AS msc",
+                incremental::written(connection, "mapSystemConnections")?
+            ),
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count as usize)
     }
 
     // ---------------------------------------------------------------
@@ -1569,16 +2365,29 @@ impl Parser {
             .prepare("INSERT INTO stationServices (serviceId, serviceName) VALUES (?1, ?2)")?;
 
         let mut count = 0usize;
-        for record in iter_jsonl_records(&self.sde_directory, "stationServices")? {
-            let record = record?;
-            let id = self.required_i64(&record, "_key")?;
-            let name = self.config.required_localized(&record, "serviceName")?;
-            insert.execute(rusqlite::params![id, name])?;
-            count += 1;
-        }
-        if self.config.verbose {
-            println!("Parsed {count} station services");
-        }
+        self.each_record(
+            connection,
+            "stationServices",
+            |record: records::StationService| {
+                let id = record.id;
+                let name = self.config.required_localized(
+                    "stationServices",
+                    id,
+                    "serviceName",
+                    &record.service_name,
+                )?;
+                insert.execute(rusqlite::params![id, name])?;
+                self.store_translations(
+                    connection,
+                    "stationServices",
+                    "serviceName",
+                    id,
+                    Some(&record.service_name),
+                )?;
+                count += 1;
+                Ok(())
+            },
+        )?;
         Ok(count)
     }
 
@@ -1592,8 +2401,8 @@ impl Parser {
     /// Confirmed against the real 68 records: `_key`, `activityID`,
     /// `border`, `corridor`, `fringe`, `hub`, `manufacturingFactor`,
     /// `operationName`, `ratio`, `researchFactor`, `services` are present
-    /// in 100% of records -- treated as required
-    /// (`required_i64`/`required_f64`/`ParserConfig::required_localized`).
+    /// in 100% of records -- treated as required (non-`Option` fields of
+    /// [`records::StationOperation`], `services` aside).
     /// `description` is present in 55/68 (80.9%) -- optional
     /// (`ParserConfig::localized`, not `ParserConfig::required_localized`). `stationTypes` is
     /// present in 47/68 (69.1%) -- also optional, only inserted into
@@ -1621,56 +2430,61 @@ impl Parser {
         )?;
 
         let mut count = 0usize;
-        for record in iter_jsonl_records(&self.sde_directory, "stationOperations")? {
-            let record = record?;
-            let id = self.required_i64(&record, "_key")?;
-            let activity_id = self.required_i64(&record, "activityID")?;
-            let name = self.config.required_localized(&record, "operationName")?;
-            let description = self.config.localized(&record, "description");
-            let border = self.required_f64(&record, "border")?;
-            let corridor = self.required_f64(&record, "corridor")?;
-            let fringe = self.required_f64(&record, "fringe")?;
-            let hub = self.required_f64(&record, "hub")?;
-            let ratio = self.required_f64(&record, "ratio")?;
-            let manufacturing_factor = self.required_f64(&record, "manufacturingFactor")?;
-            let research_factor = self.required_f64(&record, "researchFactor")?;
-
-            insert_operation.execute(rusqlite::params![
-                id,
-                activity_id,
-                name,
-                description,
-                border,
-                corridor,
-                fringe,
-                hub,
-                ratio,
-                manufacturing_factor,
-                research_factor
-            ])?;
+        self.each_record(
+            connection,
+            "stationOperations",
+            |record: records::StationOperation| {
+                let id = record.id;
+                let name = self.config.required_localized(
+                    "stationOperations",
+                    id,
+                    "operationName",
+                    &record.operation_name,
+                )?;
+                let description = self.config.localized(record.description.as_ref());
+
+                insert_operation.execute(rusqlite::params![
+                    id,
+                    record.activity_id,
+                    name,
+                    description,
+                    record.border,
+                    record.corridor,
+                    record.fringe,
+                    record.hub,
+                    record.ratio,
+                    record.manufacturing_factor,
+                    record.research_factor
+                ])?;
+                for (column, text) in [
+                    ("operationName", Some(&record.operation_name)),
+                    ("description", record.description.as_ref()),
+                ] {
+                    self.store_translations(connection, "stationOperations", column, id, text)?;
+                }
 
-            for service_id in self.optional_i64_array(&record, "services")? {
-                insert_service.execute(rusqlite::params![id, service_id])?;
-            }
+                for service_id in record.services.iter().flatten() {
+                    insert_service.execute(rusqlite::params![id, service_id])?;
+                }
 
-            if let Some(Value::Array(station_types)) = record.get("stationTypes") {
-                for entry in station_types {
-                    let size_key = self.required_i64(entry, "_key")?;
-                    let type_id = self.required_i64(entry, "_value")?;
-                    insert_type.execute(rusqlite::params![id, size_key, type_id])?;
+                for station_type in record.station_types.iter().flatten() {
+                    insert_type.execute(rusqlite::params![
+                        id,
+                        station_type.key,
+                        station_type.value
+                    ])?;
                 }
-            }
 
-            count += 1;
-        }
-        if self.config.verbose {
-            println!("Parsed {count} station operations");
-        }
+                count += 1;
+                Ok(())
+            },
+        )?;
         Ok(count)
     }
 
     /// Populates `npcStations` from `<sde_directory>/npcStations.jsonl`
-    /// (5210 records). Requires `mapMoons`/`mapPlanets`,
+    /// (5210 records), filtering by `state.systems_in_scope` (populated by
+    /// [`Self::parse_solar_systems`]). Requires `mapMoons`/`mapPlanets`,
     /// `mapSolarSystems`, `npcCorporations`, `invTypes`,
     /// and [`Self::parse_station_operations`] to have already run --
     /// every foreign key on this table points somewhere.
@@ -1699,14 +2513,21 @@ impl Parser {
     ///
     /// `celestialIndex` (present in 5209/5210, 99.98%) and `orbitIndex`
     /// (present in 3986/5210, 76.5% -- exactly the stations that orbit a
-    /// moon) are both treated as optional (`optional_i64`), matching
+    /// moon) are both treated as optional (`Option<i64>`), matching
     /// their real, confirmed absence rate -- not just a defensive
     /// assumption.
-    #[tracing::instrument]
-    pub fn parse_npc_stations(&self, connection: &Connection) -> Result<usize, BuilderError> {
+    #[tracing::instrument(skip(state))]
+    pub fn parse_npc_stations(
+        &self,
+        connection: &Connection,
+        state: &SystemScopeState,
+    ) -> Result<usize, BuilderError> {
         let mut moon_ids: std::collections::HashSet<i64> = std::collections::HashSet::new();
         {
-            let mut statement = connection.prepare("SELECT moonId FROM mapMoons")?;
+            let mut statement = connection.prepare(&format!(
+                "SELECT moonId FROM {} AS mm",
+                incremental::written(connection, "mapMoons")?
+            ))?;
             let mut rows = statement.query([])?;
             while let Some(row) = rows.next()? {
                 moon_ids.insert(row.get(0)?);
@@ -1714,7 +2535,10 @@ impl Parser {
         }
         let mut planet_ids: std::collections::HashSet<i64> = std::collections::HashSet::new();
         {
-            let mut statement = connection.prepare("SELECT planetId FROM mapPlanets")?;
+            let mut statement = connection.prepare(&format!(
+                "SELECT planetId FROM {} AS mp",
+                incremental::written(connection, "mapPlanets")?
+            ))?;
             let mut rows = statement.query([])?;
             while let Some(row) = rows.next()? {
                 planet_ids.insert(row.get(0)?);
@@ -1731,12 +2555,12 @@ impl Parser {
         )?;
 
         let mut count = 0usize;
-        for record in iter_jsonl_records(&self.sde_directory, "npcStations")? {
-            let record = record?;
-            let id = self.required_i64(&record, "_key")?;
-            let celestial_index = self.optional_i64(&record, "celestialIndex");
-            let operation_id = self.required_i64(&record, "operationID")?;
-            let orbit_id = self.required_i64(&record, "orbitID")?;
+        self.each_record(connection, "npcStations", |record: records::NpcStation| {
+            if !state.systems_in_scope.contains(&record.solar_system_id) {
+                return Ok(());
+            }
+
+            let orbit_id = record.orbit_id;
             let (orbit_moon_id, orbit_planet_id) = if moon_ids.contains(&orbit_id) {
                 (Some(orbit_id), None)
             } else if planet_ids.contains(&orbit_id) {
@@ -1744,40 +2568,45 @@ impl Parser {
             } else {
                 (None, None)
             };
-            let orbit_index = self.optional_i64(&record, "orbitIndex");
-            let owner_id = self.required_i64(&record, "ownerID")?;
-            let (x, y, z) = self.required_position(&record)?;
-            let reprocessing_efficiency = self.required_f64(&record, "reprocessingEfficiency")?;
-            let reprocessing_hangar_flag = self.required_i64(&record, "reprocessingHangarFlag")?;
-            let reprocessing_stations_take =
-                self.required_f64(&record, "reprocessingStationsTake")?;
-            let solar_system_id = self.required_i64(&record, "solarSystemID")?;
-            let type_id = self.required_i64(&record, "typeID")?;
-            let use_operation_name = self.required_bool(&record, "useOperationName")?;
+            let position = record.position;
 
             insert.execute(rusqlite::params![
-                id,
-                celestial_index,
-                operation_id,
+                record.id,
+                record.celestial_index,
+                record.operation_id,
                 orbit_moon_id,
                 orbit_planet_id,
-                orbit_index,
-                owner_id,
-                x,
-                y,
-                z,
-                reprocessing_efficiency,
-                reprocessing_hangar_flag,
-                reprocessing_stations_take,
-                solar_system_id,
-                type_id,
-                use_operation_name
+                record.orbit_index,
+                record.owner_id,
+                position.x,
+                position.y,
+                position.z,
+                record.reprocessing_efficiency,
+                record.reprocessing_hangar_flag,
+                record.reprocessing_stations_take,
+                record.solar_system_id,
+                record.type_id,
+                record.use_operation_name
             ])?;
             count += 1;
-        }
-        if self.config.verbose {
-            println!("Parsed {count} NPC stations");
-        }
+            Ok(())
+        })?;
+        Ok(count)
+    }
+
+    // ---------------------------------------------------------------------
+    // searchIndex / searchTrigrams
+    // ---------------------------------------------------------------------
+
+    /// Creates and fills the FTS5 search tables via
+    /// [`search::create_search_index`]. Like [`Self::parse_connections`],
+    /// reads no SDE file: everything it indexes is already in the database,
+    /// so it has to run after every phase it draws names from (map,
+    /// types, NPC corporations, stations, `trnTranslations`). Returns the
+    /// number of names indexed.
+    #[tracing::instrument]
+    pub fn parse_search_index(&self, connection: &Connection) -> Result<usize, BuilderError> {
+        let count = search::create_search_index(connection)?;
         Ok(count)
     }
 
@@ -1792,19 +2621,26 @@ impl Parser {
     /// back -- nothing is left half-persisted -- because rusqlite's
     /// `Transaction` rolls back automatically on `Drop` if `.commit()` was
     /// never called, and each call below's `?` operator triggers exactly
-    /// that early `Drop` when it propagates the error.
+    /// that early `Drop` when it propagates the error. With
+    /// `config.strictness` set to [`Strictness::Lenient`], a record that
+    /// can't be decoded or written is skipped instead (see there) and
+    /// counted in [`ParseSummary::skipped_records`].
     ///
     /// Requires `&mut Connection` (not `&Connection` like the individual
     /// functions) because `Connection::transaction()` requires it.
     ///
     /// ## Coverage
     ///
-    /// Populates categories, groups, types (+ `typeStar`), races, NPC
+    /// Populates `translationLanguages` first (every later phase files its
+    /// localized fields' translations under those languages), then
+    /// categories, groups, types (+ `typeStar`), races, NPC
     /// corporations, factions (+ `factionRace`), regions, constellations,
     /// solar systems, stargates (gated by `config.with_gates`), stars,
     /// planets, moons (gated by `config.with_moons`), connections,
     /// `stationServices`, `stationOperations` (+ its two junction tables),
-    /// and `npcStations`. `npcStations` runs last and unconditionally (no
+    /// and `npcStations`, then the search index (see
+    /// [`Self::parse_search_index`]). `npcStations` runs last of the SDE
+    /// files and unconditionally (no
     /// config flag gates it, same as most tables besides gates/moons), but
     /// its `orbitMoonId` resolution depends on `parse_moons`/`parse_planets`
     /// having already populated `mapMoons`/`mapPlanets` -- if
@@ -1813,38 +2649,126 @@ impl Parser {
     /// `orbitMoonId`/`orbitPlanetId` `NULL`), same as the one
     /// genuinely-neither station in the real data. See
     /// [`Self::parse_npc_stations`]'s docstring for more on this table.
-    #[tracing::instrument]
-    pub fn parse_data(&self, connection: &mut Connection) -> Result<ParseSummary, BuilderError> {
-        let tx = connection.transaction()?;
+    ///
+    /// ## Pipelining
+    ///
+    /// Decoding the SDE's records (JSON, or YAML) costs more than writing
+    /// them, and doesn't depend on anything another phase does. So before
+    /// the first phase runs, every file it's going to read gets a worker
+    /// thread of its own (`start_decoding`) that decodes it all the way
+    /// into the phase's [`records`] struct -- a JSON line deserialized
+    /// straight into it, no intermediate `Value` -- and queues the typed
+    /// records up, or their errors with their line numbers (a bounded
+    /// queue, `DECODE_CAPACITY` records). The phases themselves
+    /// are unchanged: one after another, in the order above, on this one
+    /// transaction -- the only writer -- each taking its file's records
+    /// from the queue instead of reading it. So the phase order, the
+    /// state threaded between phases and the deferred foreign keys
+    /// (checked at commit, see below) all work exactly as they would
+    /// sequentially; what overlaps is only a phase's writing with the
+    /// decoding of the files after it. Each phase's time ends up in
+    /// [`ParseSummary::timings`], and `observer` hears about each one as
+    /// it starts and finishes (see [`PHASES`]).
+    #[tracing::instrument(skip(observer))]
+    pub fn parse_data(
+        &self,
+        connection: &mut Connection,
+        observer: &dyn BuildObserver,
+    ) -> Result<ParseSummary, BuilderError> {
+        self.parse_with_workers(connection, observer, false)
+    }
 
-        let categories = self.parse_categories(&tx)?;
-        let mut state = StarTypeState::default();
-        let groups = self.parse_groups(&tx, &mut state)?;
-        let types = self.parse_types(&tx, &mut state)?;
-        let races = self.parse_races(&tx)?;
-        let npc_corporation_divisions = self.parse_npc_corporation_divisions(&tx)?;
-        let npc_corporations = self.parse_npc_corporations(&tx)?;
-        let factions = self.parse_factions(&tx)?;
-        let regions = self.parse_regions(&tx)?;
-        let constellations = self.parse_constellations(&tx)?;
-        let mut scope = SystemScopeState::default();
-        let solar_systems = self.parse_solar_systems(&tx, &mut scope)?;
-        let stargates = if self.config.with_gates {
-            self.parse_stargates(&tx, &scope)?
-        } else {
-            0
-        };
-        let stars = self.parse_stars(&tx, &scope, &state)?;
-        let planets = self.parse_planets(&tx, &scope)?;
-        let moons = if self.config.with_moons {
-            self.parse_moons(&tx, &scope)?
+    /// [`Self::parse_data`], or with `update` [`Self::update_database`]:
+    /// the decoding workers around [`Self::run_phases`].
+    fn parse_with_workers(
+        &self,
+        connection: &mut Connection,
+        observer: &dyn BuildObserver,
+        update: bool,
+    ) -> Result<ParseSummary, BuilderError> {
+        std::thread::scope(|scope| {
+            let workers = self.start_decoding(scope);
+            let result = self.run_phases(connection, observer, update);
+            // Workers of files no phase read (failed early, or gated off)
+            // are blocked on a full channel: dropping the receivers makes
+            // their next send fail, so they return. A worker's panic needs
+            // no handling here: its phase already got an error in its
+            // place (see `DecodedRecords`), and rolled back.
+            self.decoding_lock().clear();
+            for worker in workers {
+                let _ = worker.join();
+            }
+            result
+        })
+    }
+
+    /// [`Self::parse_data`]'s phases, on the writer side. With `update`,
+    /// on an existing database instead (see [`Self::update_database`]):
+    /// an [`incremental::Tracker`] compares what the phases write with
+    /// what's stored, and before the search index is rebuilt the
+    /// metadata is written and what no record wrote deleted.
+    fn run_phases(
+        &self,
+        connection: &mut Connection,
+        observer: &dyn BuildObserver,
+        update: bool,
+    ) -> Result<ParseSummary, BuilderError> {
+        let tx = connection.transaction()?;
+        let mut tracker = if update {
+            Some(incremental::Tracker::start(&tx)?)
         } else {
-            0
+            None
         };
-        let connections = self.parse_connections(&tx)?;
+        let mut timer = PhaseTimer::start(observer, &self.config.cancellation);
 
-        let station_services = self.parse_station_services(&tx)?;
-        let station_operations = self.parse_station_operations(&tx)?;
+        let translation_languages = timer.counted("translation_languages", || {
+            self.parse_translation_languages(&tx)
+        })?;
+        let categories = timer.counted("categories", || self.parse_categories(&tx))?;
+        let mut state = StarTypeState::default();
+        let groups = timer.counted("groups", || self.parse_groups(&tx, &mut state))?;
+        let types = timer.counted("types", || self.parse_types(&tx, &mut state))?;
+        let races = timer.counted("races", || self.parse_races(&tx))?;
+        let npc_corporation_divisions = timer.counted("npc_corporation_divisions", || {
+            self.parse_npc_corporation_divisions(&tx)
+        })?;
+        let npc_corporations =
+            timer.counted("npc_corporations", || self.parse_npc_corporations(&tx))?;
+        let factions = timer.counted("factions", || self.parse_factions(&tx))?;
+        let regions = timer.counted("regions", || self.parse_regions(&tx))?;
+        let constellations = timer.counted("constellations", || self.parse_constellations(&tx))?;
+        let mut scope = SystemScopeState::default();
+        let solar_systems = timer.counted("solar_systems", || {
+            self.parse_solar_systems(&tx, &mut scope)
+        })?;
+        let stargates = timer.counted("stargates", || {
+            if self.config.with_gates {
+                self.parse_stargates(&tx, &scope)
+            } else {
+                Ok(0)
+            }
+        })?;
+        let stars = timer.counted("stars", || self.parse_stars(&tx, &scope, &state))?;
+        let planets = timer.counted("planets", || self.parse_planets(&tx, &scope))?;
+        let moons = timer.counted("moons", || {
+            if self.config.with_moons {
+                self.parse_moons(&tx, &scope)
+            } else {
+                Ok(0)
+            }
+        })?;
+        let connections = timer.counted("connections", || self.parse_connections(&tx))?;
+
+        let station_services =
+            timer.counted("station_services", || self.parse_station_services(&tx))?;
+        let station_operations =
+            timer.counted("station_operations", || self.parse_station_operations(&tx))?;
+        let npc_stations =
+            timer.counted("npc_stations", || self.parse_npc_stations(&tx, &scope))?;
+        if let Some(tracker) = &mut tracker {
+            timer.run("metadata", || self.write_metadata(&tx))?;
+            timer.run("delete_unwritten", || tracker.delete_unwritten(&tx))?;
+        }
         let station_operation_services: usize =
             tx.query_row("SELECT COUNT(*) FROM stationOperationServices", [], |row| {
                 row.get::<usize, i64>(0)
@@ -1853,8 +2777,17 @@ impl Parser {
             tx.query_row("SELECT COUNT(*) FROM stationOperationTypes", [], |row| {
                 row.get::<usize, i64>(0)
             })? as usize;
-        let npc_stations = self.parse_npc_stations(&tx)?;
+        let translations: usize =
+            tx.query_row("SELECT COUNT(*) FROM trnTranslations", [], |row| {
+                row.get::<usize, i64>(0)
+            })? as usize;
+        let search_entries = timer.counted("search_index", || self.parse_search_index(&tx))?;
+        let skipped_records: usize =
+            tx.query_row("SELECT COUNT(*) FROM buildIssues", [], |row| {
+                row.get::<usize, i64>(0)
+            })? as usize;
 
+        timer.begin("foreign_key_check")?;
         // Diagnostic: PRAGMA foreign_key_check runs within this transaction,
         // before COMMIT, so it can point at exactly which row/table/FK is
         // unsatisfied -- instead of letting a bare `tx.commit()` fail with
@@ -1885,6 +2818,13 @@ impl Parser {
         // fails loudly below, since silently nulling out a column with no
         // real-data evidence that it can legitimately be unresolved would
         // risk masking an actual bug instead of a known data quirk.
+        //
+        // The one exception is a scoped build (`region_ids`/
+        // `constellation_ids`/`faction_ids`): a faction's or an NPC
+        // corporation's home system (`solarSystemId`), or a corporation's
+        // station, can legitimately be one the scope left out. Those are
+        // cleared the same way -- the faction or corporation itself stays,
+        // it just doesn't point at a system this database doesn't have.
         {
             let mut fk_list_cache: std::collections::HashMap<
                 String,
@@ -1893,7 +2833,7 @@ impl Parser {
             let mut check = tx.prepare("PRAGMA foreign_key_check")?;
             let mut rows = check.query([])?;
             let mut violations = Vec::new();
-            let mut to_null: Vec<i64> = Vec::new();
+            let mut to_null: Vec<(String, String, i64)> = Vec::new();
             while let Some(row) = rows.next()? {
                 let table: String = row.get(0)?;
                 let rowid: Option<i64> = row.get(1)?;
@@ -1917,11 +2857,18 @@ impl Parser {
                     .map(String::as_str)
                     .unwrap_or("<unknown column>");
 
-                if table == "npcCorporations" && column == "stationId" && parent == "npcStations" {
-                    if let Some(rowid) = rowid {
-                        to_null.push(rowid);
-                        continue;
-                    }
+                let out_of_scope = self.config.scope_filtered()
+                    && matches!(table.as_str(), "npcCorporations" | "factions")
+                    && column == "solarSystemId"
+                    && parent == "mapSolarSystems";
+                if ((table == "npcCorporations"
+                    && column == "stationId"
+                    && parent == "npcStations")
+                    || out_of_scope)
+                    && let Some(rowid) = rowid
+                {
+                    to_null.push((table, column.to_string(), rowid));
+                    continue;
                 }
 
                 let rowid_str = rowid
@@ -1931,9 +2878,9 @@ impl Parser {
                     "table {table}, rowid {rowid_str}, column {column} references {parent}"
                 ));
             }
-            for rowid in to_null {
+            for (table, column, rowid) in to_null {
                 tx.execute(
-                    "UPDATE npcCorporations SET stationId = NULL WHERE rowid = ?1",
+                    &format!("UPDATE {table} SET {column} = NULL WHERE rowid = ?1"),
                     [rowid],
                 )?;
             }
@@ -1946,9 +2893,16 @@ impl Parser {
             }
         }
 
-        tx.commit()?;
+        timer.end(None);
+
+        let changes = match tracker {
+            Some(tracker) => tracker.finish(&tx)?,
+            None => Vec::new(),
+        };
+        timer.run("commit", || Ok(tx.commit()?))?;
 
         Ok(ParseSummary {
+            translation_languages,
             categories,
             groups,
             types,
@@ -1970,6 +2924,11 @@ impl Parser {
             station_operation_services,
             station_operation_types,
             npc_stations,
+            translations,
+            search_entries,
+            skipped_records,
+            changes,
+            timings: timer.timings,
         })
     }
 
@@ -1987,38 +2946,149 @@ impl Parser {
     /// leaving it to every caller (CLI included) to reimplement the same
     /// `if config.with_third_party { community::process(...) }` check.
     ///
+    /// Last, it records where the database came from in `sdeMetadata`
+    /// ([`metadata::write_metadata`]), with `config.release` or
+    /// whatever `sde_directory`'s `_sde` record says.
+    ///
+    /// Cancelled (`config.cancellation`) once [`Self::parse_data`] has
+    /// committed, the database keeps the canonical SDE but misses what
+    /// came after it (community data, `sdeMetadata`): not a database to
+    /// ship. [`Self::build_database_file`] builds into a scratch file it
+    /// deletes on any failure, cancellation included -- what the CLI
+    /// uses, and what a caller should unless it cleans up after itself.
+    ///
     /// `client`/`maps_url_base` are the same two pieces of information
     /// [`community::process`] itself needs -- passed through unchanged, not
     /// duplicated as separate config fields, so a caller that isn't using
     /// `with_third_party` doesn't need to supply a real `maps_url_base` at
     /// all (any string works; it's never read).
-    #[tracing::instrument]
+    #[tracing::instrument(skip(observer))]
     pub async fn build_database(
         &self,
         connection: &mut Connection,
         client: &Client,
         maps_url_base: &str,
+        observer: &dyn BuildObserver,
+    ) -> Result<ParseSummary, BuilderError> {
+        let mut summary = self.parse_data(connection, observer)?;
+        let mut timer = PhaseTimer::start(observer, &self.config.cancellation);
+
+        if self.config.with_third_party {
+            timer.begin("community")?;
+            community::process(
+                connection,
+                client,
+                &self.sde_directory,
+                maps_url_base,
+                &self.config.community,
+                observer,
+                &self.config.cancellation,
+            )
+            .await?;
+            timer.end(None);
+        }
+
+        timer.run("metadata", || self.write_metadata(connection))?;
+        summary.timings.extend(timer.timings);
+
+        Ok(summary)
+    }
+
+    /// Records the build's provenance ([`metadata::write_metadata`]),
+    /// with `config.release` or whatever `sde_directory`'s `_sde` record
+    /// says.
+    fn write_metadata(&self, connection: &Connection) -> Result<usize, BuilderError> {
+        let release = match &self.config.release {
+            Some(release) => Some(release.clone()),
+            None => extract::directory_release(&self.sde_directory, self.config.format)?,
+        };
+        metadata::write_metadata(
+            connection,
+            &self.config,
+            release.as_ref(),
+            &self.sde_directory.join("maps"),
+        )
+    }
+
+    /// [`Self::build_database`] into a new database file that replaces
+    /// `target` once complete: the build is written to
+    /// [`install::building_path`] (a stale one from an interrupted run is
+    /// removed first), with the schema created, and put in place by
+    /// [`install::replace_database`], which keeps `keep_previous`
+    /// backups and returns the one it made, if any.
+    ///
+    /// On any failure -- [`BuilderError::Cancelled`] included, whether
+    /// it came before or after [`Self::parse_data`] committed -- the
+    /// scratch file is deleted and `target` left as it was, so a caller
+    /// has nothing to clean up.
+    #[tracing::instrument(skip(client, observer))]
+    pub async fn build_database_file(
+        &self,
+        target: &Path,
+        keep_previous: usize,
+        client: &Client,
+        maps_url_base: &str,
+        observer: &dyn BuildObserver,
+    ) -> Result<(ParseSummary, Option<Backup>), BuilderError> {
+        let building = install::building_path(target);
+        if building.exists() {
+            std::fs::remove_file(&building)?;
+        }
+        let result = async {
+            let mut connection = Connection::open(&building)?;
+            crate::builder::schema::create_schema(&connection)?;
+            self.build_database(&mut connection, client, maps_url_base, observer)
+                .await
+        }
+        .await;
+        let summary = match result {
+            Ok(summary) => summary,
+            Err(err) => {
+                if building.exists() {
+                    std::fs::remove_file(&building)?;
+                }
+                return Err(err);
+            }
+        };
+        let backup = install::replace_database(&building, target, keep_previous)?;
+        Ok((summary, backup))
+    }
+
+    /// In-place alternative to [`Self::build_database`] for a database
+    /// that already exists: the same phases decode every SDE file, but
+    /// write into `connection`'s database, where each record is
+    /// compared by primary key with the row already stored -- inserted
+    /// if it's new, updated if it differs, left alone if it doesn't --
+    /// and the rows no record wrote are deleted, all in one transaction
+    /// (see [`incremental`]). Nothing is parsed into a second database:
+    /// the cost over reading the SDE is the rows that changed, not a
+    /// rewrite of every table, and readers keep the previous database
+    /// until the commit.
+    ///
+    /// Turns `PRAGMA foreign_keys` on for `connection`, and leaves it on
+    /// afterwards. The returned summary counts the records written like
+    /// [`Self::build_database`]'s does, plus [`ParseSummary::changes`],
+    /// the rows inserted/updated/deleted per table.
+    ///
+    /// Fails with [`BuilderError::IncompatibleDatabase`], leaving the
+    /// database untouched, when it doesn't have the schema of this
+    /// crate's version, or when third-party data is involved
+    /// (`config.with_third_party`, or a database built with it) -- see
+    /// [`incremental`]'s docs. Any other failure, cancellation included,
+    /// rolls the whole update back.
+    #[tracing::instrument(skip(connection, observer))]
+    pub fn update_database(
+        &self,
+        connection: &mut Connection,
+        observer: &dyn BuildObserver,
     ) -> Result<ParseSummary, BuilderError> {
-        let summary = self.parse_data(connection)?;
-
         if self.config.with_third_party {
-            let community_config = CommunityConfig {
-                with_icebelts: true,
-                with_triglavian_status: true,
-                with_jove_observatories: true,
-                with_special_ore: true,
-            };
-            community::process(
-                connection,
-                client,
-                &self.sde_directory,
-                maps_url_base,
-                &community_config,
-            )
-            .await?;
+            return Err(BuilderError::IncompatibleDatabase(
+                "third-party data is only added by a full build".to_string(),
+            ));
         }
-
-        Ok(summary)
+        connection.execute_batch("PRAGMA foreign_keys = ON;")?;
+        self.parse_with_workers(connection, observer, true)
     }
 }
 
@@ -2032,9 +3102,30 @@ impl Parser {
 /// generated by [`Parser::parse_types`] when it detects "Sun"-group types).
 /// `station_operation_services`/`station_operation_types` count rows
 /// in those two junction tables (not their own phase either: they're
-/// generated by [`Parser::parse_station_operations`]).
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+/// generated by [`Parser::parse_station_operations`]). `translations`
+/// counts `trnTranslations`' rows, written along the way by every phase
+/// that reads a localized field. `search_entries` counts the names
+/// [`Parser::parse_search_index`] indexed. `skipped_records` counts the
+/// records a [`Strictness::Lenient`] build left out (`buildIssues`'
+/// rows); always `0` for a strict one.
+///
+/// After [`Parser::update_database`], the counts are of the records
+/// written, whether they changed anything or not, and `changes` has
+/// what the update did to each table of the existing database. It's
+/// empty after a full build.
+///
+/// `timings` has one entry per phase, named after its count field
+/// (`"types"`, `"solar_systems"`, ...; `"search_index"` for
+/// `search_entries`), plus `"foreign_key_check"` and `"commit"`, then
+/// [`Parser::build_database`]'s `"community"` (with third-party data)
+/// and `"metadata"`. [`Parser::update_database`] has its
+/// `"metadata"` and `"delete_unwritten"` right before `"search_index"`
+/// instead. A phase's time is the writer's: decoding its file
+/// runs ahead on a worker (see [`Parser::parse_data`]), so it only
+/// counts what the phase still had to wait for.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct ParseSummary {
+    pub translation_languages: usize,
     pub categories: usize,
     pub groups: usize,
     pub types: usize,
@@ -2062,11 +3153,51 @@ pub struct ParseSummary {
     pub station_operation_services: usize,
     pub station_operation_types: usize,
     pub npc_stations: usize,
+    pub translations: usize,
+    pub search_entries: usize,
+    pub skipped_records: usize,
+    /// Per table, in dependency order (parents first), including
+    /// unchanged ones.
+    pub changes: Vec<(String, TableChanges)>,
+    /// Wall time of each phase, in the order they ran.
+    pub timings: Vec<(&'static str, Duration)>,
 }
 
+/// [`Parser::parse_data`]'s phases, in the order they run (every one of
+/// them runs, even gated off: `stargates` and `moons` then write nothing)
+/// -- what a progress display counts [`BuildEvent::PhaseStarted`]
+/// against. [`Parser::build_database`] adds its own after these, and
+/// [`Parser::update_database`] two among them (see
+/// [`ParseSummary::timings`]).
+pub const PHASES: &[&str] = &[
+    "translation_languages",
+    "categories",
+    "groups",
+    "types",
+    "races",
+    "npc_corporation_divisions",
+    "npc_corporations",
+    "factions",
+    "regions",
+    "constellations",
+    "solar_systems",
+    "stargates",
+    "stars",
+    "planets",
+    "moons",
+    "connections",
+    "station_services",
+    "station_operations",
+    "npc_stations",
+    "search_index",
+    "foreign_key_check",
+    "commit",
+];
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::builder::progress::Silent;
     use std::sync::atomic::{AtomicUsize, Ordering};
 
     static COUNTER: AtomicUsize = AtomicUsize::new(0);
@@ -2261,10 +3392,9 @@ mod tests {
             language: "fr".to_string(),
             ..Default::default()
         };
-        let record: Value =
-            serde_json::from_str(r#"{"name": {"en": "Jita", "de": "Jita"}}"#).unwrap();
+        let name: Localized = serde_json::from_str(r#"{"en": "Jita", "de": "Jita"}"#).unwrap();
         // "fr" isn't present -> falls back to "en".
-        assert_eq!(config.localized(&record, "name"), Some("Jita"));
+        assert_eq!(config.localized(Some(&name)), Some("Jita"));
     }
 
     #[test]
@@ -2273,9 +3403,8 @@ mod tests {
             language: "de".to_string(),
             ..Default::default()
         };
-        let record: Value =
-            serde_json::from_str(r#"{"name": {"en": "Jita", "de": "Jita (de)"}}"#).unwrap();
-        assert_eq!(config.localized(&record, "name"), Some("Jita (de)"));
+        let name: Localized = serde_json::from_str(r#"{"en": "Jita", "de": "Jita (de)"}"#).unwrap();
+        assert_eq!(config.localized(Some(&name)), Some("Jita (de)"));
     }
 
     #[test]
@@ -2306,8 +3435,38 @@ mod tests {
         // behavior is to trust the position2D CCP already provides when
         // it's present.
         let config = ParserConfig::default();
-        assert!(!config.force_isometric_position_2d);
-        assert_eq!(config.isometric_projected_axis, ProjectedAxis::Y);
+        assert!(!config.force_position_2d);
+        assert_eq!(
+            config.position_2d_projection,
+            Projection::Isometric(ProjectedAxis::Y)
+        );
+        assert!(config.projections.is_empty());
+        assert_eq!(config.position_2d(), None);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_isometric_fields_still_force_the_2d_position() {
+        let config = ParserConfig {
+            force_isometric_position_2d: true,
+            isometric_projected_axis: ProjectedAxis::Z,
+            ..Default::default()
+        };
+        assert_eq!(
+            config.position_2d(),
+            Some(Projection::Isometric(ProjectedAxis::Z))
+        );
+
+        // The new fields win when both are set.
+        let config = ParserConfig {
+            force_position_2d: true,
+            position_2d_projection: Projection::Dimetric(ProjectedAxis::Y),
+            ..config
+        };
+        assert_eq!(
+            config.position_2d(),
+            Some(Projection::Dimetric(ProjectedAxis::Y))
+        );
     }
 
     #[test]
@@ -2465,6 +3624,129 @@ mod tests {
         assert_eq!(investor_shares, 42.0);
     }
 
+    #[test]
+    fn yaml_records_match_the_jsonl_shape() {
+        let dir = TempSdeDir::new(
+            "yaml_shape",
+            &[
+                (
+                    "npcCorporations.jsonl",
+                    "{\"_key\": 1000002, \"name\": {\"en\": \"Corp\", \"de\": \"Korp\"}, \
+                     \"allowedMemberRaces\": [1, 2], \"position\": {\"x\": 1.5}, \
+                     \"divisions\": [{\"_key\": 22, \"size\": 37}], \
+                     \"corporationTrades\": [{\"_key\": 41, \"_value\": 0.42}]}\n",
+                ),
+                (
+                    "npcCorporations.yaml",
+                    "1000002:\n\
+                     \x20 name:\n\
+                     \x20   en: Corp\n\
+                     \x20   de: Korp\n\
+                     \x20 allowedMemberRaces: [1, 2]\n\
+                     \x20 position: {x: 1.5}\n\
+                     \x20 divisions:\n\
+                     \x20   22: {size: 37}\n\
+                     \x20 corporationTrades:\n\
+                     \x20   41: 0.42\n",
+                ),
+            ],
+        );
+        let jsonl: Vec<Value> = iter_records(&dir.path, "npcCorporations", SdeFormat::Jsonl)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        let yaml: Vec<Value> = iter_records(&dir.path, "npcCorporations", SdeFormat::Yaml)
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(yaml, jsonl);
+    }
+
+    #[test]
+    fn parse_npc_corporations_reads_yaml_junction_tables() {
+        let dir = TempSdeDir::new(
+            "npc_corp_yaml",
+            &[(
+                "npcCorporations.yaml",
+                "1000002:\n\
+                 \x20 name: {en: Corp}\n\
+                 \x20 tickerName: C\n\
+                 \x20 deleted: false\n\
+                 \x20 extent: L\n\
+                 \x20 hasPlayerPersonnelManager: false\n\
+                 \x20 initialPrice: 0\n\
+                 \x20 memberLimit: -1\n\
+                 \x20 minSecurity: 0.0\n\
+                 \x20 minimumJoinStanding: 1\n\
+                 \x20 sendCharTerminationMessage: true\n\
+                 \x20 shares: 1000\n\
+                 \x20 size: L\n\
+                 \x20 taxRate: 0.1\n\
+                 \x20 uniqueName: true\n\
+                 \x20 corporationTrades: {41: 0.42}\n\
+                 \x20 investors: {1000002: 42}\n",
+            )],
+        );
+        let connection = Connection::open_in_memory().unwrap();
+        crate::builder::schema::create_schema(&connection).unwrap();
+        connection
+            .execute(
+                "INSERT INTO invTypes (typeId, groupId, typeName, published) VALUES (41, NULL, 'x', 0)",
+                [],
+            )
+            .unwrap();
+        let config = ParserConfig {
+            format: SdeFormat::Yaml,
+            ..Default::default()
+        };
+        let parser = Parser::new(&dir.path, config);
+
+        assert_eq!(parser.parse_npc_corporations(&connection).unwrap(), 1);
+        let (affinity, investor_shares): (f64, f64) = connection
+            .query_row(
+                "SELECT t.affinity, i.shares FROM npcCorporationTrades AS t \
+                 INNER JOIN npcCorporationInvestors AS i ON (i.corporationId = t.corporationId) \
+                 WHERE t.corporationId = 1000002",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((affinity, investor_shares), (0.42, 42.0));
+    }
+
+    #[test]
+    fn parse_translation_languages_accepts_yaml_scalar_names() {
+        let dir = TempSdeDir::new(
+            "languages_yaml",
+            &[("translationLanguages.yaml", "en: English\nde: German\n")],
+        );
+        let connection = Connection::open_in_memory().unwrap();
+        crate::builder::schema::create_schema(&connection).unwrap();
+        let config = ParserConfig {
+            format: SdeFormat::Yaml,
+            ..Default::default()
+        };
+        let parser = Parser::new(&dir.path, config);
+
+        assert_eq!(parser.parse_translation_languages(&connection).unwrap(), 2);
+        let name: String = connection
+            .query_row(
+                "SELECT languageName FROM translationLanguages WHERE languageId = 'de'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(name, "German");
+    }
+
+    #[test]
+    fn sde_format_round_trips_through_its_variant_name() {
+        for format in [SdeFormat::Jsonl, SdeFormat::Yaml] {
+            assert_eq!(format.variant().parse::<SdeFormat>(), Ok(format));
+        }
+        assert!("xml".parse::<SdeFormat>().is_err());
+    }
+
     #[test]
     fn parse_npc_corporations_missing_ticker_errors() {
         // tickerName is TEXT NOT NULL and is accessed as a required field.
@@ -2901,6 +4183,7 @@ mod tests {
         assert_eq!(count, 1);
         assert!(scope.systems_in_scope.contains(&30000142));
 
+        #[allow(clippy::type_complexity)]
         let (
             name,
             security,
@@ -3077,7 +4360,7 @@ mod tests {
             )
             .unwrap();
         let config = ParserConfig {
-            force_isometric_position_2d: true,
+            force_position_2d: true,
             ..Default::default()
         };
         let parser = Parser::new(&dir.path, config);
@@ -3097,6 +4380,92 @@ mod tests {
         assert_eq!((p2dx, p2dy), (-300.0, -250.0));
     }
 
+    #[test]
+    fn parse_solar_systems_stores_extra_projections() {
+        let dir = TempSdeDir::new(
+            "solar_systems_projections",
+            &[(
+                "mapSolarSystems.jsonl",
+                "{\"_key\": 30000142, \"name\": {\"en\": \"Jita\"}, \"constellationID\": 20000020, \
+                 \"radius\": 1.0, \"position\": {\"x\": -100.0, \"y\": 200.0, \"z\": -300.0}, \
+                 \"securityStatus\": 0.9459, \"position2D\": {\"x\": 12.5, \"y\": -7.25}}\n",
+            )],
+        );
+        let connection = Connection::open_in_memory().unwrap();
+        crate::builder::schema::create_schema(&connection).unwrap();
+        connection
+            .execute(
+                "INSERT INTO mapRegions \
+                 (regionId, regionName, factionId, centerX, centerY, centerZ, nebula, wormholeClassId) \
+                 VALUES (10000002, 'The Forge', NULL, 0, 0, 0, 5, NULL)",
+                [],
+            )
+            .unwrap();
+        connection
+            .execute(
+                "INSERT INTO mapConstellations \
+                 (constellationId, constellationName, regionId, centerX, centerY, centerZ) \
+                 VALUES (20000020, 'Kimotoro', 10000002, 0, 0, 0)",
+                [],
+            )
+            .unwrap();
+        let config = ParserConfig {
+            projections: vec![
+                Projection::Dimetric(ProjectedAxis::Z),
+                Projection::Orthographic(ProjectedAxis::Y),
+            ],
+            ..Default::default()
+        };
+        let parser = Parser::new(&dir.path, config);
+        let mut scope = SystemScopeState::default();
+
+        parser.parse_solar_systems(&connection, &mut scope).unwrap();
+
+        let mut statement = connection
+            .prepare(
+                "SELECT projection, x, y FROM mapSolarSystemProjections \
+                 WHERE solarSystemId = 30000142 ORDER BY projection",
+            )
+            .unwrap();
+        let rows: Vec<(String, f64, f64)> = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                ("dimetricZ".to_string(), 50.0, 75.0),
+                ("orthographicY".to_string(), -100.0, -300.0),
+            ]
+        );
+        // Not forced: position2DX/Y still carry CCP's own value.
+        let p2dx: f64 = connection
+            .query_row(
+                "SELECT position2DX FROM mapSolarSystems WHERE solarSystemId = 30000142",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(p2dx, 12.5);
+    }
+
+    #[test]
+    fn system_in_filters_is_a_union_of_the_filters() {
+        let config = ParserConfig::default();
+        assert!(config.system_in_filters(1, 2, None), "no filter, no limit");
+
+        let config = ParserConfig {
+            region_ids: vec![10000002],
+            faction_ids: vec![500001],
+            ..Default::default()
+        };
+        assert!(config.system_in_filters(10000002, 20000020, None));
+        assert!(config.system_in_filters(10000043, 20000322, Some(500001)));
+        assert!(!config.system_in_filters(10000043, 20000322, Some(500003)));
+        assert!(!config.system_in_filters(10000043, 20000322, None));
+    }
+
     #[test]
     fn parse_solar_systems_excludes_out_of_scope_systems() {
         let dir = TempSdeDir::new(
@@ -3321,13 +4690,17 @@ mod tests {
                 "mapStargates.jsonl",
                 "{\"_key\": 50000003, \"solarSystemID\": 30000003, \"typeID\": 16, \
                  \"position\": {\"x\": 0.0, \"y\": 0.0, \"z\": 0.0}, \
-                 \"destination\": {\"stargateID\": 50000004, \"solarSystemID\": 30000001}}\n",
+                 \"destination\": {\"stargateID\": 50000004, \"solarSystemID\": 30000001}}\n\
+                 {\"_key\": 50000004, \"solarSystemID\": 30000001, \"typeID\": 16, \
+                 \"position\": {\"x\": 0.0, \"y\": 0.0, \"z\": 0.0}, \
+                 \"destination\": {\"stargateID\": 50000003, \"solarSystemID\": 30000003}}\n",
             )],
         );
         let mut connection = Connection::open_in_memory().unwrap();
         crate::builder::schema::create_schema(&connection).unwrap();
         insert_stargate_prerequisites(&connection);
-        // 30000003 is NOT in scope (unlike 30000001/30000002).
+        // 30000003 is NOT in scope (unlike 30000001/30000002): neither
+        // its gate nor the one leading into it is imported.
         let mut scope = SystemScopeState::default();
         scope.systems_in_scope.insert(30000001);
         scope.systems_in_scope.insert(30000002);
@@ -3505,6 +4878,36 @@ mod tests {
         assert_eq!(locked, Some(1));
     }
 
+    #[test]
+    fn parse_stars_stores_a_wrong_typed_radius_as_null() {
+        // A float radius where mapStars.radius is an INTEGER: the star is
+        // stored radius-less rather than failing the whole file.
+        let dir = TempSdeDir::new(
+            "stars_float_radius",
+            &[(
+                "mapStars.jsonl",
+                "{\"_key\": 40000001, \"radius\": 63350000.5, \"solarSystemID\": 30000001, \
+                 \"statistics\": {\"luminosity\": 0.01575}, \"typeID\": 3000}\n",
+            )],
+        );
+        let (connection, star_state, scope, config) = setup_for_parse_stars("stars_setup_float");
+        let parser = Parser::new(&dir.path, config);
+
+        let count = parser
+            .parse_stars(&connection, &scope, &star_state)
+            .unwrap();
+        assert_eq!(count, 1);
+
+        let radius: Option<i64> = connection
+            .query_row(
+                "SELECT radius FROM mapStars WHERE starId = 40000001",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(radius, None);
+    }
+
     #[test]
     fn parse_stars_skips_systems_outside_scope() {
         let dir = TempSdeDir::new(
@@ -4014,121 +5417,132 @@ mod tests {
         // Same as above: no file ever read, path is just a placeholder.
         let parser = Parser::new(Path::new("."), config);
         let count = parser.parse_connections(&connection).unwrap();
-        assert_eq!(count, 0);
-
-        let total: i64 = connection
-            .query_row("SELECT COUNT(*) FROM mapSystemConnections", [], |row| {
-                row.get(0)
-            })
-            .unwrap();
-        assert_eq!(total, 0);
-    }
-
-    #[test]
-    fn parse_data_happy_path_returns_summary_and_commits() {
-        let dir = TempSdeDir::new(
-            "parse_data_happy",
-            &[
-                (
-                    "categories.jsonl",
-                    "{\"_key\": 6, \"name\": {\"en\": \"Celestial\"}, \"published\": true}\n",
-                ),
-                (
-                    "groups.jsonl",
-                    "{\"_key\": 6, \"categoryID\": 6, \"name\": {\"en\": \"Sun\"}, \"anchorable\": false}\n\
-                     {\"_key\": 7, \"categoryID\": 6, \"name\": {\"en\": \"Frigate\"}, \"anchorable\": false}\n",
-                ),
-                (
-                    "races.jsonl",
-                    "{\"_key\": 1, \"name\": {\"en\": \"Caldari\"}}\n",
-                ),
-                (
-                    "npcCorporations.jsonl",
-                    "{\"_key\": 1000004, \"name\": {\"en\": \"CBD Corporation\"}, \
-                     \"tickerName\": \"CBD\", \"deleted\": false, \"extent\": \"L\", \
-                     \"hasPlayerPersonnelManager\": false, \"initialPrice\": 0, \"memberLimit\": -1, \
-                     \"minSecurity\": 0.0, \"minimumJoinStanding\": 1, \
-                     \"sendCharTerminationMessage\": true, \"shares\": 1000, \"size\": \"L\", \
-                     \"taxRate\": 0.0, \"uniqueName\": true, \"iconID\": 500, \"raceID\": 1}\n",
-                ),
-                (
-                    "factions.jsonl",
-                    "{\"_key\": 500001, \"name\": {\"en\": \"Caldari State\"}, \"iconID\": 600, \
-                     \"sizeFactor\": 3.0, \"uniqueName\": true, \"description\": {\"en\": \"x\"}, \
-                     \"corporationID\": 1000004, \"memberRaces\": [1]}\n",
-                ),
-                ("npcCorporationDivisions.jsonl", ""),
-                ("stationServices.jsonl", ""),
-                ("stationOperations.jsonl", ""),
-                ("npcStations.jsonl", ""),
-                (
-                    "mapRegions.jsonl",
-                    "{\"_key\": 10000002, \"name\": {\"en\": \"The Forge\"}, \"nebulaID\": 5, \
-                     \"position\": {\"x\": 100.0, \"y\": 200.0, \"z\": 300.0}}\n",
-                ),
-                (
-                    "mapConstellations.jsonl",
-                    "{\"_key\": 20000020, \"name\": {\"en\": \"Kimotoro\"}, \"regionID\": 10000002, \
-                     \"position\": {\"x\": 110.0, \"y\": 210.0, \"z\": 310.0}}\n",
-                ),
-                (
-                    "mapSolarSystems.jsonl",
-                    "{\"_key\": 30000142, \"name\": {\"en\": \"Jita\"}, \"constellationID\": 20000020, \
-                     \"radius\": 999999999.0, \"position\": {\"x\": -100.0, \"y\": 200.0, \"z\": -300.0}, \
-                     \"securityStatus\": 0.9459, \"securityClass\": \"B\", \"corridor\": false, \
-                     \"fringe\": false, \"hub\": true, \"international\": true, \"regional\": true, \
-                     \"luminosity\": 0.049, \"position2D\": {\"x\": 12.5, \"y\": -7.25}}\n\
-                     {\"_key\": 30002187, \"name\": {\"en\": \"Perimeter\"}, \"constellationID\": 20000020, \
-                     \"radius\": 1.0, \"position\": {\"x\": 0.0, \"y\": 0.0, \"z\": 0.0}, \
-                     \"securityStatus\": 0.9}\n",
-                ),
-                (
-                    "mapStargates.jsonl",
-                    "{\"_key\": 50000001, \"solarSystemID\": 30000142, \"typeID\": 16, \
-                     \"position\": {\"x\": 1.0, \"y\": 2.0, \"z\": 3.0}, \
-                     \"destination\": {\"stargateID\": 50000002, \"solarSystemID\": 30002187}}\n\
-                     {\"_key\": 50000002, \"solarSystemID\": 30002187, \"typeID\": 16, \
-                     \"position\": {\"x\": 4.0, \"y\": 5.0, \"z\": 6.0}, \
-                     \"destination\": {\"stargateID\": 50000001, \"solarSystemID\": 30000142}}\n",
-                ),
-                (
-                    "mapStars.jsonl",
-                    "{\"_key\": 40000001, \"radius\": 63350000, \"solarSystemID\": 30000142, \
-                     \"statistics\": {\"age\": 4.5e17, \"life\": 6.9e17, \"luminosity\": 0.01575, \
-                     \"spectralClass\": \"K2 V\", \"temperature\": 4567.0}, \"typeID\": 3000}\n",
-                ),
-                (
-                    "mapPlanets.jsonl",
-                    "{\"_key\": 40000002, \"celestialIndex\": 1, \
-                     \"position\": {\"x\": 161891117336.0, \"y\": 21288951986.0, \"z\": -73529712226.0}, \
-                     \"radius\": 5060000, \"solarSystemID\": 30000142, \
-                     \"statistics\": {\"locked\": false}, \"typeID\": 11}\n",
-                ),
-                (
-                    "mapMoons.jsonl",
-                    "{\"_key\": 40000004, \"solarSystemID\": 30000142, \"orbitIndex\": 1, \
-                     \"orbitID\": 40000002, \"typeID\": 12, \"radius\": 100000, \
-                     \"position\": {\"x\": 1.0, \"y\": 2.0, \"z\": 3.0}}\n",
-                ),
-                (
-                    "types.jsonl",
-                    "{\"_key\": 3000, \"groupID\": 6, \"name\": {\"en\": \"Yellow G5 (ffcc00)\"}, \
-                     \"iconID\": 100, \"published\": true, \"volume\": 0.0}\n\
-                     {\"_key\": 16, \"groupID\": 7, \"name\": {\"en\": \"Stargate\"}, \"published\": true}\n\
-                     {\"_key\": 11, \"groupID\": 7, \"name\": {\"en\": \"Planet (Barren)\"}, \"published\": true}\n\
-                     {\"_key\": 12, \"groupID\": 7, \"name\": {\"en\": \"Moon\"}, \"published\": true}\n",
-                ),
-            ],
-        );
+        assert_eq!(count, 0);
+
+        let total: i64 = connection
+            .query_row("SELECT COUNT(*) FROM mapSystemConnections", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(total, 0);
+    }
+
+    /// A small but complete SDE export -- every file `parse_data` reads,
+    /// one region with two connected systems -- that the whole pipeline
+    /// parses successfully.
+    const PIPELINE_FILES: &[(&str, &str)] = &[
+        (
+            "categories.jsonl",
+            "{\"_key\": 6, \"name\": {\"en\": \"Celestial\"}, \"published\": true}\n",
+        ),
+        (
+            "groups.jsonl",
+            "{\"_key\": 6, \"categoryID\": 6, \"name\": {\"en\": \"Sun\"}, \"anchorable\": false}\n\
+             {\"_key\": 7, \"categoryID\": 6, \"name\": {\"en\": \"Frigate\"}, \"anchorable\": false}\n",
+        ),
+        (
+            "races.jsonl",
+            "{\"_key\": 1, \"name\": {\"en\": \"Caldari\"}}\n",
+        ),
+        (
+            "npcCorporations.jsonl",
+            "{\"_key\": 1000004, \"name\": {\"en\": \"CBD Corporation\"}, \
+             \"tickerName\": \"CBD\", \"deleted\": false, \"extent\": \"L\", \
+             \"hasPlayerPersonnelManager\": false, \"initialPrice\": 0, \"memberLimit\": -1, \
+             \"minSecurity\": 0.0, \"minimumJoinStanding\": 1, \
+             \"sendCharTerminationMessage\": true, \"shares\": 1000, \"size\": \"L\", \
+             \"taxRate\": 0.0, \"uniqueName\": true, \"iconID\": 500, \"raceID\": 1}\n",
+        ),
+        (
+            "factions.jsonl",
+            "{\"_key\": 500001, \"name\": {\"en\": \"Caldari State\"}, \"iconID\": 600, \
+             \"sizeFactor\": 3.0, \"uniqueName\": true, \"description\": {\"en\": \"x\"}, \
+             \"corporationID\": 1000004, \"memberRaces\": [1]}\n",
+        ),
+        ("npcCorporationDivisions.jsonl", ""),
+        (
+            "translationLanguages.jsonl",
+            "{\"_key\": \"en\", \"name\": \"English\"}\n\
+             {\"_key\": \"de\", \"name\": \"German\"}\n",
+        ),
+        ("stationServices.jsonl", ""),
+        ("stationOperations.jsonl", ""),
+        ("npcStations.jsonl", ""),
+        (
+            "mapRegions.jsonl",
+            "{\"_key\": 10000002, \"name\": {\"en\": \"The Forge\"}, \"nebulaID\": 5, \
+             \"position\": {\"x\": 100.0, \"y\": 200.0, \"z\": 300.0}}\n",
+        ),
+        (
+            "mapConstellations.jsonl",
+            "{\"_key\": 20000020, \"name\": {\"en\": \"Kimotoro\"}, \"regionID\": 10000002, \
+             \"position\": {\"x\": 110.0, \"y\": 210.0, \"z\": 310.0}}\n",
+        ),
+        (
+            "mapSolarSystems.jsonl",
+            "{\"_key\": 30000142, \"name\": {\"en\": \"Jita\", \"de\": \"Jita (de)\", \"xx\": \"ignored\"}, \"constellationID\": 20000020, \
+             \"radius\": 999999999.0, \"position\": {\"x\": -100.0, \"y\": 200.0, \"z\": -300.0}, \
+             \"securityStatus\": 0.9459, \"securityClass\": \"B\", \"corridor\": false, \
+             \"fringe\": false, \"hub\": true, \"international\": true, \"regional\": true, \
+             \"luminosity\": 0.049, \"position2D\": {\"x\": 12.5, \"y\": -7.25}}\n\
+             {\"_key\": 30002187, \"name\": {\"en\": \"Perimeter\"}, \"constellationID\": 20000020, \
+             \"radius\": 1.0, \"position\": {\"x\": 0.0, \"y\": 0.0, \"z\": 0.0}, \
+             \"securityStatus\": 0.9}\n",
+        ),
+        (
+            "mapStargates.jsonl",
+            "{\"_key\": 50000001, \"solarSystemID\": 30000142, \"typeID\": 16, \
+             \"position\": {\"x\": 1.0, \"y\": 2.0, \"z\": 3.0}, \
+             \"destination\": {\"stargateID\": 50000002, \"solarSystemID\": 30002187}}\n\
+             {\"_key\": 50000002, \"solarSystemID\": 30002187, \"typeID\": 16, \
+             \"position\": {\"x\": 4.0, \"y\": 5.0, \"z\": 6.0}, \
+             \"destination\": {\"stargateID\": 50000001, \"solarSystemID\": 30000142}}\n",
+        ),
+        (
+            "mapStars.jsonl",
+            "{\"_key\": 40000001, \"radius\": 63350000, \"solarSystemID\": 30000142, \
+             \"statistics\": {\"age\": 4.5e17, \"life\": 6.9e17, \"luminosity\": 0.01575, \
+             \"spectralClass\": \"K2 V\", \"temperature\": 4567.0}, \"typeID\": 3000}\n",
+        ),
+        (
+            "mapPlanets.jsonl",
+            "{\"_key\": 40000002, \"celestialIndex\": 1, \
+             \"position\": {\"x\": 161891117336.0, \"y\": 21288951986.0, \"z\": -73529712226.0}, \
+             \"radius\": 5060000, \"solarSystemID\": 30000142, \
+             \"statistics\": {\"locked\": false}, \"typeID\": 11}\n",
+        ),
+        (
+            "mapMoons.jsonl",
+            "{\"_key\": 40000004, \"solarSystemID\": 30000142, \"orbitIndex\": 1, \
+             \"orbitID\": 40000002, \"typeID\": 12, \"radius\": 100000, \
+             \"position\": {\"x\": 1.0, \"y\": 2.0, \"z\": 3.0}}\n",
+        ),
+        (
+            "types.jsonl",
+            "{\"_key\": 3000, \"groupID\": 6, \"name\": {\"en\": \"Yellow G5 (ffcc00)\"}, \
+             \"iconID\": 100, \"published\": true, \"volume\": 0.0}\n\
+             {\"_key\": 16, \"groupID\": 7, \"name\": {\"en\": \"Stargate\"}, \"published\": true}\n\
+             {\"_key\": 11, \"groupID\": 7, \"name\": {\"en\": \"Planet (Barren)\"}, \"published\": true}\n\
+             {\"_key\": 12, \"groupID\": 7, \"name\": {\"en\": \"Moon\"}, \"published\": true}\n",
+        ),
+    ];
+
+    #[test]
+    fn parse_data_happy_path_returns_summary_and_commits() {
+        let dir = TempSdeDir::new("parse_data_happy", PIPELINE_FILES);
         let mut connection = Connection::open_in_memory().unwrap();
         crate::builder::schema::create_schema(&connection).unwrap();
         let config = ParserConfig::default();
         let parser = Parser::new(&dir.path, config);
 
-        let summary = parser.parse_data(&mut connection).unwrap();
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let summary = parser.parse_data(&mut connection, &sender).unwrap();
+        drop(sender);
+        let events: Vec<BuildEvent> = receiver.iter().collect();
         assert_eq!(
             summary,
             ParseSummary {
+                translation_languages: 2,
                 categories: 1,
                 groups: 2,
                 types: 4,
@@ -4150,13 +5564,67 @@ mod tests {
                 station_operation_services: 0,
                 station_operation_types: 0,
                 npc_stations: 0,
+                translations: 16,
+                search_entries: 10,
+                skipped_records: 0,
+                changes: Vec::new(),
+                // Checked on their own below: only the phases are fixed.
+                timings: summary.timings.clone(),
             }
         );
+        let phases: Vec<&str> = summary.timings.iter().map(|(phase, _)| *phase).collect();
+        assert_eq!(
+            phases,
+            vec![
+                "translation_languages",
+                "categories",
+                "groups",
+                "types",
+                "races",
+                "npc_corporation_divisions",
+                "npc_corporations",
+                "factions",
+                "regions",
+                "constellations",
+                "solar_systems",
+                "stargates",
+                "stars",
+                "planets",
+                "moons",
+                "connections",
+                "station_services",
+                "station_operations",
+                "npc_stations",
+                "search_index",
+                "foreign_key_check",
+                "commit",
+            ]
+        );
+        assert_eq!(phases, PHASES);
+        // Each phase starts, then finishes, before the next one starts.
+        let started: Vec<&str> = events
+            .iter()
+            .filter_map(|event| match event {
+                BuildEvent::PhaseStarted { phase } => Some(*phase),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(started, PHASES);
+        assert_eq!(events.len(), 2 * PHASES.len());
+        assert!(events.contains(&BuildEvent::PhaseFinished {
+            phase: "types",
+            rows: Some(summary.types),
+            elapsed: summary.timings[3].1,
+        }));
 
         let total_faction_race: i64 = connection
             .query_row("SELECT COUNT(*) FROM factionRace", [], |row| row.get(0))
             .unwrap();
         assert_eq!(total_faction_race, 1);
+        // What `install::replace_database` checks before swapping a
+        // build in.
+        let report = crate::builder::verify::verify_connection(&connection).unwrap();
+        assert!(report.passed(), "{}", report.to_markdown());
 
         let (conn_system_a, conn_system_b): (i64, i64) = connection
             .query_row(
@@ -4177,6 +5645,99 @@ mod tests {
             .unwrap();
         assert_eq!(dest_gate, 50000002);
         assert_eq!(dest_system, 30002187);
+
+        // Jita carries en/de/xx: en and de are stored, xx isn't listed in
+        // translationLanguages.jsonl and is skipped.
+        let mut statement = connection
+            .prepare(
+                "SELECT languageId, text FROM trnTranslations \
+                 WHERE tableName = 'mapSolarSystems' AND columnName = 'solarSystemName' \
+                 AND keyId = 30000142 ORDER BY languageId",
+            )
+            .unwrap();
+        let jita: Vec<(String, String)> = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(
+            jita,
+            vec![
+                ("de".to_string(), "Jita (de)".to_string()),
+                ("en".to_string(), "Jita".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_categories_without_translation_languages_stores_no_translations() {
+        // Called on its own, without parse_translation_languages() having
+        // run first: the base column is still populated, but there's no
+        // language to file the translations under.
+        let dir = TempSdeDir::new(
+            "categories_no_languages",
+            &[(
+                "categories.jsonl",
+                "{\"_key\": 6, \"name\": {\"en\": \"Ship\", \"de\": \"Schiff\"}, \"published\": true}\n",
+            )],
+        );
+        let connection = Connection::open_in_memory().unwrap();
+        crate::builder::schema::create_schema(&connection).unwrap();
+        let parser = Parser::new(&dir.path, ParserConfig::default());
+
+        assert_eq!(parser.parse_categories(&connection).unwrap(), 1);
+        let total: i64 = connection
+            .query_row("SELECT COUNT(*) FROM trnTranslations", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(total, 0);
+    }
+
+    #[test]
+    fn parse_categories_stores_every_listed_language() {
+        let dir = TempSdeDir::new(
+            "categories_languages",
+            &[
+                (
+                    "translationLanguages.jsonl",
+                    "{\"_key\": \"en\", \"name\": \"English\"}\n\
+                     {\"_key\": \"de\", \"name\": \"German\"}\n",
+                ),
+                (
+                    "categories.jsonl",
+                    "{\"_key\": 6, \"name\": {\"en\": \"Ship\", \"de\": \"Schiff\"}, \"published\": true}\n",
+                ),
+            ],
+        );
+        let connection = Connection::open_in_memory().unwrap();
+        crate::builder::schema::create_schema(&connection).unwrap();
+        let config = ParserConfig {
+            language: "de".to_string(),
+            ..ParserConfig::default()
+        };
+        let parser = Parser::new(&dir.path, config);
+
+        assert_eq!(parser.parse_translation_languages(&connection).unwrap(), 2);
+        parser.parse_categories(&connection).unwrap();
+
+        // The base column carries config.language...
+        let name: String = connection
+            .query_row(
+                "SELECT categoryName FROM invCategories WHERE categoryId = 6",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(name, "Schiff");
+        // ...and trnTranslations carries both.
+        let english: String = connection
+            .query_row(
+                "SELECT text FROM trnTranslations WHERE tableName = 'invCategories' \
+                 AND columnName = 'categoryName' AND keyId = 6 AND languageId = 'en'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(english, "Ship");
     }
 
     #[test]
@@ -4220,6 +5781,11 @@ mod tests {
                      \"corporationID\": 1000004, \"memberRaces\": [1]}\n",
                 ),
                 ("npcCorporationDivisions.jsonl", ""),
+                (
+                    "translationLanguages.jsonl",
+                    "{\"_key\": \"en\", \"name\": \"English\"}\n\
+                     {\"_key\": \"de\", \"name\": \"German\"}\n",
+                ),
                 ("stationServices.jsonl", ""),
                 ("stationOperations.jsonl", ""),
                 ("npcStations.jsonl", ""),
@@ -4251,7 +5817,7 @@ mod tests {
         let config = ParserConfig::default();
         let parser = Parser::new(&dir.path, config);
 
-        let error = parser.parse_data(&mut connection).unwrap_err();
+        let error = parser.parse_data(&mut connection, &Silent).unwrap_err();
         let message = error.to_string();
         assert!(
             message.contains("npcCorporations"),
@@ -4305,6 +5871,11 @@ mod tests {
                 ),
                 ("factions.jsonl", ""),
                 ("npcCorporationDivisions.jsonl", ""),
+                (
+                    "translationLanguages.jsonl",
+                    "{\"_key\": \"en\", \"name\": \"English\"}\n\
+                     {\"_key\": \"de\", \"name\": \"German\"}\n",
+                ),
                 ("stationServices.jsonl", ""),
                 ("stationOperations.jsonl", ""),
                 ("npcStations.jsonl", ""),
@@ -4338,7 +5909,7 @@ mod tests {
 
         // Doesn't fail -- the known Doomheim/InterBus-style case is
         // cleared to NULL automatically, not reported as an error.
-        let summary = parser.parse_data(&mut connection).unwrap();
+        let summary = parser.parse_data(&mut connection, &Silent).unwrap();
         assert_eq!(summary.npc_corporations, 1);
 
         let station_id: Option<i64> = connection
@@ -4351,6 +5922,119 @@ mod tests {
         assert_eq!(station_id, None);
     }
 
+    #[test]
+    fn parse_data_scoped_to_a_faction_keeps_a_self_consistent_database() {
+        // PIPELINE_FILES, but with Perimeter moved to a second region
+        // (so the Jita <-> Perimeter gates cross a region border), The
+        // Forge owned by the Caldari State, and the faction's and the
+        // corporation's home system in Perimeter.
+        let files: Vec<(&str, &str)> = PIPELINE_FILES
+            .iter()
+            .map(|&(name, contents)| match name {
+                "mapRegions.jsonl" => (
+                    name,
+                    "{\"_key\": 10000002, \"name\": {\"en\": \"The Forge\"}, \"nebulaID\": 5, \
+                     \"factionID\": 500001, \"position\": {\"x\": 100.0, \"y\": 200.0, \"z\": 300.0}}\n\
+                     {\"_key\": 10000043, \"name\": {\"en\": \"Domain\"}, \"nebulaID\": 6, \
+                     \"position\": {\"x\": 0.0, \"y\": 0.0, \"z\": 0.0}}\n",
+                ),
+                "mapConstellations.jsonl" => (
+                    name,
+                    "{\"_key\": 20000020, \"name\": {\"en\": \"Kimotoro\"}, \"regionID\": 10000002, \
+                     \"position\": {\"x\": 110.0, \"y\": 210.0, \"z\": 310.0}}\n\
+                     {\"_key\": 20000322, \"name\": {\"en\": \"Throne Worlds\"}, \"regionID\": 10000043, \
+                     \"position\": {\"x\": 0.0, \"y\": 0.0, \"z\": 0.0}}\n",
+                ),
+                "mapSolarSystems.jsonl" => (
+                    name,
+                    "{\"_key\": 30000142, \"name\": {\"en\": \"Jita\"}, \"constellationID\": 20000020, \
+                     \"radius\": 1.0, \"position\": {\"x\": -100.0, \"y\": 200.0, \"z\": -300.0}, \
+                     \"securityStatus\": 0.9459}\n\
+                     {\"_key\": 30002187, \"name\": {\"en\": \"Perimeter\"}, \"constellationID\": 20000322, \
+                     \"radius\": 1.0, \"position\": {\"x\": 0.0, \"y\": 0.0, \"z\": 0.0}, \
+                     \"securityStatus\": 0.9}\n",
+                ),
+                "factions.jsonl" => (
+                    name,
+                    "{\"_key\": 500001, \"name\": {\"en\": \"Caldari State\"}, \"iconID\": 600, \
+                     \"sizeFactor\": 3.0, \"uniqueName\": true, \"description\": {\"en\": \"x\"}, \
+                     \"corporationID\": 1000004, \"memberRaces\": [1], \"solarSystemID\": 30002187}\n",
+                ),
+                "npcCorporations.jsonl" => (
+                    name,
+                    "{\"_key\": 1000004, \"name\": {\"en\": \"CBD Corporation\"}, \
+                     \"tickerName\": \"CBD\", \"deleted\": false, \"extent\": \"L\", \
+                     \"hasPlayerPersonnelManager\": false, \"initialPrice\": 0, \"memberLimit\": -1, \
+                     \"minSecurity\": 0.0, \"minimumJoinStanding\": 1, \
+                     \"sendCharTerminationMessage\": true, \"shares\": 1000, \"size\": \"L\", \
+                     \"taxRate\": 0.0, \"uniqueName\": true, \"iconID\": 500, \"raceID\": 1, \
+                     \"solarSystemID\": 30002187}\n",
+                ),
+                _ => (name, contents),
+            })
+            .collect();
+        let dir = TempSdeDir::new("parse_data_scoped", &files);
+        let mut connection = Connection::open_in_memory().unwrap();
+        crate::builder::schema::create_schema(&connection).unwrap();
+        let config = ParserConfig {
+            faction_ids: vec![500001],
+            ..Default::default()
+        };
+        let parser = Parser::new(&dir.path, config);
+
+        let summary = parser.parse_data(&mut connection, &Silent).unwrap();
+
+        assert_eq!(
+            summary.solar_systems, 1,
+            "only Jita, in the faction's region"
+        );
+        assert_eq!(summary.stargates, 0, "both gates lead out of scope");
+        assert_eq!(summary.connections, 0);
+        assert_eq!(summary.stars, 1);
+        assert_eq!(summary.moons, 1);
+        assert_eq!(summary.regions, 2, "regions are kept whole");
+        let (faction_home, corporation_home): (Option<i64>, Option<i64>) = connection
+            .query_row(
+                "SELECT f.solarSystemId, c.solarSystemId \
+                 FROM factions AS f, npcCorporations AS c",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!((faction_home, corporation_home), (None, None));
+        let report = crate::builder::verify::verify_connection(&connection).unwrap();
+        assert!(report.passed(), "{}", report.to_markdown());
+    }
+
+    #[test]
+    fn parse_data_without_a_scope_still_fails_on_a_missing_home_system() {
+        // The same dangling faction home system, unscoped: a real bug,
+        // not cleared.
+        let files: Vec<(&str, &str)> = PIPELINE_FILES
+            .iter()
+            .map(|&(name, contents)| match name {
+                "factions.jsonl" => (
+                    name,
+                    "{\"_key\": 500001, \"name\": {\"en\": \"Caldari State\"}, \"iconID\": 600, \
+                     \"sizeFactor\": 3.0, \"uniqueName\": true, \"description\": {\"en\": \"x\"}, \
+                     \"corporationID\": 1000004, \"memberRaces\": [1], \"solarSystemID\": 30009999}\n",
+                ),
+                _ => (name, contents),
+            })
+            .collect();
+        let dir = TempSdeDir::new("parse_data_unscoped_home", &files);
+        let mut connection = Connection::open_in_memory().unwrap();
+        crate::builder::schema::create_schema(&connection).unwrap();
+        let parser = Parser::new(&dir.path, ParserConfig::default());
+
+        let result = parser.parse_data(&mut connection, &Silent);
+
+        assert!(
+            matches!(&result, Err(BuilderError::Data(message)) if message.contains("factions")),
+            "{result:?}"
+        );
+    }
+
     #[test]
     fn parse_data_rolls_back_everything_on_failure() {
         let dir = TempSdeDir::new(
@@ -4374,6 +6058,11 @@ mod tests {
                     "{\"_key\": 1, \"name\": {\"en\": \"Caldari\"}}\n",
                 ),
                 ("npcCorporationDivisions.jsonl", ""),
+                (
+                    "translationLanguages.jsonl",
+                    "{\"_key\": \"en\", \"name\": \"English\"}\n\
+                     {\"_key\": \"de\", \"name\": \"German\"}\n",
+                ),
                 (
                     "npcCorporations.jsonl",
                     "{\"_key\": 1000004, \"name\": {\"en\": \"CBD Corporation\"}, \
@@ -4398,7 +6087,7 @@ mod tests {
         let config = ParserConfig::default();
         let parser = Parser::new(&dir.path, config);
 
-        let result = parser.parse_data(&mut connection);
+        let result = parser.parse_data(&mut connection, &Silent);
         assert!(result.is_err());
 
         // Nothing should have been left persisted, not even the phases
@@ -4424,6 +6113,315 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_data_stops_and_rolls_back_when_cancelled() {
+        let dir = TempSdeDir::new("parse_data_cancelled", PIPELINE_FILES);
+        let mut connection = Connection::open_in_memory().unwrap();
+        crate::builder::schema::create_schema(&connection).unwrap();
+        let config = ParserConfig::default();
+        let cancellation = config.cancellation.clone();
+        let parser = Parser::new(&dir.path, config);
+
+        // Cancelled from "outside" once a few phases have written rows.
+        let started = Mutex::new(Vec::new());
+        let observer = |event| {
+            if let BuildEvent::PhaseStarted { phase } = event {
+                started.lock().unwrap().push(phase);
+                if phase == "regions" {
+                    cancellation.cancel();
+                }
+            }
+        };
+        let result = parser.parse_data(&mut connection, &observer);
+
+        assert!(matches!(result, Err(BuilderError::Cancelled)));
+        // "regions" started, and stopped at its first record; nothing
+        // after it did.
+        assert_eq!(started.into_inner().unwrap().last(), Some(&"regions"));
+        for table in ["invCategories", "invTypes", "mapRegions"] {
+            let count: i64 = connection
+                .query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                    row.get(0)
+                })
+                .unwrap();
+            assert_eq!(count, 0, "table {table} should be empty after the rollback");
+        }
+    }
+
+    #[tokio::test]
+    async fn build_database_file_removes_a_build_cancelled_after_the_parse() {
+        let dir = TempSdeDir::new("build_database_file_cancelled", PIPELINE_FILES);
+        let target = dir.path.join("sde.db");
+        let config = ParserConfig::default();
+        let cancellation = config.cancellation.clone();
+        let parser = Parser::new(&dir.path, config);
+
+        // The parse has committed by then: only `metadata` is left.
+        let observer = |event| {
+            if let BuildEvent::PhaseFinished {
+                phase: "commit", ..
+            } = event
+            {
+                cancellation.cancel();
+            }
+        };
+        let result = parser
+            .build_database_file(&target, 1, &Client::new(), "unused", &observer)
+            .await;
+
+        assert!(matches!(result, Err(BuilderError::Cancelled)));
+        assert!(!install::building_path(&target).exists());
+        assert!(!target.exists());
+    }
+
+    #[test]
+    fn parse_data_rejects_a_record_that_is_not_an_object() {
+        let files: Vec<(&str, &str)> = PIPELINE_FILES
+            .iter()
+            .map(|&(name, content)| match name {
+                "categories.jsonl" => (name, "[6, \"Celestial\"]\n"),
+                _ => (name, content),
+            })
+            .collect();
+        let dir = TempSdeDir::new("parse_data_not_an_object", &files);
+        let mut connection = Connection::open_in_memory().unwrap();
+        crate::builder::schema::create_schema(&connection).unwrap();
+        let parser = Parser::new(&dir.path, ParserConfig::default());
+
+        let result = parser.parse_data(&mut connection, &Silent);
+        assert!(matches!(
+            result,
+            Err(BuilderError::Data(ref message)) if message == "record 1 of categories is not an object"
+        ));
+        let languages: i64 = connection
+            .query_row("SELECT COUNT(*) FROM translationLanguages", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(languages, 0);
+    }
+
+    #[test]
+    fn parse_data_in_lenient_mode_quarantines_malformed_records() {
+        let groups = "{\"_key\": 6, \"categoryID\": 6, \"name\": {\"en\": \"Sun\"}, \"anchorable\": false}\n\
+                      {\"_key\": 8, \"categoryID\": \"x\", \"name\": {\"en\": \"Broken\"}}\n\
+                      \n\
+                      not json\n\
+                      {\"_key\": 7, \"categoryID\": 6, \"name\": {\"en\": \"Frigate\"}, \"anchorable\": false}\n";
+        let files: Vec<(&str, &str)> = PIPELINE_FILES
+            .iter()
+            .map(|&(name, content)| match name {
+                "groups.jsonl" => (name, groups),
+                _ => (name, content),
+            })
+            .collect();
+        let dir = TempSdeDir::new("parse_data_lenient", &files);
+
+        let mut connection = Connection::open_in_memory().unwrap();
+        crate::builder::schema::create_schema(&connection).unwrap();
+        let strict =
+            Parser::new(&dir.path, ParserConfig::default()).parse_data(&mut connection, &Silent);
+        assert!(matches!(
+            strict,
+            Err(BuilderError::Data(ref message)) if message.starts_with("groups 8: categoryID:")
+        ));
+
+        let mut connection = Connection::open_in_memory().unwrap();
+        crate::builder::schema::create_schema(&connection).unwrap();
+        let config = ParserConfig {
+            strictness: Strictness::Lenient,
+            ..ParserConfig::default()
+        };
+        let summary = Parser::new(&dir.path, config)
+            .parse_data(&mut connection, &Silent)
+            .unwrap();
+        assert_eq!(summary.groups, 2);
+        assert_eq!(summary.skipped_records, 2);
+
+        let mut statement = connection
+            .prepare("SELECT fileName, line, recordKey, reason FROM buildIssues ORDER BY issueId")
+            .unwrap();
+        let issues: Vec<(String, Option<i64>, Option<String>, String)> = statement
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })
+            .unwrap()
+            .collect::<rusqlite::Result<_>>()
+            .unwrap();
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].0, "groups.jsonl");
+        assert_eq!(issues[0].1, Some(2));
+        assert_eq!(issues[0].2.as_deref(), Some("8"));
+        assert!(
+            issues[0]
+                .3
+                .starts_with("groups 8: categoryID: invalid type")
+        );
+        assert_eq!(issues[1].0, "groups.jsonl");
+        assert_eq!(issues[1].1, Some(4));
+        assert_eq!(issues[1].2, None);
+    }
+
+    #[test]
+    fn strictness_round_trips_through_its_name() {
+        for strictness in [Strictness::Strict, Strictness::Lenient] {
+            assert_eq!(strictness.name().parse::<Strictness>(), Ok(strictness));
+        }
+        assert!("loose".parse::<Strictness>().is_err());
+    }
+
+    #[test]
+    fn typed_records_decode_each_line_with_its_number() {
+        let dir = TempSdeDir::new(
+            "typed_records",
+            &[(
+                "categories.jsonl",
+                "{\"_key\": 6, \"name\": {\"en\": \"Ship\"}, \"published\": true}\n\
+                 \n\
+                 {\"_key\": 7}\n\
+                 [1]\n\
+                 {not json\n",
+            )],
+        );
+        let records: Vec<TypedRecord<records::Category>> =
+            typed_records(&dir.path, "categories", SdeFormat::Jsonl, true)
+                .unwrap()
+                .collect();
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[0].line, Some(1));
+        assert_eq!(records[0].key.as_deref(), Some("6"));
+        assert_eq!(records[0].record.as_ref().unwrap().id, 6);
+        assert_eq!(records[1].line, Some(3));
+        assert_eq!(records[1].key.as_deref(), Some("7"));
+        assert!(matches!(
+            records[1].record,
+            Err(BuilderError::Data(ref message)) if message == "categories 7: missing field `name`"
+        ));
+        assert!(matches!(
+            records[2].record,
+            Err(BuilderError::Data(ref message)) if message == "record 3 of categories is not an object"
+        ));
+        assert_eq!(records[2].key, None);
+        assert_eq!(records[3].line, Some(5));
+        assert!(matches!(records[3].record, Err(BuilderError::Json(_))));
+
+        let strict: Vec<TypedRecord<records::Category>> =
+            typed_records(&dir.path, "categories", SdeFormat::Jsonl, false)
+                .unwrap()
+                .collect();
+        assert_eq!(strict[0].key, None);
+    }
+
+    #[test]
+    fn decoded_records_end_in_an_error_without_the_end_marker() {
+        let (sender, receiver) = sync_channel::<Decoded>(4);
+        sender
+            .send(Some(TypedRecord {
+                line: Some(1),
+                key: Some("6".to_string()),
+                record: Ok(Box::new(6u32)),
+            }))
+            .unwrap();
+        drop(sender);
+        let records: Vec<TypedRecord<AnyRecord>> = DecodedRecords {
+            stem: "categories",
+            receiver,
+            finished: false,
+        }
+        .collect();
+        assert_eq!(records.len(), 2);
+        assert!(records[0].record.is_ok());
+        assert!(matches!(
+            records[1].record,
+            Err(BuilderError::Io(ref error)) if error.to_string().contains("categories")
+        ));
+    }
+
+    #[tokio::test]
+    async fn update_database_applies_only_the_differences() {
+        let old_dir = TempSdeDir::new("update_database_old", PIPELINE_FILES);
+        // The new build renames Perimeter and drops both stargates (and
+        // so the connection derived from them).
+        let new_files: Vec<(&str, String)> = PIPELINE_FILES
+            .iter()
+            .map(|(name, content)| match *name {
+                "mapSolarSystems.jsonl" => (
+                    *name,
+                    content.replace("{\"en\": \"Perimeter\"}", "{\"en\": \"Perimeter II\"}"),
+                ),
+                "mapStargates.jsonl" => (*name, String::new()),
+                _ => (*name, content.to_string()),
+            })
+            .collect();
+        let new_files: Vec<(&str, &str)> = new_files
+            .iter()
+            .map(|(name, content)| (*name, content.as_str()))
+            .collect();
+        let new_dir = TempSdeDir::new("update_database_new", &new_files);
+        let client = Client::new();
+
+        let mut connection = Connection::open_in_memory().unwrap();
+        crate::builder::schema::create_schema(&connection).unwrap();
+        Parser::new(&old_dir.path, ParserConfig::default())
+            .build_database(&mut connection, &client, "unused", &Silent)
+            .await
+            .unwrap();
+
+        // The same SDE again changes nothing.
+        let unchanged = Parser::new(&old_dir.path, ParserConfig::default())
+            .update_database(&mut connection, &Silent)
+            .unwrap();
+        assert!(
+            unchanged
+                .changes
+                .iter()
+                .all(|(_, changes)| changes.is_empty()),
+            "{:?}",
+            unchanged.changes
+        );
+
+        let summary = Parser::new(&new_dir.path, ParserConfig::default())
+            .update_database(&mut connection, &Silent)
+            .unwrap();
+        assert_eq!(summary.solar_systems, 2);
+        assert_eq!(summary.stargates, 0);
+        let changes_of = |table: &str| {
+            summary
+                .changes
+                .iter()
+                .find(|(name, _)| name == table)
+                .map(|(_, changes)| *changes)
+                .unwrap()
+        };
+        assert_eq!(
+            changes_of("mapSolarSystems"),
+            TableChanges {
+                updated: 1,
+                ..TableChanges::default()
+            }
+        );
+        assert_eq!(changes_of("mapSystemGates").deleted, 2);
+        assert_eq!(changes_of("mapSystemConnections").deleted, 1);
+        assert!(changes_of("mapRegions").is_empty());
+
+        let name: String = connection
+            .query_row(
+                "SELECT solarSystemName FROM mapSolarSystems WHERE solarSystemId = 30002187",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(name, "Perimeter II");
+        let indexed: i64 = connection
+            .query_row(
+                "SELECT COUNT(*) FROM searchIndex WHERE name = 'Perimeter II'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(indexed, 1);
+    }
+
     // ---------------------------------------------------------------------
     // stationServices / stationOperations / npcStations
     // ---------------------------------------------------------------------
@@ -4671,7 +6669,9 @@ mod tests {
 
         let config = ParserConfig::default();
         let parser = Parser::new(&dir.path, config);
-        let count = parser.parse_npc_stations(&connection).unwrap();
+        let mut scope = SystemScopeState::default();
+        scope.systems_in_scope.insert(30000001);
+        let count = parser.parse_npc_stations(&connection, &scope).unwrap();
         assert_eq!(count, 1);
 
         let (orbit_moon, orbit_planet): (Option<i64>, Option<i64>) = connection
@@ -4685,6 +6685,30 @@ mod tests {
         assert_eq!(orbit_planet, None);
     }
 
+    #[test]
+    fn parse_npc_stations_skips_stations_outside_scope() {
+        let dir = TempSdeDir::new(
+            "npc_stations_scope",
+            &[(
+                "npcStations.jsonl",
+                "{\"_key\": 60000004, \"celestialIndex\": 10, \"operationID\": 26, \
+                 \"orbitID\": 40000002, \"orbitIndex\": 1, \"ownerID\": 1000002, \
+                 \"position\": {\"x\": 1.0, \"y\": 2.0, \"z\": 3.0}, \
+                 \"reprocessingEfficiency\": 0.5, \"reprocessingHangarFlag\": 4, \
+                 \"reprocessingStationsTake\": 0.05, \"solarSystemID\": 30000001, \
+                 \"typeID\": 1531, \"useOperationName\": true}\n",
+            )],
+        );
+        let connection = Connection::open_in_memory().unwrap();
+        setup_for_npc_stations(&connection);
+
+        let parser = Parser::new(&dir.path, ParserConfig::default());
+        let count = parser
+            .parse_npc_stations(&connection, &SystemScopeState::default())
+            .unwrap();
+        assert_eq!(count, 0);
+    }
+
     #[test]
     fn parse_npc_stations_resolves_planet_orbit() {
         let dir = TempSdeDir::new(
@@ -4704,7 +6728,9 @@ mod tests {
 
         let config = ParserConfig::default();
         let parser = Parser::new(&dir.path, config);
-        let count = parser.parse_npc_stations(&connection).unwrap();
+        let mut scope = SystemScopeState::default();
+        scope.systems_in_scope.insert(30000001);
+        let count = parser.parse_npc_stations(&connection, &scope).unwrap();
         assert_eq!(count, 1);
 
         let (orbit_moon, orbit_planet, orbit_index): (Option<i64>, Option<i64>, Option<i64>) = connection
@@ -4742,7 +6768,9 @@ mod tests {
 
         let config = ParserConfig::default();
         let parser = Parser::new(&dir.path, config);
-        let count = parser.parse_npc_stations(&connection).unwrap();
+        let mut scope = SystemScopeState::default();
+        scope.systems_in_scope.insert(30000001);
+        let count = parser.parse_npc_stations(&connection, &scope).unwrap();
         assert_eq!(count, 1);
 
         let (celestial_index, orbit_moon, orbit_planet): (Option<i64>, Option<i64>, Option<i64>) = connection
@@ -0,0 +1,391 @@
+//! Build profiles: a [`ParserConfig`] described by name ([`Preset`]) and
+//! by a file of overrides ([`BuildProfile`], TOML or JSON), so a build
+//! can be reproduced without retyping a dozen flags.
+//!
+//! A profile only lists what it changes; everything else comes from its
+//! `preset` ([`Preset::Canonical`] when it names none). Keys are
+//! [`ParserConfig`]'s field names, with the position projection under
+//! `projection` and the community layers under `community_layers`;
+//! enum values go by the names their `FromStr` accepts:
+//!
+//! ```toml
+//! preset = "kspace-map-only"
+//! language = "de"
+//! projection = "dimetricZ"
+//! community_layers = ["icebelts", "jove-observatories"]
+//! ```
+//!
+//! A [`BuildProfile`] is also what the `sde-builder` CLI turns its own
+//! flags into, laid over the file's with [`BuildProfile::overridden_by`]:
+//! flags beat the profile file, which beats its preset.
+
+use crate::builder::BuilderError;
+use crate::builder::community::{CommunityConfig, CommunityLayer};
+use crate::builder::parser::{ParserConfig, SdeFormat, Strictness};
+use crate::objects::{ProjectedAxis, Projection};
+use serde::{Deserialize, Deserializer};
+use std::path::Path;
+use std::str::FromStr;
+
+/// A named starting point for a build.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Preset {
+    /// The whole canonical SDE -- every kind of space, gates and moons,
+    /// no third-party data -- with `position2DX`/`Y` computed locally
+    /// (isometric on Y). What `sde-builder build` builds by default.
+    #[default]
+    Canonical,
+    /// [`Self::Canonical`] plus every community layer.
+    Full,
+    /// Just what a k-space map needs: k-space systems and their gates;
+    /// no wormhole, abyssal or void systems, no moons, no third-party
+    /// data.
+    KspaceMapOnly,
+}
+
+impl Preset {
+    pub const ALL: [Preset; 3] = [Preset::Canonical, Preset::Full, Preset::KspaceMapOnly];
+
+    /// `"canonical"`, `"full"` or `"kspace-map-only"`, also what
+    /// [`std::str::FromStr`] accepts.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Preset::Canonical => "canonical",
+            Preset::Full => "full",
+            Preset::KspaceMapOnly => "kspace-map-only",
+        }
+    }
+
+    /// The preset's config; fields it doesn't pick are
+    /// [`ParserConfig::default`]'s.
+    pub fn config(&self) -> ParserConfig {
+        let canonical = ParserConfig {
+            force_position_2d: true,
+            position_2d_projection: Projection::Isometric(ProjectedAxis::Y),
+            map_kspace: true,
+            map_wspace: true,
+            map_abyssal: true,
+            map_void: true,
+            with_gates: true,
+            with_moons: true,
+            with_third_party: false,
+            ..ParserConfig::default()
+        };
+        match self {
+            Preset::Canonical => canonical,
+            Preset::Full => ParserConfig {
+                with_third_party: true,
+                community: CommunityConfig::ALL,
+                ..canonical
+            },
+            Preset::KspaceMapOnly => ParserConfig {
+                map_wspace: false,
+                map_abyssal: false,
+                map_void: false,
+                with_moons: false,
+                ..canonical
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for Preset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl std::str::FromStr for Preset {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Preset::ALL
+            .into_iter()
+            .find(|preset| preset.name() == value)
+            .ok_or_else(|| {
+                format!(
+                    "unknown preset `{value}` (expected `canonical`, `full` or `kspace-map-only`)"
+                )
+            })
+    }
+}
+
+/// Overrides on top of a [`Preset`]: every field is optional, and only
+/// the ones that are set change [`Self::config`]. See the module docs
+/// for the file format.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BuildProfile {
+    #[serde(default, deserialize_with = "by_name")]
+    pub preset: Option<Preset>,
+    pub language: Option<String>,
+    pub force_position_2d: Option<bool>,
+    /// `ParserConfig.position_2d_projection`.
+    #[serde(default, deserialize_with = "by_name")]
+    pub projection: Option<Projection>,
+    #[serde(default, deserialize_with = "by_names")]
+    pub projections: Option<Vec<Projection>>,
+    pub map_kspace: Option<bool>,
+    pub map_wspace: Option<bool>,
+    pub map_abyssal: Option<bool>,
+    pub map_void: Option<bool>,
+    pub region_ids: Option<Vec<i64>>,
+    pub constellation_ids: Option<Vec<i64>>,
+    pub faction_ids: Option<Vec<i64>>,
+    pub with_gates: Option<bool>,
+    pub with_moons: Option<bool>,
+    pub with_third_party: Option<bool>,
+    /// `ParserConfig.community`, as the layers that are on. Listing
+    /// layers turns `with_third_party` on too, unless the profile sets
+    /// that itself.
+    #[serde(default, deserialize_with = "by_names")]
+    pub community_layers: Option<Vec<CommunityLayer>>,
+    #[serde(default, deserialize_with = "by_name")]
+    pub format: Option<SdeFormat>,
+    #[serde(default, deserialize_with = "by_name")]
+    pub strictness: Option<Strictness>,
+}
+
+impl BuildProfile {
+    /// Reads a profile file: TOML for a `.toml` extension, JSON for
+    /// `.json`. Anything else, or a key that isn't one of
+    /// [`BuildProfile`]'s, is an error.
+    #[tracing::instrument]
+    pub fn load(path: &Path) -> Result<BuildProfile, BuilderError> {
+        let contents = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("toml") => Ok(toml::from_str(&contents)?),
+            Some("json") => Ok(serde_json::from_str(&contents)?),
+            _ => Err(BuilderError::Data(format!(
+                "{} isn't a build profile: expected a .toml or .json file",
+                path.display()
+            ))),
+        }
+    }
+
+    /// `self`, with every field `other` sets replaced by `other`'s.
+    pub fn overridden_by(self, other: BuildProfile) -> BuildProfile {
+        BuildProfile {
+            preset: other.preset.or(self.preset),
+            language: other.language.or(self.language),
+            force_position_2d: other.force_position_2d.or(self.force_position_2d),
+            projection: other.projection.or(self.projection),
+            projections: other.projections.or(self.projections),
+            map_kspace: other.map_kspace.or(self.map_kspace),
+            map_wspace: other.map_wspace.or(self.map_wspace),
+            map_abyssal: other.map_abyssal.or(self.map_abyssal),
+            map_void: other.map_void.or(self.map_void),
+            region_ids: other.region_ids.or(self.region_ids),
+            constellation_ids: other.constellation_ids.or(self.constellation_ids),
+            faction_ids: other.faction_ids.or(self.faction_ids),
+            with_gates: other.with_gates.or(self.with_gates),
+            with_moons: other.with_moons.or(self.with_moons),
+            with_third_party: other.with_third_party.or(self.with_third_party),
+            community_layers: other.community_layers.or(self.community_layers),
+            format: other.format.or(self.format),
+            strictness: other.strictness.or(self.strictness),
+        }
+    }
+
+    /// The preset's [`ParserConfig`] with this profile's overrides.
+    pub fn config(&self) -> ParserConfig {
+        let mut config = self.preset.unwrap_or_default().config();
+        if let Some(language) = &self.language {
+            config.language = language.clone();
+        }
+        if let Some(projections) = &self.projections {
+            config.projections = projections.clone();
+        }
+        for (field, ids) in [
+            (&mut config.region_ids, &self.region_ids),
+            (&mut config.constellation_ids, &self.constellation_ids),
+            (&mut config.faction_ids, &self.faction_ids),
+        ] {
+            if let Some(ids) = ids {
+                field.clone_from(ids);
+            }
+        }
+        if let Some(layers) = &self.community_layers {
+            config.community = CommunityConfig::with_layers(layers);
+            config.with_third_party = true;
+        }
+        let flags = [
+            (&mut config.force_position_2d, self.force_position_2d),
+            (&mut config.map_kspace, self.map_kspace),
+            (&mut config.map_wspace, self.map_wspace),
+            (&mut config.map_abyssal, self.map_abyssal),
+            (&mut config.map_void, self.map_void),
+            (&mut config.with_gates, self.with_gates),
+            (&mut config.with_moons, self.with_moons),
+            (&mut config.with_third_party, self.with_third_party),
+        ];
+        for (field, value) in flags {
+            if let Some(value) = value {
+                *field = value;
+            }
+        }
+        if let Some(projection) = self.projection {
+            config.position_2d_projection = projection;
+        }
+        if let Some(format) = self.format {
+            config.format = format;
+        }
+        if let Some(strictness) = self.strictness {
+            config.strictness = strictness;
+        }
+        config
+    }
+}
+
+/// A value given by the name its `FromStr` parses.
+fn by_name<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = String>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|name| name.parse().map_err(serde::de::Error::custom))
+        .transpose()
+}
+
+/// A list of values given by name, like [`by_name`].
+fn by_names<'de, D, T>(deserializer: D) -> Result<Option<Vec<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = String>,
+{
+    Option::<Vec<String>>::deserialize(deserializer)?
+        .map(|names| {
+            names
+                .iter()
+                .map(|name| name.parse().map_err(serde::de::Error::custom))
+                .collect()
+        })
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str, contents: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("sde-profile-{}-{name}", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn presets_round_trip_through_their_names() {
+        for preset in Preset::ALL {
+            assert_eq!(preset.name().parse::<Preset>(), Ok(preset));
+        }
+        assert!("everything".parse::<Preset>().is_err());
+
+        let kspace = Preset::KspaceMapOnly.config();
+        assert!(kspace.map_kspace && kspace.with_gates);
+        assert!(!kspace.map_wspace && !kspace.with_moons && !kspace.with_third_party);
+        let full = Preset::Full.config();
+        assert!(full.with_third_party);
+        assert_eq!(full.community, CommunityConfig::ALL);
+    }
+
+    #[test]
+    fn a_toml_profile_overrides_its_preset() {
+        let path = temp_file(
+            "kspace.toml",
+            "preset = \"kspace-map-only\"\n\
+             language = \"de\"\n\
+             projection = \"dimetricZ\"\n\
+             with_moons = true\n\
+             region_ids = [10000002]\n\
+             community_layers = [\"icebelts\"]\n",
+        );
+        let config = BuildProfile::load(&path).unwrap().config();
+
+        assert_eq!(config.language, "de");
+        assert_eq!(
+            config.position_2d_projection,
+            Projection::Dimetric(ProjectedAxis::Z)
+        );
+        assert!(config.with_moons);
+        assert_eq!(config.region_ids, vec![10000002]);
+        assert!(config.faction_ids.is_empty());
+        assert!(!config.map_wspace, "kept from the preset");
+        assert!(config.with_third_party, "implied by community_layers");
+        assert_eq!(config.community.layers(), vec![CommunityLayer::Icebelts]);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn a_json_profile_is_read_too_and_flags_beat_it() {
+        let path = temp_file(
+            "full.json",
+            r#"{"preset": "full", "strictness": "lenient", "map_void": false}"#,
+        );
+        let flags = BuildProfile {
+            map_void: Some(true),
+            with_third_party: Some(false),
+            ..BuildProfile::default()
+        };
+        let config = BuildProfile::load(&path)
+            .unwrap()
+            .overridden_by(flags)
+            .config();
+
+        assert_eq!(config.strictness, Strictness::Lenient);
+        assert!(config.map_void);
+        assert!(!config.with_third_party);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn a_flag_turns_off_third_party_data_a_profile_turns_on() {
+        let path = temp_file(
+            "third-party.toml",
+            "preset = \"canonical\"\n\
+             with_third_party = true\n\
+             community_layers = [\"icebelts\"]\n",
+        );
+        let file = BuildProfile::load(&path).unwrap();
+        assert!(file.clone().config().with_third_party);
+
+        // What `build --profile third-party.toml --with-third-party false`
+        // merges the file with.
+        let flags = BuildProfile {
+            with_third_party: Some(false),
+            ..BuildProfile::default()
+        };
+        let config = file.clone().overridden_by(flags).config();
+        assert!(
+            !config.with_third_party,
+            "beats the layers' implied `true` too"
+        );
+
+        // Without the flag, the profile's value stands.
+        let config = file.overridden_by(BuildProfile::default()).config();
+        assert!(config.with_third_party);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn a_profile_with_unknown_keys_or_values_is_rejected() {
+        let typo = temp_file("typo.toml", "map_kpsace = false\n");
+        assert!(matches!(
+            BuildProfile::load(&typo),
+            Err(BuilderError::Toml(_))
+        ));
+        let bad_value = temp_file("bad.json", r#"{"projection": "sideways"}"#);
+        assert!(matches!(
+            BuildProfile::load(&bad_value),
+            Err(BuilderError::Json(_))
+        ));
+        let not_a_profile = temp_file("profile.yaml", "preset: full\n");
+        assert!(matches!(
+            BuildProfile::load(&not_a_profile),
+            Err(BuilderError::Data(_))
+        ));
+        for path in [typo, bad_value, not_a_profile] {
+            std::fs::remove_file(&path).ok();
+        }
+    }
+}
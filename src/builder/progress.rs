@@ -0,0 +1,95 @@
+//! Progress reporting for the build pipeline: what
+//! [`super::parser::Parser::build_database`],
+//! [`super::community::process`] and [`super::sde_index::update_as_needed`]
+//! tell a [`BuildObserver`] as they go, instead of printing it.
+//!
+//! Every [`BuildEvent`] is sent from the thread doing the work, as it
+//! happens; an observer should hand it off quickly (a GUI would forward
+//! it to its own thread, which is what the `mpsc::Sender` implementation
+//! does). Closures taking a `BuildEvent` are observers too, and
+//! [`Silent`] drops everything.
+
+use crate::builder::http::DownloadProgress;
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+/// One thing that happened during a build.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildEvent {
+    /// A phase started: one of [`super::parser::PHASES`], or
+    /// `"foreign_key_check"`, `"commit"`, `"community"`, `"metadata"`,
+    /// `"delete_unwritten"` (see [`super::parser::ParseSummary::timings`]).
+    PhaseStarted { phase: &'static str },
+    /// The phase that last started ended: how many rows it wrote, for
+    /// the phases that count them (`None` otherwise), and how long it
+    /// took.
+    PhaseFinished {
+        phase: &'static str,
+        rows: Option<usize>,
+        elapsed: Duration,
+    },
+    /// More of `url` was downloaded (the SDE zip).
+    Download {
+        url: String,
+        progress: DownloadProgress,
+    },
+    /// Something that happened along the way, worth telling a user but
+    /// not a problem ("map downloaded for The Forge").
+    Message(String),
+    /// Something went wrong without stopping the build (a map that
+    /// couldn't be downloaded, a download attempt being retried, an
+    /// index that couldn't be read).
+    Warning(String),
+}
+
+/// Receives the [`BuildEvent`]s of a build. `Send + Sync` so a build
+/// future holding one can move between threads.
+pub trait BuildObserver: Send + Sync {
+    fn event(&self, event: BuildEvent);
+}
+
+impl<F: Fn(BuildEvent) + Send + Sync> BuildObserver for F {
+    fn event(&self, event: BuildEvent) {
+        self(event)
+    }
+}
+
+/// Forwards every event to the receiving end; a receiver that's gone
+/// away is ignored, the build doesn't depend on it.
+impl BuildObserver for Sender<BuildEvent> {
+    fn event(&self, event: BuildEvent) {
+        let _ = self.send(event);
+    }
+}
+
+/// An observer that ignores every event.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Silent;
+
+impl BuildObserver for Silent {
+    fn event(&self, _event: BuildEvent) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn closures_and_senders_are_observers() {
+        let seen = Mutex::new(Vec::new());
+        let closure = |event: BuildEvent| seen.lock().unwrap().push(event);
+        let observer: &dyn BuildObserver = &closure;
+        observer.event(BuildEvent::Message("hello".to_string()));
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![BuildEvent::Message("hello".to_string())]
+        );
+
+        let (sender, receiver) = std::sync::mpsc::channel();
+        sender.event(BuildEvent::PhaseStarted { phase: "types" });
+        drop(receiver);
+        // A gone receiver doesn't fail the sender's side.
+        sender.event(BuildEvent::Warning("nobody listens".to_string()));
+    }
+}
@@ -0,0 +1,883 @@
+//! Typed shape of every SDE record [`super::parser`] reads.
+//!
+//! One struct per file (`types.jsonl` -> [`Type`], `mapStars.jsonl` ->
+//! [`Star`], ...), deserialized with serde straight from the text of a
+//! JSONL line ([`decode_str`]) or from the JSONL-shaped [`Value`] a YAML
+//! document is reshaped into ([`decode`], see `parser::SdeFormat`). Only the fields the parser stores are declared:
+//! CCP adds fields to its export regularly, and unknown ones are
+//! ignored rather than rejected. What *is* required is checked strictly
+//! -- a required field that's missing or of the wrong type fails
+//! [`decode`] with the file, the record's `_key` and the path of the
+//! offending field, e.g. `npcCorporations 1000035: divisions[0].leaderID:
+//! invalid type: string "x", expected i64`. An optional scalar field of
+//! the wrong type (a float `radius` where an integer is stored, a string
+//! `iconID`) is read as absent instead, the way the
+//! parser always treated them; optional lists are still checked.
+//!
+//! Field names follow the SDE's own spelling (`groupID`, `iconID`, ...)
+//! via `#[serde(rename)]`; the Rust side uses the crate's usual
+//! snake_case. Fields the SDE has moved between the top level and a
+//! nested `statistics` object over time (`radius`, `locked`,
+//! `fragmented`) are declared in both places, and the record's accessor
+//! prefers the top-level copy.
+
+use crate::builder::BuilderError;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Deserializes `record`, read from `<stem>.jsonl`, into `T`. A
+/// mismatch is a [`BuilderError::Data`] naming `stem`, the record's
+/// `_key` and the path of the field that didn't match.
+pub fn decode<T: DeserializeOwned>(stem: &str, record: &Value) -> Result<T, BuilderError> {
+    serde_path_to_error::deserialize(record).map_err(|err| {
+        let key = record_key(record).unwrap_or_else(|| "(no `_key`)".to_string());
+        let path = err.path().to_string();
+        let inner = err.into_inner();
+        if path == "." {
+            BuilderError::Data(format!("{stem} {key}: {inner}"))
+        } else {
+            BuilderError::Data(format!("{stem} {key}: {path}: {inner}"))
+        }
+    })
+}
+
+/// Same as [`decode`], straight from `line`, the JSON text of a record
+/// of `<stem>.jsonl`: no [`Value`] is built for a record that decodes.
+/// One that doesn't is parsed into one after all, to fail with the same
+/// error [`decode`] gives (or a [`BuilderError::Json`], for text that
+/// isn't JSON).
+pub fn decode_str<T: DeserializeOwned>(stem: &str, line: &str) -> Result<T, BuilderError> {
+    let mut deserializer = serde_json::Deserializer::from_str(line);
+    if let Ok(record) = T::deserialize(&mut deserializer)
+        && deserializer.end().is_ok()
+    {
+        return Ok(record);
+    }
+    decode(stem, &serde_json::from_str::<Value>(line)?)
+}
+
+/// `record`'s `_key` as text (a string key as-is, without quotes), if it
+/// has one.
+pub fn record_key(record: &Value) -> Option<String> {
+    match record.get("_key")? {
+        Value::String(key) => Some(key.clone()),
+        key => Some(key.to_string()),
+    }
+}
+
+/// `#[serde(deserialize_with)]` for optional fields: the field's value
+/// if it has `T`'s type, `T::default()` (`None`, for an `Option`) if it
+/// doesn't. Put together with `#[serde(default)]`, which covers a field
+/// that's missing altogether.
+fn lenient<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: DeserializeOwned + Default,
+{
+    Ok(T::deserialize(Value::deserialize(deserializer)?).unwrap_or_default())
+}
+
+/// [`record_key`] of `line`, the JSON text of a record, read without
+/// decoding the rest of it. `None` as well for text that isn't a JSON
+/// object.
+pub fn line_key(line: &str) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Keyed {
+        #[serde(rename = "_key")]
+        key: Option<Value>,
+    }
+    if !line.trim_start().starts_with('{') {
+        return None;
+    }
+    match serde_json::from_str::<Keyed>(line).ok()?.key? {
+        Value::String(key) => Some(key),
+        key => Some(key.to_string()),
+    }
+}
+
+// ---------------------------------------------------------------------
+// Shared shapes
+// ---------------------------------------------------------------------
+
+/// A localized text field: either one string per language (`{"en":
+/// "Jita", "de": "Jita"}`) or a plain string with no language attached.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(untagged)]
+pub enum Localized {
+    Plain(String),
+    Translated(BTreeMap<String, String>),
+}
+
+impl Localized {
+    /// The text in `language`, falling back to `"en"`. A plain string is
+    /// returned as-is whatever the language.
+    pub fn get(&self, language: &str) -> Option<&str> {
+        match self {
+            Localized::Plain(text) => Some(text),
+            Localized::Translated(texts) => texts
+                .get(language)
+                .or_else(|| texts.get("en"))
+                .map(String::as_str),
+        }
+    }
+
+    /// Every `(language, text)` pair; none for a plain string, which has
+    /// no language to file it under.
+    pub fn translations(&self) -> impl Iterator<Item = (&str, &str)> {
+        let texts = match self {
+            Localized::Plain(_) => None,
+            Localized::Translated(texts) => Some(texts),
+        };
+        texts
+            .into_iter()
+            .flatten()
+            .map(|(language, text)| (language.as_str(), text.as_str()))
+    }
+}
+
+/// `position`: a point in space, in meters.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Position {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+/// `position2D`: CCP's precomputed 2D map position of a solar system.
+/// Either component may be missing.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+pub struct Position2D {
+    #[serde(default, deserialize_with = "lenient")]
+    pub x: Option<f64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub y: Option<f64>,
+}
+
+/// One `{"_key": ..., "_value": ...}` entry, the way the JSONL export
+/// writes maps keyed by integers (`corporationTrades`, `investors`,
+/// `stationTypes`).
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct Keyed<V> {
+    #[serde(rename = "_key")]
+    pub key: i64,
+    #[serde(rename = "_value")]
+    pub value: V,
+}
+
+// ---------------------------------------------------------------------
+// translationLanguages / types / categories / groups / races
+// ---------------------------------------------------------------------
+
+/// `translationLanguages.jsonl`. The name is `name` in the JSONL export
+/// and `_value` in a YAML one listing languages as `en: English`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct TranslationLanguage {
+    #[serde(rename = "_key")]
+    pub id: String,
+    pub name: Option<Localized>,
+    #[serde(rename = "_value")]
+    pub value: Option<Localized>,
+}
+
+/// `types.jsonl`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Type {
+    #[serde(rename = "_key")]
+    pub id: i64,
+    #[serde(rename = "groupID")]
+    pub group_id: i64,
+    pub name: Localized,
+    #[serde(rename = "iconID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub icon_id: Option<i64>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub published: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub volume: Option<f64>,
+}
+
+/// `categories.jsonl`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Category {
+    #[serde(rename = "_key")]
+    pub id: i64,
+    pub name: Localized,
+    #[serde(default, deserialize_with = "lenient")]
+    pub published: Option<bool>,
+}
+
+/// `groups.jsonl`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Group {
+    #[serde(rename = "_key")]
+    pub id: i64,
+    #[serde(rename = "categoryID")]
+    pub category_id: i64,
+    pub name: Localized,
+    #[serde(default, deserialize_with = "lenient")]
+    pub anchorable: Option<bool>,
+}
+
+/// `races.jsonl`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Race {
+    #[serde(rename = "_key")]
+    pub id: i64,
+    pub name: Localized,
+}
+
+// ---------------------------------------------------------------------
+// npcCorporationDivisions / npcCorporations / factions
+// ---------------------------------------------------------------------
+
+/// `npcCorporationDivisions.jsonl`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NpcCorporationDivision {
+    #[serde(rename = "_key")]
+    pub id: i64,
+    pub internal_name: String,
+    pub leader_type_name: Localized,
+}
+
+/// `npcCorporations.jsonl`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NpcCorporation {
+    #[serde(rename = "_key")]
+    pub id: i64,
+    pub name: Localized,
+    pub ticker_name: String,
+    pub deleted: bool,
+    pub description: Option<Localized>,
+    pub extent: String,
+    pub has_player_personnel_manager: bool,
+    pub initial_price: i64,
+    pub member_limit: i64,
+    pub min_security: f64,
+    pub minimum_join_standing: f64,
+    pub send_char_termination_message: bool,
+    pub shares: i64,
+    pub size: String,
+    #[serde(default, deserialize_with = "lenient")]
+    pub size_factor: Option<f64>,
+    pub tax_rate: f64,
+    pub unique_name: bool,
+    #[serde(rename = "ceoID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub ceo_id: Option<i64>,
+    #[serde(rename = "mainActivityID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub main_activity_id: Option<i64>,
+    #[serde(rename = "secondaryActivityID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub secondary_activity_id: Option<i64>,
+    #[serde(rename = "iconID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub icon_id: Option<i64>,
+    #[serde(rename = "raceID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub race_id: Option<i64>,
+    #[serde(rename = "enemyID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub enemy_id: Option<i64>,
+    #[serde(rename = "friendID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub friend_id: Option<i64>,
+    #[serde(rename = "factionID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub faction_id: Option<i64>,
+    #[serde(rename = "solarSystemID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub solar_system_id: Option<i64>,
+    #[serde(rename = "stationID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub station_id: Option<i64>,
+    pub allowed_member_races: Option<Vec<i64>>,
+    pub divisions: Option<Vec<CorporationDivision>>,
+    /// `typeId -> affinity`.
+    pub corporation_trades: Option<Vec<Keyed<f64>>>,
+    /// `corporationId -> shares`.
+    pub investors: Option<Vec<Keyed<f64>>>,
+}
+
+/// One entry of [`NpcCorporation::divisions`].
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorporationDivision {
+    #[serde(rename = "_key")]
+    pub division_id: i64,
+    pub division_number: i64,
+    #[serde(rename = "leaderID")]
+    pub leader_id: i64,
+    pub size: i64,
+}
+
+/// `factions.jsonl`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Faction {
+    #[serde(rename = "_key")]
+    pub id: i64,
+    pub name: Localized,
+    #[serde(rename = "iconID")]
+    pub icon_id: i64,
+    pub size_factor: f64,
+    pub unique_name: bool,
+    pub description: Localized,
+    pub short_description: Option<Localized>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub flat_logo: Option<String>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub flat_logo_with_name: Option<String>,
+    #[serde(rename = "corporationID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub corporation_id: Option<i64>,
+    #[serde(rename = "militiaCorporationID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub militia_corporation_id: Option<i64>,
+    #[serde(rename = "solarSystemID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub solar_system_id: Option<i64>,
+    pub member_races: Option<Vec<i64>>,
+}
+
+// ---------------------------------------------------------------------
+// mapRegions / mapConstellations / mapSolarSystems / mapStargates
+// ---------------------------------------------------------------------
+
+/// `mapRegions.jsonl`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Region {
+    #[serde(rename = "_key")]
+    pub id: i64,
+    pub name: Localized,
+    #[serde(rename = "factionID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub faction_id: Option<i64>,
+    #[serde(rename = "nebulaID")]
+    pub nebula_id: i64,
+    #[serde(rename = "wormholeClassID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub wormhole_class_id: Option<i64>,
+    pub position: Position,
+}
+
+/// `mapConstellations.jsonl`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Constellation {
+    #[serde(rename = "_key")]
+    pub key: i64,
+    /// Preferred over `_key` when present, see [`Self::id`].
+    #[serde(rename = "constellationID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub constellation_id: Option<i64>,
+    pub name: Localized,
+    #[serde(rename = "regionID")]
+    pub region_id: i64,
+    pub position: Position,
+}
+
+impl Constellation {
+    /// `constellationID` if the record carries it, `_key` otherwise.
+    pub fn id(&self) -> i64 {
+        self.constellation_id.unwrap_or(self.key)
+    }
+}
+
+/// `mapSolarSystems.jsonl`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SolarSystem {
+    #[serde(rename = "_key")]
+    pub id: i64,
+    #[serde(rename = "wormholeClassID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub wormhole_class_id: Option<i64>,
+    pub name: Localized,
+    #[serde(rename = "constellationID")]
+    pub constellation_id: i64,
+    #[serde(default, deserialize_with = "lenient")]
+    pub hub: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub corridor: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub fringe: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub border: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub regional: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub international: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    pub luminosity: Option<f64>,
+    pub radius: f64,
+    pub position: Position,
+    #[serde(rename = "position2D")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub position_2d: Option<Position2D>,
+    pub security_status: f64,
+    #[serde(default, deserialize_with = "lenient")]
+    pub security_class: Option<String>,
+    #[serde(rename = "factionID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub faction_id: Option<i64>,
+    pub disallowed_anchor_categories: Option<Vec<i64>>,
+    pub disallowed_anchor_groups: Option<Vec<i64>>,
+}
+
+/// `mapStargates.jsonl`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Stargate {
+    #[serde(rename = "_key")]
+    pub id: i64,
+    #[serde(rename = "solarSystemID")]
+    pub solar_system_id: i64,
+    #[serde(rename = "typeID")]
+    pub type_id: i64,
+    pub position: Position,
+    pub destination: StargateDestination,
+}
+
+/// [`Stargate::destination`]: the gate on the other side, and its system.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct StargateDestination {
+    #[serde(rename = "stargateID")]
+    pub stargate_id: i64,
+    #[serde(rename = "solarSystemID")]
+    pub solar_system_id: i64,
+}
+
+// ---------------------------------------------------------------------
+// mapStars / mapPlanets / mapMoons
+// ---------------------------------------------------------------------
+
+/// `mapStars.jsonl`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Star {
+    #[serde(rename = "_key")]
+    pub id: i64,
+    #[serde(rename = "solarSystemID")]
+    pub solar_system_id: i64,
+    #[serde(rename = "typeID")]
+    pub type_id: i64,
+    #[serde(default, deserialize_with = "lenient")]
+    radius: Option<i64>,
+    #[serde(default, deserialize_with = "lenient")]
+    locked: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    statistics: StarStatistics,
+}
+
+/// [`Star`]'s `statistics`, holding the fields it may carry there
+/// instead of at the top level.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+struct StarStatistics {
+    #[serde(default, deserialize_with = "lenient")]
+    radius: Option<i64>,
+    #[serde(default, deserialize_with = "lenient")]
+    locked: Option<bool>,
+}
+
+impl Star {
+    /// `radius`, or `statistics.radius` when the top level has none.
+    pub fn radius(&self) -> Option<i64> {
+        self.radius.or(self.statistics.radius)
+    }
+
+    /// `locked`, or `statistics.locked` when the top level has none.
+    pub fn locked(&self) -> Option<bool> {
+        self.locked.or(self.statistics.locked)
+    }
+}
+
+/// `mapPlanets.jsonl`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Planet {
+    #[serde(rename = "_key")]
+    pub id: i64,
+    #[serde(rename = "solarSystemID")]
+    pub solar_system_id: i64,
+    pub celestial_index: i64,
+    #[serde(rename = "typeID")]
+    pub type_id: i64,
+    pub position: Position,
+    #[serde(default, deserialize_with = "lenient")]
+    radius: Option<f64>,
+    #[serde(default, deserialize_with = "lenient")]
+    locked: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    fragmented: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    statistics: PlanetStatistics,
+}
+
+/// [`Planet`]'s `statistics`, see [`StarStatistics`].
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+struct PlanetStatistics {
+    #[serde(default, deserialize_with = "lenient")]
+    radius: Option<f64>,
+    #[serde(default, deserialize_with = "lenient")]
+    locked: Option<bool>,
+    #[serde(default, deserialize_with = "lenient")]
+    fragmented: Option<bool>,
+}
+
+impl Planet {
+    /// `radius`, or `statistics.radius` when the top level has none.
+    pub fn radius(&self) -> Option<f64> {
+        self.radius.or(self.statistics.radius)
+    }
+
+    /// `locked`, or `statistics.locked` when the top level has none.
+    pub fn locked(&self) -> Option<bool> {
+        self.locked.or(self.statistics.locked)
+    }
+
+    /// `fragmented`, or `statistics.fragmented` when the top level has
+    /// none.
+    pub fn fragmented(&self) -> Option<bool> {
+        self.fragmented.or(self.statistics.fragmented)
+    }
+}
+
+/// `mapMoons.jsonl`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Moon {
+    #[serde(rename = "_key")]
+    pub id: i64,
+    #[serde(rename = "solarSystemID")]
+    pub solar_system_id: i64,
+    pub orbit_index: i64,
+    #[serde(rename = "orbitID")]
+    #[serde(default, deserialize_with = "lenient")]
+    pub orbit_id: Option<i64>,
+    #[serde(rename = "typeID")]
+    pub type_id: i64,
+    pub position: Position,
+    #[serde(default, deserialize_with = "lenient")]
+    radius: Option<i64>,
+    #[serde(default, deserialize_with = "lenient")]
+    statistics: MoonStatistics,
+}
+
+/// [`Moon`]'s `statistics`, see [`StarStatistics`].
+#[derive(Debug, Clone, Copy, PartialEq, Default, Deserialize)]
+struct MoonStatistics {
+    #[serde(default, deserialize_with = "lenient")]
+    radius: Option<i64>,
+}
+
+impl Moon {
+    /// `radius`, or `statistics.radius` when the top level has none.
+    pub fn radius(&self) -> Option<i64> {
+        self.radius.or(self.statistics.radius)
+    }
+}
+
+// ---------------------------------------------------------------------
+// stationServices / stationOperations / npcStations
+// ---------------------------------------------------------------------
+
+/// `stationServices.jsonl`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StationService {
+    #[serde(rename = "_key")]
+    pub id: i64,
+    pub service_name: Localized,
+}
+
+/// `stationOperations.jsonl`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StationOperation {
+    #[serde(rename = "_key")]
+    pub id: i64,
+    #[serde(rename = "activityID")]
+    pub activity_id: i64,
+    pub operation_name: Localized,
+    pub description: Option<Localized>,
+    pub border: f64,
+    pub corridor: f64,
+    pub fringe: f64,
+    pub hub: f64,
+    pub ratio: f64,
+    pub manufacturing_factor: f64,
+    pub research_factor: f64,
+    pub services: Option<Vec<i64>>,
+    /// `sizeKey -> typeId`.
+    pub station_types: Option<Vec<Keyed<i64>>>,
+}
+
+/// `npcStations.jsonl`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NpcStation {
+    #[serde(rename = "_key")]
+    pub id: i64,
+    #[serde(default, deserialize_with = "lenient")]
+    pub celestial_index: Option<i64>,
+    #[serde(rename = "operationID")]
+    pub operation_id: i64,
+    #[serde(rename = "orbitID")]
+    pub orbit_id: i64,
+    #[serde(default, deserialize_with = "lenient")]
+    pub orbit_index: Option<i64>,
+    #[serde(rename = "ownerID")]
+    pub owner_id: i64,
+    pub position: Position,
+    pub reprocessing_efficiency: f64,
+    pub reprocessing_hangar_flag: i64,
+    pub reprocessing_stations_take: f64,
+    #[serde(rename = "solarSystemID")]
+    pub solar_system_id: i64,
+    #[serde(rename = "typeID")]
+    pub type_id: i64,
+    pub use_operation_name: bool,
+}
+
+// ---------------------------------------------------------------------
+// Field inventory
+// ---------------------------------------------------------------------
+
+/// Top-level fields the parser reads from `<stem>.jsonl`, in the SDE's
+/// spelling -- what [`super::audit`] compares a file against. `None` for
+/// a file the parser doesn't read. Nested fields (`position.x`,
+/// `statistics.radius`, ...) are covered by their parent's name.
+pub fn expected_fields(stem: &str) -> Option<&'static [&'static str]> {
+    Some(match stem {
+        "translationLanguages" => fields_of::<TranslationLanguage>(),
+        "categories" => fields_of::<Category>(),
+        "groups" => fields_of::<Group>(),
+        "types" => fields_of::<Type>(),
+        "races" => fields_of::<Race>(),
+        "npcCorporationDivisions" => fields_of::<NpcCorporationDivision>(),
+        "npcCorporations" => fields_of::<NpcCorporation>(),
+        "factions" => fields_of::<Faction>(),
+        "mapRegions" => fields_of::<Region>(),
+        "mapConstellations" => fields_of::<Constellation>(),
+        "mapSolarSystems" => fields_of::<SolarSystem>(),
+        "mapStargates" => fields_of::<Stargate>(),
+        "mapStars" => fields_of::<Star>(),
+        "mapPlanets" => fields_of::<Planet>(),
+        "mapMoons" => fields_of::<Moon>(),
+        "stationServices" => fields_of::<StationService>(),
+        "stationOperations" => fields_of::<StationOperation>(),
+        "npcStations" => fields_of::<NpcStation>(),
+        _ => return None,
+    })
+}
+
+/// Field names `T`'s derived `Deserialize` asks for, renames applied.
+/// The derive passes them to `Deserializer::deserialize_struct`, so a
+/// deserializer that only records them and bails out reads the list
+/// straight from the struct definition -- it can't drift from what
+/// [`decode`] actually checks.
+fn fields_of<T: DeserializeOwned>() -> &'static [&'static str] {
+    struct Introspect<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> serde::Deserializer<'de> for Introspect<'_> {
+        type Error = serde::de::value::Error;
+
+        fn deserialize_any<V: serde::de::Visitor<'de>>(
+            self,
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            Err(serde::de::Error::custom("only structs can be introspected"))
+        }
+
+        fn deserialize_struct<V: serde::de::Visitor<'de>>(
+            self,
+            _name: &'static str,
+            fields: &'static [&'static str],
+            _visitor: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(serde::de::Error::custom("introspection only"))
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut fields: &'static [&'static str] = &[];
+    let _ = T::deserialize(Introspect(&mut fields));
+    fields
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn localized_falls_back_to_english() {
+        let name: Localized = serde_json::from_value(json!({"en": "Jita", "de": "Jita"})).unwrap();
+        assert_eq!(name.get("fr"), Some("Jita"));
+        let plain: Localized = serde_json::from_value(json!("Jita")).unwrap();
+        assert_eq!(plain.get("de"), Some("Jita"));
+        assert_eq!(plain.translations().count(), 0);
+    }
+
+    #[test]
+    fn decode_names_the_record_and_the_field_that_does_not_match() {
+        let record = json!({
+            "_key": 1000035,
+            "divisions": [{"_key": 22, "divisionNumber": 1, "leaderID": "x", "size": 1}],
+        });
+        let Err(BuilderError::Data(message)) = decode::<NpcCorporation>("npcCorporations", &record)
+        else {
+            panic!("expected a data error");
+        };
+        assert_eq!(
+            message,
+            "npcCorporations 1000035: divisions[0].leaderID: \
+            invalid type: string \"x\", expected i64"
+        );
+
+        let Err(BuilderError::Data(message)) =
+            decode::<Category>("categories", &json!({"_key": 6}))
+        else {
+            panic!("expected a data error");
+        };
+        assert_eq!(message, "categories 6: missing field `name`");
+    }
+
+    #[test]
+    fn every_parsed_file_has_its_expected_fields() {
+        for stem in crate::builder::parser::DECODED_FILES {
+            let fields = expected_fields(stem).unwrap_or_else(|| panic!("{stem}"));
+            assert!(fields.contains(&"_key"), "{stem}");
+        }
+        assert_eq!(
+            expected_fields("mapStars"),
+            Some(
+                &[
+                    "_key",
+                    "solarSystemID",
+                    "typeID",
+                    "radius",
+                    "locked",
+                    "statistics"
+                ][..]
+            )
+        );
+        assert_eq!(expected_fields("dogmaEffects"), None);
+    }
+
+    #[test]
+    fn nested_statistics_fill_in_for_missing_top_level_fields() {
+        let planet: Planet = decode(
+            "mapPlanets",
+            &json!({
+                "_key": 40000002, "solarSystemID": 30000001, "celestialIndex": 1,
+                "typeID": 11, "position": {"x": 1.0, "y": 2.0, "z": 3.0},
+                "radius": 3000000.0,
+                "statistics": {"radius": 1.0, "locked": true},
+            }),
+        )
+        .unwrap();
+        assert_eq!(planet.radius(), Some(3000000.0));
+        assert_eq!(planet.locked(), Some(true));
+        assert_eq!(planet.fragmented(), None);
+    }
+
+    #[test]
+    fn optional_fields_of_the_wrong_type_read_as_absent() {
+        // mapMoons.jsonl's shape, but with a float radius where the
+        // column is an integer: the moon still decodes, radius-less.
+        let moon: Moon = decode(
+            "mapMoons",
+            &json!({
+                "_key": 40000003, "solarSystemID": 30000001, "orbitIndex": 1,
+                "orbitID": 40000002, "typeID": 14,
+                "position": {"x": 1.0, "y": 2.0, "z": 3.0},
+                "radius": 1250000.5,
+                "statistics": {"density": 2.4, "escapeVelocity": 900.0},
+            }),
+        )
+        .unwrap();
+        assert_eq!(moon.radius(), None);
+        assert_eq!(moon.orbit_id, Some(40000002));
+
+        // A wrong-typed top-level copy falls back to `statistics`, and a
+        // `statistics` that isn't an object is no `statistics` at all.
+        let star: Star = decode(
+            "mapStars",
+            &json!({
+                "_key": 40000001, "solarSystemID": 30000001, "typeID": 3000,
+                "radius": 63350000.5, "locked": "no",
+                "statistics": {"radius": 63350000},
+            }),
+        )
+        .unwrap();
+        assert_eq!(star.radius(), Some(63350000));
+        assert_eq!(star.locked(), None);
+        let star: Star = decode(
+            "mapStars",
+            &json!({
+                "_key": 40000001, "solarSystemID": 30000001, "typeID": 3000,
+                "statistics": "n/a",
+            }),
+        )
+        .unwrap();
+        assert_eq!(star.radius(), None);
+
+        let system: SolarSystem = decode(
+            "mapSolarSystems",
+            &json!({
+                "_key": 30000001, "constellationID": 20000001, "name": {"en": "Tanoo"},
+                "hub": 1, "luminosity": "bright", "radius": 1.0e12,
+                "position": {"x": 1.0, "y": 2.0, "z": 3.0},
+                "position2D": {"x": "?", "y": 4.0},
+                "securityStatus": 0.85, "securityClass": 5, "factionID": 500007,
+            }),
+        )
+        .unwrap();
+        assert_eq!(system.hub, None);
+        assert_eq!(system.luminosity, None);
+        assert_eq!(system.security_class, None);
+        assert_eq!(
+            system.position_2d,
+            Some(Position2D {
+                x: None,
+                y: Some(4.0)
+            })
+        );
+        assert_eq!(system.faction_id, Some(500007));
+
+        // Required fields and optional lists are still checked.
+        let Err(BuilderError::Data(message)) = decode::<Moon>(
+            "mapMoons",
+            &json!({
+                "_key": 40000003, "solarSystemID": 30000001, "orbitIndex": 1.5,
+                "typeID": 14, "position": {"x": 1.0, "y": 2.0, "z": 3.0},
+            }),
+        ) else {
+            panic!("expected a data error");
+        };
+        assert_eq!(
+            message,
+            "mapMoons 40000003: orbitIndex: invalid type: floating point `1.5`, expected i64"
+        );
+        assert!(
+            decode::<Faction>(
+                "factions",
+                &json!({
+                    "_key": 500001, "name": "Caldari State", "iconID": 1439,
+                    "sizeFactor": 5.0, "uniqueName": true, "description": "",
+                    "memberRaces": "1",
+                }),
+            )
+            .is_err()
+        );
+    }
+}
@@ -34,9 +34,13 @@ pub const SCHEMA_DDL: &str = include_str!("schema.sql");
 /// already exists`) instead of silently mixing in with a partial or
 /// corrupted schema from a previous run. The caller is responsible for
 /// passing in a "clean" connection (a new or empty database).
+///
+/// Last, it stamps [`crate::SCHEMA_VERSION`] into the database's `PRAGMA
+/// user_version`, which `SdeManager::open` checks before reading.
 #[tracing::instrument]
 pub fn create_schema(connection: &Connection) -> rusqlite::Result<()> {
-    connection.execute_batch(SCHEMA_DDL)
+    connection.execute_batch(SCHEMA_DDL)?;
+    connection.pragma_update(None, "user_version", crate::SCHEMA_VERSION)
 }
 
 /// Names of the tables declared by [`SCHEMA_DDL`], in the order they
@@ -88,7 +92,17 @@ mod tests {
         assert_eq!(existing, expected);
         // Extra anchor: if this number changes, the DDL likely changed
         // too, and it's worth reviewing the rest of this test file.
-        assert_eq!(expected.len(), 30);
+        assert_eq!(expected.len(), 35);
+    }
+
+    #[test]
+    fn create_schema_stamps_the_schema_version() {
+        let connection = Connection::open_in_memory().unwrap();
+        create_schema(&connection).unwrap();
+        let version: u32 = connection
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, crate::SCHEMA_VERSION);
     }
 
     #[test]
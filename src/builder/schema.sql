@@ -320,6 +320,21 @@ CREATE TABLE mapSolarSystemSubType (
 ) STRICT, WITHOUT ROWID;
 CREATE INDEX idx_mapSolarSystemSubType_subType ON mapSolarSystemSubType (subType);
 
+-- Extra 2D map projections of each system, side by side with the main
+-- one in mapSolarSystems.position2DX/Y: one row per system and
+-- projection the build was configured to store (ParserConfig.projections),
+-- so a client can switch between them without a rebuild. `projection`
+-- is the projection's name (`isometricY`, `dimetricZ`, `orthographicX`,
+-- ... -- see objects::Projection::name).
+CREATE TABLE mapSolarSystemProjections (
+  solarSystemId INTEGER NOT NULL REFERENCES mapSolarSystems(solarSystemId)
+                  ON UPDATE CASCADE ON DELETE CASCADE,
+  projection    TEXT NOT NULL,
+  x REAL NOT NULL, y REAL NOT NULL,
+  CONSTRAINT pkey PRIMARY KEY (solarSystemId, projection) ON CONFLICT FAIL
+) STRICT, WITHOUT ROWID;
+CREATE INDEX idx_mapSolarSystemProjections_projection ON mapSolarSystemProjections (projection);
+
 CREATE TABLE factionSolarSystem (
   solarSystemId INTEGER NOT NULL REFERENCES mapSolarSystems(solarSystemId)
                   ON UPDATE CASCADE ON DELETE CASCADE,
@@ -561,3 +576,63 @@ CREATE TABLE npcStations (
 CREATE INDEX idx_npcStations_solarSystemId ON npcStations(solarSystemId);
 CREATE INDEX idx_npcStations_operationId ON npcStations(operationId);
 CREATE INDEX idx_npcStations_ownerId ON npcStations(ownerId);
+
+-- ------------------------------------------------------------
+-- Traducciones
+-- ------------------------------------------------------------
+
+-- Languages the SDE ships localized text in, from
+-- translationLanguages.jsonl (`_key` is the language code -- "en",
+-- "de", "fr", ...). trnTranslations only stores languages listed here.
+CREATE TABLE translationLanguages (
+  languageId    TEXT NOT NULL PRIMARY KEY,
+  languageName  TEXT
+) STRICT;
+
+-- Every language variant of every localized field the parser reads
+-- (names, descriptions, ...), keyed by the entity it belongs to:
+-- (tableName, columnName, keyId) points at the row/column holding the
+-- base-language copy (e.g. ('mapSolarSystems', 'solarSystemName',
+-- 30000142)). The base columns themselves keep a single language
+-- (ParserConfig.language), so readers that don't care about
+-- localization never need to touch this table.
+-- One generic table instead of one per entity: every localized field
+-- has the same shape (a text per language), and the read side resolves
+-- all of them the same way.
+CREATE TABLE trnTranslations (
+  tableName   TEXT NOT NULL,
+  columnName  TEXT NOT NULL,
+  keyId       INTEGER NOT NULL,
+  languageId  TEXT NOT NULL REFERENCES translationLanguages(languageId)
+                ON UPDATE CASCADE ON DELETE CASCADE,
+  text        TEXT NOT NULL,
+  CONSTRAINT pkey PRIMARY KEY (tableName, columnName, keyId, languageId) ON CONFLICT FAIL
+) STRICT, WITHOUT ROWID;
+CREATE INDEX idx_trnTranslations_languageId ON trnTranslations(languageId);
+
+-- ------------------------------------------------------------
+-- Procedencia del build
+-- ------------------------------------------------------------
+
+-- Where this database came from: SDE build number and release date,
+-- crate version, the ParserConfig used, languages, whether third-party
+-- data was layered in, dotlan's map fingerprints. Plain key/value
+-- (see builder::metadata for the keys) rather than one column each,
+-- so adding a key later doesn't change the schema; compound values
+-- (the config, the fingerprints) are JSON text.
+CREATE TABLE sdeMetadata (
+  key    TEXT NOT NULL PRIMARY KEY,
+  value  TEXT NOT NULL
+) STRICT, WITHOUT ROWID;
+
+-- Records a lenient build (ParserConfig.strictness) skipped instead of
+-- failing: the file, the line it's on (NULL in a YAML export, which
+-- isn't read line by line), its `_key` when it could be read, and why.
+-- Always empty after a strict build.
+CREATE TABLE buildIssues (
+  issueId    INTEGER PRIMARY KEY,
+  fileName   TEXT NOT NULL,
+  line       INTEGER,
+  recordKey  TEXT,
+  reason     TEXT NOT NULL
+) STRICT;
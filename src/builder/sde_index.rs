@@ -10,12 +10,28 @@
 //! `builder::extract`'s job.
 
 use crate::builder::BuilderError;
+use crate::builder::cancel::CancellationToken;
 use crate::builder::http;
+use crate::builder::progress::{BuildEvent, BuildObserver};
 use reqwest::Client;
 use std::path::Path;
 
+/// An SDE build as CCP labels it, in `latest.jsonl` and in the `_sde`
+/// record every export ships. Recorded in `sdeMetadata` (see
+/// `builder::metadata`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SdeRelease {
+    /// `buildNumber`, as text (an opaque identifier, see
+    /// `find_sde_release`).
+    pub build_number: String,
+    /// `releaseDate`, as published (ISO 8601); `None` if the record
+    /// doesn't carry one.
+    pub release_date: Option<String>,
+}
+
 /// Looks, line by line, for the `latest.jsonl` record with
-/// `_key == "sde"` and returns its `buildNumber` as text.
+/// `_key == "sde"` and returns its `buildNumber` as text (plus its
+/// `releaseDate`, if any, see [`SdeRelease`]).
 ///
 /// Verified against a real `latest.jsonl` (August 2026): it carries a
 /// single line (`{"_key": "sde", "buildNumber": 3458726, "releaseDate":
@@ -36,7 +52,7 @@ use std::path::Path;
 /// number is an opaque identifier meant to be compared as text, not
 /// something meant to be operated on numerically.
 #[tracing::instrument]
-fn find_sde_build_number(jsonl: &str) -> Option<String> {
+pub(crate) fn find_sde_release(jsonl: &str) -> Option<SdeRelease> {
     for line in jsonl.lines() {
         let line = line.trim();
         if line.is_empty() {
@@ -49,14 +65,52 @@ fn find_sde_build_number(jsonl: &str) -> Option<String> {
             continue;
         }
         let build = record.get("buildNumber")?;
-        return Some(match build {
-            serde_json::Value::String(s) => s.clone(),
-            other => other.to_string(),
+        return Some(SdeRelease {
+            build_number: match build {
+                serde_json::Value::String(s) => s.clone(),
+                other => other.to_string(),
+            },
+            release_date: record
+                .get("releaseDate")
+                .and_then(|date| date.as_str())
+                .map(str::to_string),
         });
     }
     None
 }
 
+/// [`find_sde_release`]'s build number alone.
+fn find_sde_build_number(jsonl: &str) -> Option<String> {
+    find_sde_release(jsonl).map(|release| release.build_number)
+}
+
+/// The size and SHA-256 the `sde` record of `latest.jsonl` publishes for
+/// `variant`'s zip, as `files.<variant>.size`/`files.<variant>.sha256`;
+/// each `None` when absent.
+///
+/// The real `latest.jsonl` (August 2026) carries neither -- only the
+/// build number and release date, see [`find_sde_release`] -- so for
+/// now a download is only checked against the `Content-Length` the
+/// server sends with it. Read anyway, so an index that starts
+/// publishing them gets its downloads checked without a code change.
+pub(crate) fn find_expected_download(jsonl: &str, variant: &str) -> http::ExpectedDownload {
+    let file = jsonl
+        .lines()
+        .filter_map(|line| serde_json::from_str::<serde_json::Value>(line.trim()).ok())
+        .find(|record| record.get("_key").and_then(|v| v.as_str()) == Some("sde"))
+        .and_then(|record| record.get("files")?.get(variant).cloned());
+    let Some(file) = file else {
+        return http::ExpectedDownload::default();
+    };
+    http::ExpectedDownload {
+        size: file.get("size").and_then(|size| size.as_u64()),
+        sha256: file
+            .get("sha256")
+            .and_then(|sha256| sha256.as_str())
+            .map(str::to_string),
+    }
+}
+
 /// Checks the most recent SDE build number
 /// (`{sde_url_base}latest.jsonl`) and downloads
 /// `eve-online-static-data-{build}-{variant}.zip` to
@@ -75,37 +129,57 @@ fn find_sde_build_number(jsonl: &str) -> Option<String> {
 /// etc.): a one-off problem checking the version doesn't block the
 /// whole build.
 ///
+/// The download goes through [`http::download_resumable`], retried per
+/// `retry` and checked against whatever size and SHA-256 the index
+/// publishes for it (`files.<variant>.size`/`.sha256` of the `sde`
+/// record -- none, as of August 2026). `observer` follows it, and hears
+/// what was decided along the way ([`BuildEvent::Message`]) and why the
+/// check was given up on, if it was ([`BuildEvent::Warning`]).
+/// Cancelling `cancellation` stops the download with
+/// [`BuilderError::Cancelled`], keeping the partial file for the next
+/// run to resume (see below).
+///
 /// # Downloads to temp, then renames
 ///
 /// This function downloads to a temporary file
-/// (`sde-{variant}.zip.tmp`) and only replaces `sde-{variant}.zip` (via
-/// `rename`, atomic on the same filesystem) once the download finished
-/// successfully -- if it fails, the previous zip stays intact instead
-/// of being deleted upfront and left missing.
-#[tracing::instrument]
+/// (`sde-{variant}.zip.{build}.tmp`) and only replaces
+/// `sde-{variant}.zip` (via `rename`, atomic on the same filesystem)
+/// once the download finished successfully -- if it fails, the previous
+/// zip stays intact instead of being deleted upfront and left missing.
+/// A failed download leaves the temporary file behind on purpose: the
+/// next run resumes it. Its name carries the build number so a partial
+/// file is only ever resumed with the same build; partial files of any
+/// other build are deleted.
+#[tracing::instrument(skip(observer))]
 pub async fn update_as_needed(
     client: &Client,
     data_dir: &Path,
     sde_url_base: &str,
     variant: &str,
+    retry: &http::RetryPolicy,
+    observer: &dyn BuildObserver,
+    cancellation: &CancellationToken,
 ) -> Result<bool, BuilderError> {
     std::fs::create_dir_all(data_dir)?;
 
     let build_file = data_dir.join(format!("sde-{variant}.build"));
     let zip_file = data_dir.join(format!("sde-{variant}.zip"));
-    let temp_zip_file = data_dir.join(format!("sde-{variant}.zip.tmp"));
 
     let index_url = format!("{sde_url_base}latest.jsonl");
     let index_contents = match http::fetch_text(client, &index_url).await {
         Ok(contents) => contents,
         Err(err) => {
-            eprintln!("sde_index: couldn't download {index_url} ({err})");
+            observer.event(BuildEvent::Warning(format!(
+                "sde_index: couldn't download {index_url} ({err})"
+            )));
             return Ok(false);
         }
     };
 
     let Some(latest_build) = find_sde_build_number(&index_contents) else {
-        eprintln!("sde_index: couldn't determine the most recent build number in {index_url}");
+        observer.event(BuildEvent::Warning(format!(
+            "sde_index: couldn't determine the most recent build number in {index_url}"
+        )));
         return Ok(false);
     };
 
@@ -114,17 +188,43 @@ pub async fn update_as_needed(
         .map(|s| s.trim().to_string());
 
     if current_build.as_deref() == Some(latest_build.as_str()) && zip_file.exists() {
-        println!("sde_index: {variant} data already up to date (build {latest_build})");
+        observer.event(BuildEvent::Message(format!(
+            "sde_index: {variant} data already up to date (build {latest_build})"
+        )));
         return Ok(false);
     }
 
-    println!(
+    observer.event(BuildEvent::Message(format!(
         "sde_index: new build available ({} -> {latest_build}), downloading {variant} data",
         current_build.as_deref().unwrap_or("none")
-    );
+    )));
+
+    let temp_name = format!("sde-{variant}.zip.{latest_build}.tmp");
+    for entry in std::fs::read_dir(data_dir)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        if name != temp_name
+            && name.starts_with(&format!("sde-{variant}.zip."))
+            && name.ends_with(".tmp")
+        {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+    let temp_zip_file = data_dir.join(temp_name);
 
     let zip_url = format!("{sde_url_base}eve-online-static-data-{latest_build}-{variant}.zip");
-    http::download(client, &zip_url, &temp_zip_file, |_| {}).await?;
+    let expected = find_expected_download(&index_contents, variant);
+    http::download_resumable(
+        client,
+        &zip_url,
+        &temp_zip_file,
+        &expected,
+        retry,
+        observer,
+        cancellation,
+    )
+    .await?;
     std::fs::rename(&temp_zip_file, &zip_file)?;
     std::fs::write(&build_file, &latest_build)?;
 
@@ -134,6 +234,7 @@ pub async fn update_as_needed(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::builder::progress::Silent;
 
     #[test]
     fn find_sde_build_number_extracts_matching_key() {
@@ -174,6 +275,18 @@ mod tests {
         assert_eq!(find_sde_build_number(jsonl), Some("12345".to_string()));
     }
 
+    #[test]
+    fn find_sde_release_keeps_the_release_date() {
+        let jsonl = "{\"_key\": \"sde\", \"buildNumber\": 3458726, \"releaseDate\": \"2026-08-06T11:07:36Z\"}\r\n";
+        assert_eq!(
+            find_sde_release(jsonl),
+            Some(SdeRelease {
+                build_number: "3458726".to_string(),
+                release_date: Some("2026-08-06T11:07:36Z".to_string()),
+            })
+        );
+    }
+
     #[test]
     fn find_sde_build_number_handles_real_latest_jsonl() {
         // EXACT content of a real latest.jsonl (August 2026), with its
@@ -182,6 +295,23 @@ mod tests {
         assert_eq!(find_sde_build_number(jsonl), Some("3458726".to_string()));
     }
 
+    #[test]
+    fn find_expected_download_reads_the_variant_published_size_and_hash() {
+        let jsonl = "{\"_key\": \"sde\", \"buildNumber\": 1, \"files\": {\"jsonl\": \
+                     {\"size\": 11, \"sha256\": \"ab12\"}}}\n";
+        assert_eq!(
+            find_expected_download(jsonl, "jsonl"),
+            http::ExpectedDownload {
+                size: Some(11),
+                sha256: Some("ab12".to_string()),
+            }
+        );
+        assert_eq!(
+            find_expected_download(jsonl, "yaml"),
+            http::ExpectedDownload::default()
+        );
+    }
+
     fn temp_data_dir(name: &str) -> std::path::PathBuf {
         let dir =
             std::env::temp_dir().join(format!("sde-index-test-{name}-{}", std::process::id()));
@@ -214,16 +344,68 @@ mod tests {
         let data_dir = temp_data_dir("first_run");
         let base_url = format!("{}/", server.uri());
 
-        let changed = update_as_needed(&client, &data_dir, &base_url, "jsonl")
-            .await
-            .unwrap();
+        let changed = update_as_needed(
+            &client,
+            &data_dir,
+            &base_url,
+            "jsonl",
+            &http::RetryPolicy::default(),
+            &Silent,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
         assert!(changed);
 
         let build = std::fs::read_to_string(data_dir.join("sde-jsonl.build")).unwrap();
         assert_eq!(build, "123");
         let zip_contents = std::fs::read(data_dir.join("sde-jsonl.zip")).unwrap();
         assert_eq!(zip_contents, b"zip content");
-        assert!(!data_dir.join("sde-jsonl.zip.tmp").exists());
+        assert!(!data_dir.join("sde-jsonl.zip.123.tmp").exists());
+    }
+
+    #[tokio::test]
+    async fn update_as_needed_fetches_yaml_variant_independently() {
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/latest.jsonl"))
+            .respond_with(
+                wiremock::ResponseTemplate::new(200)
+                    .set_body_string("{\"_key\": \"sde\", \"buildNumber\": 123}\n"),
+            )
+            .mount(&server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path(
+                "/eve-online-static-data-123-yaml.zip",
+            ))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_bytes(b"yaml zip".to_vec()))
+            .mount(&server)
+            .await;
+
+        let client = http::build_client().unwrap();
+        let data_dir = temp_data_dir("yaml_variant");
+        // An up-to-date JSONL download doesn't count for the YAML variant.
+        std::fs::write(data_dir.join("sde-jsonl.build"), "123").unwrap();
+        let base_url = format!("{}/", server.uri());
+
+        let changed = update_as_needed(
+            &client,
+            &data_dir,
+            &base_url,
+            "yaml",
+            &http::RetryPolicy::default(),
+            &Silent,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+        assert!(changed);
+
+        let build = std::fs::read_to_string(data_dir.join("sde-yaml.build")).unwrap();
+        assert_eq!(build, "123");
+        let zip_contents = std::fs::read(data_dir.join("sde-yaml.zip")).unwrap();
+        assert_eq!(zip_contents, b"yaml zip");
     }
 
     #[tokio::test]
@@ -253,9 +435,17 @@ mod tests {
         std::fs::write(data_dir.join("sde-jsonl.zip"), b"previous zip").unwrap();
         let base_url = format!("{}/", server.uri());
 
-        let changed = update_as_needed(&client, &data_dir, &base_url, "jsonl")
-            .await
-            .unwrap();
+        let changed = update_as_needed(
+            &client,
+            &data_dir,
+            &base_url,
+            "jsonl",
+            &http::RetryPolicy::default(),
+            &Silent,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
         assert!(!changed);
 
         // The previous zip must not have been touched.
@@ -288,9 +478,17 @@ mod tests {
         std::fs::write(data_dir.join("sde-jsonl.zip"), b"old zip").unwrap();
         let base_url = format!("{}/", server.uri());
 
-        let changed = update_as_needed(&client, &data_dir, &base_url, "jsonl")
-            .await
-            .unwrap();
+        let changed = update_as_needed(
+            &client,
+            &data_dir,
+            &base_url,
+            "jsonl",
+            &http::RetryPolicy::default(),
+            &Silent,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
         assert!(changed);
 
         let build = std::fs::read_to_string(data_dir.join("sde-jsonl.build")).unwrap();
@@ -299,6 +497,65 @@ mod tests {
         assert_eq!(zip_contents, b"new zip");
     }
 
+    #[tokio::test]
+    async fn update_as_needed_resumes_a_partial_download_of_the_same_build() {
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/latest.jsonl"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(
+                "{\"_key\": \"sde\", \"buildNumber\": 789, \"files\": {\"jsonl\": \
+                 {\"size\": 11}}}\n",
+            ))
+            .mount(&server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path(
+                "/eve-online-static-data-789-jsonl.zip",
+            ))
+            .and(wiremock::matchers::header("Range", "bytes=4-"))
+            .respond_with(
+                wiremock::ResponseTemplate::new(206)
+                    .insert_header("Content-Range", "bytes 4-10/11")
+                    .set_body_bytes(b"content".to_vec()),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = http::build_client().unwrap();
+        let data_dir = temp_data_dir("resume");
+        std::fs::write(data_dir.join("sde-jsonl.zip.789.tmp"), b"zip ").unwrap();
+        std::fs::write(data_dir.join("sde-jsonl.zip.456.tmp"), b"older build").unwrap();
+        let base_url = format!("{}/", server.uri());
+
+        let progress = std::sync::Mutex::new(Vec::new());
+        let observer = |event| {
+            if let BuildEvent::Download { progress: p, .. } = event {
+                progress.lock().unwrap().push(p.downloaded_bytes);
+            }
+        };
+        let changed = update_as_needed(
+            &client,
+            &data_dir,
+            &base_url,
+            "jsonl",
+            &http::RetryPolicy::default(),
+            &observer,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
+        assert!(changed);
+        let progress = progress.into_inner().unwrap();
+
+        let zip_contents = std::fs::read(data_dir.join("sde-jsonl.zip")).unwrap();
+        assert_eq!(zip_contents, b"zip content");
+        assert_eq!(progress.first(), Some(&4));
+        assert_eq!(progress.last(), Some(&11));
+        assert!(!data_dir.join("sde-jsonl.zip.456.tmp").exists());
+        std::fs::remove_dir_all(&data_dir).ok();
+    }
+
     #[tokio::test]
     async fn update_as_needed_returns_false_when_index_unreachable() {
         let server = wiremock::MockServer::start().await;
@@ -312,9 +569,17 @@ mod tests {
         let data_dir = temp_data_dir("unreachable");
         let base_url = format!("{}/", server.uri());
 
-        let changed = update_as_needed(&client, &data_dir, &base_url, "jsonl")
-            .await
-            .unwrap();
+        let changed = update_as_needed(
+            &client,
+            &data_dir,
+            &base_url,
+            "jsonl",
+            &http::RetryPolicy::default(),
+            &Silent,
+            &CancellationToken::new(),
+        )
+        .await
+        .unwrap();
         assert!(!changed);
     }
 }
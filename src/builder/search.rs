@@ -0,0 +1,346 @@
+//! Full-text search index (`searchIndex`/`searchTrigrams`), read by
+//! `SdeManager::search` (see [`crate::search`] for how it's queried).
+//!
+//! Both tables are FTS5 virtual tables derived entirely from data already
+//! in the database -- same idea as `mapSystemConnections` being derived
+//! from `mapSystemGates` -- so they're created and filled in one go by
+//! [`create_search_index`], as the last phase of `Parser::parse_data`,
+//! rather than declared in `schema.sql`: FTS5 also creates shadow tables
+//! of its own (`searchIndex_data`, `searchIndex_idx`, ...), which would
+//! otherwise show up as undeclared tables next to the static schema.
+//!
+//! Every searchable name is indexed once from its base column
+//! (`languageId` `NULL`), plus once per language in `trnTranslations`
+//! whose text differs from it (no point indexing "Jita" eight times).
+//! Covered: solar systems, constellations, regions, published types, NPC
+//! corporations and NPC stations.
+//!
+//! ## Station names
+//!
+//! `npcStations.jsonl` carries no name at all -- the game composes it
+//! from other data, and [`station_name`] (in [`crate::search`], so the
+//! read side can compose it too) does the same: the system name
+//! and `celestialIndex` (the orbited planet) as a Roman numeral
+//! (`Jita IV`), `orbitIndex` if the station orbits a moon (`- Moon 4`),
+//! then the owner corporation's name, followed by the station
+//! operation's name when `useOperationName` is set (`- Caldari Navy
+//! Assembly Plant`). The base
+//! language is used throughout; stations get no per-language entries.
+
+use crate::search::SearchKind;
+pub use crate::search::station_name;
+use rusqlite::Connection;
+
+/// DDL for both FTS5 tables. `kind`/`entityId`/`languageId` are
+/// `UNINDEXED`: stored alongside each name so a hit can be resolved and
+/// filtered, but never matched against.
+///
+/// - `searchIndex`: `unicode61` with diacritics removed ("Ösköld"
+///   matches "oskold"), plus prefix indexes for 2- and 3-character
+///   prefixes, the lengths an omnibox queries most.
+/// - `searchTrigrams`: `trigram` tokenizer, the candidate source for
+///   typo-tolerant matching.
+pub const SEARCH_DDL: &str = "
+CREATE VIRTUAL TABLE searchIndex USING fts5(
+  name, kind UNINDEXED, entityId UNINDEXED, languageId UNINDEXED,
+  tokenize = 'unicode61 remove_diacritics 2', prefix = '2 3'
+);
+CREATE VIRTUAL TABLE searchTrigrams USING fts5(
+  name, kind UNINDEXED, entityId UNINDEXED, languageId UNINDEXED,
+  tokenize = 'trigram'
+);
+";
+
+/// (kind, table, name column, id column, extra `WHERE` condition) for
+/// every entity indexed straight from a base column.
+const INDEXED_COLUMNS: [(SearchKind, &str, &str, &str, &str); 5] = [
+    (
+        SearchKind::SolarSystem,
+        "mapSolarSystems",
+        "solarSystemName",
+        "solarSystemId",
+        "1",
+    ),
+    (
+        SearchKind::Constellation,
+        "mapConstellations",
+        "constellationName",
+        "constellationId",
+        "1",
+    ),
+    (
+        SearchKind::Region,
+        "mapRegions",
+        "regionName",
+        "regionId",
+        "1",
+    ),
+    (
+        SearchKind::Type,
+        "invTypes",
+        "typeName",
+        "typeId",
+        "base.published = 1",
+    ),
+    (
+        SearchKind::NpcCorporation,
+        "npcCorporations",
+        "corporationName",
+        "corporationId",
+        "1",
+    ),
+];
+
+/// Creates `searchIndex`/`searchTrigrams` ([`SEARCH_DDL`]) and fills them
+/// from the tables already populated on `connection`. Returns the number
+/// of names indexed (rows in `searchIndex`; `searchTrigrams` holds the
+/// same rows).
+///
+/// Same "no `IF NOT EXISTS`" stance as `schema::create_schema`: running
+/// it twice on the same database fails instead of indexing everything
+/// twice.
+#[tracing::instrument(skip(connection))]
+pub fn create_search_index(connection: &Connection) -> rusqlite::Result<usize> {
+    connection.execute_batch(SEARCH_DDL)?;
+
+    let mut count = 0usize;
+    for (kind, table, column, id_column, condition) in INDEXED_COLUMNS {
+        count += connection.execute(
+            &format!(
+                "INSERT INTO searchIndex (name, kind, entityId, languageId) \
+                 SELECT base.{column}, ?1, base.{id_column}, NULL FROM {table} AS base \
+                 WHERE {condition}"
+            ),
+            [kind.as_str()],
+        )?;
+        count += connection.execute(
+            &format!(
+                "INSERT INTO searchIndex (name, kind, entityId, languageId) \
+                 SELECT trn.text, ?1, trn.keyId, trn.languageId FROM trnTranslations AS trn \
+                 INNER JOIN {table} AS base ON (base.{id_column} = trn.keyId) \
+                 WHERE trn.tableName = ?2 AND trn.columnName = ?3 \
+                 AND trn.text <> base.{column} AND {condition}"
+            ),
+            [kind.as_str(), table, column],
+        )?;
+    }
+
+    count += index_stations(connection)?;
+
+    connection.execute(
+        "INSERT INTO searchTrigrams (name, kind, entityId, languageId) \
+         SELECT name, kind, entityId, languageId FROM searchIndex",
+        [],
+    )?;
+    Ok(count)
+}
+
+/// Indexes every `npcStations` row under the name [`station_name`]
+/// composes for it.
+fn index_stations(connection: &Connection) -> rusqlite::Result<usize> {
+    let mut select = connection.prepare(
+        "SELECT st.stationId, mss.solarSystemName, st.celestialIndex, st.orbitIndex, \
+         corp.corporationName, op.operationName, st.useOperationName \
+         FROM npcStations AS st \
+         INNER JOIN mapSolarSystems AS mss ON (mss.solarSystemId = st.solarSystemId) \
+         INNER JOIN npcCorporations AS corp ON (corp.corporationId = st.ownerId) \
+         INNER JOIN stationOperations AS op ON (op.operationId = st.operationId)",
+    )?;
+    let mut insert = connection.prepare(
+        "INSERT INTO searchIndex (name, kind, entityId, languageId) VALUES (?1, ?2, ?3, NULL)",
+    )?;
+    let mut rows = select.query([])?;
+    let mut count = 0usize;
+    while let Some(row) = rows.next()? {
+        let use_operation_name: bool = row.get(6)?;
+        let operation_name: String = row.get(5)?;
+        let name = station_name(
+            &row.get::<usize, String>(1)?,
+            row.get(2)?,
+            row.get(3)?,
+            &row.get::<usize, String>(4)?,
+            use_operation_name.then_some(operation_name.as_str()),
+        );
+        insert.execute(rusqlite::params![
+            name,
+            SearchKind::Station.as_str(),
+            row.get::<usize, i64>(0)?
+        ])?;
+        count += 1;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SdeManager;
+    use crate::search::MatchKind;
+
+    #[test]
+    fn create_search_index_names_stations() {
+        let connection = Connection::open_in_memory().unwrap();
+        crate::builder::schema::create_schema(&connection).unwrap();
+        connection
+            .execute_batch(
+                "
+                INSERT INTO mapSolarSystems (solarSystemId, solarSystemName, radius,
+                    centerX, centerY, centerZ, security)
+                    VALUES (30000142, 'Jita', 1, 0, 0, 0, 0.9);
+                INSERT INTO invCategories (categoryId, categoryName, published) VALUES (3, 'Station', 1);
+                INSERT INTO invGroups (groupId, groupName, categoryId, anchorable)
+                    VALUES (15, 'Station', 3, 0);
+                INSERT INTO invTypes (typeId, groupId, typeName, published)
+                    VALUES (1531, 15, 'Caldari Food Processing Plant Station', 0);
+                INSERT INTO npcCorporations (corporationId, corporationName, tickerName, deleted,
+                    extent, hasPlayerPersonnelManager, initialPrice, memberLimit, minSecurity,
+                    minimumJoinStanding, sendCharTerminationMessage, shares, size, taxRate,
+                    uniqueName)
+                    VALUES (1000035, 'Caldari Navy', 'CN', 0, 'G', 0, 0, -1, 0, 0, 1, 0, 'H', 0, 1);
+                INSERT INTO stationOperations (operationId, activityId, operationName, border,
+                    corridor, fringe, hub, ratio, manufacturingFactor, researchFactor)
+                    VALUES (26, 1, 'Assembly Plant', 0, 0, 0, 0, 0, 1, 1);
+                INSERT INTO npcStations (stationId, celestialIndex, operationId, orbitIndex,
+                    ownerId, positionX, positionY, positionZ, reprocessingEfficiency,
+                    reprocessingHangarFlag, reprocessingStationsTake, solarSystemId, typeId,
+                    useOperationName)
+                    VALUES (60003760, 4, 26, 4, 1000035, 0, 0, 0, 0.5, 4, 0.05, 30000142, 1531, 1);
+                ",
+            )
+            .unwrap();
+        create_search_index(&connection).unwrap();
+        let name: String = connection
+            .query_row(
+                "SELECT name FROM searchIndex WHERE kind = 'station' AND entityId = 60003760",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(name, "Jita IV - Moon 4 - Caldari Navy Assembly Plant");
+    }
+
+    /// A real (file-backed, `SdeManager` opens its own connections)
+    /// database with the full schema, a few map entities, one type and
+    /// one German translation, indexed by [`create_search_index`].
+    struct SearchFixture {
+        path: std::path::PathBuf,
+    }
+
+    impl SearchFixture {
+        fn new(test_name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "sde_search_test_{}_{}.db",
+                test_name,
+                std::process::id()
+            ));
+            let _ = std::fs::remove_file(&path);
+            let connection = Connection::open(&path).unwrap();
+            crate::builder::schema::create_schema(&connection).unwrap();
+            connection
+                .execute_batch(
+                    "
+                    INSERT INTO translationLanguages (languageId) VALUES ('en'), ('de');
+                    INSERT INTO mapRegions (regionId, regionName, nebula, centerX, centerY, centerZ)
+                        VALUES (10000002, 'The Forge', 1, 0, 0, 0);
+                    INSERT INTO mapConstellations (constellationId, constellationName, regionId,
+                        centerX, centerY, centerZ)
+                        VALUES (20000020, 'Kimotoro', 10000002, 0, 0, 0);
+                    INSERT INTO mapSolarSystems (solarSystemId, solarSystemName, constellationId,
+                        radius, centerX, centerY, centerZ, security)
+                        VALUES (30000142, 'Jita', 20000020, 1, 0, 0, 0, 0.9),
+                               (30000144, 'Perimeter', 20000020, 1, 0, 0, 0, 0.9);
+                    INSERT INTO invCategories (categoryId, categoryName, published) VALUES (6, 'Ship', 1);
+                    INSERT INTO invGroups (groupId, groupName, categoryId, anchorable)
+                        VALUES (25, 'Frigate', 6, 0);
+                    INSERT INTO invTypes (typeId, groupId, typeName, published)
+                        VALUES (587, 25, 'Rifter', 1), (588, 25, 'Reaper', 0);
+                    INSERT INTO trnTranslations (tableName, columnName, keyId, languageId, text)
+                        VALUES ('mapRegions', 'regionName', 10000002, 'de', 'Die Schmiede');
+                    ",
+                )
+                .unwrap();
+            create_search_index(&connection).unwrap();
+            Self { path }
+        }
+    }
+
+    impl Drop for SearchFixture {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    #[test]
+    fn create_search_index_indexes_base_names_and_differing_translations() {
+        let fixture = SearchFixture::new("index_counts");
+        let connection = Connection::open(&fixture.path).unwrap();
+        // 2 systems + 1 constellation + 1 region + 1 published type + 1
+        // German region name (the unpublished Reaper isn't indexed).
+        let total: i64 = connection
+            .query_row("SELECT COUNT(*) FROM searchIndex", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(total, 6);
+        let trigrams: i64 = connection
+            .query_row("SELECT COUNT(*) FROM searchTrigrams", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(trigrams, 6);
+    }
+
+    #[test]
+    fn search_matches_prefixes_across_kinds() {
+        let fixture = SearchFixture::new("prefix");
+        let manager = SdeManager::new(&fixture.path, 1.0);
+        let results = manager.search("ji", &[], 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].kind, SearchKind::SolarSystem);
+        assert_eq!(results[0].id, 30000142);
+        assert_eq!(results[0].match_kind, MatchKind::Prefix);
+
+        let results = manager.search("rifter", &[], 10).unwrap();
+        assert_eq!(results[0].kind, SearchKind::Type);
+        assert_eq!(results[0].match_kind, MatchKind::Exact);
+    }
+
+    #[test]
+    fn search_tolerates_typos() {
+        let fixture = SearchFixture::new("typo");
+        let manager = SdeManager::new(&fixture.path, 1.0);
+        let results = manager.search("jtia", &[], 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, 30000142);
+        assert_eq!(results[0].match_kind, MatchKind::Fuzzy);
+        // Long enough to go through searchTrigrams instead of a full scan.
+        let results = manager.search("kimottoro", &[], 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].kind, SearchKind::Constellation);
+    }
+
+    #[test]
+    fn search_filters_by_kind() {
+        let fixture = SearchFixture::new("kind");
+        let manager = SdeManager::new(&fixture.path, 1.0);
+        let results = manager
+            .search("kimotoro", &[SearchKind::SolarSystem], 10)
+            .unwrap();
+        assert!(results.is_empty());
+        let results = manager
+            .search("kimotoro", &[SearchKind::Constellation], 10)
+            .unwrap();
+        assert_eq!(results.len(), 1);
+    }
+
+    #[test]
+    fn search_is_language_aware() {
+        let fixture = SearchFixture::new("language");
+        let mut manager = SdeManager::new(&fixture.path, 1.0);
+        // English (the default) doesn't see the German name...
+        assert!(manager.search("schmiede", &[], 10).unwrap().is_empty());
+        // ...German does, and still finds base-column names.
+        manager.language = String::from("de");
+        let results = manager.search("schmiede", &[], 10).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].id, 10000002);
+        assert_eq!(results[0].language.as_deref(), Some("de"));
+        assert_eq!(manager.search("jita", &[], 10).unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,464 @@
+//! Integrity verification of an already-built database, for checking an
+//! `sde.db` before shipping it: [`verify_database`] runs a fixed list of
+//! checks against it and returns a [`VerifyReport`] telling which passed
+//! and, for each one that didn't, the offending ids.
+//!
+//! Two of the checks are SQLite's own -- `PRAGMA integrity_check` (the
+//! file itself) and `PRAGMA foreign_key_check` (every reference points
+//! at a row that exists, `DEFERRABLE` ones included). The rest are
+//! invariants of the data that no constraint in `schema.sql` can
+//! express:
+//!
+//! - every `mapSystemConnections` row is backed by two mutual gates,
+//!   one in each system, each pointing at the other
+//!   (`destinationGateId`/`destinationSystemId`);
+//! - every solar system belongs to an existing constellation;
+//! - every security status is within -1.0..=1.0 (the schema's `CHECK`
+//!   says so too, but a database written with
+//!   `ignore_check_constraints` wouldn't have enforced it);
+//! - every NPC station's orbit, when resolved, is a single moon or
+//!   planet in the station's own system;
+//! - every `mapAbstractSystems` row (third-party data) names a solar
+//!   system and a region that exist.
+//!
+//! Each check has a [`Severity`]: a failed [`Severity::Error`] check
+//! fails the whole report, a [`Severity::Warning`] one is only reported.
+//! Stations whose orbit resolved to neither a moon nor a planet are a
+//! warning -- the real SDE has one such station, and a build without
+//! moons (`ParserConfig.with_moons`) leaves every moon-orbiting station
+//! that way. A check whose table isn't in the database (no
+//! `mapAbstractSystems` without third-party data) is skipped.
+
+use crate::builder::BuilderError;
+use rusqlite::{Connection, OpenFlags};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Offending ids listed per failed check in [`VerifyReport::to_markdown`].
+const LISTED_OFFENDERS: usize = 50;
+
+/// Result of [`verify_database`]: one entry per check, in the order they
+/// ran.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifyReport {
+    pub checks: Vec<Check>,
+}
+
+/// One check of a [`VerifyReport`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Check {
+    /// Short identifier (`connections_backed_by_gates`).
+    pub name: &'static str,
+    /// What the check asserts, one sentence.
+    pub description: &'static str,
+    pub severity: Severity,
+    pub outcome: Outcome,
+}
+
+/// Whether a failed [`Check`] fails the whole [`VerifyReport`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// How a [`Check`] went.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outcome {
+    Passed,
+    /// The offending rows, one per entry, by id (`30000142`, a pair like
+    /// `30000142-30000144`, or a table and row for the SQLite checks).
+    Failed(Vec<String>),
+    /// Not run, and why.
+    Skipped(&'static str),
+}
+
+impl Check {
+    /// Whether the check ran and found nothing.
+    pub fn passed(&self) -> bool {
+        self.outcome == Outcome::Passed
+    }
+
+    /// The offending rows; empty unless [`Outcome::Failed`].
+    pub fn offenders(&self) -> &[String] {
+        match &self.outcome {
+            Outcome::Failed(offenders) => offenders,
+            _ => &[],
+        }
+    }
+}
+
+impl VerifyReport {
+    /// `false` as soon as one [`Severity::Error`] check failed; warnings
+    /// and skipped checks don't count.
+    pub fn passed(&self) -> bool {
+        self.checks
+            .iter()
+            .all(|check| check.severity == Severity::Warning || check.offenders().is_empty())
+    }
+
+    /// The check named `name`, if it's one of [`verify_database`]'s.
+    pub fn check(&self, name: &str) -> Option<&Check> {
+        self.checks.iter().find(|check| check.name == name)
+    }
+
+    /// The report as Markdown: the overall result, a summary table, then
+    /// the offending ids of every failed check (the first 50 of them,
+    /// the rest only counted).
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!(
+            "# SDE verification: {}\n\n",
+            if self.passed() {
+                "passed"
+            } else {
+                "**FAILED**"
+            }
+        );
+        out.push_str("| Check | Severity | Result |\n|---|---|---|\n");
+        for check in &self.checks {
+            let result = match &check.outcome {
+                Outcome::Passed => "passed".to_string(),
+                Outcome::Failed(offenders) => format!("**failed** ({})", offenders.len()),
+                Outcome::Skipped(reason) => format!("skipped: {reason}"),
+            };
+            let severity = match check.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            let _ = writeln!(out, "| `{}` | {severity} | {result} |", check.name);
+        }
+
+        for check in &self.checks {
+            let offenders = check.offenders();
+            if offenders.is_empty() {
+                continue;
+            }
+            let _ = write!(out, "\n## {}\n\n{}\n\n", check.name, check.description);
+            for offender in offenders.iter().take(LISTED_OFFENDERS) {
+                let _ = writeln!(out, "- {offender}");
+            }
+            if offenders.len() > LISTED_OFFENDERS {
+                let _ = writeln!(out, "- ... and {} more", offenders.len() - LISTED_OFFENDERS);
+            }
+        }
+        out
+    }
+}
+
+// ------------------------------------------------------------
+// Checks
+// ------------------------------------------------------------
+
+/// Opens the database at `path` read-only (a missing file is an error,
+/// never created) and runs [`verify_connection`] on it.
+#[tracing::instrument]
+pub fn verify_database(path: &Path) -> Result<VerifyReport, BuilderError> {
+    let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    verify_connection(&connection)
+}
+
+/// Runs every check (see the module docs) against `connection`. Failed
+/// checks are in the report, not an error: this only fails when a query
+/// does, e.g. on a database that isn't an SDE build at all.
+#[tracing::instrument(skip(connection))]
+pub fn verify_connection(connection: &Connection) -> Result<VerifyReport, BuilderError> {
+    let mut checks = Vec::new();
+
+    let integrity = connection
+        .prepare("PRAGMA integrity_check")?
+        .query_map([], |row| row.get::<usize, String>(0))?
+        .collect::<rusqlite::Result<Vec<_>>>()?
+        .into_iter()
+        .filter(|message| message != "ok")
+        .collect();
+    checks.push(Check {
+        name: "integrity",
+        description: "The database file passes SQLite's `PRAGMA integrity_check`.",
+        severity: Severity::Error,
+        outcome: outcome(integrity),
+    });
+
+    let foreign_keys = connection
+        .prepare("PRAGMA foreign_key_check")?
+        .query_map([], |row| {
+            let table: String = row.get(0)?;
+            let rowid: Option<i64> = row.get(1)?;
+            let parent: String = row.get(2)?;
+            Ok(match rowid {
+                Some(rowid) => format!("{table} row {rowid} -> {parent}"),
+                None => format!("{table} -> {parent}"),
+            })
+        })?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    checks.push(Check {
+        name: "foreign_keys",
+        description: "Every reference points at a row that exists (`PRAGMA foreign_key_check`).",
+        severity: Severity::Error,
+        outcome: outcome(foreign_keys),
+    });
+
+    checks.push(Check {
+        name: "connections_backed_by_gates",
+        description: "Every `mapSystemConnections` row has a gate in each system, each one's \
+                      destination being the other.",
+        severity: Severity::Error,
+        outcome: outcome(ids(
+            connection,
+            "SELECT c.systemA || '-' || c.systemB FROM mapSystemConnections AS c \
+            WHERE NOT EXISTS ( \
+                SELECT 1 FROM mapSystemGates AS ga \
+                INNER JOIN mapSystemGates AS gb ON gb.systemGateId = ga.destinationGateId \
+                WHERE ga.solarSystemId = c.systemA AND ga.destinationSystemId = c.systemB \
+                AND gb.solarSystemId = c.systemB AND gb.destinationSystemId = c.systemA \
+                AND gb.destinationGateId = ga.systemGateId) \
+            ORDER BY c.systemA, c.systemB",
+        )?),
+    });
+
+    checks.push(Check {
+        name: "systems_in_constellations",
+        description: "Every solar system belongs to a constellation that exists.",
+        severity: Severity::Error,
+        outcome: outcome(ids(
+            connection,
+            "SELECT s.solarSystemId FROM mapSolarSystems AS s \
+            LEFT JOIN mapConstellations AS c ON c.constellationId = s.constellationId \
+            WHERE c.constellationId IS NULL ORDER BY s.solarSystemId",
+        )?),
+    });
+
+    checks.push(Check {
+        name: "security_in_range",
+        description: "Every solar system's security status is between -1.0 and 1.0.",
+        severity: Severity::Error,
+        outcome: outcome(ids(
+            connection,
+            "SELECT solarSystemId || ' (' || security || ')' FROM mapSolarSystems \
+            WHERE NOT (security BETWEEN -1.0 AND 1.0) ORDER BY solarSystemId",
+        )?),
+    });
+
+    checks.push(Check {
+        name: "station_orbits",
+        description: "Every NPC station's resolved orbit is one moon or one planet, in the \
+                      station's own system.",
+        severity: Severity::Error,
+        outcome: outcome(ids(
+            connection,
+            "SELECT s.stationId FROM npcStations AS s \
+            LEFT JOIN mapMoons AS m ON m.moonId = s.orbitMoonId \
+            LEFT JOIN mapPlanets AS p ON p.planetId = s.orbitPlanetId \
+            WHERE (s.orbitMoonId IS NOT NULL AND s.orbitPlanetId IS NOT NULL) \
+            OR (s.orbitMoonId IS NOT NULL AND m.solarSystemId IS NOT s.solarSystemId) \
+            OR (s.orbitPlanetId IS NOT NULL AND p.solarSystemId IS NOT s.solarSystemId) \
+            ORDER BY s.stationId",
+        )?),
+    });
+
+    checks.push(Check {
+        name: "unresolved_station_orbits",
+        description: "NPC stations whose orbit is neither a moon nor a planet of the database \
+                      (one in the real SDE; every moon-orbiting one in a build without moons).",
+        severity: Severity::Warning,
+        outcome: outcome(ids(
+            connection,
+            "SELECT stationId FROM npcStations \
+            WHERE orbitMoonId IS NULL AND orbitPlanetId IS NULL ORDER BY stationId",
+        )?),
+    });
+
+    let has_abstract_systems: bool = connection.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master \
+        WHERE type = 'table' AND name = 'mapAbstractSystems')",
+        [],
+        |row| row.get(0),
+    )?;
+    checks.push(Check {
+        name: "abstract_systems",
+        description: "Every `mapAbstractSystems` row names a solar system and a region that \
+                      exist.",
+        severity: Severity::Error,
+        outcome: if has_abstract_systems {
+            outcome(ids(
+                connection,
+                "SELECT a.solarSystemId || ' in region ' || a.regionId \
+                FROM mapAbstractSystems AS a \
+                LEFT JOIN mapSolarSystems AS s ON s.solarSystemId = a.solarSystemId \
+                LEFT JOIN mapRegions AS r ON r.regionId = a.regionId \
+                WHERE s.solarSystemId IS NULL OR r.regionId IS NULL \
+                ORDER BY a.solarSystemId, a.regionId",
+            )?)
+        } else {
+            Outcome::Skipped("no third-party data")
+        },
+    });
+
+    Ok(VerifyReport { checks })
+}
+
+fn outcome(offenders: Vec<String>) -> Outcome {
+    if offenders.is_empty() {
+        Outcome::Passed
+    } else {
+        Outcome::Failed(offenders)
+    }
+}
+
+/// First column of every row `sql` returns, as text.
+fn ids(connection: &Connection, sql: &str) -> rusqlite::Result<Vec<String>> {
+    connection
+        .prepare(sql)?
+        .query_map([], |row| {
+            let value: rusqlite::types::Value = row.get(0)?;
+            Ok(match value {
+                rusqlite::types::Value::Integer(id) => id.to_string(),
+                rusqlite::types::Value::Text(text) => text,
+                other => format!("{other:?}"),
+            })
+        })?
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::schema::create_schema;
+
+    /// Two connected systems in one constellation, with their pair of
+    /// gates, and a station orbiting a planet of the first one.
+    const VALID: &str = "
+        INSERT INTO invCategories VALUES (6, 'Celestial', 1);
+        INSERT INTO invGroups VALUES (10, 'Stargate', 6, 0);
+        INSERT INTO invTypes VALUES (16, 10, NULL, 'Stargate', 1, NULL);
+        INSERT INTO races VALUES (1, 'Caldari');
+        INSERT INTO mapRegions (regionId, regionName, nebula, centerX, centerY, centerZ)
+            VALUES (10000001, 'Derelik', 0, 0, 0, 0);
+        INSERT INTO mapConstellations VALUES (20000001, 'San Matar', 10000001, 0, 0, 0);
+        INSERT INTO mapSolarSystems (solarSystemId, solarSystemName, constellationId, radius,
+            centerX, centerY, centerZ, security)
+            VALUES (30000001, 'Tanoo', 20000001, 1, 0, 0, 0, 0.86),
+                   (30000002, 'Lashesih', 20000001, 1, 1, 0, 0, 0.75);
+        INSERT INTO mapSystemGates VALUES
+            (50000001, 30000001, 50000002, 30000002, 16, 0, 0, 0),
+            (50000002, 30000002, 50000001, 30000001, 16, 0, 0, 0);
+        INSERT INTO mapSystemConnections VALUES (30000001, 30000002);
+        INSERT INTO mapPlanets (planetId, solarSystemId, planetaryIndex, typeId,
+            positionX, positionY, positionZ)
+            VALUES (40000001, 30000001, 1, 16, 0, 0, 0),
+                   (40000002, 30000002, 1, 16, 0, 0, 0);
+        INSERT INTO stationOperations (operationId, activityId, operationName, border,
+            corridor, fringe, hub, ratio, manufacturingFactor, researchFactor)
+            VALUES (1, 1, 'Trading Post', 0, 0, 0, 0, 0, 1, 1);
+        INSERT INTO npcCorporations (corporationId, corporationName, tickerName, deleted,
+            extent, hasPlayerPersonnelManager, initialPrice, memberLimit, minSecurity,
+            minimumJoinStanding, sendCharTerminationMessage, shares, size, taxRate, uniqueName)
+            VALUES (1000002, 'CBD Corporation', 'CBD', 0, 'N', 0, 0, 0, 0, 0, 0, 0, 'L', 0, 0);
+        INSERT INTO npcStations (stationId, operationId, orbitPlanetId, ownerId, positionX,
+            positionY, positionZ, reprocessingEfficiency, reprocessingHangarFlag,
+            reprocessingStationsTake, solarSystemId, typeId, useOperationName)
+            VALUES (60000001, 1, 40000001, 1000002, 0, 0, 0, 0.5, 4, 0.05, 30000001, 16, 1);
+    ";
+
+    fn database(sql: &str) -> Connection {
+        let connection = Connection::open_in_memory().unwrap();
+        create_schema(&connection).unwrap();
+        connection.execute_batch(VALID).unwrap();
+        connection.execute_batch(sql).unwrap();
+        connection
+    }
+
+    #[test]
+    fn verify_passes_a_consistent_database() {
+        let report = verify_connection(&database("")).unwrap();
+        assert!(report.passed(), "{}", report.to_markdown());
+        assert!(
+            report
+                .checks
+                .iter()
+                .filter(|check| check.name != "abstract_systems")
+                .all(Check::passed)
+        );
+        assert_eq!(
+            report.check("abstract_systems").unwrap().outcome,
+            Outcome::Skipped("no third-party data")
+        );
+        assert!(
+            report
+                .to_markdown()
+                .starts_with("# SDE verification: passed\n")
+        );
+    }
+
+    #[test]
+    fn verify_reports_the_offending_ids_of_each_broken_invariant() {
+        let connection = database(
+            "PRAGMA foreign_keys = OFF;
+            PRAGMA ignore_check_constraints = ON;
+            UPDATE mapSystemGates SET destinationGateId = 50000009 WHERE systemGateId = 50000002;
+            INSERT INTO mapSolarSystems (solarSystemId, solarSystemName, constellationId,
+                radius, centerX, centerY, centerZ, security)
+                VALUES (30000003, 'Lost', NULL, 1, 0, 0, 0, 1.5);
+            UPDATE npcStations SET orbitPlanetId = 40000002 WHERE stationId = 60000001;
+            INSERT INTO npcStations (stationId, operationId, ownerId, positionX, positionY,
+                positionZ, reprocessingEfficiency, reprocessingHangarFlag,
+                reprocessingStationsTake, solarSystemId, typeId, useOperationName)
+                VALUES (60000002, 1, 1000002, 0, 0, 0, 0.5, 4, 0.05, 30000002, 16, 1);
+            CREATE TABLE mapAbstractSystems (solarSystemId INTEGER NOT NULL,
+                regionId INTEGER NOT NULL, x REAL NOT NULL, y REAL NOT NULL);
+            INSERT INTO mapAbstractSystems VALUES (30000001, 10000001, 0, 0),
+                (30000099, 10000001, 0, 0);",
+        );
+        let report = verify_connection(&connection).unwrap();
+        assert!(!report.passed());
+
+        let offenders = |name: &str| report.check(name).unwrap().offenders().to_vec();
+        assert_eq!(
+            offenders("foreign_keys"),
+            vec!["mapSystemGates row 2 -> mapSystemGates"]
+        );
+        assert_eq!(
+            offenders("connections_backed_by_gates"),
+            vec!["30000001-30000002"]
+        );
+        assert_eq!(offenders("systems_in_constellations"), vec!["30000003"]);
+        assert_eq!(offenders("security_in_range"), vec!["30000003 (1.5)"]);
+        assert_eq!(offenders("station_orbits"), vec!["60000001"]);
+        assert_eq!(offenders("unresolved_station_orbits"), vec!["60000002"]);
+        assert_eq!(
+            offenders("abstract_systems"),
+            vec!["30000099 in region 10000001"]
+        );
+        assert!(report.check("integrity").unwrap().passed());
+
+        let markdown = report.to_markdown();
+        assert!(markdown.starts_with("# SDE verification: **FAILED**\n"));
+        assert!(markdown.contains("| `station_orbits` | error | **failed** (1) |"));
+        assert!(markdown.contains("\n## security_in_range\n"));
+        assert!(markdown.contains("- 30000003 (1.5)\n"));
+    }
+
+    #[test]
+    fn verify_only_fails_on_errors_not_warnings() {
+        let connection =
+            database("UPDATE npcStations SET orbitPlanetId = NULL WHERE stationId = 60000001;");
+        let report = verify_connection(&connection).unwrap();
+        assert!(report.passed());
+        assert_eq!(
+            report
+                .check("unresolved_station_orbits")
+                .unwrap()
+                .offenders(),
+            ["60000001"]
+        );
+    }
+
+    #[test]
+    fn verify_database_fails_on_a_missing_file() {
+        let missing = std::env::temp_dir().join("sde-verify-test-does-not-exist.db");
+        assert!(matches!(
+            verify_database(&missing),
+            Err(BuilderError::Sqlite(_))
+        ));
+        assert!(!missing.exists());
+    }
+}
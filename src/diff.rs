@@ -0,0 +1,820 @@
+//! Changelog between two builds of the database (feature `builder`):
+//! [`diff_databases`] compares an old and a new `sde.db` row by row and
+//! returns a [`Changelog`], rendered as JSON ([`Changelog::to_json`]) or
+//! Markdown ([`Changelog::to_markdown`]). `sde-builder diff old.db
+//! new.db` is the CLI front-end.
+//!
+//! The comparison is generic rather than one hand-written query per
+//! kind of change: every table `builder::schema::table_names` declares
+//! is matched up by its primary key (`pragma_table_info`), and each row
+//! is reported as added, removed, or changed with the columns that
+//! differ. A renamed system is a `mapSolarSystems` row whose
+//! `solarSystemName` changed, a new gate an added `mapSystemGates` row,
+//! an unpublished type an `invTypes` row whose `published` went from 1
+//! to 0, and so on -- a table added to the schema later is covered
+//! without touching this module. `sdeMetadata` is the exception: it's
+//! shown as each side's provenance (build number, release date, crate
+//! version, ...) at the top of the changelog instead of as row changes.
+//! `buildIssues` (the records a lenient build skipped) is left out too:
+//! it's about how a build went, not about the data.
+//!
+//! Both databases are only ever opened read-only; the old one is
+//! `ATTACH`ed to the new one's connection so every table is compared
+//! in SQL, without loading either side into memory.
+//!
+//! [`diff_databases`]: crate::diff::diff_databases
+//! [`Changelog`]: crate::diff::Changelog
+//! [`Changelog::to_json`]: crate::diff::Changelog::to_json
+//! [`Changelog::to_markdown`]: crate::diff::Changelog::to_markdown
+
+use crate::builder::schema;
+use crate::objects::SdeMetadata;
+use crate::{Error, SdeManager};
+use rusqlite::types::ValueRef;
+use rusqlite::{Connection, OpenFlags, Row};
+use serde_json::{Map, Value, json};
+use std::fmt::Write as _;
+use std::path::Path;
+
+/// Tables left out of the row comparison (see the module docs).
+const SKIPPED_TABLES: &[&str] = &["sdeMetadata", "buildIssues"];
+
+// ------------------------------------------------------------
+// Changelog
+// ------------------------------------------------------------
+
+/// Everything that differs between two databases, as returned by
+/// [`diff_databases`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Changelog {
+    /// Provenance of the old database; `None` when it predates the
+    /// `sdeMetadata` table.
+    pub old: Option<SdeMetadata>,
+    /// Provenance of the new database, same as [`Self::old`].
+    pub new: Option<SdeMetadata>,
+    /// One entry per table with at least one difference, in schema
+    /// order. Tables identical on both sides are left out.
+    pub tables: Vec<TableDiff>,
+}
+
+/// Differences within one table.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableDiff {
+    /// Name of the table.
+    pub table: String,
+    /// Which side doesn't have the table at all, if any -- then there's
+    /// nothing to compare row by row and every list below is empty.
+    pub missing_in: Option<Side>,
+    /// Columns rows are matched by: the primary key, or every common
+    /// column for a table without one.
+    pub key_columns: Vec<String>,
+    /// Columns both sides have, in the new database's order; what
+    /// [`DiffRow::values`] lines up with.
+    pub columns: Vec<String>,
+    /// Columns only the new database has.
+    pub columns_added: Vec<String>,
+    /// Columns only the old database has.
+    pub columns_removed: Vec<String>,
+    /// Rows only in the new database, by key.
+    pub added: Vec<DiffRow>,
+    /// Rows only in the old database, by key.
+    pub removed: Vec<DiffRow>,
+    /// Rows in both whose other columns differ, by key.
+    pub changed: Vec<ChangedRow>,
+}
+
+/// Which of the two databases.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// The first argument of [`diff_databases`].
+    Old,
+    /// The second one.
+    New,
+}
+
+/// An added or removed row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffRow {
+    /// Values of [`TableDiff::key_columns`].
+    pub key: Vec<Value>,
+    /// The row's name, when the table has a name column (e.g.
+    /// `solarSystemName`), to make the key readable.
+    pub label: Option<String>,
+    /// Values of [`TableDiff::columns`].
+    pub values: Vec<Value>,
+}
+
+/// A row present on both sides with different values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangedRow {
+    /// Values of [`TableDiff::key_columns`].
+    pub key: Vec<Value>,
+    /// The row's name in the new database, same as [`DiffRow::label`].
+    pub label: Option<String>,
+    /// Only the columns that differ.
+    pub changes: Vec<FieldChange>,
+}
+
+/// One column of a [`ChangedRow`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    /// Name of the column.
+    pub column: String,
+    /// Its value in the old database.
+    pub old: Value,
+    /// Its value in the new database.
+    pub new: Value,
+}
+
+impl TableDiff {
+    /// Whether nothing differs (same columns, same rows).
+    pub fn is_empty(&self) -> bool {
+        self.missing_in.is_none()
+            && self.columns_added.is_empty()
+            && self.columns_removed.is_empty()
+            && self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+    }
+}
+
+/// Output format of `sde-builder diff`, parsed from `json` or
+/// `markdown`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiffFormat {
+    /// [`Changelog::to_json`], pretty-printed.
+    Json,
+    /// [`Changelog::to_markdown`].
+    #[default]
+    Markdown,
+}
+
+impl std::str::FromStr for DiffFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "json" => Ok(DiffFormat::Json),
+            "markdown" | "md" => Ok(DiffFormat::Markdown),
+            other => Err(format!(
+                "unknown diff format `{other}` (expected `json` or `markdown`)"
+            )),
+        }
+    }
+}
+
+// ------------------------------------------------------------
+// Comparison
+// ------------------------------------------------------------
+
+/// Compares the database at `old` with the one at `new` (see the module
+/// docs for how). Fails if either can't be opened -- a missing file is
+/// an error, never created -- or a query fails.
+#[tracing::instrument]
+pub fn diff_databases(old: &Path, new: &Path) -> Result<Changelog, Error> {
+    // Read-only first: `SdeManager::metadata` would create a missing
+    // file rather than fail on it.
+    Connection::open_with_flags(old, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let connection = Connection::open_with_flags(new, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    let old_metadata = SdeManager::new(old, 1.0).metadata()?;
+    let new_metadata = SdeManager::new(new, 1.0).metadata()?;
+    connection.execute(
+        "ATTACH DATABASE ?1 AS old",
+        [old.to_string_lossy().as_ref()],
+    )?;
+
+    let mut tables = Vec::new();
+    for table in schema::table_names() {
+        if SKIPPED_TABLES.contains(&table) {
+            continue;
+        }
+        let table_diff = diff_table(&connection, table)?;
+        if !table_diff.is_empty() {
+            tables.push(table_diff);
+        }
+    }
+    Ok(Changelog {
+        old: old_metadata,
+        new: new_metadata,
+        tables,
+    })
+}
+
+/// `(name, position in the primary key or 0)` of every column of
+/// `schema.table`, in declaration order; empty when the table doesn't
+/// exist there.
+fn table_columns(
+    connection: &Connection,
+    schema: &str,
+    table: &str,
+) -> rusqlite::Result<Vec<(String, i64)>> {
+    let mut statement =
+        connection.prepare("SELECT name, pk FROM pragma_table_info(?1, ?2) ORDER BY cid")?;
+    statement
+        .query_map([table, schema], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect()
+}
+
+/// Double-quotes `name` as an SQL identifier.
+fn quoted(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+/// `value` as JSON: integers and reals as numbers, text as strings,
+/// blobs as lowercase hex strings.
+fn json_value(value: ValueRef<'_>) -> Value {
+    match value {
+        ValueRef::Null => Value::Null,
+        ValueRef::Integer(value) => json!(value),
+        ValueRef::Real(value) => json!(value),
+        ValueRef::Text(value) => Value::String(String::from_utf8_lossy(value).into_owned()),
+        ValueRef::Blob(value) => Value::String(value.iter().map(|b| format!("{b:02x}")).collect()),
+    }
+}
+
+/// Columns `first..first + count` of `row`, as JSON.
+fn json_values(row: &Row<'_>, first: usize, count: usize) -> rusqlite::Result<Vec<Value>> {
+    (first..first + count)
+        .map(|index| row.get_ref(index).map(json_value))
+        .collect()
+}
+
+/// The text of `values[index]`, when there's a name column and it holds
+/// text.
+fn label_of(values: &[Value], index: Option<usize>) -> Option<String> {
+    index
+        .and_then(|index| values.get(index))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+/// Compares `table` between the `old` and `main` schemas of
+/// `connection`.
+fn diff_table(connection: &Connection, table: &str) -> Result<TableDiff, Error> {
+    let old_columns = table_columns(connection, "old", table)?;
+    let new_columns = table_columns(connection, "main", table)?;
+    let mut table_diff = TableDiff {
+        table: table.to_string(),
+        missing_in: None,
+        key_columns: Vec::new(),
+        columns: Vec::new(),
+        columns_added: Vec::new(),
+        columns_removed: Vec::new(),
+        added: Vec::new(),
+        removed: Vec::new(),
+        changed: Vec::new(),
+    };
+    match (old_columns.is_empty(), new_columns.is_empty()) {
+        (true, true) => return Ok(table_diff),
+        (true, false) => {
+            table_diff.missing_in = Some(Side::Old);
+            return Ok(table_diff);
+        }
+        (false, true) => {
+            table_diff.missing_in = Some(Side::New);
+            return Ok(table_diff);
+        }
+        (false, false) => {}
+    }
+
+    let has_column =
+        |columns: &[(String, i64)], name: &str| columns.iter().any(|(column, _)| column == name);
+    for (name, _) in &new_columns {
+        if has_column(&old_columns, name) {
+            table_diff.columns.push(name.clone());
+        } else {
+            table_diff.columns_added.push(name.clone());
+        }
+    }
+    for (name, _) in &old_columns {
+        if !has_column(&new_columns, name) {
+            table_diff.columns_removed.push(name.clone());
+        }
+    }
+    let mut key_columns: Vec<&(String, i64)> = new_columns
+        .iter()
+        .filter(|(name, pk)| *pk > 0 && table_diff.columns.contains(name))
+        .collect();
+    key_columns.sort_by_key(|(_, pk)| *pk);
+    table_diff.key_columns = if key_columns.is_empty() {
+        table_diff.columns.clone()
+    } else {
+        key_columns
+            .into_iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    };
+    if table_diff.key_columns.is_empty() {
+        return Ok(table_diff);
+    }
+
+    let label_index = table_diff
+        .columns
+        .iter()
+        .position(|column| column.ends_with("Name") || column == "name");
+    let key_indexes: Vec<usize> = table_diff
+        .key_columns
+        .iter()
+        .filter_map(|key| table_diff.columns.iter().position(|column| column == key))
+        .collect();
+    let value_columns: Vec<&String> = table_diff
+        .columns
+        .iter()
+        .filter(|column| !table_diff.key_columns.contains(column))
+        .collect();
+
+    let table_sql = quoted(table);
+    let select = |alias: &str, columns: &mut dyn Iterator<Item = &String>| {
+        columns
+            .map(|column| format!("{alias}.{}", quoted(column)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    let join = table_diff
+        .key_columns
+        .iter()
+        .map(|key| format!("n.{0} IS o.{0}", quoted(key)))
+        .collect::<Vec<_>>()
+        .join(" AND ");
+    let order_by = |alias: &str| select(alias, &mut table_diff.key_columns.iter());
+
+    // Added and removed: the same anti-join, either way round.
+    for (side, this, other) in [(Side::New, "main", "old"), (Side::Old, "old", "main")] {
+        let (this_alias, other_alias) = match side {
+            Side::New => ("n", "o"),
+            Side::Old => ("o", "n"),
+        };
+        let sql = format!(
+            "SELECT {columns} FROM {this}.{table_sql} AS {this_alias} \
+             WHERE NOT EXISTS (SELECT 1 FROM {other}.{table_sql} AS {other_alias} WHERE {join}) \
+             ORDER BY {order}",
+            columns = select(this_alias, &mut table_diff.columns.iter()),
+            order = order_by(this_alias),
+        );
+        let count = table_diff.columns.len();
+        let mut statement = connection.prepare(&sql)?;
+        let rows = statement
+            .query_map([], |row| {
+                let values = json_values(row, 0, count)?;
+                Ok(DiffRow {
+                    key: key_indexes.iter().map(|&i| values[i].clone()).collect(),
+                    label: label_of(&values, label_index),
+                    values,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        match side {
+            Side::New => table_diff.added = rows,
+            Side::Old => table_diff.removed = rows,
+        }
+    }
+
+    // Changed: rows on both sides, any non-key column different.
+    if !value_columns.is_empty() {
+        let differs = value_columns
+            .iter()
+            .map(|column| format!("n.{0} IS NOT o.{0}", quoted(column)))
+            .collect::<Vec<_>>()
+            .join(" OR ");
+        let sql = format!(
+            "SELECT {keys}, {old_values}, {new_values} \
+             FROM main.{table_sql} AS n JOIN old.{table_sql} AS o ON {join} \
+             WHERE {differs} ORDER BY {order}",
+            keys = order_by("n"),
+            old_values = select("o", &mut value_columns.iter().copied()),
+            new_values = select("n", &mut value_columns.iter().copied()),
+            order = order_by("n"),
+        );
+        let key_count = table_diff.key_columns.len();
+        let value_count = value_columns.len();
+        let new_label_index = label_index.and_then(|index| {
+            value_columns
+                .iter()
+                .position(|c| **c == table_diff.columns[index])
+        });
+        let mut statement = connection.prepare(&sql)?;
+        table_diff.changed = statement
+            .query_map([], |row| {
+                let key = json_values(row, 0, key_count)?;
+                let old_values = json_values(row, key_count, value_count)?;
+                let new_values = json_values(row, key_count + value_count, value_count)?;
+                let changes = value_columns
+                    .iter()
+                    .zip(old_values.into_iter().zip(new_values.iter().cloned()))
+                    .filter(|(_, (old, new))| old != new)
+                    .map(|(column, (old, new))| FieldChange {
+                        column: (*column).clone(),
+                        old,
+                        new,
+                    })
+                    .collect();
+                Ok(ChangedRow {
+                    label: label_of(&new_values, new_label_index),
+                    key,
+                    changes,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+    }
+    Ok(table_diff)
+}
+
+// ------------------------------------------------------------
+// Rendering
+// ------------------------------------------------------------
+
+/// `metadata` as a JSON object (`null` without one), same member names
+/// as the `sdeMetadata` keys; the parser configuration and map
+/// fingerprints are embedded as JSON rather than as strings.
+fn provenance_json(metadata: Option<&SdeMetadata>) -> Value {
+    let Some(metadata) = metadata else {
+        return Value::Null;
+    };
+    let embedded = |value: &Option<String>| {
+        value.as_deref().map_or(Value::Null, |value| {
+            serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()))
+        })
+    };
+    json!({
+        SdeMetadata::BUILD_NUMBER: metadata.build_number,
+        SdeMetadata::RELEASE_DATE: metadata.release_date,
+        SdeMetadata::CRATE_VERSION: metadata.crate_version,
+        SdeMetadata::BASE_LANGUAGE: metadata.base_language,
+        SdeMetadata::LANGUAGES: metadata.languages,
+        SdeMetadata::WITH_THIRD_PARTY: metadata.with_third_party,
+        SdeMetadata::FORMAT: metadata.format,
+        SdeMetadata::PARSER_CONFIG: embedded(&metadata.parser_config),
+        SdeMetadata::MAP_FINGERPRINTS: embedded(&metadata.map_fingerprints),
+    })
+}
+
+/// Row headers of the Markdown provenance table, in
+/// [`provenance_cells`] order.
+const PROVENANCE_ROWS: [&str; 5] = [
+    "Build",
+    "Release date",
+    "Crate version",
+    "Languages",
+    "Third-party data",
+];
+
+/// One database's column of the Markdown provenance table: `unknown`
+/// for whatever it doesn't record.
+fn provenance_cells(metadata: Option<&SdeMetadata>) -> [String; 5] {
+    let unknown = || "unknown".to_string();
+    let Some(metadata) = metadata else {
+        return std::array::from_fn(|_| unknown());
+    };
+    [
+        metadata.build_number.clone().unwrap_or_else(unknown),
+        metadata.release_date.clone().unwrap_or_else(unknown),
+        metadata.crate_version.clone().unwrap_or_else(unknown),
+        metadata.languages.join(", "),
+        if metadata.with_third_party {
+            "yes"
+        } else {
+            "no"
+        }
+        .to_string(),
+    ]
+}
+
+/// A row's key as Markdown: the key values, comma-separated, then the
+/// label if there is one.
+fn markdown_key(key: &[Value], label: Option<&str>) -> String {
+    let key = key
+        .iter()
+        .map(markdown_value)
+        .collect::<Vec<_>>()
+        .join(", ");
+    match label {
+        Some(label) => format!("`{key}` {label}"),
+        None => format!("`{key}`"),
+    }
+}
+
+/// A value as Markdown text: strings unquoted, everything else as JSON.
+fn markdown_value(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        other => other.to_string(),
+    }
+}
+
+impl Changelog {
+    /// Whether the two databases have the same data (provenance aside).
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    /// The changelog as a JSON object: `old` and `new` provenance (see
+    /// [`SdeMetadata`]), then `tables`, one object per [`TableDiff`]
+    /// with its counts, and added/removed rows as `{column: value}`
+    /// objects.
+    pub fn to_json(&self) -> Value {
+        let tables = self
+            .tables
+            .iter()
+            .map(|table| {
+                let row_json = |row: &DiffRow| {
+                    let values: Map<String, Value> = table
+                        .columns
+                        .iter()
+                        .cloned()
+                        .zip(row.values.iter().cloned())
+                        .collect();
+                    json!({ "key": row.key, "label": row.label, "values": values })
+                };
+                json!({
+                    "table": table.table,
+                    "missingIn": table.missing_in.map(|side| match side {
+                        Side::Old => "old",
+                        Side::New => "new",
+                    }),
+                    "keyColumns": table.key_columns,
+                    "columnsAdded": table.columns_added,
+                    "columnsRemoved": table.columns_removed,
+                    "counts": {
+                        "added": table.added.len(),
+                        "removed": table.removed.len(),
+                        "changed": table.changed.len(),
+                    },
+                    "added": table.added.iter().map(row_json).collect::<Vec<_>>(),
+                    "removed": table.removed.iter().map(row_json).collect::<Vec<_>>(),
+                    "changed": table.changed.iter().map(|row| json!({
+                        "key": row.key,
+                        "label": row.label,
+                        "changes": row.changes.iter().map(|change| json!({
+                            "column": change.column,
+                            "old": change.old,
+                            "new": change.new,
+                        })).collect::<Vec<_>>(),
+                    })).collect::<Vec<_>>(),
+                })
+            })
+            .collect::<Vec<_>>();
+        json!({
+            "old": provenance_json(self.old.as_ref()),
+            "new": provenance_json(self.new.as_ref()),
+            "tables": tables,
+        })
+    }
+
+    /// The changelog as a Markdown document: a provenance table (old
+    /// vs. new), a summary table of counts per table, then one section
+    /// per table listing changed, added and removed rows by key (and
+    /// name). Added/removed rows' other values are only in the JSON.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("# SDE changelog\n\n");
+        out.push_str("| | Old | New |\n|---|---|---|\n");
+        let (old, new) = (
+            provenance_cells(self.old.as_ref()),
+            provenance_cells(self.new.as_ref()),
+        );
+        for ((name, old), new) in PROVENANCE_ROWS.iter().zip(old).zip(new) {
+            let _ = writeln!(out, "| {name} | {old} | {new} |");
+        }
+
+        if self.tables.is_empty() {
+            out.push_str("\nNo differences.\n");
+            return out;
+        }
+        out.push_str("\n## Summary\n\n| Table | Added | Removed | Changed |\n|---|---|---|---|\n");
+        for table in &self.tables {
+            let _ = writeln!(
+                out,
+                "| {} | {} | {} | {} |",
+                table.table,
+                table.added.len(),
+                table.removed.len(),
+                table.changed.len()
+            );
+        }
+
+        for table in &self.tables {
+            let _ = write!(out, "\n## {}\n\n", table.table);
+            match table.missing_in {
+                Some(Side::Old) => out.push_str("New table, not in the old database.\n"),
+                Some(Side::New) => out.push_str("Dropped, not in the new database.\n"),
+                None => {}
+            }
+            if !table.columns_added.is_empty() {
+                let _ = writeln!(out, "Columns added: {}", table.columns_added.join(", "));
+            }
+            if !table.columns_removed.is_empty() {
+                let _ = writeln!(out, "Columns removed: {}", table.columns_removed.join(", "));
+            }
+            if !table.changed.is_empty() {
+                out.push_str("\n### Changed\n\n");
+                for row in &table.changed {
+                    let changes = row
+                        .changes
+                        .iter()
+                        .map(|change| {
+                            format!(
+                                "{}: {} → {}",
+                                change.column,
+                                markdown_value(&change.old),
+                                markdown_value(&change.new)
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join("; ");
+                    let _ = writeln!(
+                        out,
+                        "- {} -- {changes}",
+                        markdown_key(&row.key, row.label.as_deref())
+                    );
+                }
+            }
+            for (heading, rows) in [("Added", &table.added), ("Removed", &table.removed)] {
+                if rows.is_empty() {
+                    continue;
+                }
+                let _ = write!(out, "\n### {heading}\n\n");
+                for row in rows {
+                    let _ = writeln!(out, "- {}", markdown_key(&row.key, row.label.as_deref()));
+                }
+            }
+        }
+        out
+    }
+
+    /// The changelog in `format`; JSON is pretty-printed.
+    pub fn render(&self, format: DiffFormat) -> String {
+        match format {
+            DiffFormat::Json => {
+                serde_json::to_string_pretty(&self.to_json()).unwrap_or_default() + "\n"
+            }
+            DiffFormat::Markdown => self.to_markdown(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A fresh database at a unique temporary path, with the schema and
+    /// `sql` applied.
+    fn database(name: &str, sql: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("sde-diff-test-{name}-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let connection = Connection::open(&path).unwrap();
+        schema::create_schema(&connection).unwrap();
+        connection.execute_batch(sql).unwrap();
+        path
+    }
+
+    const BASE: &str = "
+        INSERT INTO invCategories VALUES (6, 'Ship', 1), (7, 'Module', 1);
+        INSERT INTO invGroups VALUES (25, 'Frigate', 6, 0);
+        INSERT INTO translationLanguages VALUES ('en', 'English');
+        INSERT INTO trnTranslations VALUES ('invCategories', 'categoryName', 6, 'en', 'Ship');
+    ";
+
+    #[test]
+    fn diff_reports_added_removed_and_changed_rows_by_key() {
+        let old = database("rows-old", BASE);
+        let new = database(
+            "rows-new",
+            "INSERT INTO invCategories VALUES (6, 'Ships', 0), (8, 'Charge', 1);
+             INSERT INTO invGroups VALUES (25, 'Frigate', 6, 0);
+             INSERT INTO translationLanguages VALUES ('en', 'English');
+             INSERT INTO trnTranslations VALUES ('invCategories', 'categoryName', 6, 'en', 'Ship');",
+        );
+
+        let changelog = diff_databases(&old, &new).unwrap();
+        let names: Vec<&str> = changelog.tables.iter().map(|t| t.table.as_str()).collect();
+        assert_eq!(names, vec!["invCategories"]);
+        let categories = &changelog.tables[0];
+        assert_eq!(categories.key_columns, vec!["categoryId"]);
+        assert_eq!(categories.added.len(), 1);
+        assert_eq!(categories.added[0].key, vec![json!(8)]);
+        assert_eq!(categories.added[0].label.as_deref(), Some("Charge"));
+        assert_eq!(categories.removed[0].key, vec![json!(7)]);
+        assert_eq!(categories.removed[0].label.as_deref(), Some("Module"));
+        assert_eq!(
+            categories.changed,
+            vec![ChangedRow {
+                key: vec![json!(6)],
+                label: Some("Ships".to_string()),
+                changes: vec![
+                    FieldChange {
+                        column: "categoryName".to_string(),
+                        old: json!("Ship"),
+                        new: json!("Ships"),
+                    },
+                    FieldChange {
+                        column: "published".to_string(),
+                        old: json!(1),
+                        new: json!(0),
+                    },
+                ],
+            }]
+        );
+
+        let json = changelog.to_json();
+        assert_eq!(json["tables"][0]["counts"]["changed"], json!(1));
+        assert_eq!(
+            json["tables"][0]["added"][0]["values"]["categoryName"],
+            json!("Charge")
+        );
+        let markdown = changelog.to_markdown();
+        assert!(markdown.contains("| invCategories | 1 | 1 | 1 |"));
+        assert!(markdown.contains("- `6` Ships -- categoryName: Ship → Ships; published: 1 → 0"));
+        std::fs::remove_file(old).unwrap();
+        std::fs::remove_file(new).unwrap();
+    }
+
+    #[test]
+    fn diff_matches_composite_keys_and_ignores_identical_databases() {
+        let old = database("composite-old", BASE);
+        let same = database("composite-same", BASE);
+        let new = database(
+            "composite-new",
+            &format!(
+                "{BASE} INSERT INTO trnTranslations VALUES \
+                 ('invCategories', 'categoryName', 7, 'en', 'Module');"
+            ),
+        );
+
+        assert!(diff_databases(&old, &same).unwrap().is_empty());
+        let changelog = diff_databases(&old, &new).unwrap();
+        assert_eq!(changelog.tables.len(), 1);
+        let translations = &changelog.tables[0];
+        assert_eq!(
+            translations.key_columns,
+            vec!["tableName", "columnName", "keyId", "languageId"]
+        );
+        assert_eq!(
+            translations.added[0].key,
+            vec![
+                json!("invCategories"),
+                json!("categoryName"),
+                json!(7),
+                json!("en")
+            ]
+        );
+        assert!(translations.removed.is_empty() && translations.changed.is_empty());
+        for path in [old, same, new] {
+            std::fs::remove_file(path).unwrap();
+        }
+    }
+
+    #[test]
+    fn diff_reports_provenance_and_missing_tables() {
+        let old = database(
+            "provenance-old",
+            "INSERT INTO sdeMetadata VALUES ('buildNumber', '3458726');
+             DROP TABLE mapSolarSystemProjections;",
+        );
+        let new = database(
+            "provenance-new",
+            "INSERT INTO sdeMetadata VALUES ('buildNumber', '3460000');",
+        );
+
+        let changelog = diff_databases(&old, &new).unwrap();
+        assert_eq!(
+            changelog.old.as_ref().unwrap().build_number.as_deref(),
+            Some("3458726")
+        );
+        assert_eq!(
+            changelog.new.as_ref().unwrap().build_number.as_deref(),
+            Some("3460000")
+        );
+        // sdeMetadata differs, but is provenance, not a table change.
+        let names: Vec<&str> = changelog.tables.iter().map(|t| t.table.as_str()).collect();
+        assert_eq!(names, vec!["mapSolarSystemProjections"]);
+        assert_eq!(changelog.tables[0].missing_in, Some(Side::Old));
+        assert_eq!(changelog.to_json()["old"]["buildNumber"], json!("3458726"));
+        assert!(
+            changelog
+                .to_markdown()
+                .contains("| Build | 3458726 | 3460000 |")
+        );
+        std::fs::remove_file(old).unwrap();
+        std::fs::remove_file(new).unwrap();
+    }
+
+    #[test]
+    fn diff_fails_on_a_missing_database() {
+        let new = database("missing-new", "");
+        let missing = std::env::temp_dir().join("sde-diff-test-does-not-exist.db");
+        assert!(matches!(
+            diff_databases(&missing, &new),
+            Err(Error::Sqlite(_))
+        ));
+        assert!(!missing.exists());
+        std::fs::remove_file(new).unwrap();
+    }
+
+    #[test]
+    fn diff_format_parses_from_str() {
+        assert_eq!("json".parse::<DiffFormat>(), Ok(DiffFormat::Json));
+        assert_eq!("markdown".parse::<DiffFormat>(), Ok(DiffFormat::Markdown));
+        assert!("yaml".parse::<DiffFormat>().is_err());
+    }
+}
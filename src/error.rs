@@ -0,0 +1,81 @@
+//! The read side's error type, [`Error`] (re-exported as `sde::Error`),
+//! returned by every [`crate::SdeManager`] method.
+//!
+//! Deliberately without `thiserror`, same as `builder::BuilderError`:
+//! a hand-written enum with `Display`/`Error`/`From` impls, wrapping
+//! the underlying `rusqlite` error as-is where there is one. The other
+//! variants are the cases a caller can tell apart and act on -- a
+//! missing id, an optional table that isn't there, a database this
+//! version can't read -- instead of all of them surfacing as the same
+//! `rusqlite::Error` with a message to parse.
+
+/// Why a [`crate::SdeManager`] call failed.
+#[derive(Debug)]
+pub enum Error {
+    /// Any other SQLite failure: the file can't be opened or queried
+    /// (doesn't exist, isn't SQLite, is corrupt, ...), or a value
+    /// doesn't have the type its column should (a malformed row). The
+    /// message names the table/column involved.
+    Sqlite(rusqlite::Error),
+    /// What was asked for by id or name isn't in the database.
+    NotFound {
+        /// What was looked up (e.g. `"projection"`).
+        kind: &'static str,
+        /// The id or name it was looked up by.
+        key: String,
+    },
+    /// An optional layer the call needs wasn't built into this
+    /// database (see [`crate::SdeManager::optional_layers`]).
+    MissingLayer {
+        /// Name of the layer (e.g. `"abstract map"`).
+        layer: &'static str,
+        /// The table it lives in (e.g. `mapAbstractSystems`).
+        table: &'static str,
+    },
+    /// The database was stamped (`PRAGMA user_version`) by a newer
+    /// builder, with a schema this version doesn't know how to read.
+    UnsupportedVersion {
+        /// Version stamped in the database.
+        found: u32,
+        /// Newest version this crate reads, [`crate::SCHEMA_VERSION`].
+        supported: u32,
+    },
+    /// Tables every getter relies on aren't there -- not an SDE database,
+    /// or an incomplete one.
+    SchemaMismatch {
+        /// The missing tables, in [`crate::SdeManager::REQUIRED_TABLES`]
+        /// order.
+        missing_tables: Vec<String>,
+    },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Sqlite(err) => write!(f, "SQLite error: {err}"),
+            Error::NotFound { kind, key } => write!(f, "no {kind} {key} in the database"),
+            Error::MissingLayer { layer, table } => write!(
+                f,
+                "the database has no {layer} layer (table {table} is only built with \
+                 --with-third-party)"
+            ),
+            Error::UnsupportedVersion { found, supported } => write!(
+                f,
+                "database schema version {found} is newer than this crate supports ({supported})"
+            ),
+            Error::SchemaMismatch { missing_tables } => write!(
+                f,
+                "not an SDE database, missing tables: {}",
+                missing_tables.join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<rusqlite::Error> for Error {
+    fn from(err: rusqlite::Error) -> Self {
+        Error::Sqlite(err)
+    }
+}
@@ -0,0 +1,287 @@
+//! The universe map as GeoJSON (RFC 7946): one `Point` feature per solar
+//! system and one `LineString` per stargate connection, in a single
+//! `FeatureCollection` a web map can load as is.
+//!
+//! Positions come from [`crate::SdeManager::get_systems`] and
+//! [`crate::SdeManager::get_connections`] -- so the stored
+//! `position2DX`/`position2DY` or whatever `SdeManager.projection`
+//! computes, scaled by its `factor` -- or, with
+//! [`GeoJsonLayout::Abstract`], from the community abstract map
+//! (`mapAbstractSystems`). Either way they're planar map units, not
+//! longitude/latitude: draw them on a flat CRS (Leaflet's `CRS.Simple`,
+//! a MapLibre custom projection), and pick a `factor` that brings them
+//! into a range the map library is comfortable with. As with those
+//! getters, only K-space systems with at least one gate are included.
+//!
+//! System properties: `id`, `name`, `security`, `securityClass`,
+//! `constellationId`/`constellation`, `regionId`/`region`, `factionId`,
+//! plus one property per community layer the database was built with
+//! (`iceBelt`, `joveObservatory`, `specialOre`, `triglavianStatus`).
+//! Connection properties: `from`, `to` (system ids) and
+//! `crossRegion`, whether the gate leads into another region.
+//!
+//! [`GeoJsonLayout::Abstract`]: crate::export::geojson::GeoJsonLayout::Abstract
+
+use crate::objects::{SdePoint, SdeSegment};
+use crate::{Error, SdeManager};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+
+/// Which 2D positions the features are placed at.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GeoJsonLayout {
+    /// The canonical map: [`SdeManager::get_systems`] and
+    /// [`SdeManager::get_connections`].
+    #[default]
+    Position2d,
+    /// The abstract map: [`SdeManager::get_abstract_systems`] and
+    /// [`SdeManager::get_abstract_connections`]. Fails with
+    /// [`Error::MissingLayer`] against a database built without it.
+    Abstract,
+}
+
+impl std::str::FromStr for GeoJsonLayout {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "position2d" => Ok(GeoJsonLayout::Position2d),
+            "abstract" => Ok(GeoJsonLayout::Abstract),
+            other => Err(format!(
+                "unknown GeoJSON layout `{other}` (expected `position2d` or `abstract`)"
+            )),
+        }
+    }
+}
+
+/// The `FeatureCollection` for the database `sde` points at (see the
+/// module docs): systems first, then connections, each sorted by id.
+#[tracing::instrument(skip(sde))]
+pub fn export_geojson(sde: &SdeManager, layout: GeoJsonLayout) -> Result<Value, Error> {
+    let (systems, connections) = match layout {
+        GeoJsonLayout::Position2d => (sde.get_systems()?, sde.get_connections()?),
+        GeoJsonLayout::Abstract => (
+            sde.get_abstract_systems(Vec::new())?,
+            sde.get_abstract_connections(Vec::new())?,
+        ),
+    };
+    let layers = sde.optional_layers()?;
+    let details: HashMap<u32, _> = sde
+        .get_system_details(Vec::new())?
+        .into_iter()
+        .map(|system| (system.id, system))
+        .collect();
+    let flags = sde.get_community_flags(Vec::new())?;
+
+    let mut systems: Vec<(usize, SdePoint)> = systems.into_iter().collect();
+    systems.sort_by_key(|(id, _)| *id);
+    let mut connections: Vec<SdeSegment> = connections.into_values().collect();
+    connections.sort_by_key(|segment| segment.id);
+
+    let mut features = Vec::with_capacity(systems.len() + connections.len());
+    // Ids that don't fit a `u32` have no details: they'd be no SDE id.
+    let system_id = |id: usize| u32::try_from(id).ok();
+    for (id, point) in systems {
+        let mut properties = Map::new();
+        properties.insert("id".to_string(), json!(id));
+        properties.insert("name".to_string(), json!(point.name));
+        if let Some(system) = system_id(id).and_then(|id| details.get(&id)) {
+            properties.insert("security".to_string(), json!(system.security));
+            properties.insert("securityClass".to_string(), json!(system.security_class));
+            properties.insert(
+                "constellationId".to_string(),
+                json!(system.constellation_id),
+            );
+            properties.insert(
+                "constellation".to_string(),
+                json!(system.constellation_name),
+            );
+            properties.insert("regionId".to_string(), json!(system.region_id));
+            properties.insert("region".to_string(), json!(system.region_name));
+            properties.insert("factionId".to_string(), json!(system.faction_id));
+        }
+        if let Some(system_flags) = system_id(id).and_then(|id| flags.get(&id)) {
+            if layers.ice_belt {
+                properties.insert("iceBelt".to_string(), json!(system_flags.ice_belt));
+            }
+            if layers.jove_observatory {
+                properties.insert(
+                    "joveObservatory".to_string(),
+                    json!(system_flags.jove_observatory),
+                );
+            }
+            if layers.special_ore {
+                properties.insert("specialOre".to_string(), json!(system_flags.special_ore));
+            }
+            if layers.triglavian_status {
+                properties.insert(
+                    "triglavianStatus".to_string(),
+                    json!(system_flags.triglavian_status),
+                );
+            }
+        }
+        features.push(json!({
+            "type": "Feature",
+            "id": id,
+            "geometry": {"type": "Point", "coordinates": [point.coords[0], point.coords[1]]},
+            "properties": properties,
+        }));
+    }
+    for segment in connections {
+        let (from, to) = segment.id;
+        let region = |id: usize| {
+            system_id(id)
+                .and_then(|id| details.get(&id))
+                .map(|system| system.region_id)
+        };
+        features.push(json!({
+            "type": "Feature",
+            "geometry": {"type": "LineString", "coordinates": [segment.point1, segment.point2]},
+            "properties": {
+                "from": from,
+                "to": to,
+                "crossRegion": region(from) != region(to),
+            },
+        }));
+    }
+    Ok(json!({"type": "FeatureCollection", "features": features}))
+}
+
+// The fixtures are written with `builder::schema`.
+#[cfg(all(test, feature = "builder"))]
+mod tests {
+    use super::*;
+    use crate::builder::{community, schema};
+    use rusqlite::Connection;
+    use std::path::PathBuf;
+
+    /// A fresh database at a unique temporary path: two regions with a
+    /// system each, a gate between them and a third system without
+    /// gates.
+    fn database(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("sde-geojson-test-{name}-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let connection = Connection::open(&path).unwrap();
+        schema::create_schema(&connection).unwrap();
+        connection
+            .execute_batch(
+                "
+                INSERT INTO mapRegions (regionId, regionName, nebula, centerX, centerY, centerZ)
+                    VALUES (10000002, 'The Forge', 1, 0, 0, 0),
+                           (10000043, 'Domain', 1, 0, 0, 0);
+                INSERT INTO mapConstellations (constellationId, constellationName, regionId,
+                    centerX, centerY, centerZ)
+                    VALUES (20000020, 'Kimotoro', 10000002, 0, 0, 0),
+                           (20000322, 'Throne Worlds', 10000043, 0, 0, 0);
+                INSERT INTO mapSolarSystems (solarSystemId, solarSystemName, constellationId,
+                    radius, centerX, centerY, centerZ, position2DX, position2DY, security,
+                    securityClass, factionId)
+                    VALUES (30000142, 'Jita', 20000020, 1, 0, 0, 0, 10, 20, 0.9, 'B', NULL),
+                           (30002187, 'Amarr', 20000322, 1, 0, 0, 0, 30, 40, 1.0, 'A', NULL),
+                           (30000144, 'Perimeter', 20000020, 1, 0, 0, 0, 50, 60, 0.9, 'B', NULL);
+                INSERT INTO mapSystemConnections VALUES (30000142, 30002187);
+                ",
+            )
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn layout_parses_its_names() {
+        assert_eq!("position2d".parse(), Ok(GeoJsonLayout::Position2d));
+        assert_eq!("abstract".parse(), Ok(GeoJsonLayout::Abstract));
+        assert!("mercator".parse::<GeoJsonLayout>().is_err());
+    }
+
+    #[test]
+    fn systems_are_points_and_gates_are_line_strings() {
+        let path = database("canonical");
+        let mut sde = SdeManager::new(&path, 1.0);
+        sde.invert_coordinates = false;
+
+        let collection = export_geojson(&sde, GeoJsonLayout::Position2d).unwrap();
+
+        assert_eq!(collection["type"], "FeatureCollection");
+        let features = collection["features"].as_array().unwrap();
+        assert_eq!(features.len(), 3);
+        assert_eq!(features[0]["id"], 30000142);
+        assert_eq!(
+            features[0]["geometry"],
+            json!({"type": "Point", "coordinates": [10.0, 20.0]})
+        );
+        let properties = &features[0]["properties"];
+        assert_eq!(properties["name"], "Jita");
+        assert_eq!(properties["security"], 0.9);
+        assert_eq!(properties["region"], "The Forge");
+        assert_eq!(properties["constellation"], "Kimotoro");
+        assert_eq!(properties["factionId"], Value::Null);
+        assert!(properties.get("iceBelt").is_none());
+        assert_eq!(features[1]["properties"]["name"], "Amarr");
+        assert_eq!(
+            features[2]["geometry"],
+            json!({"type": "LineString", "coordinates": [[10.0, 20.0], [30.0, 40.0]]})
+        );
+        assert_eq!(
+            features[2]["properties"],
+            json!({"from": 30000142, "to": 30002187, "crossRegion": true})
+        );
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn community_flags_follow_the_layers_built_in() {
+        let path = database("flags");
+        let connection = Connection::open(&path).unwrap();
+        community::create_icebelts(&connection).unwrap();
+        community::setup_triglavian_status(&connection).unwrap();
+        connection
+            .execute_batch(
+                "UPDATE mapSolarSystems SET iceBelt = 1 WHERE solarSystemId = 30000142;
+                 UPDATE mapSolarSystems SET trigStatusID = 3 WHERE solarSystemId = 30002187;",
+            )
+            .unwrap();
+        drop(connection);
+        let sde = SdeManager::new(&path, 1.0);
+
+        let collection = export_geojson(&sde, GeoJsonLayout::Position2d).unwrap();
+
+        let features = collection["features"].as_array().unwrap();
+        assert_eq!(features[0]["properties"]["iceBelt"], true);
+        assert_eq!(features[0]["properties"]["triglavianStatus"], Value::Null);
+        assert_eq!(features[1]["properties"]["iceBelt"], false);
+        assert_eq!(features[1]["properties"]["triglavianStatus"], "Fortress");
+        assert!(features[0]["properties"].get("joveObservatory").is_none());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn abstract_layout_reads_the_abstract_map() {
+        let path = database("abstract");
+        let sde = SdeManager::new(&path, 1.0);
+        assert!(matches!(
+            export_geojson(&sde, GeoJsonLayout::Abstract),
+            Err(Error::MissingLayer { .. })
+        ));
+
+        let connection = Connection::open(&path).unwrap();
+        community::create_abstract_map(&connection).unwrap();
+        connection
+            .execute_batch(
+                "INSERT INTO mapAbstractSystems (solarSystemId, regionId, x, y)
+                    VALUES (30000142, 10000002, 1, 2), (30002187, 10000043, 3, 4);",
+            )
+            .unwrap();
+        drop(connection);
+
+        let collection = export_geojson(&sde, GeoJsonLayout::Abstract).unwrap();
+
+        let features = collection["features"].as_array().unwrap();
+        assert_eq!(features[0]["geometry"]["coordinates"], json!([1.0, 2.0]));
+        assert_eq!(
+            features[2]["geometry"]["coordinates"],
+            json!([[1.0, 2.0], [3.0, 4.0]])
+        );
+        std::fs::remove_file(&path).ok();
+    }
+}
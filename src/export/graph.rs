@@ -0,0 +1,416 @@
+//! The stargate network as a graph for network-analysis tools
+//! (networkx, Gephi, Graphviz): [`jump_graph`] reads every solar system
+//! and `mapSystemConnections` pair through the read API
+//! ([`crate::SdeManager::get_system_details`], positions from
+//! [`crate::SdeManager::get_systems`]), optionally narrowed by a
+//! [`GraphFilter`], and [`JumpGraph::render`] writes it as GraphML, DOT,
+//! a CSV edge list or the matching CSV node table ([`GraphFormat`]).
+//!
+//! The graph is undirected, one edge per connected pair. Node
+//! attributes: `name`, `security`, `regionId`, `region`, and `x`/`y`,
+//! the system's 2D map position (scaled like
+//! [`crate::SdeManager::get_systems`]' points) -- absent for a system
+//! that has none there, e.g. a wormhole system. A filter keeps only the
+//! edges with both ends inside it.
+//!
+//! [`jump_graph`]: crate::export::graph::jump_graph
+//! [`GraphFilter`]: crate::export::graph::GraphFilter
+//! [`JumpGraph::render`]: crate::export::graph::JumpGraph::render
+//! [`GraphFormat`]: crate::export::graph::GraphFormat
+
+use crate::{Error, SdeManager};
+use std::collections::HashSet;
+use std::fmt::Write as _;
+
+/// What [`JumpGraph::render`] writes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// [`JumpGraph::to_graphml`].
+    #[default]
+    GraphMl,
+    /// [`JumpGraph::to_dot`].
+    Dot,
+    /// [`JumpGraph::to_csv`].
+    Csv,
+    /// [`JumpGraph::nodes_to_csv`].
+    CsvNodes,
+}
+
+impl std::str::FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "graphml" => Ok(GraphFormat::GraphMl),
+            "dot" => Ok(GraphFormat::Dot),
+            "csv" => Ok(GraphFormat::Csv),
+            "csv-nodes" => Ok(GraphFormat::CsvNodes),
+            other => Err(format!(
+                "unknown graph format `{other}` (expected `graphml`, `dot`, `csv` or `csv-nodes`)"
+            )),
+        }
+    }
+}
+
+/// Which systems [`jump_graph`] keeps. The default keeps them all.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GraphFilter {
+    /// Only systems in these regions; empty means every region.
+    pub regions: Vec<u32>,
+    /// Only systems with at least this security status.
+    pub min_security: Option<f64>,
+    /// Only systems with at most this security status.
+    pub max_security: Option<f64>,
+}
+
+impl GraphFilter {
+    fn keeps(&self, region_id: u32, security: f64) -> bool {
+        (self.regions.is_empty() || self.regions.contains(&region_id))
+            && self.min_security.is_none_or(|min| security >= min)
+            && self.max_security.is_none_or(|max| security <= max)
+    }
+}
+
+/// A solar system, as a node of the [`JumpGraph`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphNode {
+    pub id: u32,
+    pub name: String,
+    pub security: f64,
+    pub region_id: u32,
+    pub region: String,
+    /// 2D map position, when the system has one.
+    pub position: Option<[f64; 2]>,
+}
+
+/// The stargate network, as returned by [`jump_graph`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JumpGraph {
+    /// Sorted by id.
+    pub nodes: Vec<GraphNode>,
+    /// `(lower id, higher id)` pairs, sorted.
+    pub edges: Vec<(u32, u32)>,
+}
+
+/// The jump graph of the database `sde` points at, narrowed by `filter`
+/// (see the module docs). Names are in `sde.language`.
+#[tracing::instrument(skip(sde))]
+pub fn jump_graph(sde: &SdeManager, filter: &GraphFilter) -> Result<JumpGraph, Error> {
+    let positions = sde.get_systems()?;
+    let mut nodes = Vec::new();
+    let mut kept = HashSet::new();
+    let mut connections = Vec::new();
+    for system in sde.get_system_details(Vec::new())? {
+        if !filter.keeps(system.region_id, system.security) {
+            continue;
+        }
+        kept.insert(system.id);
+        connections.extend(
+            system
+                .connections
+                .iter()
+                .filter(|&&other| system.id < other)
+                .map(|&other| (system.id, other)),
+        );
+        nodes.push(GraphNode {
+            id: system.id,
+            position: usize::try_from(system.id)
+                .ok()
+                .and_then(|id| positions.get(&id))
+                .map(|point| [point.coords[0], point.coords[1]]),
+            name: system.name,
+            security: system.security,
+            region_id: system.region_id,
+            region: system.region_name,
+        });
+    }
+    nodes.sort_by_key(|node| node.id);
+    let mut edges: Vec<(u32, u32)> = connections
+        .into_iter()
+        .filter(|(_, other)| kept.contains(other))
+        .collect();
+    edges.sort_unstable();
+    Ok(JumpGraph { nodes, edges })
+}
+
+impl JumpGraph {
+    /// GraphML, every node attribute declared as a `<key>` (read by
+    /// networkx's `read_graphml` and Gephi alike).
+    pub fn to_graphml(&self) -> String {
+        let mut xml = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n\
+             \x20 <key id=\"name\" for=\"node\" attr.name=\"name\" attr.type=\"string\"/>\n\
+             \x20 <key id=\"security\" for=\"node\" attr.name=\"security\" attr.type=\"double\"/>\n\
+             \x20 <key id=\"regionId\" for=\"node\" attr.name=\"regionId\" attr.type=\"long\"/>\n\
+             \x20 <key id=\"region\" for=\"node\" attr.name=\"region\" attr.type=\"string\"/>\n\
+             \x20 <key id=\"x\" for=\"node\" attr.name=\"x\" attr.type=\"double\"/>\n\
+             \x20 <key id=\"y\" for=\"node\" attr.name=\"y\" attr.type=\"double\"/>\n\
+             \x20 <graph id=\"jumps\" edgedefault=\"undirected\">\n",
+        );
+        for node in &self.nodes {
+            let _ = writeln!(xml, "    <node id=\"{}\">", node.id);
+            let _ = writeln!(
+                xml,
+                "      <data key=\"name\">{}</data>",
+                xml_escape(&node.name)
+            );
+            let _ = writeln!(xml, "      <data key=\"security\">{}</data>", node.security);
+            let _ = writeln!(
+                xml,
+                "      <data key=\"regionId\">{}</data>",
+                node.region_id
+            );
+            let _ = writeln!(
+                xml,
+                "      <data key=\"region\">{}</data>",
+                xml_escape(&node.region)
+            );
+            if let Some([x, y]) = node.position {
+                let _ = writeln!(xml, "      <data key=\"x\">{x}</data>");
+                let _ = writeln!(xml, "      <data key=\"y\">{y}</data>");
+            }
+            xml += "    </node>\n";
+        }
+        for (from, to) in &self.edges {
+            let _ = writeln!(xml, "    <edge source=\"{from}\" target=\"{to}\"/>");
+        }
+        xml += "  </graph>\n</graphml>\n";
+        xml
+    }
+
+    /// Graphviz DOT: an undirected `graph`, nodes labelled with their
+    /// name, positions as `pos` (honored by `neato -n`).
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph jumps {\n");
+        for node in &self.nodes {
+            let _ = write!(
+                dot,
+                "  {} [label={}, security={}, regionId={}, region={}",
+                node.id,
+                dot_quote(&node.name),
+                node.security,
+                node.region_id,
+                dot_quote(&node.region)
+            );
+            if let Some([x, y]) = node.position {
+                let _ = write!(dot, ", pos=\"{x},{y}\"");
+            }
+            dot += "];\n";
+        }
+        for (from, to) in &self.edges {
+            let _ = writeln!(dot, "  {from} -- {to};");
+        }
+        dot += "}\n";
+        dot
+    }
+
+    /// A `source,target` edge list of system ids, with a header line.
+    /// The nodes' attributes are in [`JumpGraph::nodes_to_csv`], keyed
+    /// by the same ids -- the two files Gephi's spreadsheet import and
+    /// networkx (`from_pandas_edgelist` plus `set_node_attributes`)
+    /// expect.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("source,target\r\n");
+        for (from, to) in &self.edges {
+            let _ = write!(csv, "{from},{to}\r\n");
+        }
+        csv
+    }
+
+    /// One `id,name,security,regionId,region,x,y` line per node, with a
+    /// header line; `x` and `y` are empty for a system without a 2D
+    /// position. Every node is in it, including those no edge touches.
+    pub fn nodes_to_csv(&self) -> String {
+        let mut csv = String::from("id,name,security,regionId,region,x,y\r\n");
+        for node in &self.nodes {
+            let (x, y) = node
+                .position
+                .map(|[x, y]| (x.to_string(), y.to_string()))
+                .unwrap_or_default();
+            let _ = write!(
+                csv,
+                "{},{},{},{},{},{x},{y}\r\n",
+                node.id,
+                csv_field(&node.name),
+                node.security,
+                node.region_id,
+                csv_field(&node.region)
+            );
+        }
+        csv
+    }
+
+    /// The graph in `format`.
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::GraphMl => self.to_graphml(),
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Csv => self.to_csv(),
+            GraphFormat::CsvNodes => self.nodes_to_csv(),
+        }
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// `text` as a CSV field, quoted when it holds a separator or a quote.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// `text` as a DOT double-quoted string.
+fn dot_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+// The fixtures are written with `builder::schema`.
+#[cfg(all(test, feature = "builder"))]
+mod tests {
+    use super::*;
+    use crate::builder::schema;
+    use rusqlite::Connection;
+    use std::path::PathBuf;
+
+    /// A fresh database at a unique temporary path: Jita and Perimeter
+    /// (high-sec, The Forge) and Tama (low-sec, Black Rise) in a chain,
+    /// plus an isolated wormhole system without a 2D position.
+    fn database(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("sde-graph-test-{name}-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let connection = Connection::open(&path).unwrap();
+        schema::create_schema(&connection).unwrap();
+        connection
+            .execute_batch(
+                "
+                INSERT INTO mapRegions (regionId, regionName, nebula, centerX, centerY, centerZ)
+                    VALUES (10000002, 'The Forge', 1, 0, 0, 0),
+                           (10000069, 'Black Rise', 1, 0, 0, 0),
+                           (11000001, 'A-R00001', 1, 0, 0, 0);
+                INSERT INTO mapConstellations (constellationId, constellationName, regionId,
+                    centerX, centerY, centerZ)
+                    VALUES (20000020, 'Kimotoro', 10000002, 0, 0, 0),
+                           (20000795, 'Kurala', 10000069, 0, 0, 0),
+                           (21000001, 'A-C00001', 11000001, 0, 0, 0);
+                INSERT INTO mapSolarSystems (solarSystemId, solarSystemName, constellationId,
+                    radius, centerX, centerY, centerZ, position2DX, position2DY, security)
+                    VALUES (30000142, 'Jita', 20000020, 1, 0, 0, 0, 10, 20, 0.95),
+                           (30000144, 'Perimeter', 20000020, 1, 0, 0, 0, 30, 40, 0.9),
+                           (30002813, 'Tama', 20000795, 1, 0, 0, 0, 50, 60, 0.3),
+                           (31000005, 'Thera <&>', 21000001, 1, 0, 0, 0, NULL, NULL, -1.0);
+                INSERT INTO mapSystemConnections VALUES
+                    (30000142, 30000144), (30000144, 30002813);
+                ",
+            )
+            .unwrap();
+        path
+    }
+
+    fn manager(path: &std::path::Path) -> SdeManager<'_> {
+        let mut sde = SdeManager::new(path, 1.0);
+        sde.invert_coordinates = false;
+        sde
+    }
+
+    #[test]
+    fn graph_format_parses_its_names() {
+        assert_eq!("graphml".parse(), Ok(GraphFormat::GraphMl));
+        assert_eq!("dot".parse(), Ok(GraphFormat::Dot));
+        assert_eq!("csv".parse(), Ok(GraphFormat::Csv));
+        assert_eq!("csv-nodes".parse(), Ok(GraphFormat::CsvNodes));
+        assert!("gexf".parse::<GraphFormat>().is_err());
+    }
+
+    #[test]
+    fn jump_graph_has_every_system_and_each_connection_once() {
+        let path = database("all");
+        let graph = jump_graph(&manager(&path), &GraphFilter::default()).unwrap();
+
+        let ids: Vec<u32> = graph.nodes.iter().map(|node| node.id).collect();
+        assert_eq!(ids, vec![30000142, 30000144, 30002813, 31000005]);
+        assert_eq!(
+            graph.edges,
+            vec![(30000142, 30000144), (30000144, 30002813)]
+        );
+        assert_eq!(graph.nodes[0].region, "The Forge");
+        assert_eq!(graph.nodes[0].position, Some([10.0, 20.0]));
+        assert_eq!(graph.nodes[3].position, None);
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn filters_drop_systems_and_the_edges_leaving_them() {
+        let path = database("filtered");
+        let sde = manager(&path);
+
+        let forge = jump_graph(
+            &sde,
+            &GraphFilter {
+                regions: vec![10000002],
+                ..GraphFilter::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(forge.nodes.len(), 2);
+        assert_eq!(forge.edges, vec![(30000142, 30000144)]);
+
+        let low_sec = jump_graph(
+            &sde,
+            &GraphFilter {
+                min_security: Some(0.0),
+                max_security: Some(0.45),
+                ..GraphFilter::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(low_sec.nodes.len(), 1);
+        assert_eq!(low_sec.nodes[0].name, "Tama");
+        assert!(low_sec.edges.is_empty());
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn renders_graphml_dot_and_csv() {
+        let path = database("render");
+        let graph = jump_graph(&manager(&path), &GraphFilter::default()).unwrap();
+
+        let graphml = graph.render(GraphFormat::GraphMl);
+        assert!(graphml.contains("<graph id=\"jumps\" edgedefault=\"undirected\">"));
+        assert!(graphml.contains("<data key=\"name\">Thera &lt;&amp;&gt;</data>"));
+        assert!(graphml.contains("<data key=\"x\">10</data>"));
+        assert!(graphml.contains("<edge source=\"30000142\" target=\"30000144\"/>"));
+        assert_eq!(graphml.matches("<node ").count(), 4);
+
+        let dot = graph.render(GraphFormat::Dot);
+        assert!(dot.starts_with("graph jumps {\n"));
+        assert!(dot.contains(
+            "  30000142 [label=\"Jita\", security=0.95, regionId=10000002, \
+             region=\"The Forge\", pos=\"10,20\"];\n"
+        ));
+        assert!(dot.contains("  30000144 -- 30002813;\n"));
+
+        assert_eq!(
+            graph.render(GraphFormat::Csv),
+            "source,target\r\n30000142,30000144\r\n30000144,30002813\r\n"
+        );
+        assert_eq!(
+            graph.render(GraphFormat::CsvNodes),
+            "id,name,security,regionId,region,x,y\r\n\
+             30000142,Jita,0.95,10000002,The Forge,10,20\r\n\
+             30000144,Perimeter,0.9,10000002,The Forge,30,40\r\n\
+             30002813,Tama,0.3,10000069,Black Rise,50,60\r\n\
+             31000005,Thera <&>,-1,11000001,A-R00001,,\r\n"
+        );
+        std::fs::remove_file(&path).ok();
+    }
+}
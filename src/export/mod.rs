@@ -0,0 +1,15 @@
+//! Exporting a built database to formats other tools read (feature
+//! `export`, also enabled by `builder`), behind `sde-builder export`. Each format is its own
+//! module, built on the read API ([`crate::SdeManager`]) rather than on
+//! SQL of its own:
+//!
+//! - [`geojson`]: systems and stargate connections as a GeoJSON
+//!   `FeatureCollection`, for web maps (Leaflet, MapLibre).
+//! - [`graph`]: the stargate network as GraphML, DOT or a CSV edge
+//!   list, for network analysis (networkx, Gephi, Graphviz).
+//!
+//! [`geojson`]: crate::export::geojson
+//! [`graph`]: crate::export::graph
+
+pub mod geojson;
+pub mod graph;
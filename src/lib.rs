@@ -7,23 +7,64 @@
 //!
 //!
 use crate::objects::{
-    Constellation, Moon, Planet, Region, SdePoint, SdeSegment, SolarSystem, Universe,
+    CommunityFlags, Constellation, ItemType, MapProjection, Moon, OptionalLayers, Planet,
+    Projection, Region, SdeMetadata, SdePoint, SdeSegment, SolarSystem, Station, SystemDetails,
+    Universe,
 };
 use objects::EveRegionArea;
 use rusqlite::ToSql;
-use rusqlite::{Connection, Error, OpenFlags, params, vtab::array};
-use std::collections::HashMap;
+use rusqlite::functions::FunctionFlags;
+use rusqlite::{Connection, OpenFlags, params, vtab::array};
+use search::{MatchKind, SearchKind, SearchResult};
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::path::Path;
 use std::rc::Rc;
 
+/// [`Error`], what every [`SdeManager`] method fails with.
+pub mod error;
+pub use error::Error;
+
 /// Module that has Data object abstractions to fill with the database data.
 pub mod objects;
 
+/// Kinds, result type and matching helpers behind [`SdeManager::search`].
+pub mod search;
+
 /// Logic to (re)generate `sde.db` (feature `builder`, disabled by
 /// default). See `src/builder/mod.rs` for the detail.
 #[cfg(feature = "builder")]
 pub mod builder;
 
+/// Changelog between two builds of the database (feature `builder`),
+/// behind `sde-builder diff`.
+#[cfg(feature = "builder")]
+pub mod diff;
+
+/// Name lookups rendered as tables, JSON or CSV (feature `builder`),
+/// behind `sde-builder query`.
+#[cfg(feature = "builder")]
+pub mod query;
+
+/// Exports of the map to other tools' formats (feature `export`, which
+/// `builder` turns on), behind `sde-builder export`.
+#[cfg(feature = "export")]
+pub mod export;
+
+/// Version of the schema `builder::schema::create_schema` writes, stamped
+/// in the database's `PRAGMA user_version`. Bumped whenever a change to
+/// `schema.sql` would break a reader that doesn't know about it;
+/// [`SdeManager::open`] refuses databases stamped with a newer one.
+/// Databases built before the stamp existed read as `0`.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Column `index` of `row`, an `INTEGER` id, as a `usize` (or another
+/// unsigned integer): a negative value fails with
+/// `IntegralValueOutOfRange` instead of wrapping or panicking.
+fn sql_index<T: TryFrom<i64>>(row: &rusqlite::Row, index: usize) -> rusqlite::Result<T> {
+    let value = row.get::<usize, i64>(index)?;
+    T::try_from(value).map_err(|_| rusqlite::Error::IntegralValueOutOfRange(index, value))
+}
+
 /// Manages the process of reading SDE data and putting into different data structures
 /// for easy in-memory access.
 #[derive(Clone)]
@@ -36,6 +77,17 @@ pub struct SdeManager<'a> {
     pub factor: f64,
     /// Invert the sign of all coordinate values
     pub invert_coordinates: bool,
+    /// Language code (`"en"`, `"de"`, `"ja"`, ...) names are returned in,
+    /// looked up in `trnTranslations`, falling back to English and then
+    /// to the database's base columns when there's no translation
+    pub language: String,
+    /// The projection [`Self::get_systems`], [`Self::get_connections`]
+    /// and [`Self::get_region_coordinates`] use: the stored
+    /// `position2DX`/`position2DY`, or a projection of
+    /// `centerX`/`centerY`/`centerZ` computed on every call. A rotatable
+    /// view that changes it per frame can pass its own to the
+    /// `*_with_projection` variants instead of mutating this.
+    pub projection: MapProjection,
 }
 
 impl<'a> SdeManager<'a> {
@@ -45,7 +97,8 @@ impl<'a> SdeManager<'a> {
     /// one). `factor` is the coordinate-scaling divisor/multiplier used
     /// throughout (see `Self::scale_coords`); it's also passed to
     /// [`objects::Universe::new`] to build the initial, empty
-    /// `universe`. `invert_coordinates` starts `true`.
+    /// `universe`. `invert_coordinates` starts `true`, `language`
+    /// starts `"en"`, `projection` starts [`MapProjection::Stored`].
     #[tracing::instrument]
     pub fn new(path: &Path, factor: f64) -> SdeManager<'_> {
         SdeManager {
@@ -53,7 +106,237 @@ impl<'a> SdeManager<'a> {
             universe: Universe::new(factor),
             factor, // 10000000000000
             invert_coordinates: true,
+            language: String::from("en"),
+            projection: MapProjection::Stored,
+        }
+    }
+
+    /// SQL column list `position_2d_at` reads back, for the
+    /// `mapSolarSystems` row aliased `alias`: the stored 2D position
+    /// followed by the 3D center, in that order.
+    fn position_columns(alias: &str) -> String {
+        format!(
+            "{alias}.position2DX, {alias}.position2DY, {alias}.centerX, {alias}.centerY, {alias}.centerZ"
+        )
+    }
+
+    /// `projection`'s unscaled 2D position for the five columns
+    /// [`Self::position_columns`] put at `first`..`first + 5`: the
+    /// stored `position2DX`/`position2DY` for [`MapProjection::Stored`]
+    /// (`None` if either is `NULL`), the projected center otherwise.
+    fn position_2d_at(
+        projection: MapProjection,
+        row: &rusqlite::Row,
+        first: usize,
+    ) -> Result<Option<[f64; 2]>, Error> {
+        if projection == MapProjection::Stored {
+            let x = row.get::<usize, Option<f64>>(first)?;
+            let y = row.get::<usize, Option<f64>>(first + 1)?;
+            return Ok(x.zip(y).map(|(x, y)| [x, y]));
+        }
+        Ok(projection
+            .project(
+                row.get::<usize, f64>(first + 2)?,
+                row.get::<usize, f64>(first + 3)?,
+                row.get::<usize, f64>(first + 4)?,
+            )
+            .map(|(x, y)| [x, y]))
+    }
+
+    /// Tables every getter reads from, checked by [`Self::open`].
+    /// Everything else is optional: built by a later builder version
+    /// (and fallen back on by the getters that read it) or only present
+    /// with third-party data (see [`Self::optional_layers`]).
+    pub const REQUIRED_TABLES: [&'static str; 6] = [
+        "mapRegions",
+        "mapConstellations",
+        "mapSolarSystems",
+        "mapSystemConnections",
+        "mapPlanets",
+        "mapMoons",
+    ];
+
+    /// Same as [`Self::new`], but checks first that `path` is a database
+    /// this version can read: it must exist (it's never created, unlike
+    /// with `new`, whose first query would leave an empty file behind),
+    /// not be stamped with a [`SCHEMA_VERSION`] newer than this crate's
+    /// ([`Error::UnsupportedVersion`]), and have every one of
+    /// [`Self::REQUIRED_TABLES`] ([`Error::SchemaMismatch`]).
+    /// Unstamped databases (version `0`, built before the stamp existed)
+    /// pass on the table check alone.
+    #[tracing::instrument]
+    pub fn open(path: &Path, factor: f64) -> Result<SdeManager<'_>, Error> {
+        let connection = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let found: u32 = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if found > SCHEMA_VERSION {
+            return Err(Error::UnsupportedVersion {
+                found,
+                supported: SCHEMA_VERSION,
+            });
+        }
+        let mut missing_tables = Vec::new();
+        for table in Self::REQUIRED_TABLES {
+            if !Self::has_table(&connection, table)? {
+                missing_tables.push(table.to_string());
+            }
+        }
+        if !missing_tables.is_empty() {
+            return Err(Error::SchemaMismatch { missing_tables });
+        }
+        Ok(Self::new(path, factor))
+    }
+
+    /// Which of the community-maintained, third-party layers the
+    /// database has -- e.g. whether [`Self::get_abstract_systems`] can
+    /// work at all, without having to call it and handle the `no such
+    /// table` error.
+    #[tracing::instrument(skip(self))]
+    pub fn optional_layers(&self) -> Result<OptionalLayers, Error> {
+        let connection = self.get_standart_connection()?;
+        let mut statement =
+            connection.prepare("SELECT name FROM pragma_table_info('mapSolarSystems')")?;
+        let columns = statement
+            .query_map([], |row| row.get::<usize, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        let has_column = |name: &str| columns.iter().any(|column| column == name);
+        Ok(OptionalLayers {
+            abstract_map: Self::has_table(&connection, "mapAbstractSystems")?,
+            ice_belt: has_column("iceBelt"),
+            triglavian_status: Self::has_table(&connection, "mapTriglavianStatus")?
+                && has_column("trigStatusID"),
+            jove_observatory: has_column("joveObservatory"),
+            special_ore: has_column("specialOreAnom"),
+        })
+    }
+
+    /// The community-maintained flags of the given `systems` (an id
+    /// allowlist; empty means every system), keyed by `solarSystemId`.
+    /// Only the layers the database has are read (see
+    /// [`Self::optional_layers`]); the others are `None` on every
+    /// system, rather than an error.
+    #[tracing::instrument(skip(self))]
+    pub fn get_community_flags(
+        &self,
+        systems: Vec<u32>,
+    ) -> Result<HashMap<u32, CommunityFlags>, Error> {
+        let layers = self.optional_layers()?;
+        let connection = self.get_standart_connection()?;
+        let column = |present: bool, expression: &str| {
+            if present {
+                expression.to_string()
+            } else {
+                "NULL".to_string()
+            }
+        };
+        let mut query = format!(
+            "SELECT mss.solarSystemId, {}, {}, {}, {} FROM mapSolarSystems AS mss ",
+            column(layers.ice_belt, "mss.iceBelt"),
+            column(layers.jove_observatory, "mss.joveObservatory"),
+            column(layers.special_ore, "mss.specialOreAnom"),
+            column(layers.triglavian_status, "mts.trigStatusName"),
+        );
+        if layers.triglavian_status {
+            query +=
+                "LEFT JOIN mapTriglavianStatus AS mts ON (mts.trigStatusId = mss.trigStatusID) ";
+        }
+        if !systems.is_empty() {
+            query += "WHERE mss.solarSystemId IN rarray(?1)";
+        }
+
+        let mut statement = connection.prepare(query.as_str())?;
+        let mut rows;
+        if systems.is_empty() {
+            rows = statement.query([])?;
+        } else {
+            let id_list: array::Array = Rc::new(
+                systems
+                    .into_iter()
+                    .map(rusqlite::types::Value::from)
+                    .collect::<Vec<rusqlite::types::Value>>(),
+            );
+            rows = statement.query([id_list])?;
+        }
+
+        let mut result = HashMap::new();
+        while let Some(row) = rows.next()? {
+            result.insert(
+                row.get(0)?,
+                CommunityFlags {
+                    ice_belt: row.get(1)?,
+                    jove_observatory: row.get(2)?,
+                    special_ore: row.get(3)?,
+                    triglavian_status: row.get(4)?,
+                },
+            );
+        }
+        Ok(result)
+    }
+
+    /// [`Error::MissingLayer`] unless the database has
+    /// `mapAbstractSystems` -- checked up front by the abstract-map
+    /// getters, rather than letting their query fail on a missing table.
+    fn require_abstract_map(connection: &Connection) -> Result<(), Error> {
+        if Self::has_table(connection, "mapAbstractSystems")? {
+            Ok(())
+        } else {
+            Err(Error::MissingLayer {
+                layer: "abstract map",
+                table: "mapAbstractSystems",
+            })
+        }
+    }
+
+    /// Whether `connection`'s database has a table called `name`.
+    fn has_table(connection: &Connection, name: &str) -> Result<bool, Error> {
+        Ok(connection.query_row(
+            "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+            [name],
+            |row| row.get(0),
+        )?)
+    }
+
+    /// SQL expression for the localized value of `alias.column` (the
+    /// base-language copy of the field, as written by the builder), in
+    /// `self.language`: a correlated subquery over `trnTranslations`
+    /// picking the requested language first, then English, wrapped in a
+    /// `COALESCE` that falls back to the base column itself when neither
+    /// exists. `key` is the SQL expression for the row's id
+    /// (`trnTranslations.keyId`).
+    ///
+    /// Databases without `trnTranslations` (built before the builder
+    /// stored every language) get the bare `alias.column` back, so the
+    /// same queries keep working against them unchanged.
+    ///
+    /// The language code never becomes part of the SQL text: it's handed
+    /// to SQLite as a value, through an `sde_language()` function this
+    /// registers on `connection`. A `?N` parameter would do the same,
+    /// but every caller numbers its own, and the expression only uses it
+    /// when `trnTranslations` exists -- the function spares them both.
+    fn localized_column(
+        &self,
+        connection: &Connection,
+        table: &str,
+        column: &str,
+        alias: &str,
+        key: &str,
+    ) -> Result<String, Error> {
+        let has_translations = Self::has_table(connection, "trnTranslations")?;
+        if !has_translations {
+            return Ok(format!("{alias}.{column}"));
         }
+        let language = self.language.clone();
+        connection.create_scalar_function(
+            "sde_language",
+            0,
+            FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+            move |_| Ok(language.clone()),
+        )?;
+        Ok(format!(
+            "COALESCE((SELECT trn.text FROM trnTranslations AS trn \
+             WHERE trn.tableName = '{table}' AND trn.columnName = '{column}' \
+             AND trn.keyId = {key} AND trn.languageId IN (sde_language(), 'en') \
+             ORDER BY trn.languageId <> sde_language() LIMIT 1), {alias}.{column})"
+        ))
     }
 
     /// Applies the adjustment factor (`self.factor`) and, if `invert` is
@@ -127,6 +410,25 @@ impl<'a> SdeManager<'a> {
         Ok(true)
     }
 
+    /// Build provenance of the database (`sdeMetadata`, written by the
+    /// builder): which SDE build it came from, with what config and
+    /// crate version -- for showing the data version or checking it's
+    /// recent enough. `None` for databases built before that table
+    /// existed.
+    #[tracing::instrument(skip(self))]
+    pub fn metadata(&self) -> Result<Option<SdeMetadata>, Error> {
+        let connection = self.get_standart_connection()?;
+        let has_table = Self::has_table(&connection, "sdeMetadata")?;
+        if !has_table {
+            return Ok(None);
+        }
+        let mut statement = connection.prepare("SELECT key, value FROM sdeMetadata")?;
+        let entries = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<Vec<(String, String)>>>()?;
+        Ok(Some(SdeMetadata::from_entries(entries)))
+    }
+
     /// All K-space solar systems with a computed 2D map projection,
     /// keyed by `solarSystemId` -- `SdePoint.id` is `Some(that same
     /// id)`, kept on the point too (same redundancy already accepted
@@ -139,20 +441,38 @@ impl<'a> SdeManager<'a> {
     /// this read-side method selects). Systems without a 2D projection
     /// (`position2DX`/`position2DY` both `NULL` -- CCP doesn't provide
     /// one for every system, and [`builder::parser`] only computes one
-    /// locally when `force_isometric_position_2d` is set) are excluded
-    /// entirely rather than appearing with a placeholder position.
+    /// locally when `force_position_2d` is set) are excluded
+    /// entirely rather than appearing with a placeholder position --
+    /// unless `self.projection` computes the position from
+    /// `centerX`/`centerY`/`centerZ` instead, which every system has.
     ///
     /// Each point also carries the ids of every solar system it has a
     /// stargate connection to (via `mapSystemConnections`), in
     /// [`objects::SdePoint::connections`].
-    #[tracing::instrument(skip(self))]
     pub fn get_systems(&self) -> Result<HashMap<usize, SdePoint>, Error> {
+        self.get_systems_with_projection(self.projection)
+    }
+
+    /// [`Self::get_systems`] with `projection` in place of
+    /// `self.projection`.
+    #[tracing::instrument(skip(self))]
+    pub fn get_systems_with_projection(
+        &self,
+        projection: MapProjection,
+    ) -> Result<HashMap<usize, SdePoint>, Error> {
         let connection = self.get_standart_connection()?;
 
         let mut result = HashMap::new();
-        // centerX, centerY, centerZ,
-        let mut query = String::from(
-            "SELECT sos.SolarSystemId, sos.position2DX, sos.position2DY, sos.SolarSystemName, msc.systemA, msc.systemB ",
+        let name = self.localized_column(
+            &connection,
+            "mapSolarSystems",
+            "solarSystemName",
+            "sos",
+            "sos.solarSystemId",
+        )?;
+        let mut query = format!(
+            "SELECT sos.SolarSystemId, {}, {name}, msc.systemA, msc.systemB ",
+            Self::position_columns("sos")
         );
         query += " FROM mapSolarSystems AS sos RIGHT OUTER JOIN mapSystemConnections AS msc";
         query += " ON (msc.systemA = sos.SolarSystemId OR msc.systemB = sos.SolarSystemId)";
@@ -161,13 +481,16 @@ impl<'a> SdeManager<'a> {
         // always carried a value via DEFAULT(0.0)): a system without a
         // computed 2D projection (CCP doesn't provide one and local
         // computation wasn't forced, see
-        // ParserConfig::force_isometric_position_2d) simply doesn't show
-        // up on the map, instead of breaking the query.
-        query += " AND sos.position2DX IS NOT NULL AND sos.position2DY IS NOT NULL";
+        // ParserConfig::force_position_2d) simply doesn't show
+        // up on the map, instead of breaking the query. centerX/Y/Z are
+        // NOT NULL, so a computed projection has no such gap.
+        if projection == MapProjection::Stored {
+            query += " AND sos.position2DX IS NOT NULL AND sos.position2DY IS NOT NULL";
+        }
         query += " ORDER BY sos.SolarSystemId ASC";
         let mut statement = connection.prepare(query.as_str())?;
         let mut rows = statement.query(params![30000000, 30999999])?;
-        let mut last_id = isize::MIN;
+        let mut last_id = None;
         let mut point = SdePoint {
             id: None,
             name: None,
@@ -175,31 +498,94 @@ impl<'a> SdeManager<'a> {
             connections: Vec::new(),
         };
         while let Some(row) = rows.next()? {
-            let id = row.get::<usize, isize>(0)?;
-            if id != last_id {
-                if last_id != isize::MIN {
-                    result.insert(point.id.unwrap(), point.clone());
+            // A negative id is a conversion error (`Error::Sqlite`),
+            // not a panic.
+            let id = sql_index(row, 0)?;
+            if last_id != Some(id) {
+                if let Some(previous) = last_id {
+                    result.insert(previous, point.clone());
                 }
-                last_id = id;
-                let x = row.get::<usize, f64>(1)?;
-                let y = row.get::<usize, f64>(2)?;
+                last_id = Some(id);
+                let position = Self::position_2d_at(projection, row, 1)?.unwrap_or_default();
 
                 //we get the coordinate point and multiply with the adjust factor
-                let [x, y] = self.scale_coords([x, y], self.invert_coordinates);
+                let [x, y] = self.scale_coords(position, self.invert_coordinates);
                 point = SdePoint {
-                    id: Some(id.try_into().unwrap()),
-                    name: Some(row.get::<usize, String>(3)?),
+                    id: Some(id),
+                    name: Some(row.get::<usize, String>(6)?),
                     coords: [x, y, 0.0],
                     connections: Vec::new(),
                 };
             }
-            point.connections.push((
-                row.get::<usize, i64>(4)? as usize,
-                row.get::<usize, i64>(5)? as usize,
-            ));
+            point
+                .connections
+                .push((sql_index(row, 7)?, sql_index(row, 8)?));
+        }
+        if let Some(previous) = last_id {
+            result.insert(previous, point);
+        }
+        Ok(result)
+    }
+
+    /// Projections stored for every solar system besides
+    /// `position2DX`/`position2DY` (`mapSolarSystemProjections`, see
+    /// `ParserConfig.projections`), i.e. what
+    /// [`Self::get_system_projection`] can switch to. Empty for databases
+    /// built without any, or before that table existed.
+    #[tracing::instrument(skip(self))]
+    pub fn get_stored_projections(&self) -> Result<Vec<Projection>, Error> {
+        let connection = self.get_standart_connection()?;
+        let has_table = Self::has_table(&connection, "mapSolarSystemProjections")?;
+        if !has_table {
+            return Ok(Vec::new());
+        }
+        let mut statement = connection.prepare(
+            "SELECT DISTINCT projection FROM mapSolarSystemProjections ORDER BY projection",
+        )?;
+        let mut rows = statement.query([])?;
+        let mut projections = Vec::new();
+        while let Some(row) = rows.next()? {
+            // Names this version doesn't know (a database built by a newer
+            // one) are skipped rather than failing the whole listing.
+            if let Some(projection) = Projection::from_name(&row.get::<usize, String>(0)?) {
+                projections.push(projection);
+            }
+        }
+        Ok(projections)
+    }
+
+    /// 2D position of every K-space solar system (same range as
+    /// [`Self::get_systems`]) in one of the projections stored in
+    /// `mapSolarSystemProjections`, keyed by `solarSystemId` and scaled
+    /// and sign-flipped exactly like [`Self::get_systems`]' points, so
+    /// a client can swap them in for `SdePoint::coords` to switch
+    /// layouts at runtime. [`Error::NotFound`] if `projection` wasn't
+    /// stored (see [`Self::get_stored_projections`]).
+    #[tracing::instrument(skip(self))]
+    pub fn get_system_projection(
+        &self,
+        projection: Projection,
+    ) -> Result<HashMap<usize, [f64; 2]>, Error> {
+        if !self.get_stored_projections()?.contains(&projection) {
+            return Err(Error::NotFound {
+                kind: "projection",
+                key: projection.name(),
+            });
         }
-        if last_id != isize::MIN {
-            result.insert(point.id.unwrap(), point);
+        let connection = self.get_standart_connection()?;
+        let mut statement = connection.prepare(
+            "SELECT solarSystemId, x, y FROM mapSolarSystemProjections \
+             WHERE projection = ?1 AND solarSystemId BETWEEN ?2 AND ?3",
+        )?;
+        let mut rows = statement.query(params![projection.name(), 30000000, 30999999])?;
+        let mut result = HashMap::new();
+        while let Some(row) = rows.next()? {
+            let id = sql_index(row, 0)?;
+            let coords = self.scale_coords(
+                [row.get::<usize, f64>(1)?, row.get::<usize, f64>(2)?],
+                self.invert_coordinates,
+            );
+            result.insert(id, coords);
         }
         Ok(result)
     }
@@ -207,7 +593,9 @@ impl<'a> SdeManager<'a> {
     /// The 2D bounding box (`EveRegionArea.max`/`.min`) of every
     /// K-space region (`regionId` between `10000000` and `10999999`),
     /// computed from the `MAX`/`MIN` of every solar system's
-    /// `position2DX`/`position2DY` across all its constellations.
+    /// `position2DX`/`position2DY` across all its constellations (or of
+    /// its `self.projection`, when that's computed rather than
+    /// [`MapProjection::Stored`]).
     /// Regions where every system lacks a 2D projection are excluded
     /// (there's no box to report); regions with at least one projected
     /// system still get a box even if others in it are missing one,
@@ -218,14 +606,30 @@ impl<'a> SdeManager<'a> {
     /// would leave what used to be the maximum corner with the smaller
     /// (now negative) coordinates, so `max`/`min` would no longer
     /// actually describe the box's extremes without the swap.
-    #[tracing::instrument(skip(self))]
     pub fn get_region_coordinates(&self) -> Result<Vec<EveRegionArea>, Error> {
+        self.get_region_coordinates_with_projection(self.projection)
+    }
+
+    /// [`Self::get_region_coordinates`] with `projection` in place of
+    /// `self.projection`.
+    #[tracing::instrument(skip(self))]
+    pub fn get_region_coordinates_with_projection(
+        &self,
+        projection: MapProjection,
+    ) -> Result<Vec<EveRegionArea>, Error> {
         let connection = self.get_standart_connection()?;
+        let name =
+            self.localized_column(&connection, "mapRegions", "regionName", "mr", "mr.regionId")?;
+        if projection != MapProjection::Stored {
+            let mut areas = Self::projected_region_coordinates(&connection, &name, projection)?;
+            self.invert_region_areas(&mut areas);
+            return Ok(areas);
+        }
 
         let mut query = String::from("SELECT reg.regionId, reg.regionName, ");
         query += "MAX(reg.max_x) AS region_max_x, MAX(reg.max_y) AS region_max_y, ";
         query += "MIN(reg.min_x) AS region_min_x, MIN(reg.min_y) AS region_min_y ";
-        query += "FROM (SELECT mr.regionId, mr.regionName, ";
+        query += &format!("FROM (SELECT mr.regionId, {name} AS regionName, ");
         query +=
             "mc.constellationId, MAX(mss.position2DX) AS max_x, MAX(mss.position2DY) AS max_y, ";
         query += "MIN(mss.position2DX) AS min_x, MIN(mss.position2DY) AS min_y ";
@@ -267,15 +671,70 @@ impl<'a> SdeManager<'a> {
                 SdePoint::from([row.get::<usize, f64>(2)?, row.get::<usize, f64>(3)?, 0.0]);
             region.min =
                 SdePoint::from([row.get::<usize, f64>(4)?, row.get::<usize, f64>(5)?, 0.0]);
-            // we invert the coordinates and swap the min with the max
-            if self.invert_coordinates {
+            areas.push(region);
+        }
+        self.invert_region_areas(&mut areas);
+        Ok(areas)
+    }
+
+    /// If `self.invert_coordinates`, flips the sign of every box's
+    /// corners and swaps them, so `max`/`min` keep describing the
+    /// extremes (see [`Self::get_region_coordinates`]).
+    fn invert_region_areas(&self, areas: &mut [EveRegionArea]) {
+        // we invert the coordinates and swap the min with the max
+        if self.invert_coordinates {
+            for region in areas {
                 std::mem::swap(&mut region.max, &mut region.min);
                 region.min *= -1.0;
                 region.max *= -1.0;
             }
-            areas.push(region);
         }
-        Ok(areas)
+    }
+
+    /// [`Self::get_region_coordinates`]' bounding boxes (before the
+    /// `invert_coordinates` swap) for a computed `projection`:
+    /// every K-space region's systems are projected one by one and
+    /// folded into a min/max here, since SQL can't aggregate a
+    /// projection it doesn't compute. Regions come back in `regionId`
+    /// order, same as the stored path's `GROUP BY`.
+    fn projected_region_coordinates(
+        connection: &Connection,
+        name: &str,
+        projection: MapProjection,
+    ) -> Result<Vec<EveRegionArea>, Error> {
+        let mut query = format!(
+            "SELECT mr.regionId, {name}, {} FROM mapRegions AS mr ",
+            Self::position_columns("mss")
+        );
+        query += "INNER JOIN mapConstellations mc ON (mc.regionId = mr.regionId) ";
+        query += "INNER JOIN mapSolarSystems mss ON (mc.constellationId = mss.constellationId) ";
+        query += "WHERE mr.regionId BETWEEN 10000000 AND 10999999";
+        let mut statement = connection.prepare(query.as_str())?;
+        let mut rows = statement.query([])?;
+        let mut regions: BTreeMap<i64, EveRegionArea> = BTreeMap::new();
+        while let Some(row) = rows.next()? {
+            let Some([x, y]) = Self::position_2d_at(projection, row, 2)? else {
+                continue;
+            };
+            let region_id: i64 = row.get(0)?;
+            match regions.get_mut(&region_id) {
+                Some(region) => {
+                    region.max.coords[0] = region.max.coords[0].max(x);
+                    region.max.coords[1] = region.max.coords[1].max(y);
+                    region.min.coords[0] = region.min.coords[0].min(x);
+                    region.min.coords[1] = region.min.coords[1].min(y);
+                }
+                None => {
+                    let mut region = EveRegionArea::new();
+                    region.region_id = region_id;
+                    region.name = row.get(1)?;
+                    region.max = SdePoint::from([x, y, 0.0]);
+                    region.min = SdePoint::from([x, y, 0.0]);
+                    regions.insert(region_id, region);
+                }
+            }
+        }
+        Ok(regions.into_values().collect())
     }
 
     /// Finds solar systems by a case-insensitive substring match on
@@ -293,15 +752,23 @@ impl<'a> SdeManager<'a> {
         name: String,
     ) -> Result<Vec<(isize, String, isize, String)>, Error> {
         let connection = self.get_standart_connection()?;
+        let system_name = self.localized_column(
+            &connection,
+            "mapSolarSystems",
+            "solarSystemName",
+            "mss",
+            "mss.solarSystemId",
+        )?;
+        let region_name =
+            self.localized_column(&connection, "mapRegions", "regionName", "mr", "mr.regionId")?;
 
-        let mut query = String::from(
-            "SELECT mss.SolarSystemId, mss.SolarSystemName, mr.RegionId, mr.regionName ",
-        );
+        let mut query =
+            format!("SELECT mss.SolarSystemId, {system_name}, mr.RegionId, {region_name} ");
         query += "FROM mapSolarSystems AS mss ";
         query +=
             "INNER JOIN mapConstellations AS mc ON (mc.constellationId = mss.constellationId) ";
         query += "INNER JOIN mapRegions AS mr ON (mr.RegionId = mc.RegionId) ";
-        query += "WHERE LOWER(mss.SolarSystemName) LIKE ?1; ";
+        query += &format!("WHERE LOWER({system_name}) LIKE ?1; ");
 
         let mut statement = connection.prepare(query.as_str())?;
         let system_like_name = "%".to_string() + name.as_str() + "%";
@@ -313,6 +780,162 @@ impl<'a> SdeManager<'a> {
         Ok(results)
     }
 
+    /// Ranked name search across solar systems, constellations, regions,
+    /// published types, NPC corporations and NPC stations (the omnibox
+    /// lookup -- [`Self::get_system_id`] stays the plain `LIKE` over
+    /// solar systems it always was). `kinds` narrows it to some of those
+    /// (empty means all of them); at most `limit` results come back,
+    /// sorted by [`search::MatchKind`] first (exact, then prefix, then
+    /// typo-tolerant) and score second. See [`search`]'s docstring for
+    /// how matching works.
+    ///
+    /// Names are matched in `self.language`, in English and in the
+    /// database's base columns; each entity is returned once, under the
+    /// best-ranked name it matched by. Requires the `searchIndex`/
+    /// `searchTrigrams` tables `builder::search` creates -- against a
+    /// database built without them this fails with SQLite's own "no such
+    /// table" error.
+    #[tracing::instrument(skip(self))]
+    pub fn search(
+        &self,
+        query: &str,
+        kinds: &[SearchKind],
+        limit: usize,
+    ) -> Result<Vec<SearchResult>, Error> {
+        let connection = self.get_standart_connection()?;
+        let kinds = if kinds.is_empty() {
+            &SearchKind::ALL[..]
+        } else {
+            kinds
+        };
+        let kind_list: array::Array = Rc::new(
+            kinds
+                .iter()
+                .map(|kind| rusqlite::types::Value::from(kind.as_str().to_string()))
+                .collect::<Vec<rusqlite::types::Value>>(),
+        );
+        let input = query.trim().to_lowercase();
+        let mut results: Vec<SearchResult> = Vec::new();
+        let mut seen: HashMap<(SearchKind, u64), usize> = HashMap::new();
+
+        // Pass 1: prefix match on searchIndex, ranked by bm25.
+        if let Some(fts_query) = search::prefix_query(query) {
+            let mut statement = connection.prepare(
+                "SELECT name, kind, entityId, languageId, bm25(searchIndex) FROM searchIndex \
+                 WHERE searchIndex MATCH ?1 AND kind IN rarray(?2) \
+                 AND (languageId IS NULL OR languageId IN (?3, 'en')) \
+                 ORDER BY bm25(searchIndex)",
+            )?;
+            let mut rows = statement.query(params![fts_query, kind_list.clone(), self.language])?;
+            while let Some(row) = rows.next()? {
+                let name: String = row.get(0)?;
+                let match_kind = if name.to_lowercase() == input {
+                    MatchKind::Exact
+                } else {
+                    MatchKind::Prefix
+                };
+                // A kind this version doesn't know is skipped, not
+                // mislabeled.
+                let Some(kind) = SearchKind::from_name(&row.get::<usize, String>(1)?) else {
+                    continue;
+                };
+                let result = SearchResult {
+                    kind,
+                    id: sql_index(row, 2)?,
+                    name,
+                    language: row.get(3)?,
+                    match_kind,
+                    score: row.get(4)?,
+                };
+                match seen.get(&(result.kind, result.id)) {
+                    Some(&index) => {
+                        // Rows arrive best score first, so a later row only
+                        // wins by being an exact match where the first wasn't.
+                        if result.match_kind < results[index].match_kind {
+                            results[index] = result;
+                        }
+                    }
+                    None => {
+                        seen.insert((result.kind, result.id), results.len());
+                        results.push(result);
+                    }
+                }
+            }
+        }
+
+        // Pass 2: typo-tolerant match, only if pass 1 came up short.
+        // Candidates come from searchTrigrams for long enough inputs, from
+        // the shortest indexed names otherwise (see
+        // search::TRIGRAM_MIN_CHARS and search::FUZZY_SCAN_LIMIT).
+        if results.len() < limit {
+            let max_distance = search::max_typo_distance(&input);
+            let trigrams = search::trigram_query(query)
+                .filter(|_| input.chars().count() >= search::TRIGRAM_MIN_CHARS);
+            let (mut statement, mut rows);
+            if let Some(fts_query) = trigrams {
+                statement = connection.prepare(
+                    "SELECT name, kind, entityId, languageId FROM searchTrigrams \
+                     WHERE searchTrigrams MATCH ?1 AND kind IN rarray(?2) \
+                     AND (languageId IS NULL OR languageId IN (?3, 'en'))",
+                )?;
+                rows = statement.query(params![fts_query, kind_list, self.language])?;
+            } else {
+                statement = connection.prepare(
+                    "SELECT name, kind, entityId, languageId FROM searchIndex \
+                     WHERE kind IN rarray(?1) \
+                     AND (languageId IS NULL OR languageId IN (?2, 'en')) \
+                     ORDER BY length(name) LIMIT ?3",
+                )?;
+                let scan_limit = i64::try_from(search::FUZZY_SCAN_LIMIT).unwrap_or(i64::MAX);
+                rows = statement.query(params![kind_list, self.language, scan_limit])?;
+            }
+            while let Some(row) = rows.next()? {
+                let name: String = row.get(0)?;
+                let distance = search::best_edit_distance(&input, &name);
+                if distance > max_distance {
+                    continue;
+                }
+                // A kind this version doesn't know is skipped, not
+                // mislabeled.
+                let Some(kind) = SearchKind::from_name(&row.get::<usize, String>(1)?) else {
+                    continue;
+                };
+                let result = SearchResult {
+                    kind,
+                    id: sql_index(row, 2)?,
+                    name,
+                    language: row.get(3)?,
+                    match_kind: MatchKind::Fuzzy,
+                    score: distance as f64,
+                };
+                match seen.get(&(result.kind, result.id)) {
+                    Some(&index) => {
+                        if results[index].match_kind == MatchKind::Fuzzy
+                            && result.score < results[index].score
+                        {
+                            results[index] = result;
+                        }
+                    }
+                    None => {
+                        seen.insert((result.kind, result.id), results.len());
+                        results.push(result);
+                    }
+                }
+            }
+        }
+
+        let kind_order = |kind: SearchKind| SearchKind::ALL.iter().position(|k| *k == kind);
+        results.sort_by(|a, b| {
+            a.match_kind
+                .cmp(&b.match_kind)
+                .then(a.score.total_cmp(&b.score))
+                .then(kind_order(a.kind).cmp(&kind_order(b.kind)))
+                .then(a.name.cmp(&b.name))
+        });
+        results.truncate(limit);
+        Ok(results)
+    }
+
     /// The real 3D coordinates (`centerX`/`Y`/`Z`, always `NOT NULL` in
     /// the schema, unlike the nullable `position2DX`/`Y` used
     /// elsewhere) of the solar system with id `id_node`, scaled by
@@ -362,12 +985,24 @@ impl<'a> SdeManager<'a> {
     /// already supports (`rstar::RTreeObject`/`rstar::PointDistance`)
     /// can build their own `rstar::RTree::bulk_load(map.into_values().collect())`
     /// from this directly.
-    #[tracing::instrument(skip(self))]
     pub fn get_connections(&self) -> Result<HashMap<(usize, usize), SdeSegment>, Error> {
+        self.get_connections_with_projection(self.projection)
+    }
+
+    /// [`Self::get_connections`] with `projection` in place of
+    /// `self.projection`.
+    #[tracing::instrument(skip(self))]
+    pub fn get_connections_with_projection(
+        &self,
+        projection: MapProjection,
+    ) -> Result<HashMap<(usize, usize), SdeSegment>, Error> {
         let connection = self.get_standart_connection()?;
 
-        let mut query = String::from("SELECT msc.systemA, msc.systemB, ");
-        query += "mssa.position2DX, mssa.position2DY, mssb.position2DX, mssb.position2DY ";
+        let mut query = format!(
+            "SELECT msc.systemA, msc.systemB, {}, {} ",
+            Self::position_columns("mssa"),
+            Self::position_columns("mssb")
+        );
         query += "FROM mapSystemConnections AS msc INNER JOIN mapSolarSystems AS mssa ";
         query += "ON(msc.systemA = mssa.solarSystemId) INNER JOIN mapSolarSystems AS mssb ";
         query += "ON(msc.systemB = mssb.solarSystemId) ";
@@ -375,25 +1010,24 @@ impl<'a> SdeManager<'a> {
         // the line; if either one is missing it (see the same
         // nullability note in get_systems), the whole connection is
         // skipped instead of failing the entire query.
-        query += "WHERE mssa.position2DX IS NOT NULL AND mssa.position2DY IS NOT NULL ";
-        query += "AND mssb.position2DX IS NOT NULL AND mssb.position2DY IS NOT NULL;";
+        if projection == MapProjection::Stored {
+            query += "WHERE mssa.position2DX IS NOT NULL AND mssa.position2DY IS NOT NULL ";
+            query += "AND mssb.position2DX IS NOT NULL AND mssb.position2DY IS NOT NULL";
+        }
 
         let mut statement = connection.prepare(query.as_str())?;
         let mut rows = statement.query([])?;
         let mut results = HashMap::new();
         while let Some(row) = rows.next()? {
-            let point1 = self.scale_coords(
-                [row.get::<usize, f64>(2)?, row.get::<usize, f64>(3)?],
-                self.invert_coordinates,
-            );
-            let point2 = self.scale_coords(
-                [row.get::<usize, f64>(4)?, row.get::<usize, f64>(5)?],
-                self.invert_coordinates,
-            );
-            let id = (
-                row.get::<usize, i64>(0)? as usize,
-                row.get::<usize, i64>(1)? as usize,
-            );
+            let (Some(point1), Some(point2)) = (
+                Self::position_2d_at(projection, row, 2)?,
+                Self::position_2d_at(projection, row, 7)?,
+            ) else {
+                continue;
+            };
+            let point1 = self.scale_coords(point1, self.invert_coordinates);
+            let point2 = self.scale_coords(point2, self.invert_coordinates);
+            let id = (sql_index(row, 0)?, sql_index(row, 1)?);
             results.insert(id, SdeSegment { id, point1, point2 });
         }
         Ok(results)
@@ -413,18 +1047,23 @@ impl<'a> SdeManager<'a> {
     /// `mapAbstractSystems` doesn't exist at all in a database built
     /// without `--with-third-party` (or, equivalently,
     /// `ParserConfig.with_third_party = false`) -- this method returns
-    /// `Err(rusqlite::Error::SqliteFailure(..., "no such table:
-    /// mapAbstractSystems"))` in that case, not a panic. There's
-    /// currently no way to check for this ahead of the call other than
-    /// handling that `Err`; a fingerprint of what a given database
-    /// actually contains, queryable without hitting this error, is
-    /// planned but not implemented yet.
+    /// [`Error::MissingLayer`] in that case, not a panic. Check
+    /// [`Self::optional_layers`]' `abstract_map` ahead of the call to
+    /// avoid hitting it.
     #[tracing::instrument(skip(self))]
     pub fn get_abstract_systems(
         &self,
         regions: Vec<u32>,
     ) -> Result<HashMap<usize, SdePoint>, Error> {
         let connection = self.get_standart_connection()?;
+        Self::require_abstract_map(&connection)?;
+        let name = self.localized_column(
+            &connection,
+            "mapSolarSystems",
+            "solarSystemName",
+            "mss",
+            "mss.solarSystemId",
+        )?;
 
         let filter = if regions.is_empty() {
             ""
@@ -433,14 +1072,14 @@ impl<'a> SdeManager<'a> {
         };
         let query = format!(
             "SELECT mas.solarSystemId AS solarSystemId, mas.x, mas.y, mas.regionId, \
-                msc.systemA, msc.systemB, mss.solarSystemName \
+                msc.systemA, msc.systemB, {name} \
              FROM mapSystemConnections AS msc \
              INNER JOIN mapAbstractSystems AS mas ON mas.solarSystemId = msc.systemA \
              INNER JOIN mapSolarSystems AS mss ON mss.solarSystemId = mas.solarSystemId \
              {filter} \
              UNION ALL \
              SELECT mas.solarSystemId AS solarSystemId, mas.x, mas.y, mas.regionId, \
-                msc.systemA, msc.systemB, mss.solarSystemName \
+                msc.systemA, msc.systemB, {name} \
              FROM mapSystemConnections AS msc \
              INNER JOIN mapAbstractSystems AS mas ON mas.solarSystemId = msc.systemB \
              INNER JOIN mapSolarSystems AS mss ON mss.solarSystemId = mas.solarSystemId \
@@ -468,7 +1107,7 @@ impl<'a> SdeManager<'a> {
             rows = statement.query([id_list])?;
         }
 
-        let mut current_index = isize::MIN;
+        let mut current_index = None;
         let mut point = SdePoint {
             id: None,
             name: None,
@@ -476,9 +1115,9 @@ impl<'a> SdeManager<'a> {
             connections: Vec::new(),
         };
         while let Some(row) = rows.next()? {
-            let id = row.get::<usize, isize>(0)?;
-            if current_index != id {
-                if current_index != isize::MIN {
+            let id = sql_index(row, 0)?;
+            if current_index != Some(id) {
+                if let Some(previous) = current_index {
                     // `mem::replace` instead of `point.clone()`: the old
                     // point is being moved into `result` anyway, so there's
                     // no need to pay for a deep clone (heap allocation for
@@ -493,9 +1132,9 @@ impl<'a> SdeManager<'a> {
                             connections: Vec::new(),
                         },
                     );
-                    result.insert(finished.id.unwrap(), finished);
+                    result.insert(previous, finished);
                 }
-                current_index = id;
+                current_index = Some(id);
                 // get_abstract_systems doesn't invert coordinates, unlike
                 // get_systems/get_connections, which do.
                 let [x, y] = self.scale_coords(
@@ -503,19 +1142,18 @@ impl<'a> SdeManager<'a> {
                     false,
                 );
                 point = SdePoint {
-                    id: Some(id.try_into().unwrap()),
+                    id: Some(id),
                     name: Some(row.get::<usize, String>(6)?),
                     coords: [x, y, 0.0],
                     connections: Vec::new(),
                 };
             }
-            point.connections.push((
-                row.get::<usize, i64>(4)? as usize,
-                row.get::<usize, i64>(5)? as usize,
-            ));
+            point
+                .connections
+                .push((sql_index(row, 4)?, sql_index(row, 5)?));
         }
-        if current_index != isize::MIN {
-            result.insert(point.id.unwrap(), point);
+        if let Some(previous) = current_index {
+            result.insert(previous, point);
         }
         Ok(result)
     }
@@ -524,8 +1162,7 @@ impl<'a> SdeManager<'a> {
     /// (`mapAbstractSystems`), optionally filtered by region.
     ///
     /// Same caveat as [`Self::get_abstract_systems`]: fails with
-    /// `Err(rusqlite::Error::SqliteFailure(..., "no such table:
-    /// mapAbstractSystems"))`, not a panic, against a database built
+    /// [`Error::MissingLayer`], not a panic, against a database built
     /// without `--with-third-party`.
     #[tracing::instrument(skip(self))]
     pub fn get_abstract_connections(
@@ -533,6 +1170,7 @@ impl<'a> SdeManager<'a> {
         regions: Vec<u32>,
     ) -> Result<HashMap<(usize, usize), SdeSegment>, Error> {
         let connection = self.get_standart_connection()?;
+        Self::require_abstract_map(&connection)?;
 
         let mut query = String::from("SELECT msc.systemA, msc.systemB, ");
         query += "masa.x, masa.y, masb.x, masb.y ";
@@ -567,10 +1205,7 @@ impl<'a> SdeManager<'a> {
                 [row.get::<usize, f64>(4)?, row.get::<usize, f64>(5)?],
                 false,
             );
-            let id = (
-                row.get::<usize, i64>(0)? as usize,
-                row.get::<usize, i64>(1)? as usize,
-            );
+            let id = (sql_index(row, 0)?, sql_index(row, 1)?);
             results.insert(id, SdeSegment { id, point1, point2 });
         }
         Ok(results)
@@ -633,8 +1268,15 @@ impl<'a> SdeManager<'a> {
 
         let connection = self.get_standart_connection()?;
         let mut result = HashMap::new();
-
-        let mut query = String::from("SELECT regionId, regionName FROM mapRegions ");
+        let name = self.localized_column(
+            &connection,
+            "mapRegions",
+            "regionName",
+            "mapRegions",
+            "mapRegions.regionId",
+        )?;
+
+        let mut query = format!("SELECT regionId, {name} AS localizedName FROM mapRegions ");
         if !regions.is_empty() || region_name.is_some() {
             let mut query_p = String::new();
 
@@ -649,20 +1291,19 @@ impl<'a> SdeManager<'a> {
                 );
                 params.push(&id_list);
             }
-            if region_name.is_some() {
+            if let Some(region_name) = &region_name {
                 if !query_p.is_empty() {
                     query_p += " AND ";
                 }
-                query_p += "LOWER(regionName) LIKE ? ";
-                _temp_value
-                    .clone_from(&("%".to_string() + region_name.clone().unwrap().as_str() + "%"));
+                query_p += "LOWER(localizedName) LIKE ? ";
+                _temp_value.clone_from(&format!("%{region_name}%"));
                 params.push(&_temp_value);
             }
             if !query_p.is_empty() {
                 query += &(" WHERE ".to_owned() + &query_p);
             }
         }
-        query += "ORDER BY regionName ";
+        query += "ORDER BY localizedName ";
 
         let mut statement = connection.prepare(query.as_str())?;
         let mut rows;
@@ -702,9 +1343,9 @@ impl<'a> SdeManager<'a> {
         }
 
         while let Some(row) = rows.next()? {
-            result
-                .entry(row.get(0)?)
-                .and_modify(|xregion| xregion.constellations.push(row.get(1).unwrap()));
+            if let Some(xregion) = result.get_mut(&row.get::<usize, u32>(0)?) {
+                xregion.constellations.push(row.get(1)?);
+            }
         }
         Ok(result)
     }
@@ -731,8 +1372,14 @@ impl<'a> SdeManager<'a> {
         let connection = self.get_standart_connection()?;
         let mut result = HashMap::new();
 
-        let mut query =
-            String::from("SELECT mss.solarSystemId, mss.solarSystemName, mc.regionId, ");
+        let name = self.localized_column(
+            &connection,
+            "mapSolarSystems",
+            "solarSystemName",
+            "mss",
+            "mss.solarSystemId",
+        )?;
+        let mut query = format!("SELECT mss.solarSystemId, {name}, mc.regionId, ");
         query += " mss.centerX, mss.centerY, mss.centerZ, mss.position2DX, mss.position2DY, ";
         query += " mss.constellationId FROM mapSolarSystems AS mss ";
         query +=
@@ -849,7 +1496,14 @@ impl<'a> SdeManager<'a> {
         let mut result = HashMap::new();
         let mut constellations = Vec::new();
 
-        let mut query = String::from("SELECT constellationId, constellationName, regionId ");
+        let name = self.localized_column(
+            &connection,
+            "mapConstellations",
+            "constellationName",
+            "mapConstellations",
+            "mapConstellations.constellationId",
+        )?;
+        let mut query = format!("SELECT constellationId, {name}, regionId ");
         query += "FROM mapConstellations ";
         if !regions.is_empty() {
             query += "WHERE regionId IN rarray(?1);";
@@ -892,9 +1546,9 @@ impl<'a> SdeManager<'a> {
         let mut rows = statement.query(params![id_list])?;
 
         while let Some(row) = rows.next()? {
-            result
-                .entry(row.get(0)?)
-                .and_modify(|constel| constel.solar_systems.push(row.get(1).unwrap()));
+            if let Some(constel) = result.get_mut(&row.get::<usize, u32>(0)?) {
+                constel.solar_systems.push(row.get(1)?);
+            }
         }
 
         Ok(result)
@@ -986,4 +1640,301 @@ impl<'a> SdeManager<'a> {
 
         Ok(result)
     }
+
+    /// Details of the given `systems` (an id allowlist; empty means
+    /// every system), sorted by name: security, constellation and region
+    /// (names in `self.language`, like the system's), faction and the
+    /// systems one jump away -- everything `sde-builder query system`
+    /// prints. Reads `security`/`securityClass`/`factionId`, which every
+    /// database the builder writes has.
+    #[tracing::instrument(skip(self))]
+    pub fn get_system_details(&self, systems: Vec<u32>) -> Result<Vec<SystemDetails>, Error> {
+        let connection = self.get_standart_connection()?;
+        let system_name = self.localized_column(
+            &connection,
+            "mapSolarSystems",
+            "solarSystemName",
+            "mss",
+            "mss.solarSystemId",
+        )?;
+        let constellation_name = self.localized_column(
+            &connection,
+            "mapConstellations",
+            "constellationName",
+            "mc",
+            "mc.constellationId",
+        )?;
+        let region_name =
+            self.localized_column(&connection, "mapRegions", "regionName", "mr", "mr.regionId")?;
+
+        let mut query = format!(
+            "SELECT mss.solarSystemId, {system_name} AS localizedName, mss.security, \
+             mss.securityClass, mc.constellationId, {constellation_name}, mr.regionId, \
+             {region_name}, mss.factionId "
+        );
+        query += "FROM mapSolarSystems AS mss ";
+        query +=
+            "INNER JOIN mapConstellations AS mc ON (mc.constellationId = mss.constellationId) ";
+        query += "INNER JOIN mapRegions AS mr ON (mr.regionId = mc.regionId) ";
+        if !systems.is_empty() {
+            query += "WHERE mss.solarSystemId IN rarray(?1) ";
+        }
+        query += "ORDER BY localizedName, mss.solarSystemId";
+
+        let mut statement = connection.prepare(query.as_str())?;
+        let mut rows;
+        if systems.is_empty() {
+            rows = statement.query([])?;
+        } else {
+            let id_list: array::Array = Rc::new(
+                systems
+                    .into_iter()
+                    .map(rusqlite::types::Value::from)
+                    .collect::<Vec<rusqlite::types::Value>>(),
+            );
+            rows = statement.query([id_list])?;
+        }
+
+        let mut graph = Self::connection_graph(&connection)?;
+        let mut result = Vec::new();
+        while let Some(row) = rows.next()? {
+            let id: u32 = row.get(0)?;
+            result.push(SystemDetails {
+                id,
+                name: row.get(1)?,
+                security: row.get(2)?,
+                security_class: row.get(3)?,
+                constellation_id: row.get(4)?,
+                constellation_name: row.get(5)?,
+                region_id: row.get(6)?,
+                region_name: row.get(7)?,
+                faction_id: row.get(8)?,
+                connections: graph.remove(&id).unwrap_or_default(),
+            });
+        }
+        Ok(result)
+    }
+
+    /// The shortest route by stargate jumps from system `from` to
+    /// system `to`, both ends included (`[from]` when they're the same
+    /// system), or `Ok(None)` when no chain of gates links them (e.g. a
+    /// wormhole system). Of several equally short routes, the one through
+    /// the lowest ids wins, so the answer is stable. An id that isn't a
+    /// solar system is [`Error::NotFound`].
+    #[tracing::instrument(skip(self))]
+    pub fn get_route(&self, from: u32, to: u32) -> Result<Option<Vec<u32>>, Error> {
+        let connection = self.get_standart_connection()?;
+        for id in [from, to] {
+            Self::require_system(&connection, id)?;
+        }
+        let graph = Self::connection_graph(&connection)?;
+
+        let mut previous: HashMap<u32, u32> = HashMap::from([(from, from)]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(system) = queue.pop_front() {
+            if system == to {
+                let mut route = vec![to];
+                let mut current = to;
+                while current != from {
+                    current = previous[&current];
+                    route.push(current);
+                }
+                route.reverse();
+                return Ok(Some(route));
+            }
+            for &next in graph.get(&system).into_iter().flatten() {
+                if let std::collections::hash_map::Entry::Vacant(entry) = previous.entry(next) {
+                    entry.insert(system);
+                    queue.push_back(next);
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Every solar system within `jumps` stargate jumps of `system`
+    /// (itself excluded), with how many jumps away it is, sorted nearest
+    /// first and by id within the same distance. An id that isn't a
+    /// solar system is [`Error::NotFound`].
+    #[tracing::instrument(skip(self))]
+    pub fn get_neighbors(&self, system: u32, jumps: usize) -> Result<Vec<(u32, usize)>, Error> {
+        let connection = self.get_standart_connection()?;
+        Self::require_system(&connection, system)?;
+        let graph = Self::connection_graph(&connection)?;
+
+        let mut distance: HashMap<u32, usize> = HashMap::from([(system, 0)]);
+        let mut queue = VecDeque::from([system]);
+        while let Some(current) = queue.pop_front() {
+            let next_distance = distance[&current] + 1;
+            if next_distance > jumps {
+                continue;
+            }
+            for &next in graph.get(&current).into_iter().flatten() {
+                if let std::collections::hash_map::Entry::Vacant(entry) = distance.entry(next) {
+                    entry.insert(next_distance);
+                    queue.push_back(next);
+                }
+            }
+        }
+        distance.remove(&system);
+        let mut result: Vec<(u32, usize)> = distance.into_iter().collect();
+        result.sort_by_key(|&(id, jumps)| (jumps, id));
+        Ok(result)
+    }
+
+    /// Every NPC station, optionally narrowed to the given
+    /// `solar_systems` (an id allowlist; empty means no filter), sorted
+    /// by name. Names are composed the way the search index does (see
+    /// [`search::station_name`]), from base-language columns.
+    #[tracing::instrument(skip(self))]
+    pub fn get_stations(&self, solar_systems: Vec<u32>) -> Result<Vec<Station>, Error> {
+        let connection = self.get_standart_connection()?;
+
+        let mut query = String::from(
+            "SELECT st.stationId, mss.solarSystemName, st.celestialIndex, st.orbitIndex, \
+             corp.corporationName, op.operationName, st.useOperationName, st.solarSystemId, \
+             st.ownerId, st.typeId ",
+        );
+        query += "FROM npcStations AS st ";
+        query += "INNER JOIN mapSolarSystems AS mss ON (mss.solarSystemId = st.solarSystemId) ";
+        query += "INNER JOIN npcCorporations AS corp ON (corp.corporationId = st.ownerId) ";
+        query += "INNER JOIN stationOperations AS op ON (op.operationId = st.operationId) ";
+        if !solar_systems.is_empty() {
+            query += "WHERE st.solarSystemId IN rarray(?1)";
+        }
+
+        let mut statement = connection.prepare(query.as_str())?;
+        let mut rows;
+        if solar_systems.is_empty() {
+            rows = statement.query([])?;
+        } else {
+            let id_list: array::Array = Rc::new(
+                solar_systems
+                    .into_iter()
+                    .map(rusqlite::types::Value::from)
+                    .collect::<Vec<rusqlite::types::Value>>(),
+            );
+            rows = statement.query([id_list])?;
+        }
+
+        let mut result = Vec::new();
+        while let Some(row) = rows.next()? {
+            let owner_name: String = row.get(4)?;
+            let operation_name: String = row.get(5)?;
+            let use_operation_name: bool = row.get(6)?;
+            result.push(Station {
+                id: row.get(0)?,
+                name: search::station_name(
+                    &row.get::<usize, String>(1)?,
+                    row.get(2)?,
+                    row.get(3)?,
+                    &owner_name,
+                    use_operation_name.then_some(operation_name.as_str()),
+                ),
+                solar_system: row.get(7)?,
+                owner: row.get(8)?,
+                owner_name,
+                type_id: row.get(9)?,
+            });
+        }
+        result.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+        Ok(result)
+    }
+
+    /// The given item `types` (an id allowlist; empty means every type),
+    /// with their group and category, sorted by name (in
+    /// `self.language`, like the group's and category's).
+    #[tracing::instrument(skip(self))]
+    pub fn get_types(&self, types: Vec<u32>) -> Result<Vec<ItemType>, Error> {
+        let connection = self.get_standart_connection()?;
+        let type_name =
+            self.localized_column(&connection, "invTypes", "typeName", "it", "it.typeId")?;
+        let group_name =
+            self.localized_column(&connection, "invGroups", "groupName", "ig", "ig.groupId")?;
+        let category_name = self.localized_column(
+            &connection,
+            "invCategories",
+            "categoryName",
+            "ic",
+            "ic.categoryId",
+        )?;
+
+        let mut query = format!(
+            "SELECT it.typeId, {type_name} AS localizedName, ig.groupId, {group_name}, \
+             ic.categoryId, {category_name}, it.published, it.volume "
+        );
+        query += "FROM invTypes AS it ";
+        query += "LEFT JOIN invGroups AS ig ON (ig.groupId = it.groupId) ";
+        query += "LEFT JOIN invCategories AS ic ON (ic.categoryId = ig.categoryId) ";
+        if !types.is_empty() {
+            query += "WHERE it.typeId IN rarray(?1) ";
+        }
+        query += "ORDER BY localizedName, it.typeId";
+
+        let mut statement = connection.prepare(query.as_str())?;
+        let mut rows;
+        if types.is_empty() {
+            rows = statement.query([])?;
+        } else {
+            let id_list: array::Array = Rc::new(
+                types
+                    .into_iter()
+                    .map(rusqlite::types::Value::from)
+                    .collect::<Vec<rusqlite::types::Value>>(),
+            );
+            rows = statement.query([id_list])?;
+        }
+
+        let mut result = Vec::new();
+        while let Some(row) = rows.next()? {
+            result.push(ItemType {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                group_id: row.get(2)?,
+                group_name: row.get(3)?,
+                category_id: row.get(4)?,
+                category_name: row.get(5)?,
+                published: row.get(6)?,
+                volume: row.get(7)?,
+            });
+        }
+        Ok(result)
+    }
+
+    /// `mapSystemConnections` as an adjacency list: each system's
+    /// neighbors, both directions, sorted by id.
+    fn connection_graph(connection: &Connection) -> Result<HashMap<u32, Vec<u32>>, Error> {
+        let mut graph: HashMap<u32, Vec<u32>> = HashMap::new();
+        let mut statement =
+            connection.prepare("SELECT systemA, systemB FROM mapSystemConnections")?;
+        let mut rows = statement.query([])?;
+        while let Some(row) = rows.next()? {
+            let system_a: u32 = row.get(0)?;
+            let system_b: u32 = row.get(1)?;
+            graph.entry(system_a).or_default().push(system_b);
+            graph.entry(system_b).or_default().push(system_a);
+        }
+        for neighbors in graph.values_mut() {
+            neighbors.sort_unstable();
+            neighbors.dedup();
+        }
+        Ok(graph)
+    }
+
+    /// [`Error::NotFound`] unless `id` is a solar system.
+    fn require_system(connection: &Connection, id: u32) -> Result<(), Error> {
+        let exists: bool = connection.query_row(
+            "SELECT EXISTS (SELECT 1 FROM mapSolarSystems WHERE solarSystemId = ?1)",
+            [id],
+            |row| row.get(0),
+        )?;
+        if exists {
+            Ok(())
+        } else {
+            Err(Error::NotFound {
+                kind: "solar system",
+                key: id.to_string(),
+            })
+        }
+    }
 }
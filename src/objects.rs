@@ -2,13 +2,11 @@ use rstar::{AABB, PointDistance, RTreeObject};
 use std::collections::HashMap;
 use std::ops::{Add, Div, DivAssign, Mul, MulAssign, Sub};
 
-/// Axis choice for a 3D-to-2D projection -- shared between
-/// [`SdePoint::to_2d`] (explicit, caller-chosen axis) and
-/// `builder::parser::isometric_projection_2d` (the isometric map
-/// projection). Lives here, not in `builder`, since `SdePoint` needs it
-/// on the read side too, and `objects.rs` isn't gated by the `builder`
-/// feature.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Axis choice for a 3D-to-2D projection -- the axis every
+/// [`Projection`] mode collapses. Lives here, not in `builder`, since
+/// `SdePoint` needs it on the read side too, and `objects.rs` isn't
+/// gated by the `builder` feature.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum ProjectedAxis {
     X,
     /// Default.
@@ -17,6 +15,172 @@ pub enum ProjectedAxis {
     Z,
 }
 
+impl ProjectedAxis {
+    /// `"X"`, `"Y"` or `"Z"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ProjectedAxis::X => "X",
+            ProjectedAxis::Y => "Y",
+            ProjectedAxis::Z => "Z",
+        }
+    }
+}
+
+/// A 3D-to-2D map projection: a mode plus the axis it collapses. Used
+/// by [`SdePoint::to_2d`] on the read side, and by the builder both for
+/// `position2DX`/`position2DY` and for the extra projections it stores
+/// side by side in `mapSolarSystemProjections` (keyed by
+/// [`Self::name`]).
+///
+/// Formulas, with `(a, b)` the two kept components and `c` the collapsed
+/// one -- `(x, y; z)`, `(x, z; y)` and `(y, z; x)` for the Z, Y and X
+/// axes respectively (from <https://www.compuphase.com/axometr.htm>):
+/// - `Orthographic`: `(a, b)` -- a true top-down view along the axis.
+/// - `Isometric`: `(a - c, b + (a + c) / 2)` -- all three axes
+///   foreshortened equally.
+/// - `Dimetric`: `(a - c / 2, b + (a + c / 2) / 2)` -- the isometric
+///   formula with the collapsed axis at half scale (the 1:1:1/2
+///   dimetric view), flattening depth so the map reads closer to
+///   top-down while still showing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Projection {
+    Orthographic(ProjectedAxis),
+    Isometric(ProjectedAxis),
+    Dimetric(ProjectedAxis),
+}
+
+impl Default for Projection {
+    /// `Isometric(Y)`, what `position2DX`/`position2DY` have always been
+    /// computed with.
+    fn default() -> Self {
+        Projection::Isometric(ProjectedAxis::Y)
+    }
+}
+
+impl From<ProjectedAxis> for Projection {
+    /// Bare axis -> top-down view along it, what [`SdePoint::to_2d`]
+    /// always did with one.
+    fn from(axis: ProjectedAxis) -> Self {
+        Projection::Orthographic(axis)
+    }
+}
+
+impl Projection {
+    /// Every mode on every axis.
+    pub const ALL: [Projection; 9] = [
+        Projection::Orthographic(ProjectedAxis::X),
+        Projection::Orthographic(ProjectedAxis::Y),
+        Projection::Orthographic(ProjectedAxis::Z),
+        Projection::Isometric(ProjectedAxis::X),
+        Projection::Isometric(ProjectedAxis::Y),
+        Projection::Isometric(ProjectedAxis::Z),
+        Projection::Dimetric(ProjectedAxis::X),
+        Projection::Dimetric(ProjectedAxis::Y),
+        Projection::Dimetric(ProjectedAxis::Z),
+    ];
+
+    pub fn axis(&self) -> ProjectedAxis {
+        match self {
+            Projection::Orthographic(axis)
+            | Projection::Isometric(axis)
+            | Projection::Dimetric(axis) => *axis,
+        }
+    }
+
+    /// Stable name, `<mode><axis>` (`"isometricY"`, `"dimetricZ"`,
+    /// `"orthographicX"`): what `mapSolarSystemProjections.projection`
+    /// stores and what [`Self::from_name`] (and `FromStr`) parse back.
+    pub fn name(&self) -> String {
+        let mode = match self {
+            Projection::Orthographic(_) => "orthographic",
+            Projection::Isometric(_) => "isometric",
+            Projection::Dimetric(_) => "dimetric",
+        };
+        format!("{mode}{}", self.axis().as_str())
+    }
+
+    /// Inverse of [`Self::name`].
+    pub fn from_name(name: &str) -> Option<Projection> {
+        Projection::ALL
+            .into_iter()
+            .find(|projection| projection.name() == name)
+    }
+
+    /// Projects `(x, y, z)`, returning the two screen components.
+    pub fn project(&self, x: f64, y: f64, z: f64) -> (f64, f64) {
+        // Per axis: the two kept components and the collapsed one, in
+        // the arrangement the isometric projection has always used.
+        let (a, b, c) = match self.axis() {
+            ProjectedAxis::Z => (x, y, z),
+            ProjectedAxis::Y => (x, z, y),
+            ProjectedAxis::X => (y, z, x),
+        };
+        match self {
+            Projection::Orthographic(_) => (a, b),
+            Projection::Isometric(_) => (a - c, b + (a + c) / 2.0),
+            Projection::Dimetric(_) => (a - c / 2.0, b + (a + c / 2.0) / 2.0),
+        }
+    }
+}
+
+impl std::fmt::Display for Projection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name())
+    }
+}
+
+impl std::str::FromStr for Projection {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Projection::from_name(value).ok_or_else(|| {
+            format!(
+                "unknown projection `{value}` (expected orthographic, isometric or dimetric \
+                 followed by X, Y or Z, e.g. `isometricY`)"
+            )
+        })
+    }
+}
+
+/// Where [`crate::SdeManager`]'s map getters (`get_systems`,
+/// `get_connections`, `get_region_coordinates`) take each system's 2D
+/// position from -- see `SdeManager::projection`.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MapProjection {
+    /// `position2DX`/`position2DY` as stored at build time (CCP's own, or
+    /// the builder's `ParserConfig.position_2d_projection`). Systems
+    /// without one are left out. Default.
+    #[default]
+    Stored,
+    /// Computed at read time from `centerX`/`centerY`/`centerZ`.
+    Computed(Projection),
+    /// `(row0 . center, row1 . center)` at read time: the first two rows
+    /// of a rotation (or any linear) matrix, for a freely rotatable 3D
+    /// view.
+    Matrix([[f64; 3]; 2]),
+}
+
+impl From<Projection> for MapProjection {
+    fn from(projection: Projection) -> Self {
+        MapProjection::Computed(projection)
+    }
+}
+
+impl MapProjection {
+    /// Projects a system's `(centerX, centerY, centerZ)`; `None` for
+    /// [`MapProjection::Stored`], which doesn't compute anything.
+    pub fn project(&self, x: f64, y: f64, z: f64) -> Option<(f64, f64)> {
+        match self {
+            MapProjection::Stored => None,
+            MapProjection::Computed(projection) => Some(projection.project(x, y, z)),
+            MapProjection::Matrix([row0, row1]) => Some((
+                row0[0] * x + row0[1] * y + row0[2] * z,
+                row1[0] * x + row1[1] * y + row1[2] * z,
+            )),
+        }
+    }
+}
+
 /// A point in EVE's universe: real 3D SDE coordinates (`centerX/Y/Z`)
 /// and 2D map-query results (`get_systems`/`get_abstract_systems`)
 /// used to be two separate types (`SdePoint`, 3D `i64`, and `SdePoint`,
@@ -129,15 +293,16 @@ impl SdePoint {
         self.coords[2]
     }
 
-    /// Explicit 2D projection, dropping `axis`'s component -- replaces
-    /// the old pivot-based `TryInto<[f32;2]>` (see the struct's
-    /// docstring for why that one is gone, not just moved).
-    pub fn to_2d(&self, axis: ProjectedAxis) -> [f32; 2] {
-        match axis {
-            ProjectedAxis::X => [self.coords[1] as f32, self.coords[2] as f32],
-            ProjectedAxis::Y => [self.coords[0] as f32, self.coords[2] as f32],
-            ProjectedAxis::Z => [self.coords[0] as f32, self.coords[1] as f32],
-        }
+    /// Explicit 2D projection -- replaces the old pivot-based
+    /// `TryInto<[f32;2]>` (see the struct's docstring for why that one is
+    /// gone, not just moved). Takes any [`Projection`]; a bare
+    /// [`ProjectedAxis`] still works and means a top-down view along it
+    /// (dropping that axis's component).
+    pub fn to_2d(&self, projection: impl Into<Projection>) -> [f32; 2] {
+        let (x, y) = projection
+            .into()
+            .project(self.coords[0], self.coords[1], self.coords[2]);
+        [x as f32, y as f32]
     }
 }
 
@@ -355,6 +520,104 @@ pub fn map_segments_to_vec(tree: &rstar::RTree<SdeSegment>) -> Vec<&SdeSegment>
     tree.iter().collect()
 }
 
+/// Which of `builder::community`'s optional, third-party additions a
+/// database has (see [`crate::SdeManager::optional_layers`]) -- each is
+/// only there if the builder ran with `with_third_party` (and, past
+/// `abstract_map`, the matching `CommunityConfig` flag).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OptionalLayers {
+    /// `mapAbstractSystems`, what [`crate::SdeManager::get_abstract_systems`]
+    /// and [`crate::SdeManager::get_abstract_connections`] read.
+    pub abstract_map: bool,
+    /// `mapSolarSystems.iceBelt`.
+    pub ice_belt: bool,
+    /// `mapTriglavianStatus` and `mapSolarSystems.trigStatusID`.
+    pub triglavian_status: bool,
+    /// `mapSolarSystems.joveObservatory`.
+    pub jove_observatory: bool,
+    /// `mapSolarSystems.specialOreAnom`.
+    pub special_ore: bool,
+}
+
+/// Build provenance of a database, from its `sdeMetadata` table (see
+/// [`crate::SdeManager::metadata`]). Every field is whatever the builder
+/// that wrote the database knew: `None`/empty when it didn't (e.g. no
+/// build number for an export without an `_sde` record), never a guess.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SdeMetadata {
+    /// CCP's SDE build number (as text, an opaque identifier).
+    pub build_number: Option<String>,
+    /// CCP's release date for that build, as published (ISO 8601).
+    pub release_date: Option<String>,
+    /// Version of this crate the database was built with.
+    pub crate_version: Option<String>,
+    /// Language the base name/description columns carry
+    /// (`ParserConfig.language`).
+    pub base_language: Option<String>,
+    /// Every language stored in `trnTranslations`.
+    pub languages: Vec<String>,
+    /// Whether community-maintained, third-party data was layered in
+    /// (`ParserConfig.with_third_party`).
+    pub with_third_party: bool,
+    /// SDE export variant the database was built from (`"jsonl"` or
+    /// `"yaml"`).
+    pub format: Option<String>,
+    /// The whole `ParserConfig` used, as JSON.
+    pub parser_config: Option<String>,
+    /// dotlan's map fingerprints (region name -> ETag, Last-Modified,
+    /// Content-Length) at build time, as JSON; only with third-party
+    /// data.
+    pub map_fingerprints: Option<String>,
+}
+
+impl SdeMetadata {
+    /// `sdeMetadata` key of [`Self::build_number`].
+    pub const BUILD_NUMBER: &str = "buildNumber";
+    /// `sdeMetadata` key of [`Self::release_date`].
+    pub const RELEASE_DATE: &str = "releaseDate";
+    /// `sdeMetadata` key of [`Self::crate_version`].
+    pub const CRATE_VERSION: &str = "crateVersion";
+    /// `sdeMetadata` key of [`Self::base_language`].
+    pub const BASE_LANGUAGE: &str = "baseLanguage";
+    /// `sdeMetadata` key of [`Self::languages`] (comma-separated).
+    pub const LANGUAGES: &str = "languages";
+    /// `sdeMetadata` key of [`Self::with_third_party`] (`"1"`/`"0"`).
+    pub const WITH_THIRD_PARTY: &str = "withThirdParty";
+    /// `sdeMetadata` key of [`Self::format`].
+    pub const FORMAT: &str = "format";
+    /// `sdeMetadata` key of [`Self::parser_config`].
+    pub const PARSER_CONFIG: &str = "parserConfig";
+    /// `sdeMetadata` key of [`Self::map_fingerprints`].
+    pub const MAP_FINGERPRINTS: &str = "mapFingerprints";
+
+    /// Builds the metadata from `sdeMetadata`'s `(key, value)` rows.
+    /// Unknown keys are ignored (written by a newer builder).
+    pub fn from_entries<I: IntoIterator<Item = (String, String)>>(entries: I) -> Self {
+        let mut metadata = SdeMetadata::default();
+        for (key, value) in entries {
+            match key.as_str() {
+                Self::BUILD_NUMBER => metadata.build_number = Some(value),
+                Self::RELEASE_DATE => metadata.release_date = Some(value),
+                Self::CRATE_VERSION => metadata.crate_version = Some(value),
+                Self::BASE_LANGUAGE => metadata.base_language = Some(value),
+                Self::LANGUAGES => {
+                    metadata.languages = value
+                        .split(',')
+                        .filter(|language| !language.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                }
+                Self::WITH_THIRD_PARTY => metadata.with_third_party = value == "1",
+                Self::FORMAT => metadata.format = Some(value),
+                Self::PARSER_CONFIG => metadata.parser_config = Some(value),
+                Self::MAP_FINGERPRINTS => metadata.map_fingerprints = Some(value),
+                _ => {}
+            }
+        }
+        metadata
+    }
+}
+
 /// Note: no longer derives `Hash`/`Eq` (only `PartialEq`) since `min`/`max`
 /// became `SdePoint`, which contains `[f64; 3]` -- `f64` doesn't
 /// implement `Eq`/`Hash` (NaN isn't equal to itself). Nothing in this
@@ -582,6 +845,80 @@ impl Default for Region {
     }
 }
 
+/// A solar system's community-maintained flags, from
+/// [`crate::SdeManager::get_community_flags`]. Each one is `None` when
+/// the database was built without its layer (see [`OptionalLayers`]),
+/// so "not marked" and "unknown" stay apart.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommunityFlags {
+    /// `mapSolarSystems.iceBelt`.
+    pub ice_belt: Option<bool>,
+    /// `mapSolarSystems.joveObservatory`.
+    pub jove_observatory: Option<bool>,
+    /// `mapSolarSystems.specialOreAnom`.
+    pub special_ore: Option<bool>,
+    /// Name of the system's `mapTriglavianStatus` (`"Fortress"`, ...);
+    /// also `None` for a system without one.
+    pub triglavian_status: Option<String>,
+}
+
+/// A solar system with what a lookup needs to show about it, names
+/// resolved: from [`crate::SdeManager::get_system_details`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SystemDetails {
+    /// Solar System identifier
+    pub id: u32,
+    /// Solar System name, in `SdeManager.language`
+    pub name: String,
+    /// Security status, unrounded (`0.9459`, shown in game as `0.9`)
+    pub security: f64,
+    /// `securityClass` (`"A"`, `"B"`, ...), if the SDE gives one
+    pub security_class: Option<String>,
+    pub constellation_id: u32,
+    pub constellation_name: String,
+    pub region_id: u32,
+    pub region_name: String,
+    /// The system's own `factionId`, if it has one
+    pub faction_id: Option<u32>,
+    /// Solar systems one stargate jump away, by id
+    pub connections: Vec<u32>,
+}
+
+/// An NPC station, from [`crate::SdeManager::get_stations`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Station {
+    /// Station identifier
+    pub id: u32,
+    /// The station's in-game name, composed by
+    /// [`crate::search::station_name`] (the SDE has none)
+    pub name: String,
+    /// Solar System identifier
+    pub solar_system: u32,
+    /// Owner corporation identifier
+    pub owner: u32,
+    /// Owner corporation name
+    pub owner_name: String,
+    /// Station type identifier (`invTypes`)
+    pub type_id: u32,
+}
+
+/// An item type with its group and category, from
+/// [`crate::SdeManager::get_types`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ItemType {
+    /// Type identifier
+    pub id: u32,
+    /// Type name, in `SdeManager.language`
+    pub name: String,
+    pub group_id: Option<u32>,
+    pub group_name: Option<String>,
+    pub category_id: Option<u32>,
+    pub category_name: Option<String>,
+    pub published: bool,
+    /// Volume in m3, if the SDE gives one
+    pub volume: Option<f64>,
+}
+
 #[derive(Clone)]
 /// Struct that contains everything in EVE Onoline Universe
 ///
@@ -717,6 +1054,60 @@ mod tests {
         let _ = point.to_2d(ProjectedAxis::Y); // must not panic
     }
 
+    #[test]
+    fn sdepoint_to_2d_accepts_every_projection_mode() {
+        let point = SdePoint::new(10.0, 20.0, 30.0);
+        assert_eq!(
+            point.to_2d(Projection::Orthographic(ProjectedAxis::Y)),
+            [10.0, 30.0]
+        );
+        assert_eq!(
+            point.to_2d(Projection::Isometric(ProjectedAxis::Y)),
+            [-10.0, 45.0]
+        );
+        assert_eq!(
+            point.to_2d(Projection::Dimetric(ProjectedAxis::Y)),
+            [0.0, 40.0]
+        );
+    }
+
+    #[test]
+    fn projection_dimetric_halves_the_collapsed_axis() {
+        for axis in [ProjectedAxis::X, ProjectedAxis::Y, ProjectedAxis::Z] {
+            let (x, y, z) = (3.0, 5.0, 7.0);
+            let halved = match axis {
+                ProjectedAxis::X => (x / 2.0, y, z),
+                ProjectedAxis::Y => (x, y / 2.0, z),
+                ProjectedAxis::Z => (x, y, z / 2.0),
+            };
+            assert_eq!(
+                Projection::Dimetric(axis).project(x, y, z),
+                Projection::Isometric(axis).project(halved.0, halved.1, halved.2)
+            );
+        }
+    }
+
+    #[test]
+    fn map_projection_matrix_applies_both_rows() {
+        // 90 degrees around Z: x' = -y, y' = x.
+        let rotation = MapProjection::Matrix([[0.0, -1.0, 0.0], [1.0, 0.0, 0.0]]);
+        assert_eq!(rotation.project(1.0, 2.0, 3.0), Some((-2.0, 1.0)));
+        assert_eq!(MapProjection::Stored.project(1.0, 2.0, 3.0), None);
+        assert_eq!(
+            MapProjection::from(Projection::Isometric(ProjectedAxis::Z)).project(1.0, 2.0, 3.0),
+            Some((-2.0, 4.0))
+        );
+    }
+
+    #[test]
+    fn projection_names_round_trip() {
+        for projection in Projection::ALL {
+            assert_eq!(projection.name().parse::<Projection>(), Ok(projection));
+        }
+        assert_eq!(Projection::default().name(), "isometricY".to_string());
+        assert!("isometricW".parse::<Projection>().is_err());
+    }
+
     #[test]
     fn sdepoint_add_owned() {
         let sum = SdePoint::new(1.0, 2.0, 3.0) + SdePoint::new(10.0, 20.0, 30.0);
@@ -0,0 +1,653 @@
+//! Looking things up in a built database from the command line (feature
+//! `builder`): each function here answers one `sde-builder query`
+//! subcommand -- [`system`], [`region`], [`route`], [`neighbors`],
+//! [`stations`], [`item_type`] -- on top of the read API
+//! ([`crate::SdeManager`]), and returns a [`QueryTable`] rendered as an
+//! aligned text table, JSON or CSV ([`QueryFormat`]).
+//!
+//! Names are looked up the way the omnibox does, through
+//! [`crate::SdeManager::search`]: every exact match when there's one
+//! ("Jita", or both systems called "New Caldari" if there were two), the
+//! best-ranked match otherwise ("jit", "jtia"). An all-digits argument
+//! is taken as an id instead, which also works against a database built
+//! without the search index. Nothing found is [`Error::NotFound`].
+//!
+//! [`system`]: crate::query::system
+//! [`region`]: crate::query::region
+//! [`route`]: crate::query::route
+//! [`neighbors`]: crate::query::neighbors
+//! [`stations`]: crate::query::stations
+//! [`item_type`]: crate::query::item_type
+//! [`QueryTable`]: crate::query::QueryTable
+//! [`QueryFormat`]: crate::query::QueryFormat
+//! [`Error::NotFound`]: crate::Error::NotFound
+
+use crate::search::{MatchKind, SearchKind};
+use crate::{Error, SdeManager};
+use serde_json::{Map, Value, json};
+use std::collections::HashMap;
+
+/// How many search results a name lookup considers.
+const SEARCH_LIMIT: usize = 20;
+
+// ------------------------------------------------------------
+// Output
+// ------------------------------------------------------------
+
+/// What [`QueryTable::render`] writes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum QueryFormat {
+    /// [`QueryTable::to_table`].
+    #[default]
+    Table,
+    /// [`QueryTable::to_json`], pretty-printed.
+    Json,
+    /// [`QueryTable::to_csv`].
+    Csv,
+}
+
+impl std::str::FromStr for QueryFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "table" => Ok(QueryFormat::Table),
+            "json" => Ok(QueryFormat::Json),
+            "csv" => Ok(QueryFormat::Csv),
+            other => Err(format!(
+                "unknown query format `{other}` (expected `table`, `json` or `csv`)"
+            )),
+        }
+    }
+}
+
+/// The answer to a query: named columns and rows of JSON values lined
+/// up with them, so every format renders the same data.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryTable {
+    pub columns: Vec<&'static str>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl QueryTable {
+    /// Columns padded to their widest cell, numbers right-aligned, a
+    /// header and a dashed rule on top. `NULL`s are empty cells.
+    pub fn to_table(&self) -> String {
+        let cells: Vec<Vec<String>> = self
+            .rows
+            .iter()
+            .map(|row| row.iter().map(cell).collect())
+            .collect();
+        let widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                cells
+                    .iter()
+                    .filter_map(|row| row.get(index))
+                    .map(|cell| cell.chars().count())
+                    .chain([column.chars().count()])
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+
+        let mut lines = Vec::with_capacity(self.rows.len() + 2);
+        lines.push(
+            self.columns
+                .iter()
+                .zip(&widths)
+                .map(|(column, &width)| format!("{column:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  "),
+        );
+        lines.push(
+            widths
+                .iter()
+                .map(|&width| "-".repeat(width))
+                .collect::<Vec<_>>()
+                .join("  "),
+        );
+        for (row, row_cells) in self.rows.iter().zip(&cells) {
+            lines.push(
+                row_cells
+                    .iter()
+                    .zip(&widths)
+                    .zip(row)
+                    .map(|((text, &width), value)| {
+                        if value.is_number() {
+                            format!("{text:>width$}")
+                        } else {
+                            format!("{text:<width$}")
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("  "),
+            );
+        }
+        lines
+            .into_iter()
+            .map(|line| line.trim_end().to_string() + "\n")
+            .collect()
+    }
+
+    /// An array with one object per row, keyed by column.
+    pub fn to_json(&self) -> Value {
+        Value::Array(
+            self.rows
+                .iter()
+                .map(|row| {
+                    Value::Object(
+                        self.columns
+                            .iter()
+                            .map(|column| column.to_string())
+                            .zip(row.iter().cloned())
+                            .collect::<Map<String, Value>>(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// RFC 4180: a header line, then one line per row, fields quoted
+    /// when they hold a comma, a quote or a line break.
+    pub fn to_csv(&self) -> String {
+        let mut csv = csv_line(self.columns.iter().map(|column| column.to_string()));
+        for row in &self.rows {
+            csv += &csv_line(row.iter().map(cell));
+        }
+        csv
+    }
+
+    /// The table in `format`, newline-terminated.
+    pub fn render(&self, format: QueryFormat) -> String {
+        match format {
+            QueryFormat::Table => self.to_table(),
+            QueryFormat::Json => {
+                serde_json::to_string_pretty(&self.to_json()).unwrap_or_default() + "\n"
+            }
+            QueryFormat::Csv => self.to_csv(),
+        }
+    }
+}
+
+/// A value as plain text: strings unquoted, `NULL` empty.
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn csv_line(fields: impl Iterator<Item = String>) -> String {
+    fields
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+        + "\r\n"
+}
+
+// ------------------------------------------------------------
+// Queries
+// ------------------------------------------------------------
+
+/// `query system <name>`: each matching system's security, constellation,
+/// region and the systems its gates lead to.
+pub fn system(sde: &SdeManager, name: &str) -> Result<QueryTable, Error> {
+    let ids = resolve(sde, name, SearchKind::SolarSystem, "solar system")?;
+    let systems = sde.get_system_details(ids)?;
+    if systems.is_empty() {
+        return Err(not_found("solar system", name));
+    }
+    let names = system_names(
+        sde,
+        systems
+            .iter()
+            .flat_map(|system| system.connections.iter().copied())
+            .collect(),
+    )?;
+    Ok(QueryTable {
+        columns: vec!["id", "name", "security", "constellation", "region", "gates"],
+        rows: systems
+            .into_iter()
+            .map(|system| {
+                let gates: Vec<&str> = system
+                    .connections
+                    .iter()
+                    .filter_map(|id| names.get(id).map(String::as_str))
+                    .collect();
+                vec![
+                    json!(system.id),
+                    json!(system.name),
+                    security(system.security),
+                    json!(system.constellation_name),
+                    json!(system.region_name),
+                    json!(gates.join(", ")),
+                ]
+            })
+            .collect(),
+    })
+}
+
+/// `query region <name>`: each matching region with how many
+/// constellations and systems it has.
+pub fn region(sde: &SdeManager, name: &str) -> Result<QueryTable, Error> {
+    let ids = resolve(sde, name, SearchKind::Region, "region")?;
+    let regions = sde.get_region(ids, None)?;
+    if regions.is_empty() {
+        return Err(not_found("region", name));
+    }
+    let mut system_counts: HashMap<u32, usize> = HashMap::new();
+    for system in sde.get_system_details(Vec::new())? {
+        *system_counts.entry(system.region_id).or_default() += 1;
+    }
+    let mut regions: Vec<_> = regions.into_values().collect();
+    regions.sort_by(|a, b| a.name.cmp(&b.name).then(a.id.cmp(&b.id)));
+    Ok(QueryTable {
+        columns: vec!["id", "name", "constellations", "systems"],
+        rows: regions
+            .into_iter()
+            .map(|region| {
+                vec![
+                    json!(region.id),
+                    json!(region.name),
+                    json!(region.constellations.len()),
+                    json!(system_counts.get(&region.id).copied().unwrap_or_default()),
+                ]
+            })
+            .collect(),
+    })
+}
+
+/// `query route <from> <to>`: the shortest route by gate jumps, one row
+/// per system, the starting one at jump 0.
+pub fn route(sde: &SdeManager, from: &str, to: &str) -> Result<QueryTable, Error> {
+    let from_id = resolve_one(sde, from, SearchKind::SolarSystem, "solar system")?;
+    let to_id = resolve_one(sde, to, SearchKind::SolarSystem, "solar system")?;
+    let route = sde
+        .get_route(from_id, to_id)?
+        .ok_or_else(|| not_found("route", &format!("{from} -> {to}")))?;
+    let systems = system_details(sde, route.clone())?;
+    Ok(QueryTable {
+        columns: vec!["jump", "id", "name", "security"],
+        rows: route
+            .into_iter()
+            .enumerate()
+            .map(|(jump, id)| system_row(json!(jump), id, &systems))
+            .collect(),
+    })
+}
+
+/// `query neighbors <system> --jumps N`: every system within `jumps`
+/// gate jumps, nearest first.
+pub fn neighbors(sde: &SdeManager, system: &str, jumps: usize) -> Result<QueryTable, Error> {
+    let id = resolve_one(sde, system, SearchKind::SolarSystem, "solar system")?;
+    let neighbors = sde.get_neighbors(id, jumps)?;
+    let systems = system_details(sde, neighbors.iter().map(|&(id, _)| id).collect())?;
+    Ok(QueryTable {
+        columns: vec!["jumps", "id", "name", "security"],
+        rows: neighbors
+            .into_iter()
+            .map(|(id, jumps)| system_row(json!(jumps), id, &systems))
+            .collect(),
+    })
+}
+
+/// `query stations <system>`: the NPC stations in the matching
+/// system(s).
+pub fn stations(sde: &SdeManager, system: &str) -> Result<QueryTable, Error> {
+    let ids = resolve(sde, system, SearchKind::SolarSystem, "solar system")?;
+    if sde.get_system_details(ids.clone())?.is_empty() {
+        return Err(not_found("solar system", system));
+    }
+    Ok(QueryTable {
+        columns: vec!["id", "name", "owner"],
+        rows: sde
+            .get_stations(ids)?
+            .into_iter()
+            .map(|station| {
+                vec![
+                    json!(station.id),
+                    json!(station.name),
+                    json!(station.owner_name),
+                ]
+            })
+            .collect(),
+    })
+}
+
+/// `query type <name>`: each matching item type with its group,
+/// category and volume. Only published types are in the search index,
+/// so an unpublished one is found by id only.
+pub fn item_type(sde: &SdeManager, name: &str) -> Result<QueryTable, Error> {
+    let ids = resolve(sde, name, SearchKind::Type, "type")?;
+    let types = sde.get_types(ids)?;
+    if types.is_empty() {
+        return Err(not_found("type", name));
+    }
+    Ok(QueryTable {
+        columns: vec!["id", "name", "group", "category", "published", "volume"],
+        rows: types
+            .into_iter()
+            .map(|item_type| {
+                vec![
+                    json!(item_type.id),
+                    json!(item_type.name),
+                    json!(item_type.group_name),
+                    json!(item_type.category_name),
+                    json!(item_type.published),
+                    json!(item_type.volume),
+                ]
+            })
+            .collect(),
+    })
+}
+
+// ------------------------------------------------------------
+// Helpers
+// ------------------------------------------------------------
+
+/// Ids `input` refers to (see the module docs): itself if all digits,
+/// else every exact [`SdeManager::search`] match of `kind`, else the
+/// best one.
+fn resolve(
+    sde: &SdeManager,
+    input: &str,
+    kind: SearchKind,
+    what: &'static str,
+) -> Result<Vec<u32>, Error> {
+    let input = input.trim();
+    if let Ok(id) = input.parse::<u32>() {
+        return Ok(vec![id]);
+    }
+    let results = sde.search(input, &[kind], SEARCH_LIMIT)?;
+    let Some(best) = results.first() else {
+        return Err(not_found(what, input));
+    };
+    let ids = if best.match_kind == MatchKind::Exact {
+        results
+            .iter()
+            .filter(|result| result.match_kind == MatchKind::Exact)
+            .filter_map(|result| u32::try_from(result.id).ok())
+            .collect()
+    } else {
+        vec![u32::try_from(best.id).map_err(|_| not_found(what, input))?]
+    };
+    Ok(ids)
+}
+
+/// The first of [`resolve`]'s ids, for queries about a single system.
+fn resolve_one(
+    sde: &SdeManager,
+    input: &str,
+    kind: SearchKind,
+    what: &'static str,
+) -> Result<u32, Error> {
+    resolve(sde, input, kind, what)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| not_found(what, input))
+}
+
+fn not_found(kind: &'static str, key: &str) -> Error {
+    Error::NotFound {
+        kind,
+        key: key.to_string(),
+    }
+}
+
+/// Names of the `ids` systems (nothing when `ids` is empty, rather than
+/// every system).
+fn system_names(sde: &SdeManager, ids: Vec<u32>) -> Result<HashMap<u32, String>, Error> {
+    Ok(system_details(sde, ids)?
+        .into_iter()
+        .map(|(id, (name, _))| (id, name))
+        .collect())
+}
+
+/// Name and security of the `ids` systems (nothing when `ids` is empty,
+/// rather than every system).
+fn system_details(sde: &SdeManager, ids: Vec<u32>) -> Result<HashMap<u32, (String, f64)>, Error> {
+    if ids.is_empty() {
+        return Ok(HashMap::new());
+    }
+    Ok(sde
+        .get_system_details(ids)?
+        .into_iter()
+        .map(|system| (system.id, (system.name, system.security)))
+        .collect())
+}
+
+/// A `[leading, id, name, security]` row of a route or neighbor list.
+fn system_row(leading: Value, id: u32, systems: &HashMap<u32, (String, f64)>) -> Vec<Value> {
+    let (name, system_security) = systems
+        .get(&id)
+        .map_or((Value::Null, Value::Null), |(name, system_security)| {
+            (json!(name), security(*system_security))
+        });
+    vec![leading, json!(id), name, system_security]
+}
+
+/// Security status to two decimals, as the raw value carries float
+/// noise (`0.9459131166`).
+fn security(value: f64) -> Value {
+    json!((value * 100.0).round() / 100.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{schema, search};
+    use rusqlite::Connection;
+    use std::path::PathBuf;
+
+    /// A small built database at a unique temporary path: two
+    /// connected systems in The Forge plus an isolated one, a station in
+    /// Jita, a ship type, and the search index.
+    fn database(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("sde-query-test-{name}-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let connection = Connection::open(&path).unwrap();
+        schema::create_schema(&connection).unwrap();
+        connection
+            .execute_batch(
+                "
+                INSERT INTO mapRegions (regionId, regionName, nebula, centerX, centerY, centerZ)
+                    VALUES (10000002, 'The Forge', 1, 0, 0, 0);
+                INSERT INTO mapConstellations (constellationId, constellationName, regionId,
+                    centerX, centerY, centerZ)
+                    VALUES (20000020, 'Kimotoro', 10000002, 0, 0, 0);
+                INSERT INTO mapSolarSystems (solarSystemId, solarSystemName, constellationId,
+                    radius, centerX, centerY, centerZ, security)
+                    VALUES (30000142, 'Jita', 20000020, 1, 0, 0, 0, 0.9459),
+                           (30000144, 'Perimeter', 20000020, 1, 0, 0, 0, 0.9),
+                           (30000145, 'New Caldari', 20000020, 1, 0, 0, 0, 1.0);
+                INSERT INTO mapSystemConnections VALUES (30000142, 30000144);
+                INSERT INTO invCategories (categoryId, categoryName, published) VALUES (6, 'Ship', 1);
+                INSERT INTO invGroups (groupId, groupName, categoryId, anchorable)
+                    VALUES (25, 'Frigate', 6, 0);
+                INSERT INTO invTypes (typeId, groupId, typeName, published, volume)
+                    VALUES (587, 25, 'Rifter', 1, 27289), (1531, 25, 'Station', 0, NULL);
+                INSERT INTO npcCorporations (corporationId, corporationName, tickerName, deleted,
+                    extent, hasPlayerPersonnelManager, initialPrice, memberLimit, minSecurity,
+                    minimumJoinStanding, sendCharTerminationMessage, shares, size, taxRate,
+                    uniqueName)
+                    VALUES (1000035, 'Caldari Navy', 'CN', 0, 'G', 0, 0, -1, 0, 0, 1, 0, 'H', 0, 1);
+                INSERT INTO stationOperations (operationId, activityId, operationName, border,
+                    corridor, fringe, hub, ratio, manufacturingFactor, researchFactor)
+                    VALUES (26, 1, 'Assembly Plant', 0, 0, 0, 0, 0, 1, 1);
+                INSERT INTO npcStations (stationId, celestialIndex, operationId, orbitIndex,
+                    ownerId, positionX, positionY, positionZ, reprocessingEfficiency,
+                    reprocessingHangarFlag, reprocessingStationsTake, solarSystemId, typeId,
+                    useOperationName)
+                    VALUES (60003760, 4, 26, 4, 1000035, 0, 0, 0, 0.5, 4, 0.05, 30000142, 1531, 1);
+                ",
+            )
+            .unwrap();
+        search::create_search_index(&connection).unwrap();
+        path
+    }
+
+    fn sample() -> QueryTable {
+        QueryTable {
+            columns: vec!["id", "name", "security"],
+            rows: vec![
+                vec![json!(30000142), json!("Jita"), json!(0.95)],
+                vec![json!(7), json!("Say \"hi\", twice"), Value::Null],
+            ],
+        }
+    }
+
+    #[test]
+    fn query_format_parses_its_names() {
+        assert_eq!("table".parse(), Ok(QueryFormat::Table));
+        assert_eq!("json".parse(), Ok(QueryFormat::Json));
+        assert_eq!("csv".parse(), Ok(QueryFormat::Csv));
+        assert!("xml".parse::<QueryFormat>().is_err());
+    }
+
+    #[test]
+    fn to_table_aligns_columns_and_right_aligns_numbers() {
+        assert_eq!(
+            sample().to_table(),
+            "id        name             security\n\
+             --------  ---------------  --------\n\
+             30000142  Jita                 0.95\n\
+             \x20      7  Say \"hi\", twice\n"
+        );
+    }
+
+    #[test]
+    fn to_csv_quotes_what_needs_quoting() {
+        assert_eq!(
+            sample().to_csv(),
+            "id,name,security\r\n30000142,Jita,0.95\r\n7,\"Say \"\"hi\"\", twice\",\r\n"
+        );
+    }
+
+    #[test]
+    fn to_json_keys_rows_by_column() {
+        assert_eq!(
+            sample().to_json()[0],
+            json!({"id": 30000142, "name": "Jita", "security": 0.95})
+        );
+    }
+
+    #[test]
+    fn system_resolves_a_name_and_lists_its_gates() {
+        let path = database("system");
+        let sde = SdeManager::new(&path, 1.0);
+
+        let table = system(&sde, "jita").unwrap();
+
+        assert_eq!(
+            table.rows,
+            vec![vec![
+                json!(30000142),
+                json!("Jita"),
+                json!(0.95),
+                json!("Kimotoro"),
+                json!("The Forge"),
+                json!("Perimeter"),
+            ]]
+        );
+        assert!(matches!(
+            system(&sde, "30000999"),
+            Err(Error::NotFound {
+                kind: "solar system",
+                ..
+            })
+        ));
+        assert!(matches!(
+            system(&sde, "zzzzzzzz"),
+            Err(Error::NotFound {
+                kind: "solar system",
+                ..
+            })
+        ));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn region_counts_constellations_and_systems() {
+        let path = database("region");
+        let sde = SdeManager::new(&path, 1.0);
+
+        assert_eq!(
+            region(&sde, "The Forge").unwrap().rows,
+            vec![vec![
+                json!(10000002),
+                json!("The Forge"),
+                json!(1),
+                json!(3)
+            ]]
+        );
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn route_lists_every_jump_or_fails_without_one() {
+        let path = database("route");
+        let sde = SdeManager::new(&path, 1.0);
+
+        let table = route(&sde, "Jita", "30000144").unwrap();
+        assert_eq!(
+            table.rows,
+            vec![
+                vec![json!(0), json!(30000142), json!("Jita"), json!(0.95)],
+                vec![json!(1), json!(30000144), json!("Perimeter"), json!(0.9)],
+            ]
+        );
+        assert!(matches!(
+            route(&sde, "Jita", "New Caldari"),
+            Err(Error::NotFound { kind: "route", .. })
+        ));
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn neighbors_stations_and_types_resolve_by_name() {
+        let path = database("others");
+        let sde = SdeManager::new(&path, 1.0);
+
+        assert_eq!(
+            neighbors(&sde, "Perimeter", 3).unwrap().rows,
+            vec![vec![json!(1), json!(30000142), json!("Jita"), json!(0.95)]]
+        );
+        assert_eq!(
+            stations(&sde, "Jita").unwrap().rows,
+            vec![vec![
+                json!(60003760),
+                json!("Jita IV - Moon 4 - Caldari Navy Assembly Plant"),
+                json!("Caldari Navy"),
+            ]]
+        );
+        assert!(stations(&sde, "Perimeter").unwrap().rows.is_empty());
+        assert_eq!(
+            item_type(&sde, "rifter").unwrap().rows,
+            vec![vec![
+                json!(587),
+                json!("Rifter"),
+                json!("Frigate"),
+                json!("Ship"),
+                json!(true),
+                json!(27289.0),
+            ]]
+        );
+        // Unpublished: not in the search index, but found by id.
+        assert_eq!(
+            item_type(&sde, "1531").unwrap().rows[0][1],
+            json!("Station")
+        );
+        std::fs::remove_file(&path).ok();
+    }
+}
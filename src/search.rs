@@ -0,0 +1,356 @@
+//! Types and matching helpers behind [`crate::SdeManager::search`].
+//!
+//! The index itself (`searchIndex`/`searchTrigrams`, two FTS5 tables) is
+//! written by the builder (`builder::search`); this module only holds
+//! what the read side needs to query it: the entity kinds it covers, the
+//! result type, how a user's raw input is turned into an FTS5 query, and
+//! the edit distance used to rank typo-tolerant matches.
+//!
+//! Matching runs in up to two passes:
+//!
+//! 1. **Prefix**: every word of the input becomes an FTS5 prefix query
+//!    (`"jit"*`) against `searchIndex` (`unicode61` tokenizer, diacritics
+//!    removed), ranked by FTS5's `bm25`. "jit" finds Jita, "caldari nav"
+//!    finds Caldari Navy.
+//! 2. **Typo-tolerant**: only when the first pass came up short, names
+//!    are kept if their [`edit_distance`] to the input is small enough
+//!    (see [`max_typo_distance`]). "jtia" still finds Jita. Inputs of
+//!    [`TRIGRAM_MIN_CHARS`] or more characters only consider candidates
+//!    sharing at least one trigram with the input (the input's trigrams
+//!    OR-ed against `searchTrigrams`, `trigram` tokenizer); shorter ones
+//!    are compared against the [`FUZZY_SCAN_LIMIT`] shortest indexed
+//!    names instead, see [`TRIGRAM_MIN_CHARS`] for why.
+//!
+//! [`edit_distance`]: crate::search::edit_distance
+//! [`max_typo_distance`]: crate::search::max_typo_distance
+//! [`TRIGRAM_MIN_CHARS`]: crate::search::TRIGRAM_MIN_CHARS
+//! [`FUZZY_SCAN_LIMIT`]: crate::search::FUZZY_SCAN_LIMIT
+
+/// What a search hit points at. The string form ([`Self::as_str`]) is
+/// what `searchIndex.kind` stores, so the builder and the read side use
+/// the same values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SearchKind {
+    SolarSystem,
+    Constellation,
+    Region,
+    /// Published items from `invTypes`.
+    Type,
+    NpcCorporation,
+    Station,
+}
+
+impl SearchKind {
+    /// Every kind, in the order results of otherwise equal rank are
+    /// listed (map entities first: they're what the omnibox is mostly
+    /// used for).
+    pub const ALL: [SearchKind; 6] = [
+        SearchKind::SolarSystem,
+        SearchKind::Constellation,
+        SearchKind::Region,
+        SearchKind::Station,
+        SearchKind::NpcCorporation,
+        SearchKind::Type,
+    ];
+
+    /// Value stored in `searchIndex.kind`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchKind::SolarSystem => "solarSystem",
+            SearchKind::Constellation => "constellation",
+            SearchKind::Region => "region",
+            SearchKind::Type => "type",
+            SearchKind::NpcCorporation => "npcCorporation",
+            SearchKind::Station => "station",
+        }
+    }
+
+    /// Inverse of [`Self::as_str`].
+    pub fn from_name(value: &str) -> Option<SearchKind> {
+        SearchKind::ALL
+            .into_iter()
+            .find(|kind| kind.as_str() == value)
+    }
+}
+
+/// How a [`SearchResult`] matched the input -- the first criterion
+/// results are sorted by, before the score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MatchKind {
+    /// The whole name equals the input (case-insensitive).
+    Exact,
+    /// Every word of the input is a prefix of some word of the name.
+    Prefix,
+    /// Found by the typo-tolerant pass only.
+    Fuzzy,
+}
+
+/// One search hit.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub kind: SearchKind,
+    /// Id of the entity in its own table (`solarSystemId`, `typeId`,
+    /// `stationId`, ...).
+    pub id: u64,
+    /// The name that matched, in the language it matched in.
+    pub name: String,
+    /// Language of `name`, `None` for the database's base columns.
+    pub language: Option<String>,
+    pub match_kind: MatchKind,
+    /// Lower is better: FTS5's `bm25` for [`MatchKind::Exact`]/
+    /// [`MatchKind::Prefix`], the [`edit_distance`] for
+    /// [`MatchKind::Fuzzy`]. Only comparable between results of the same
+    /// `match_kind`.
+    pub score: f64,
+}
+
+/// Turns raw user input into an FTS5 prefix query: every word quoted
+/// (so FTS5 operators and punctuation typed by the user are taken
+/// literally) and suffixed with `*`, implicitly AND-ed. `None` if the
+/// input has no words at all.
+pub fn prefix_query(input: &str) -> Option<String> {
+    let terms: Vec<String> = words(input)
+        .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+/// Turns raw user input into an FTS5 query for the `trigram` tokenizer:
+/// every distinct three-character window of the lowercased input,
+/// OR-ed. `None` for inputs shorter than three characters (the trigram
+/// tokenizer can't match those).
+pub fn trigram_query(input: &str) -> Option<String> {
+    let chars: Vec<char> = input.trim().to_lowercase().chars().collect();
+    if chars.len() < 3 {
+        return None;
+    }
+    let mut trigrams: Vec<String> = chars
+        .windows(3)
+        .map(|window| window.iter().collect::<String>())
+        .collect();
+    trigrams.sort();
+    trigrams.dedup();
+    Some(
+        trigrams
+            .iter()
+            .map(|trigram| format!("\"{}\"", trigram.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" OR "),
+    )
+}
+
+/// Shortest input the typo-tolerant pass narrows down via
+/// `searchTrigrams` rather than scanning every name. An input of `n`
+/// characters has `n - 2` trigrams and each edit destroys at most three
+/// of them, so with [`max_typo_distance`]'s `n / 4` edits at least one
+/// trigram is guaranteed to survive only once `n - 2 > 3 * n / 4`, i.e.
+/// from nine characters on. Below that a single typo can leave a name
+/// sharing no trigram at all with the input ("jxta" and "Jita"), so
+/// those inputs are compared against names directly, at most
+/// [`FUZZY_SCAN_LIMIT`] of them.
+pub const TRIGRAM_MIN_CHARS: usize = 9;
+
+/// Most names an input shorter than [`TRIGRAM_MIN_CHARS`] is compared
+/// against in the typo-tolerant pass: the shortest ones of the kinds
+/// searched (`ORDER BY length(name) LIMIT` in SQL), which is where a
+/// short input's near misses are -- solar systems, regions, most
+/// types. That bounds the pass on a full SDE (tens of thousands of
+/// names, most of them long station and type names), at the cost of a
+/// typo in a short input not finding a name only past that cutoff.
+pub const FUZZY_SCAN_LIMIT: usize = 20_000;
+
+/// Largest [`edit_distance`] the typo-tolerant pass accepts for an input
+/// of this many characters: one typo per four characters, at least one.
+pub fn max_typo_distance(input: &str) -> usize {
+    (input.chars().count() / 4).max(1)
+}
+
+/// Optimal string alignment distance (Levenshtein plus transposition of
+/// two adjacent characters, the most common typo) between the lowercased
+/// `a` and `b`.
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.to_lowercase().chars().collect();
+    let b: Vec<char> = b.to_lowercase().chars().collect();
+    let mut rows = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Smallest [`edit_distance`] between `input` and either the whole of
+/// `name` or any run of consecutive words in it with as many words as
+/// `input` -- so "jtia" is one typo away from "Jita IV - Moon 4 - ..."
+/// too, not just from "Jita".
+pub fn best_edit_distance(input: &str, name: &str) -> usize {
+    let input_words = words(input).count().max(1);
+    let name_words: Vec<&str> = words(name).collect();
+    let mut best = edit_distance(input, name);
+    for window in name_words.windows(input_words.min(name_words.len()).max(1)) {
+        best = best.min(edit_distance(input, &window.join(" ")));
+    }
+    best
+}
+
+fn words(text: &str) -> impl Iterator<Item = &str> {
+    text.split(|c: char| !c.is_alphanumeric() && c != '-' && c != '\'')
+        .filter(|word| word.chars().any(char::is_alphanumeric))
+}
+
+/// Composes an NPC station's in-game name (see "Station names" in
+/// `builder::search`'s docstring), e.g. `station_name("Jita", Some(4), Some(4),
+/// "Caldari Navy", Some("Assembly Plant"))` -> `"Jita IV - Moon 4 -
+/// Caldari Navy Assembly Plant"`.
+pub fn station_name(
+    system_name: &str,
+    planet_index: Option<i64>,
+    moon_index: Option<i64>,
+    corporation_name: &str,
+    operation_name: Option<&str>,
+) -> String {
+    let mut name = system_name.to_string();
+    if let Some(planet_index) = planet_index {
+        name += " ";
+        name += &roman_numeral(planet_index);
+    }
+    if let Some(moon_index) = moon_index {
+        name += &format!(" - Moon {moon_index}");
+    }
+    name += " - ";
+    name += corporation_name;
+    if let Some(operation_name) = operation_name {
+        name += " ";
+        name += operation_name;
+    }
+    name
+}
+
+/// Roman numeral for a planet's celestial index (1..=3999; anything
+/// outside that range, which no real system comes close to, falls back
+/// to the plain number).
+fn roman_numeral(value: i64) -> String {
+    if !(1..=3999).contains(&value) {
+        return value.to_string();
+    }
+    const NUMERALS: [(i64, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut remaining = value;
+    let mut result = String::new();
+    for (amount, numeral) in NUMERALS {
+        while remaining >= amount {
+            result += numeral;
+            remaining -= amount;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prefix_query_quotes_and_stars_every_word() {
+        assert_eq!(
+            prefix_query("caldari  nav").as_deref(),
+            Some("\"caldari\"* \"nav\"*")
+        );
+        assert_eq!(prefix_query("  ").as_deref(), None);
+        // FTS5 syntax typed by the user is taken literally.
+        assert_eq!(
+            prefix_query("jita OR \"amarr").as_deref(),
+            Some("\"jita\"* \"OR\"* \"amarr\"*")
+        );
+    }
+
+    #[test]
+    fn trigram_query_ors_distinct_trigrams() {
+        assert_eq!(trigram_query("Jita").as_deref(), Some("\"ita\" OR \"jit\""));
+        assert_eq!(trigram_query("ji"), None);
+    }
+
+    #[test]
+    fn edit_distance_counts_transpositions_as_one() {
+        assert_eq!(edit_distance("jita", "jita"), 0);
+        assert_eq!(edit_distance("jtia", "Jita"), 1);
+        assert_eq!(edit_distance("amar", "amarr"), 1);
+        assert_eq!(edit_distance("dodixie", "dodxie"), 1);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    #[test]
+    fn best_edit_distance_matches_inner_words() {
+        assert_eq!(
+            best_edit_distance("jtia", "Jita IV - Moon 4 - Caldari Navy Assembly Plant"),
+            1
+        );
+        assert_eq!(best_edit_distance("caldar navy", "Caldari Navy"), 1);
+    }
+
+    #[test]
+    fn search_kind_round_trips_through_its_string_form() {
+        for kind in SearchKind::ALL {
+            assert_eq!(SearchKind::from_name(kind.as_str()), Some(kind));
+        }
+        assert_eq!(SearchKind::from_name("planet"), None);
+    }
+
+    #[test]
+    fn roman_numeral_covers_planet_indexes() {
+        assert_eq!(roman_numeral(1), "I");
+        assert_eq!(roman_numeral(4), "IV");
+        assert_eq!(roman_numeral(9), "IX");
+        assert_eq!(roman_numeral(14), "XIV");
+        assert_eq!(roman_numeral(0), "0");
+    }
+
+    #[test]
+    fn station_name_composes_moon_and_operation() {
+        assert_eq!(
+            station_name(
+                "Jita",
+                Some(4),
+                Some(4),
+                "Caldari Navy",
+                Some("Assembly Plant")
+            ),
+            "Jita IV - Moon 4 - Caldari Navy Assembly Plant"
+        );
+        assert_eq!(
+            station_name("Amarr", Some(8), None, "Emperor Family", None),
+            "Amarr VIII - Emperor Family"
+        );
+    }
+}
@@ -10,8 +10,8 @@
 //! - 3 abstract systems (2 in Region Alpha, 1 in Region Beta)
 
 use rusqlite::Connection;
-use sde::SdeManager;
-use sde::objects::SdePoint;
+use sde::objects::{MapProjection, OptionalLayers, ProjectedAxis, Projection, SdePoint};
+use sde::{Error, SdeManager};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
@@ -258,6 +258,27 @@ fn connections_returns_lines_with_scaled_inverted_coords() {
     assert_eq!(line.point2, [10.0, 30.0]);
 }
 
+#[test]
+fn connections_reject_a_negative_system_id() {
+    let fixture = Fixture::new("connections_negative_id");
+    Connection::open(&fixture.path)
+        .unwrap()
+        .execute_batch(
+            "INSERT INTO mapSolarSystems (solarSystemId, solarSystemName, constellationId, \
+                position2DX, position2DY) VALUES (-1, 'Broken', 20000001, 0.0, 0.0);
+             INSERT INTO mapSystemConnections (systemA, systemB) VALUES (-1, 30000001);",
+        )
+        .unwrap();
+    let manager = fixture.manager();
+
+    assert!(matches!(
+        manager.get_connections(),
+        Err(Error::Sqlite(rusqlite::Error::IntegralValueOutOfRange(
+            0, -1
+        )))
+    ));
+}
+
 // -------------------------------------------------------------------------
 // get_region
 // -------------------------------------------------------------------------
@@ -582,6 +603,13 @@ fn abstract_systems_fails_gracefully_without_community_table() {
     let fixture = Fixture::new_without_community("abstract_systems_missing");
     let manager = fixture.manager();
     let error = manager.get_abstract_systems(vec![]).unwrap_err();
+    assert!(matches!(
+        error,
+        Error::MissingLayer {
+            table: "mapAbstractSystems",
+            ..
+        }
+    ));
     assert!(
         error.to_string().contains("mapAbstractSystems"),
         "error should mention the missing table: {error}"
@@ -594,6 +622,13 @@ fn abstract_connections_fails_gracefully_without_community_table() {
     let fixture = Fixture::new_without_community("abstract_connections_missing");
     let manager = fixture.manager();
     let error = manager.get_abstract_connections(vec![]).unwrap_err();
+    assert!(matches!(
+        error,
+        Error::MissingLayer {
+            table: "mapAbstractSystems",
+            ..
+        }
+    ));
     assert!(
         error.to_string().contains("mapAbstractSystems"),
         "error should mention the missing table: {error}"
@@ -636,3 +671,552 @@ fn region_coordinates_returns_bounding_box_per_region() {
     assert_eq!(beta.max, SdePoint::new(-5000.0, -5000.0, 0.0));
     assert_eq!(beta.min, SdePoint::new(-9000.0, -9000.0, 0.0));
 }
+
+// -------------------------------------------------------------------------
+// language (trnTranslations)
+// -------------------------------------------------------------------------
+
+/// Adds `trnTranslations` to a fixture database: German names for
+/// "Sys One" and "Region Alpha", an English-only translation for
+/// "Sys Two" (exercises the English fallback), nothing at all for
+/// "Sys Three" (exercises the base-column fallback).
+fn add_translations(fixture: &Fixture) {
+    let conn = Connection::open(&fixture.path).expect("cannot open fixture database");
+    conn.execute_batch(
+        "
+        CREATE TABLE trnTranslations (
+            tableName TEXT NOT NULL,
+            columnName TEXT NOT NULL,
+            keyId INTEGER NOT NULL,
+            languageId TEXT NOT NULL,
+            text TEXT NOT NULL,
+            PRIMARY KEY (tableName, columnName, keyId, languageId)
+        );
+        INSERT INTO trnTranslations (tableName, columnName, keyId, languageId, text) VALUES
+            ('mapSolarSystems', 'solarSystemName', 30000001, 'en', 'Sys One'),
+            ('mapSolarSystems', 'solarSystemName', 30000001, 'de', 'System Eins'),
+            ('mapSolarSystems', 'solarSystemName', 30000002, 'en', 'Sys Two (en)'),
+            ('mapRegions', 'regionName', 10000001, 'en', 'Region Alpha'),
+            ('mapRegions', 'regionName', 10000001, 'de', 'Region Alpha (de)');
+        ",
+    )
+    .expect("cannot populate trnTranslations");
+}
+
+#[test]
+fn language_defaults_to_english_translation() {
+    let fixture = Fixture::new("language_default");
+    add_translations(&fixture);
+    let manager = fixture.manager();
+    let points = manager.get_systems().unwrap();
+    assert_eq!(points[&30000001usize].name, Some(String::from("Sys One")));
+    assert_eq!(
+        points[&30000002usize].name,
+        Some(String::from("Sys Two (en)"))
+    );
+}
+
+#[test]
+fn language_falls_back_to_english_then_base_column() {
+    let fixture = Fixture::new("language_fallback");
+    add_translations(&fixture);
+    let mut manager = fixture.manager();
+    manager.language = String::from("de");
+    let points = manager.get_systems().unwrap();
+    assert_eq!(
+        points[&30000001usize].name,
+        Some(String::from("System Eins"))
+    );
+    assert_eq!(
+        points[&30000002usize].name,
+        Some(String::from("Sys Two (en)"))
+    );
+    assert_eq!(points[&30000003usize].name, Some(String::from("Sys Three")));
+
+    let regions = manager.get_region(vec![10000001], None).unwrap();
+    assert_eq!(regions[&10000001].name, "Region Alpha (de)");
+}
+
+#[test]
+fn language_applies_to_system_name_search() {
+    let fixture = Fixture::new("language_search");
+    add_translations(&fixture);
+    let mut manager = fixture.manager();
+    manager.language = String::from("de");
+    let results = manager.get_system_id(String::from("eins")).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].0, 30000001);
+    assert_eq!(results[0].1, "System Eins");
+    assert_eq!(results[0].3, "Region Alpha (de)");
+}
+
+#[test]
+fn language_is_a_value_not_sql() {
+    let fixture = Fixture::new("language_quoted");
+    add_translations(&fixture);
+    let mut manager = fixture.manager();
+    manager.language = String::from("de' OR trn.languageId = 'de");
+    let points = manager.get_systems().unwrap();
+    assert_eq!(points[&30000001usize].name, Some(String::from("Sys One")));
+}
+
+#[test]
+fn language_is_ignored_without_translation_table() {
+    let fixture = Fixture::new("language_no_table");
+    let mut manager = fixture.manager();
+    manager.language = String::from("de");
+    let points = manager.get_systems().unwrap();
+    assert_eq!(points[&30000001usize].name, Some(String::from("Sys One")));
+}
+
+// -------------------------------------------------------------------------
+// stored projections (mapSolarSystemProjections)
+// -------------------------------------------------------------------------
+
+fn add_projections(fixture: &Fixture) {
+    let conn = Connection::open(&fixture.path).unwrap();
+    conn.execute_batch(
+        "
+        CREATE TABLE mapSolarSystemProjections (
+            solarSystemId INTEGER NOT NULL,
+            projection TEXT NOT NULL,
+            x REAL NOT NULL, y REAL NOT NULL,
+            PRIMARY KEY (solarSystemId, projection)
+        );
+        INSERT INTO mapSolarSystemProjections (solarSystemId, projection, x, y) VALUES
+            (30000001, 'dimetricY', 500.0, 700.0),
+            (30000002, 'dimetricY', -500.0, -700.0),
+            (31000001, 'dimetricY', 900.0, 900.0),
+            (30000001, 'orthographicZ', 1000.0, 2000.0),
+            (30000001, 'someFutureProjection', 0.0, 0.0);
+        ",
+    )
+    .unwrap();
+}
+
+#[test]
+fn stored_projections_lists_known_names() {
+    let fixture = Fixture::new("stored_projections");
+    add_projections(&fixture);
+    let manager = SdeManager::new(&fixture.path, FACTOR);
+    assert_eq!(
+        manager.get_stored_projections().unwrap(),
+        vec![
+            Projection::Dimetric(ProjectedAxis::Y),
+            Projection::Orthographic(ProjectedAxis::Z),
+        ]
+    );
+}
+
+#[test]
+fn stored_projections_is_empty_without_table() {
+    let fixture = Fixture::new("stored_projections_no_table");
+    let manager = SdeManager::new(&fixture.path, FACTOR);
+    assert!(manager.get_stored_projections().unwrap().is_empty());
+}
+
+#[test]
+fn system_projection_scales_and_inverts_like_systempoints() {
+    let fixture = Fixture::new("system_projection");
+    add_projections(&fixture);
+    let manager = SdeManager::new(&fixture.path, FACTOR);
+    let positions = manager
+        .get_system_projection(Projection::Dimetric(ProjectedAxis::Y))
+        .unwrap();
+    // 31000001 is outside K-space, same as get_systems.
+    assert_eq!(positions.len(), 2);
+    assert_eq!(positions[&30000001], [-5.0, -7.0]);
+    assert_eq!(positions[&30000002], [5.0, 7.0]);
+    match manager.get_system_projection(Projection::Isometric(ProjectedAxis::X)) {
+        Err(Error::NotFound { kind, key }) => {
+            assert_eq!(kind, "projection");
+            assert_eq!(key, "isometricX");
+        }
+        other => panic!("expected NotFound, got {other:?}"),
+    }
+}
+
+// -------------------------------------------------------------------------
+// read-time projection (SdeManager.projection)
+// -------------------------------------------------------------------------
+
+#[test]
+fn computed_projection_uses_center_and_ignores_position_2d() {
+    let fixture = Fixture::new("computed_projection_systems");
+    // A computed projection doesn't need position2D at all.
+    Connection::open(&fixture.path)
+        .unwrap()
+        .execute(
+            "UPDATE mapSolarSystems SET position2DX = NULL, position2DY = NULL WHERE solarSystemId = 30000003",
+            [],
+        )
+        .unwrap();
+    let mut manager = fixture.manager();
+    assert_eq!(manager.get_systems().unwrap().len(), 2);
+
+    manager.projection = MapProjection::from(Projection::Orthographic(ProjectedAxis::Z));
+    let points = manager.get_systems().unwrap();
+    assert_eq!(points.len(), 3);
+    // centerX/Y (1000, 2000) / 100, inverted
+    assert_eq!(points[&30000001].coords, [-10.0, -20.0, 0.0]);
+    assert_eq!(points[&30000003].coords, [-50.0, -50.0, 0.0]);
+    assert_eq!(points[&30000002].connections.len(), 2);
+}
+
+#[test]
+fn matrix_projection_rotates_connection_endpoints() {
+    let fixture = Fixture::new("matrix_projection_connections");
+    let mut manager = fixture.manager();
+    // 90 degrees around Z: (x, y) -> (-y, x)
+    manager.projection = MapProjection::Matrix([[0.0, -1.0, 0.0], [1.0, 0.0, 0.0]]);
+    let lines = manager.get_connections().unwrap();
+    assert_eq!(lines.len(), 2);
+    let line = &lines[&(30000001, 30000002)];
+    // Sys One (1000, 2000, 3000) -> (-2000, 1000) / 100, inverted
+    assert_eq!(line.point1, [20.0, -10.0]);
+    assert_eq!(line.point2, [-20.0, 10.0]);
+}
+
+#[test]
+fn computed_projection_region_coordinates_match_stored_shape() {
+    let fixture = Fixture::new("computed_projection_regions");
+    let mut manager = fixture.manager();
+    manager.projection = MapProjection::from(Projection::Orthographic(ProjectedAxis::Z));
+    let areas = manager.get_region_coordinates().unwrap();
+
+    assert_eq!(areas.len(), 2);
+    assert_eq!(areas[0].region_id, 10000001);
+    assert_eq!(areas[0].name, "Region Alpha");
+    // centerX/Y (1000, 2000) and (-1000, -2000): symmetric, so the
+    // inversion maps the box back onto itself (and no factor, same as
+    // the stored path).
+    assert_eq!(areas[0].max, SdePoint::new(1000.0, 2000.0, 0.0));
+    assert_eq!(areas[0].min, SdePoint::new(-1000.0, -2000.0, 0.0));
+    assert_eq!(areas[1].max, SdePoint::new(-5000.0, -5000.0, 0.0));
+    assert_eq!(areas[1].min, SdePoint::new(-9000.0, -9000.0, 0.0));
+}
+
+#[test]
+fn with_projection_getters_leave_the_default_projection_alone() {
+    let fixture = Fixture::new("with_projection_getters");
+    let manager = fixture.manager();
+    let projection = MapProjection::from(Projection::Orthographic(ProjectedAxis::Z));
+
+    let points = manager.get_systems_with_projection(projection).unwrap();
+    assert_eq!(points[&30000001].coords, [-10.0, -20.0, 0.0]);
+    let lines = manager.get_connections_with_projection(projection).unwrap();
+    assert_eq!(lines[&(30000001, 30000002)].point1, [-10.0, -20.0]);
+    let areas = manager
+        .get_region_coordinates_with_projection(projection)
+        .unwrap();
+    assert_eq!(areas[0].max, SdePoint::new(1000.0, 2000.0, 0.0));
+
+    // The field still drives the plain getters.
+    assert_eq!(manager.projection, MapProjection::Stored);
+    assert_eq!(
+        manager.get_systems().unwrap(),
+        manager
+            .get_systems_with_projection(MapProjection::Stored)
+            .unwrap()
+    );
+    assert_ne!(manager.get_systems().unwrap(), points);
+}
+
+// -------------------------------------------------------------------------
+// metadata (sdeMetadata)
+// -------------------------------------------------------------------------
+
+#[test]
+fn metadata_is_none_without_table() {
+    let fixture = Fixture::new("metadata_no_table");
+    assert_eq!(fixture.manager().metadata().unwrap(), None);
+}
+
+#[test]
+fn metadata_reads_known_keys_and_ignores_the_rest() {
+    let fixture = Fixture::new("metadata");
+    Connection::open(&fixture.path)
+        .unwrap()
+        .execute_batch(
+            "
+            CREATE TABLE sdeMetadata (key TEXT NOT NULL PRIMARY KEY, value TEXT NOT NULL);
+            INSERT INTO sdeMetadata (key, value) VALUES
+                ('buildNumber', '3458726'),
+                ('releaseDate', '2026-08-06T11:07:36Z'),
+                ('languages', 'de,en'),
+                ('withThirdParty', '1'),
+                ('someFutureKey', 'ignored');
+            ",
+        )
+        .unwrap();
+    let metadata = fixture.manager().metadata().unwrap().unwrap();
+    assert_eq!(metadata.build_number.as_deref(), Some("3458726"));
+    assert_eq!(
+        metadata.release_date.as_deref(),
+        Some("2026-08-06T11:07:36Z")
+    );
+    assert_eq!(metadata.languages, vec!["de", "en"]);
+    assert!(metadata.with_third_party);
+    assert_eq!(metadata.crate_version, None);
+}
+
+// -------------------------------------------------------------------------
+// open (schema version) / optional_layers
+// -------------------------------------------------------------------------
+
+#[test]
+fn open_accepts_unstamped_sde_databases() {
+    let fixture = Fixture::new("open_unstamped");
+    let manager = SdeManager::open(&fixture.path, FACTOR).unwrap();
+    assert_eq!(manager.get_systems().unwrap().len(), 3);
+}
+
+#[test]
+fn open_rejects_newer_schema_versions() {
+    let fixture = Fixture::new("open_newer");
+    Connection::open(&fixture.path)
+        .unwrap()
+        .pragma_update(None, "user_version", sde::SCHEMA_VERSION + 1)
+        .unwrap();
+    match SdeManager::open(&fixture.path, FACTOR).err() {
+        Some(Error::UnsupportedVersion { found, supported }) => {
+            assert_eq!(found, sde::SCHEMA_VERSION + 1);
+            assert_eq!(supported, sde::SCHEMA_VERSION);
+        }
+        other => panic!("expected UnsupportedVersion, got {other:?}"),
+    }
+}
+
+#[test]
+fn open_reports_missing_tables_without_creating_files() {
+    let fixture = Fixture::new("open_missing_tables");
+    Connection::open(&fixture.path)
+        .unwrap()
+        .execute_batch("DROP TABLE mapMoons; DROP TABLE mapPlanets;")
+        .unwrap();
+    match SdeManager::open(&fixture.path, FACTOR).err() {
+        Some(Error::SchemaMismatch { missing_tables }) => {
+            assert_eq!(missing_tables, vec!["mapPlanets", "mapMoons"]);
+        }
+        other => panic!("expected SchemaMismatch, got {other:?}"),
+    }
+
+    let missing = fixture.path.with_extension("missing.db");
+    assert!(matches!(
+        SdeManager::open(&missing, FACTOR),
+        Err(Error::Sqlite(_))
+    ));
+    assert!(!missing.exists());
+}
+
+#[test]
+fn optional_layers_reflect_the_community_tables() {
+    let with_community = Fixture::new("layers_community");
+    let layers = with_community.manager().optional_layers().unwrap();
+    assert!(layers.abstract_map);
+    assert!(!layers.ice_belt);
+    assert!(!layers.triglavian_status);
+
+    let without_community = Fixture::new_without_community("layers_plain");
+    Connection::open(&without_community.path)
+        .unwrap()
+        .execute_batch("ALTER TABLE mapSolarSystems ADD COLUMN iceBelt INTEGER;")
+        .unwrap();
+    let layers = without_community.manager().optional_layers().unwrap();
+    assert_eq!(
+        layers,
+        OptionalLayers {
+            ice_belt: true,
+            ..OptionalLayers::default()
+        }
+    );
+}
+
+#[test]
+fn malformed_rows_are_errors_not_panics() {
+    // get_region used to unwrap() each constellation id inside a
+    // closure; one that doesn't fit its u32 now surfaces as
+    // Error::Sqlite.
+    let fixture = Fixture::new("malformed_rows");
+    Connection::open(&fixture.path)
+        .unwrap()
+        .execute(
+            "UPDATE mapConstellations SET constellationId = 5000000000 WHERE constellationId = 20000001",
+            [],
+        )
+        .unwrap();
+    let manager = fixture.manager();
+    assert!(matches!(
+        manager.get_region(vec![], None),
+        Err(Error::Sqlite(_))
+    ));
+}
+
+// -------------------------------------------------------------------------
+// get_system_details / get_route / get_neighbors / get_stations / get_types
+// -------------------------------------------------------------------------
+
+/// Adds what the fixture lacks for these getters: security and faction
+/// columns, an NPC station in "Sys One", and a ship type.
+fn add_query_tables(fixture: &Fixture) {
+    let conn = Connection::open(&fixture.path).expect("cannot open fixture database");
+    conn.execute_batch(
+        "
+        ALTER TABLE mapSolarSystems ADD COLUMN security REAL NOT NULL DEFAULT 0;
+        ALTER TABLE mapSolarSystems ADD COLUMN securityClass TEXT;
+        ALTER TABLE mapSolarSystems ADD COLUMN factionId INTEGER;
+        UPDATE mapSolarSystems SET security = 0.9, securityClass = 'B', factionId = 500001
+            WHERE solarSystemId = 30000001;
+        UPDATE mapSolarSystems SET security = -0.2 WHERE solarSystemId = 30000003;
+        CREATE TABLE npcCorporations (corporationId INTEGER PRIMARY KEY, corporationName TEXT NOT NULL);
+        CREATE TABLE stationOperations (operationId INTEGER PRIMARY KEY, operationName TEXT NOT NULL);
+        CREATE TABLE npcStations (
+            stationId INTEGER PRIMARY KEY,
+            celestialIndex INTEGER, orbitIndex INTEGER,
+            operationId INTEGER NOT NULL,
+            ownerId INTEGER NOT NULL,
+            solarSystemId INTEGER NOT NULL,
+            typeId INTEGER NOT NULL,
+            useOperationName INTEGER NOT NULL
+        );
+        CREATE TABLE invTypes (
+            typeId INTEGER PRIMARY KEY,
+            groupId INTEGER,
+            typeName TEXT NOT NULL,
+            published INTEGER NOT NULL,
+            volume REAL
+        );
+        INSERT INTO npcCorporations VALUES (1000035, 'Caldari Navy');
+        INSERT INTO stationOperations VALUES (26, 'Assembly Plant');
+        INSERT INTO npcStations VALUES
+            (60003760, 1, NULL, 26, 1000035, 30000001, 1531, 1),
+            (60003761, 2, 1, 26, 1000035, 30000001, 1531, 0);
+        INSERT INTO invGroups (groupId, categoryId, groupName) VALUES (25, 22, 'Frigate');
+        INSERT INTO invTypes VALUES
+            (587, 25, 'Rifter', 1, 27289.0),
+            (1531, NULL, 'Station', 0, NULL);
+        ",
+    )
+    .expect("cannot populate query tables");
+}
+
+#[test]
+fn system_details_join_names_security_and_connections() {
+    let fixture = Fixture::new("system_details");
+    add_query_tables(&fixture);
+    let details = fixture
+        .manager()
+        .get_system_details(vec![30000001, 30000002])
+        .unwrap();
+    assert_eq!(details.len(), 2);
+    assert_eq!(details[0].name, "Sys One");
+    assert_eq!(details[0].security, 0.9);
+    assert_eq!(details[0].security_class.as_deref(), Some("B"));
+    assert_eq!(details[0].constellation_name, "Const One");
+    assert_eq!(details[0].region_name, "Region Alpha");
+    assert_eq!(details[0].faction_id, Some(500001));
+    assert_eq!(details[0].connections, vec![30000002]);
+    assert_eq!(details[1].name, "Sys Two");
+    assert_eq!(details[1].connections, vec![30000001, 30000003]);
+
+    let all = fixture.manager().get_system_details(vec![]).unwrap();
+    assert_eq!(all.len(), 4);
+}
+
+#[test]
+fn route_follows_gates_in_both_directions() {
+    let fixture = Fixture::new("route");
+    add_query_tables(&fixture);
+    let manager = fixture.manager();
+    assert_eq!(
+        manager.get_route(30000003, 30000001).unwrap(),
+        Some(vec![30000003, 30000002, 30000001])
+    );
+    assert_eq!(
+        manager.get_route(30000001, 30000001).unwrap(),
+        Some(vec![30000001])
+    );
+    assert_eq!(manager.get_route(30000001, 31000001).unwrap(), None);
+    assert!(matches!(
+        manager.get_route(30000001, 30009999),
+        Err(Error::NotFound {
+            kind: "solar system",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn neighbors_are_limited_by_jumps_and_sorted_by_distance() {
+    let fixture = Fixture::new("neighbors");
+    add_query_tables(&fixture);
+    let manager = fixture.manager();
+    assert_eq!(
+        manager.get_neighbors(30000001, 1).unwrap(),
+        vec![(30000002, 1)]
+    );
+    assert_eq!(
+        manager.get_neighbors(30000001, 5).unwrap(),
+        vec![(30000002, 1), (30000003, 2)]
+    );
+    assert!(manager.get_neighbors(30000001, 0).unwrap().is_empty());
+    assert!(manager.get_neighbors(31000001, 3).unwrap().is_empty());
+}
+
+#[test]
+fn stations_compose_their_names() {
+    let fixture = Fixture::new("stations");
+    add_query_tables(&fixture);
+    let manager = fixture.manager();
+    let stations = manager.get_stations(vec![30000001]).unwrap();
+    let names: Vec<&str> = stations.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec![
+            "Sys One I - Caldari Navy Assembly Plant",
+            "Sys One II - Moon 1 - Caldari Navy",
+        ]
+    );
+    assert_eq!(stations[0].owner_name, "Caldari Navy");
+    assert_eq!(stations[0].solar_system, 30000001);
+    assert!(manager.get_stations(vec![30000002]).unwrap().is_empty());
+    assert_eq!(manager.get_stations(vec![]).unwrap().len(), 2);
+}
+
+#[test]
+fn types_join_group_and_category() {
+    let fixture = Fixture::new("types");
+    add_query_tables(&fixture);
+    let types = fixture.manager().get_types(vec![587, 1531]).unwrap();
+    assert_eq!(types.len(), 2);
+    assert_eq!(types[0].name, "Rifter");
+    assert_eq!(types[0].group_name.as_deref(), Some("Frigate"));
+    assert_eq!(types[0].category_name.as_deref(), Some("Deployable"));
+    assert!(types[0].published);
+    assert_eq!(types[0].volume, Some(27289.0));
+    assert_eq!(types[1].name, "Station");
+    assert_eq!(types[1].group_id, None);
+    assert!(!types[1].published);
+}
+
+#[test]
+fn community_flags_are_none_for_layers_not_built() {
+    let fixture = Fixture::new("community_flags");
+    let manager = fixture.manager();
+    let flags = manager.get_community_flags(vec![30000001]).unwrap();
+    assert_eq!(flags.len(), 1);
+    assert_eq!(flags[&30000001], Default::default());
+
+    Connection::open(&fixture.path)
+        .unwrap()
+        .execute_batch(
+            "ALTER TABLE mapSolarSystems ADD COLUMN iceBelt INTEGER NOT NULL DEFAULT 0;
+             UPDATE mapSolarSystems SET iceBelt = 1 WHERE solarSystemId = 30000002;",
+        )
+        .unwrap();
+    let flags = manager.get_community_flags(vec![]).unwrap();
+    assert_eq!(flags.len(), 4);
+    assert_eq!(flags[&30000001].ice_belt, Some(false));
+    assert_eq!(flags[&30000002].ice_belt, Some(true));
+    assert_eq!(flags[&30000002].special_ore, None);
+}